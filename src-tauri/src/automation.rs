@@ -2,23 +2,59 @@
 
 use anyhow::{Result, Context};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::Mutex;
 use chrono::Utc;
-use crate::models::{AutomationRequest, AutomationStatus};
+use crate::i18n::Message;
+use crate::models::{MAX_OVERALL_DEADLINE_SECS, AppSettings, QuotaEstimate, AutomationBackend, AutomationRequest, AutomationResetReport, AutomationStage, AutomationStatus, BrowserDiagnostics, CompletenessReport, IdCardRole, IpAsset, FileStatus, Locale, Profile, RunResult, SelectorProbe, SitePlatform, TabInfo, UploadFileCheck, UploadValidationReport};
+use crate::browser::{check_chrome_debug_port, get_chrome_user_data_dir, is_chrome_running, DEBUG_API_CLIENT};
+use crate::bilibili_script::{build_self_test_script, BilibiliAutomation, SELF_TEST_FIXTURE_HTML};
 use once_cell::sync::Lazy;
+use tracing::Instrument;
 use std::process::{Command, Child};
 use reqwest;
 use serde_json;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
 
 static AUTOMATION_STATUS: Lazy<Arc<Mutex<AutomationStatus>>> = 
-    Lazy::new(|| Arc::new(Mutex::new(AutomationStatus {
+    Lazy::new(|| Arc::new(Mutex::new(idle_status())));
+
+// 状态的只读快照，每次修改AUTOMATION_STATUS时在持有锁的情况下更新
+// 前端频繁轮询时只读取快照，不与运行中的状态更新争用AUTOMATION_STATUS
+static STATUS_SNAPSHOT: Lazy<std::sync::RwLock<Arc<AutomationStatus>>> =
+    Lazy::new(|| std::sync::RwLock::new(Arc::new(idle_status())));
+
+fn idle_status() -> AutomationStatus {
+    AutomationStatus {
         is_running: false,
         current_step: None,
         progress: None,
         error: None,
         started_at: None,
-    })));
+        finished_at: None,
+        duration_secs: None,
+        error_code: None,
+        run_id: None,
+        queue_length: 0,
+        step_key: None,
+        step_args: Vec::new(),
+    }
+}
+
+// 写入当前步骤的本地化文本和消息key
+fn set_step(status: &mut AutomationStatus, step: &Message) {
+    status.current_step = Some(step.text());
+    status.step_key = Some(step.key().to_string());
+    status.step_args = step.args().to_vec();
+}
+
+// 更新快照并返回副本，调用方在释放AUTOMATION_STATUS前调用，保证快照与写入顺序一致
+fn cache_status(status: &AutomationStatus) -> AutomationStatus {
+    let snapshot = status.clone();
+    *STATUS_SNAPSHOT.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(snapshot.clone());
+    snapshot
+}
 
 static VERIFICATION_COMPLETED: Lazy<Arc<Mutex<bool>>> = 
     Lazy::new(|| Arc::new(Mutex::new(false)));
@@ -26,45 +62,365 @@ static VERIFICATION_COMPLETED: Lazy<Arc<Mutex<bool>>> =
 static CHROME_PROCESS: Lazy<Arc<Mutex<Option<Child>>>> = 
     Lazy::new(|| Arc::new(Mutex::new(None)));
 
+// 正在运行的Playwright进程PID，供reset_automation_state结束卡住的进程
+static PLAYWRIGHT_PID: Lazy<std::sync::Mutex<Option<u32>>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
+// 本应用上次启动Chrome时指定的User-Agent，None表示使用Chrome默认UA
+static LAUNCHED_USER_AGENT: Lazy<std::sync::Mutex<Option<String>>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
+// 运行过程中在项目根目录下生成的临时文件
+const CONNECT_SCRIPT_NAME: &str = "temp_connect_script.spec.js";
+const VERIFICATION_SIGNAL_FILE: &str = "verification_completed.txt";
+
+// 后台自动化任务是否仍在执行；stop_automation只修改状态，任务可能尚未退出
+static RUN_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+// 当前后台自动化任务，reset_automation_state在清除运行状态前中止它
+static RUN_TASK: Lazy<std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
+/// 已有自动化流程在运行时返回的错误
+#[derive(Debug, thiserror::Error)]
+#[error("自动化流程已在运行中")]
+pub struct AutomationAlreadyRunning;
+
+/// 自动化流程中可识别的失败原因，前端根据code给出针对性的处理建议
+/// 内部仍使用anyhow传递，只在写入AutomationStatus时转换
+#[derive(Debug, thiserror::Error)]
+pub enum AutomationError {
+    #[error("未登录B站账号，请先点击\"登录B站\"在打开的浏览器中完成登录后重试")]
+    NotLoggedIn,
+    #[error("等待验证码完成超时，请在5分钟内输入验证码并点击下一步")]
+    VerificationTimeout,
+    #[error("文件不存在: {0}")]
+    FileNotFound(String),
+    #[error("未找到npx，请确认已安装Node.js并加入PATH")]
+    NpxNotFound,
+    #[error("B站表单校验未通过: {0}")]
+    FormValidation(String),
+    #[error("部分字段未能填入，请在页面上检查后重试: {0}")]
+    FieldsNotFilled(String),
+    #[error("{0}")]
+    MissingData(String),
+    #[error("自测未通过: {0}")]
+    SelfTestFailed(String),
+    #[error("未能确认申诉已提交: {0}")]
+    SubmitNotConfirmed(String),
+    #[error("B站申诉次数已达上限，请明天再试: {0}")]
+    QuotaExceeded(String),
+    #[error("Chrome在运行中被关闭，申诉未完成。请重新开始申诉，运行期间不要关闭自动打开的浏览器")]
+    BrowserClosed,
+    #[error("运行超过总时限{secs}秒，已在\"{stage}\"阶段终止")]
+    DeadlineExceeded { stage: String, secs: u64 },
+}
+
+impl AutomationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AutomationError::NotLoggedIn => "not_logged_in",
+            AutomationError::VerificationTimeout => "verification_timeout",
+            AutomationError::FileNotFound(_) => "file_not_found",
+            AutomationError::NpxNotFound => "npx_not_found",
+            AutomationError::FormValidation(_) => "form_validation",
+            AutomationError::FieldsNotFilled(_) => "fields_not_filled",
+            AutomationError::MissingData(_) => "missing_data",
+            AutomationError::SelfTestFailed(_) => "self_test_failed",
+            AutomationError::SubmitNotConfirmed(_) => "submit_not_confirmed",
+            AutomationError::QuotaExceeded(_) => "quota_exceeded",
+            AutomationError::BrowserClosed => "browser_closed",
+            AutomationError::DeadlineExceeded { .. } => "deadline_exceeded",
+        }
+    }
+
+    /// 面向用户的本地化错误信息，key为error.加错误代码
+    pub fn message(&self) -> Message {
+        let key = match self {
+            AutomationError::NotLoggedIn => "error.not_logged_in",
+            AutomationError::VerificationTimeout => "error.verification_timeout",
+            AutomationError::FileNotFound(_) => "error.file_not_found",
+            AutomationError::NpxNotFound => "error.npx_not_found",
+            AutomationError::FormValidation(_) => "error.form_validation",
+            AutomationError::FieldsNotFilled(_) => "error.fields_not_filled",
+            AutomationError::MissingData(_) => "error.missing_data",
+            AutomationError::SelfTestFailed(_) => "error.self_test_failed",
+            AutomationError::SubmitNotConfirmed(_) => "error.submit_not_confirmed",
+            AutomationError::QuotaExceeded(_) => "error.quota_exceeded",
+            AutomationError::BrowserClosed => "error.browser_closed",
+            AutomationError::DeadlineExceeded { .. } => "error.deadline_exceeded",
+        };
+        let args = match self {
+            AutomationError::FileNotFound(detail)
+            | AutomationError::FormValidation(detail)
+            | AutomationError::FieldsNotFilled(detail)
+            | AutomationError::MissingData(detail)
+            | AutomationError::SelfTestFailed(detail)
+            | AutomationError::SubmitNotConfirmed(detail)
+            | AutomationError::QuotaExceeded(detail) => vec![detail.clone()],
+            AutomationError::DeadlineExceeded { stage, secs } => vec![secs.to_string(), stage.clone()],
+            AutomationError::NotLoggedIn
+            | AutomationError::VerificationTimeout
+            | AutomationError::NpxNotFound
+            | AutomationError::BrowserClosed => Vec::new(),
+        };
+        Message::with_args(key, args)
+    }
+
+    // 只有验证码超时可能因重试而成功，其余都是确定性错误
+    fn is_retryable(&self) -> bool {
+        matches!(self, AutomationError::VerificationTimeout)
+    }
+}
+
+/// 按设置的语言给出失败信息: 中文保留完整的错误链；其他语言只翻译可识别的AutomationError，
+/// 其余错误仍显示原文
+fn localized_error(error: &anyhow::Error) -> String {
+    match automation_error(error) {
+        Some(automation_error) if crate::i18n::current_locale() != Locale::ZhCn => automation_error.message().text(),
+        _ => format!("{:#}", error),
+    }
+}
+
+/// 在错误链中查找AutomationError
+pub fn automation_error(error: &anyhow::Error) -> Option<&AutomationError> {
+    error.chain().find_map(|cause| cause.downcast_ref::<AutomationError>())
+}
+
+// 自动化状态变化时向前端发送的事件名
+pub const AUTOMATION_STATUS_EVENT: &str = "automation-status";
+
+// 默认的B站版权申诉入口
+pub const DEFAULT_APPEAL_URL: &str = "https://www.bilibili.com/v/copyright/apply?origin=home";
+
+// B站登录页面，用于手动登录并保存会话
+const BILIBILI_LOGIN_URL: &str = "https://passport.bilibili.com/login";
+
+// 生成脚本检测到未登录时输出的标记
+pub(crate) const NOT_LOGGED_IN_MARKER: &str = "[NOT_LOGGED_IN]";
+
+// 重试的初始退避时间，每次重试翻倍
+const RETRY_BASE_DELAY_SECS: u64 = 5;
+
+// 生成脚本在按钮不可用时输出的表单校验错误标记
+pub(crate) const FORM_VALIDATION_MARKER: &str = "[FORM_VALIDATION_ERRORS]";
+
+// 填写后读回的值与期望不一致时脚本输出的标记，内容为"手机号未填入"之类的提示
+pub(crate) const FIELDS_NOT_FILLED_MARKER: &str = "[FIELDS_NOT_FILLED]";
+
+// 脚本等待验证码完成超时时输出的标记
+pub(crate) const VERIFICATION_TIMEOUT_MARKER: &str = "[VERIFICATION_TIMEOUT]";
+
+// 脚本中待上传文件全部无效时输出的标记
+pub(crate) const FILE_NOT_FOUND_MARKER: &str = "[FILE_NOT_FOUND]";
+
+// 脚本点击提交后未检测到提交成功提示时输出的标记
+pub(crate) const SUBMIT_NOT_CONFIRMED_MARKER: &str = "[SUBMIT_NOT_CONFIRMED]";
+
+// 运行中Chrome被关闭、CDP连接断开时脚本输出的标记
+pub(crate) const BROWSER_CLOSED_MARKER: &str = "[BROWSER_CLOSED]";
+
+// 脚本检测到申诉次数超限提示时输出的标记
+pub(crate) const QUOTA_EXCEEDED_MARKER: &str = "[QUOTA_EXCEEDED]";
+
+// 诊断脚本输出B站登录Cookie检查结果时使用的标记
+const SESSION_CHECK_MARKER: &str = "[SESSION_CHECK]";
+
+// 选择器探测脚本输出结果时使用的标记
+const SELECTOR_PROBE_MARKER: &str = "[SELECTOR_PROBE]";
+
+// 自测脚本在字段校验失败时输出的标记
+pub(crate) const SELF_TEST_FAILED_MARKER: &str = "[SELF_TEST_FAILED]";
+
+// 自测上传用的1x1 PNG图片
+const SELF_TEST_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d,
+    0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01,
+    0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4, 0x89, 0x00, 0x00, 0x00,
+    0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x64, 0x60, 0xf8, 0x5f,
+    0x0f, 0x00, 0x02, 0x87, 0x01, 0x80, 0xeb, 0x47, 0xba, 0x92, 0x00, 0x00,
+    0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+];
+
+// 不经过代理的地址，保证本地CDP调试连接可用
+const PROXY_BYPASS_HOSTS: &[&str] = &["127.0.0.1", "localhost"];
+
+/// 各申诉平台的脚本生成；Chrome启动与CDP连接由本模块统一处理
+pub trait SiteAutomation: Send + Sync {
+    /// 本次申诉要打开的申诉页面地址
+    fn appeal_url(&self, request: &AutomationRequest, settings: &AppSettings) -> Result<String>;
+
+    /// 生成通过CDP连接已启动Chrome并填写申诉表单的Playwright脚本
+    fn build_script(
+        &self,
+        profile: &Profile,
+        ip_asset: Option<&IpAsset>,
+        request: &AutomationRequest,
+        settings: &AppSettings,
+    ) -> Result<String>;
+
+    /// 根据脚本输出的一行日志估算进度，返回(步骤, 进度百分比)
+    fn progress_for_line(&self, _line: &str) -> Option<(Message, f32)> {
+        None
+    }
+}
+
+fn site_automation(platform: SitePlatform) -> Result<Box<dyn SiteAutomation>> {
+    match platform {
+        SitePlatform::Bilibili => Ok(Box::new(BilibiliAutomation)),
+        other => Err(anyhow::anyhow!("暂不支持{:?}平台的自动化申诉", other)),
+    }
+}
+
 // ==============================================
 // Public API Functions
 // ==============================================
 
-pub async fn start_automation(request: AutomationRequest) -> Result<()> {
+// 距每日上限不超过该次数时，启动前需要用户确认
+const QUOTA_WARNING_REMAINING: u32 = 3;
+
+/// 根据本地案件记录估算今日剩余的申诉次数，B站按北京时间零点重置，与本地时区无关
+pub(crate) async fn estimate_quota(daily_limit: u32) -> Result<QuotaEstimate> {
+    let beijing = chrono::FixedOffset::east_opt(8 * 3600).expect("valid offset");
+    let today = Utc::now().with_timezone(&beijing).date_naive();
+    let day_start = today
+        .and_time(chrono::NaiveTime::MIN)
+        .and_local_timezone(beijing)
+        .single()
+        .expect("fixed offset has no gaps")
+        .with_timezone(&Utc);
+
+    let submitted_today = crate::database::count_submissions_since(day_start).await?;
+    let remaining = daily_limit.saturating_sub(submitted_today);
+    Ok(QuotaEstimate {
+        daily_limit,
+        submitted_today,
+        remaining,
+        near_limit: remaining <= QUOTA_WARNING_REMAINING,
+        resets_at: day_start + chrono::Duration::days(1),
+    })
+}
+
+// 所有启动入口共用的次数检查: 接近每日上限时只有用户已确认的交互启动可以继续，
+// 排队和计划运行无人确认，直接拒绝。测试模式不提交申诉，不检查
+async fn ensure_quota_allows(request: &AutomationRequest, settings: &AppSettings) -> Result<()> {
+    if request.test_mode || request.quota_confirmed {
+        return Ok(());
+    }
+    let estimate = estimate_quota(settings.daily_appeal_limit).await?;
+    if estimate.near_limit {
+        tracing::warn!("今日已提交{}次申诉，估计剩余{}次，未经确认不启动", estimate.submitted_today, estimate.remaining);
+        return Err(AutomationError::QuotaExceeded(format!(
+            "今日已提交{}次，按每日{}次估算剩余{}次",
+            estimate.submitted_today, estimate.daily_limit, estimate.remaining
+        )).into());
+    }
+    Ok(())
+}
+
+pub async fn start_automation(mut request: AutomationRequest) -> Result<()> {
+    let settings = load_settings().await;
+    crate::i18n::set_locale(settings.locale);
+    apply_last_used_ip_asset(&mut request, &settings).await?;
+    ensure_ip_asset_resolved(&request).await?;
+    site_automation(request.platform)?.appeal_url(&request, &settings)?;
+    ensure_quota_allows(&request, &settings).await?;
+
     let mut status = AUTOMATION_STATUS.lock().await;
-    if status.is_running { return Err(anyhow::anyhow!("自动化流程已在运行中")); }
+    claim_run(&status)?;
     
+    // 排队的申诉在入队时已分配run_id
+    let run_id = *request.run_id.get_or_insert_with(uuid::Uuid::new_v4);
+    let started_at = Utc::now();
     *status = AutomationStatus {
         is_running: true,
-        current_step: Some("初始化".to_string()),
+        current_step: None,
         progress: Some(0.0),
         error: None,
-        started_at: Some(Utc::now()),
+        started_at: Some(started_at),
+        finished_at: None,
+        duration_secs: None,
+        error_code: None,
+        run_id: Some(run_id),
+        queue_length: crate::run_queue::len() as u32,
+        step_key: None,
+        step_args: Vec::new(),
     };
+    set_step(&mut status, &Message::new("step.initializing"));
+    let snapshot = cache_status(&status);
     drop(status);
+    publish_status(&snapshot).await;
+    tracing::info!(%run_id, "开始自动化运行");
+    // 上次运行异常结束时可能留下已完成验证的信号，不能让本次运行直接跳过验证码等待
+    *VERIFICATION_COMPLETED.lock().await = false;
+    let removed_files = remove_stale_run_files(&settings);
+    if !removed_files.is_empty() {
+        tracing::info!("删除上次运行遗留的临时文件: {:?}", removed_files);
+    }
+    if let Err(e) = crate::database::insert_automation_run(run_id, &request, started_at).await {
+        tracing::warn!("记录自动化运行失败: {}", e);
+    }
+
+    if let Some(ip_asset_id) = request.ip_asset_id {
+        if let Err(e) = crate::database::set_setting(crate::database::SETTING_LAST_USED_IP_ASSET_ID, &ip_asset_id.to_string()).await {
+            tracing::warn!("记录上次使用的IP资产失败: {}", e);
+        }
+    }
 
     let request_arc = Arc::new(request);
-    tokio::spawn(async move {
-        let result = run_automation_process(request_arc).await;
+    let run_task = tokio::spawn(async move {
+        let result = run_automation_with_retries(request_arc.clone()).await;
         let mut status = AUTOMATION_STATUS.lock().await;
+        // 用户停止时is_running已被stop_automation清除
+        let was_stopped = !status.is_running;
+        let outcome = match (&result, was_stopped) {
+            (Ok(_), _) => crate::database::RUN_OUTCOME_SUCCEEDED,
+            (Err(_), true) => crate::database::RUN_OUTCOME_STOPPED,
+            (Err(_), false) => crate::database::RUN_OUTCOME_FAILED,
+        };
         
-        match result {
-            Ok(()) => {
+        let (notification_title, notification_body) = match &result {
+            Ok(submitted) => {
                 status.is_running = false;
-                status.current_step = Some("完成".to_string());
+                set_step(&mut status, &final_step(*submitted));
                 status.progress = Some(100.0);
                 status.error = None;
+                status.error_code = None;
+                let title = if *submitted { "申诉提交完成" } else { "申诉已填写，等待确认提交" };
+                (title, format!("侵权链接: {}", request_arc.infringing_url))
+            }
+            // 停止导致的失败不是错误，保持stop_automation设置的最终状态
+            Err(e) if was_stopped => {
+                tracing::info!("自动化流程已被用户停止: {:#}", e);
+                set_step(&mut status, &Message::new(STOPPED_STEP));
+                status.progress = None;
+                status.error = None;
+                status.error_code = None;
+                ("申诉自动化已停止", format!("侵权链接: {}", request_arc.infringing_url))
             }
             Err(e) => {
-                let error_message = format!("{:#}", e);
-                tracing::error!("自动化流程失败: {}", error_message);
+                tracing::error!("自动化流程失败: {:#}", e);
+                let error_message = localized_error(e);
                 status.is_running = false;
-                status.current_step = Some("失败".to_string());
-                status.error = Some(error_message);
+                set_step(&mut status, &Message::new("step.failed"));
+                status.error = Some(error_message.clone());
+                status.error_code = automation_error(e).map(|error| error.code().to_string());
+                ("申诉自动化失败", error_message)
             }
-        }
+        };
+        
+        let finished_at = Utc::now();
+        status.finished_at = Some(finished_at);
+        status.duration_secs = status.started_at.map(|started| (finished_at - started).num_seconds());
+        tracing::info!("自动化流程结束，耗时: {:?}秒", status.duration_secs);
         
+        let snapshot = cache_status(&status);
         drop(status);
+        publish_status(&snapshot).await;
+        if let Err(e) = crate::database::finish_automation_run(run_id, outcome, &snapshot).await {
+            tracing::warn!("更新自动化运行记录失败: {}", e);
+        }
+        notify_run_finished(notification_title, &notification_body).await;
         
         let mut process_handle = CHROME_PROCESS.lock().await;
         if let Some(mut child) = process_handle.take() {
@@ -74,15 +430,77 @@ pub async fn start_automation(request: AutomationRequest) -> Result<()> {
                 tracing::info!("成功清理Chrome进程");
             }
         }
-    });
+        drop(process_handle);
+        
+        RUN_IN_PROGRESS.store(false, Ordering::SeqCst);
+    }.instrument(tracing::info_span!("automation_run", %run_id)));
+    *RUN_TASK.lock().unwrap_or_else(|e| e.into_inner()) = Some(run_task);
     
     Ok(())
 }
 
+/// 生成本次请求对应的完整Playwright脚本但不运行: 不写文件、不启动或连接Chrome
+/// 数据读取与正式运行一致，脚本中是真实解析后的文件路径，用于排查用户反馈的选择器问题
+pub async fn preview_script(mut request: AutomationRequest) -> Result<String> {
+    let settings = load_settings().await;
+    apply_last_used_ip_asset(&mut request, &settings).await?;
+    ensure_ip_asset_resolved(&request).await?;
+    let (profile, ip_asset) = load_run_data(&request).await?;
+    site_automation(request.platform)?.build_script(&profile, ip_asset.as_ref(), &request, &settings)
+}
+
+// 未指定IP资产且设置允许时，使用上次使用的IP资产
+async fn apply_last_used_ip_asset(request: &mut AutomationRequest, settings: &AppSettings) -> Result<()> {
+    if request.ip_asset_id.is_none() && settings.use_last_ip_asset {
+        if let Some(asset) = crate::database::get_last_used_ip_asset().await? {
+            tracing::info!("未指定IP资产，使用上次使用的IP资产: {}", asset.work_name);
+            request.ip_asset_id = asset.id;
+        }
+    }
+    Ok(())
+}
+
+// 读取生成脚本所需的个人档案和IP资产
+async fn load_run_data(request: &AutomationRequest) -> Result<(Profile, Option<IpAsset>)> {
+    let profile = crate::database::get_profile().await?.ok_or_else(|| AutomationError::MissingData("未找到个人档案".to_string()))?;
+    let ip_asset = if let Some(ip_id) = request.ip_asset_id {
+        Some(crate::database::get_ip_asset(ip_id).await?.ok_or_else(|| AutomationError::MissingData("未找到指定的IP资产".to_string()))?)
+    } else { None };
+    Ok((profile, ip_asset))
+}
+
+// B站申诉必须填写权利信息；没有IP资产时脚本会停在IP资产页面直到超时，
+// 所以在启动Chrome之前就失败。从申诉详情阶段继续时不再填写IP资产，不需要检查
+async fn ensure_ip_asset_resolved(request: &AutomationRequest) -> Result<()> {
+    if request.start_stage == AutomationStage::FromAppealDetails {
+        return Ok(());
+    }
+    match request.ip_asset_id {
+        Some(ip_asset_id) => {
+            if crate::database::get_ip_asset(ip_asset_id).await?.is_none() {
+                return Err(AutomationError::MissingData(format!("本次申诉需要选择IP资产 (IP资产不存在: {})", ip_asset_id)).into());
+            }
+        }
+        None if request.allow_without_ip_asset => {
+            tracing::warn!("⚠️ 未选择IP资产，按请求继续运行，IP资产部分需要手动填写");
+        }
+        None => return Err(AutomationError::MissingData("本次申诉需要选择IP资产".to_string()).into()),
+    }
+    Ok(())
+}
+
+// 用户停止运行后的最终状态
+const STOPPED_STEP: &str = "step.stopped";
+
+/// 停止运行: 结束Chrome，删除生成的脚本和验证信号文件并清除验证标记，下一次运行不受影响
 pub async fn stop_automation() -> Result<()> {
     let mut status = AUTOMATION_STATUS.lock().await;
     status.is_running = false;
-    status.current_step = Some("已停止".to_string());
+    set_step(&mut status, &Message::new(STOPPED_STEP));
+    status.progress = None;
+    let snapshot = cache_status(&status);
+    drop(status);
+    publish_status(&snapshot).await;
     
     let mut process_handle = CHROME_PROCESS.lock().await;
     if let Some(mut child) = process_handle.take() {
@@ -92,1289 +510,1257 @@ pub async fn stop_automation() -> Result<()> {
             tracing::info!("Successfully killed Chrome process on stop");
         }
     }
+    drop(process_handle);
+
+    *VERIFICATION_COMPLETED.lock().await = false;
+    let removed_files = remove_stale_run_files(&load_settings().await);
+    if !removed_files.is_empty() {
+        tracing::info!("停止时删除临时文件: {:?}", removed_files);
+    }
     Ok(())
 }
 
-pub async fn get_automation_status() -> Result<AutomationStatus> {
-    let status = AUTOMATION_STATUS.lock().await;
-    Ok(AutomationStatus {
-        is_running: status.is_running,
-        current_step: status.current_step.clone(),
-        progress: status.progress,
-        error: status.error.clone(),
-        started_at: status.started_at,
-    })
+/// 强制清理自动化状态: 清除内存和数据库中的运行标记，结束记录的Chrome/Playwright进程，删除临时文件
+/// 用于运行卡住或应用崩溃后无法再次启动的情况
+pub async fn reset_automation_state() -> Result<AutomationResetReport> {
+    if is_run_in_progress() {
+        tracing::warn!("强制重置正在运行的自动化流程");
+    }
+    // 先中止旧任务再清除运行标记，否则新运行开始后旧任务结束时会覆盖状态和RUN_IN_PROGRESS
+    abort_run_task().await;
+
+    let mut status = AUTOMATION_STATUS.lock().await;
+    let was_running = status.is_running || is_run_in_progress();
+    status.is_running = false;
+    set_step(&mut status, &Message::new("step.reset"));
+    let snapshot = cache_status(&status);
+    drop(status);
+    RUN_IN_PROGRESS.store(false, Ordering::SeqCst);
+    *VERIFICATION_COMPLETED.lock().await = false;
+    publish_status(&snapshot).await;
+
+    let killed_chrome = match CHROME_PROCESS.lock().await.take() {
+        Some(mut child) => match child.kill() {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("重置时结束Chrome进程失败: {}", e);
+                false
+            }
+        },
+        None => false,
+    };
+    let killed_playwright = kill_tracked_playwright();
+    let removed_files = remove_stale_run_files(&load_settings().await);
+    if let Err(e) = crate::database::mark_unfinished_runs_interrupted().await {
+        tracing::warn!("更新未结束的运行记录失败: {}", e);
+    }
+
+    tracing::info!("自动化状态已重置: 运行中={}, Chrome={}, Playwright={}, 删除文件={:?}",
+        was_running, killed_chrome, killed_playwright, removed_files);
+    Ok(AutomationResetReport { was_running, killed_chrome, killed_playwright, removed_files })
 }
 
-pub async fn check_automation_environment_public() -> Result<String> {
-    Ok("环境检查功能就绪。".to_string())
+/// 启动时的轻量恢复: 上次运行被强制结束时数据库中仍是运行中，清除该标记和遗留的临时文件
+/// 不结束任何进程，避免误关用户自己打开的Chrome
+pub async fn recover_interrupted_run() {
+    match crate::database::clear_stale_running_status().await {
+        Ok(true) => tracing::warn!("检测到上次自动化运行意外中断，已清除运行状态"),
+        Ok(false) => {}
+        Err(e) => tracing::warn!("清除遗留的自动化运行状态失败: {}", e),
+    }
+    match crate::database::mark_unfinished_runs_interrupted().await {
+        Ok(0) => {}
+        Ok(count) => tracing::warn!("{}条运行记录因应用关闭而中断", count),
+        Err(e) => tracing::warn!("更新未结束的运行记录失败: {}", e),
+    }
+    let removed_files = remove_stale_run_files(&load_settings().await);
+    if !removed_files.is_empty() {
+        tracing::info!("已删除遗留的临时文件: {:?}", removed_files);
+    }
 }
 
-pub async fn continue_after_verification() -> Result<()> {
-    use std::fs;
-    let project_root = std::env::current_dir()?.parent().ok_or_else(|| anyhow::anyhow!("Cannot find project root"))?.to_path_buf();
-    let signal_file = project_root.join("verification_completed.txt");
-    fs::write(signal_file, "completed")?;
-    
-    let mut verification = VERIFICATION_COMPLETED.lock().await;
-    *verification = true;
-    tracing::info!("Verification completed signal sent to Playwright");
+async fn abort_run_task() {
+    let Some(run_task) = RUN_TASK.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return;
+    };
+    if run_task.is_finished() {
+        return;
+    }
+    run_task.abort();
+    match run_task.await {
+        Err(e) if e.is_cancelled() => tracing::info!("已中止仍在执行的自动化任务"),
+        Err(e) => tracing::warn!("自动化任务异常结束: {}", e),
+        Ok(()) => {}
+    }
+}
+
+// Playwright在独立的进程组中运行 (进程组ID即npx的PID)，结束整个进程组，
+// 包括npx启动的node/Playwright子进程和它驱动的浏览器
+fn kill_tracked_playwright() -> bool {
+    let Some(pid) = PLAYWRIGHT_PID.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return false;
+    };
+    #[cfg(target_os = "windows")]
+    let result = Command::new("taskkill").args(&["/F", "/T", "/PID", &pid.to_string()]).output();
+    #[cfg(not(target_os = "windows"))]
+    let result = {
+        let process_group = format!("-{}", pid);
+        Command::new("kill").args(["-KILL", "--", process_group.as_str()]).output()
+    };
+    match result {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            tracing::warn!("结束Playwright进程{}失败: {}", pid, String::from_utf8_lossy(&output.stderr).trim());
+            false
+        }
+        Err(e) => {
+            tracing::warn!("结束Playwright进程{}失败: {}", pid, e);
+            false
+        }
+    }
+}
+
+// 删除验证信号文件和生成的连接脚本，返回实际删除的文件
+fn remove_stale_run_files(settings: &AppSettings) -> Vec<String> {
+    let Ok(work_dir) = automation_work_dir(settings) else {
+        return Vec::new();
+    };
+    [
+        work_dir.join(VERIFICATION_SIGNAL_FILE),
+        work_dir.join(CONNECT_SCRIPT_NAME),
+    ]
+    .into_iter()
+    .filter(|path| path.exists())
+    .filter_map(|path| match std::fs::remove_file(&path) {
+        Ok(()) => Some(path.to_string_lossy().to_string()),
+        Err(e) => {
+            tracing::warn!("删除临时文件失败 {:?}: {}", path, e);
+            None
+        }
+    })
+    .collect()
+}
+
+// 在同一个锁范围内检查并占用运行状态，连续点击时只有一次能成功
+// 调用方需持有AUTOMATION_STATUS锁，并在成功后把status.is_running设为true
+fn claim_run(status: &AutomationStatus) -> Result<()> {
+    if status.is_running
+        || RUN_IN_PROGRESS.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err()
+    {
+        tracing::warn!("自动化流程已在运行中，忽略重复的启动请求");
+        return Err(AutomationAlreadyRunning.into());
+    }
     Ok(())
 }
 
-// ==============================================
-// Core Automation Logic
-// ==============================================
+/// 后台自动化任务是否仍在执行(包括已请求停止但尚未退出的任务)
+pub fn is_run_in_progress() -> bool {
+    RUN_IN_PROGRESS.load(Ordering::SeqCst)
+}
 
-async fn run_automation_process(request: Arc<AutomationRequest>) -> Result<()> {
-    update_status("获取数据...", 5.0).await;
-    let profile = crate::database::get_profile().await?.ok_or_else(|| anyhow::anyhow!("未找到个人档案"))?;
-    let ip_asset = if let Some(ip_id) = request.ip_asset_id {
-        Some(crate::database::get_ip_asset(ip_id).await?.ok_or_else(|| anyhow::anyhow!("未找到指定的IP资产"))?)
-    } else { None };
+/// 运行队列变化时更新状态中的排队数量并通知前端
+pub(crate) async fn set_queue_length(queue_length: u32) {
+    let mut status = AUTOMATION_STATUS.lock().await;
+    if status.queue_length == queue_length {
+        return;
+    }
+    status.queue_length = queue_length;
+    let snapshot = cache_status(&status);
+    drop(status);
+    emit_status(&snapshot);
+}
 
-    update_status("启动浏览器...", 10.0).await;
-    start_chrome_with_remote_debugging().await.context("启动带调试端口的Chrome失败")?;
+/// 读取最近一次的状态快照，写锁只在替换Arc时短暂持有，轮询不会阻塞进度更新
+pub async fn get_automation_status() -> Result<AutomationStatus> {
+    let snapshot = STATUS_SNAPSHOT.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    Ok((*snapshot).clone())
+}
 
-    update_status("生成连接脚本...", 25.0).await;
-    let project_root = std::env::current_dir()?.parent().ok_or_else(|| anyhow::anyhow!("Cannot find project root"))?.to_path_buf();
-    let tests_dir = project_root.join("tests");
-    std::fs::create_dir_all(&tests_dir).context("无法创建tests目录")?;
+/// 校验侵权链接是B站视频/动态链接，并将b23.tv短链接解析为完整地址
+pub async fn validate_infringing_url(raw_url: &str) -> Result<String> {
+    let trimmed = raw_url.trim();
+    let parsed = reqwest::Url::parse(trimmed)
+        .with_context(|| format!("侵权链接格式无效: {}", trimmed))?;
 
-    let script_name = "temp_connect_script.spec.js";
-    let script_path_buf = tests_dir.join(script_name);
-    let script_path_for_command = format!("tests/{}", script_name);
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(anyhow::anyhow!("侵权链接必须是http或https地址: {}", trimmed));
+    }
 
-    let script_content = generate_connect_script(&profile, ip_asset.as_ref(), &request, &project_root)?;
-    std::fs::write(&script_path_buf, &script_content).context("写入Playwright脚本失败")?;
-    tracing::info!("Playwright脚本已生成: {:?}", script_path_buf);
-    
-    update_status("正在启动Playwright测试...", 35.0).await;
-    tracing::info!("🚀 开始执行Playwright脚本，监控日志输出...");
-    execute_playwright_test(&script_path_for_command, &project_root).await.context("执行Playwright脚本失败")?;
-    
-    update_status("Playwright脚本执行完成", 90.0).await;
-    tracing::info!("✅ Playwright脚本执行完成，检查输出结果...");
-    let _ = std::fs::remove_file(&script_path_buf);
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    let is_host = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
 
-    update_status("申诉提交成功", 100.0).await;
-    save_case_record(&request).await?;
-    Ok(())
-}
+    if is_host("bilibili.com") {
+        return Ok(parsed.to_string());
+    }
+    if !is_host("b23.tv") {
+        return Err(anyhow::anyhow!("侵权链接不是B站链接 (需为bilibili.com或b23.tv): {}", trimmed));
+    }
 
-async fn update_status(step: &str, progress: f32) {
-    let mut status = AUTOMATION_STATUS.lock().await;
-    status.current_step = Some(step.to_string());
-    status.progress = Some(progress);
+    // b23.tv短链接通过重定向解析为完整地址，解析失败时保留原链接
+    let settings = load_settings().await;
+    let client = build_http_client(settings.proxy.as_deref(), 10)?;
+    match client.head(parsed.clone()).send().await {
+        Ok(response) => {
+            let resolved = response.url().clone();
+            let resolved_host = resolved.host_str().unwrap_or_default();
+            if resolved_host == "bilibili.com" || resolved_host.ends_with(".bilibili.com") {
+                tracing::info!("短链接已解析: {} -> {}", parsed, resolved);
+                Ok(resolved.to_string())
+            } else {
+                Err(anyhow::anyhow!("短链接未指向B站页面: {} -> {}", parsed, resolved))
+            }
+        }
+        Err(e) => {
+            tracing::warn!("解析短链接失败，使用原链接: {} ({})", parsed, e);
+            Ok(parsed.to_string())
+        }
+    }
 }
 
-fn find_npx_executable() -> Result<String> {
-    let possible_paths = vec![
-        "C:\\Program Files\\nodejs\\npx.cmd",
-        "C:\\Program Files (x86)\\nodejs\\npx.cmd",
-    ];
-    for path in possible_paths {
-        if std::path::Path::new(path).exists() {
-            return Ok(path.to_string());
+/// 启动带调试端口的Chrome并打开B站登录页，供用户手动登录
+/// 登录会话保存在持久化的用户数据目录中，后续自动化直接复用
+pub async fn launch_browser_for_login() -> Result<String> {
+    let settings = load_settings().await;
+    if check_chrome_debug_port(&settings).await {
+        tracing::info!("Chrome调试端口已可用，在现有浏览器中打开登录页");
+        open_tab_via_debug_api(&settings, BILIBILI_LOGIN_URL).await?;
+    } else {
+        if is_chrome_running().await {
+            close_existing_chrome().await?;
+            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
         }
+        // 登录需要用户操作，始终以有界面模式启动
+        let login_settings = AppSettings { headless: false, ..settings.clone() };
+        start_new_chrome_with_debugging(&login_settings, Some(BILIBILI_LOGIN_URL), settings.user_agent.as_deref()).await
+            .context("启动用于登录的Chrome失败")?;
     }
-    if Command::new("npx").arg("--version").output().is_ok() {
-        return Ok("npx".to_string());
-    }
-    Err(anyhow::anyhow!("在常见路径中未找到npx.cmd。"))
+
+    tracing::info!("已打开B站登录页: {}", BILIBILI_LOGIN_URL);
+    Ok(if check_chrome_debug_port(&settings).await { "connected" } else { "disconnected" }.to_string())
 }
 
-async fn execute_playwright_test(script_path: &str, project_root: &std::path::Path) -> Result<()> {
-    let npx_path = find_npx_executable()?;
-    let mut cmd = Command::new(&npx_path);
-    cmd.args(&["playwright", "test", script_path, "--timeout=300000"])
-       .env("PLAYWRIGHT_BROWSERS_PATH", "0")
-       .current_dir(project_root);
-        
-    let output = cmd.output()?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+/// 列出调试Chrome中打开的标签页，id可作为AutomationRequest.target_tab_id
+pub async fn list_browser_tabs() -> Result<Vec<TabInfo>> {
+    let settings = load_settings().await;
+    let targets: Vec<serde_json::Value> = DEBUG_API_CLIENT
+        .get(format!("http://127.0.0.1:{}/json/list", settings.debug_port))
+        .send()
+        .await
+        .with_context(|| format!("无法连接Chrome调试端口{}", settings.debug_port))?
+        .json()
+        .await
+        .context("解析标签页列表失败")?;
 
-    tracing::info!("📊 Playwright执行完成，开始分析输出日志...");
-    tracing::info!("📏 stdout长度: {} 字符", stdout.len());
-    tracing::info!("📏 stderr长度: {} 字符", stderr.len());
-    
-    // 分块输出stdout，避免单行过长
-    if !stdout.is_empty() {
-        let stdout_lines: Vec<&str> = stdout.lines().collect();
-        tracing::info!("📄 Playwright stdout ({} 行):", stdout_lines.len());
-        
-        for (i, line) in stdout_lines.iter().enumerate() {
-            if i < 100 { // 限制显示前100行，避免日志过长
-                tracing::info!("  stdout[{}]: {}", i + 1, line);
-            } else if i == 100 {
-                tracing::info!("  stdout[...]: 剩余 {} 行已省略", stdout_lines.len() - 100);
-                break;
+    let tabs = page_tabs(&targets);
+    tracing::info!("🗂️ 调试Chrome中共有{}个标签页", tabs.len());
+    Ok(tabs)
+}
+
+// /json/list中的页面类型目标，Chrome按最近激活的顺序排列
+fn page_tabs(targets: &[serde_json::Value]) -> Vec<TabInfo> {
+    targets.iter()
+        .filter(|target| target["type"].as_str() == Some("page"))
+        .filter_map(|target| Some(TabInfo {
+            id: target["id"].as_str()?.to_string(),
+            title: target["title"].as_str().unwrap_or_default().to_string(),
+            url: target["url"].as_str().unwrap_or_default().to_string(),
+        }))
+        .collect()
+}
+
+/// 汇总调试Chrome的版本、标签页和B站登录状态
+/// 单项检查失败只记录到errors中，调试端口无法连接时返回connected=false的报告
+pub async fn get_browser_diagnostics() -> Result<BrowserDiagnostics> {
+    let settings = load_settings().await;
+    let mut diagnostics = BrowserDiagnostics {
+        debug_port: settings.debug_port,
+        connected: false,
+        browser_version: None,
+        protocol_version: None,
+        user_agent: None,
+        tab_count: 0,
+        active_tab_url: None,
+        bilibili_session: None,
+        errors: Vec::new(),
+    };
+
+    let version = DEBUG_API_CLIENT
+        .get(format!("http://127.0.0.1:{}/json/version", settings.debug_port))
+        .send()
+        .await;
+    let version: serde_json::Value = match version {
+        Ok(response) => match response.json().await {
+            Ok(version) => version,
+            Err(e) => {
+                diagnostics.errors.push(format!("解析/json/version失败: {}", e));
+                return Ok(diagnostics);
             }
+        },
+        Err(e) => {
+            diagnostics.errors.push(format!("无法连接Chrome调试端口{}: {}", settings.debug_port, e));
+            return Ok(diagnostics);
         }
-    } else {
-        tracing::warn!("⚠️ Playwright stdout为空，可能脚本未正常执行");
-    }
-    
-    if !stderr.is_empty() {
-        tracing::warn!("📄 Playwright stderr: {}", stderr);
+    };
+    diagnostics.connected = true;
+    diagnostics.browser_version = version["Browser"].as_str().map(str::to_string);
+    diagnostics.protocol_version = version["Protocol-Version"].as_str().map(str::to_string);
+    diagnostics.user_agent = version["User-Agent"].as_str().map(str::to_string);
+
+    match list_browser_tabs().await {
+        Ok(tabs) => {
+            diagnostics.tab_count = tabs.len() as u32;
+            diagnostics.active_tab_url = tabs.first().map(|tab| tab.url.clone());
+        }
+        Err(e) => diagnostics.errors.push(format!("{:#}", e)),
     }
-    
-    if !output.status.success() {
-        return Err(anyhow::anyhow!("Playwright测试失败 (退出码: {:?}): {}", output.status.code(), stderr));
+
+    // Cookie无法通过/json接口读取，需要连接浏览器上下文
+    match check_bilibili_session(&settings).await {
+        Ok(has_session) => diagnostics.bilibili_session = Some(has_session),
+        Err(e) => diagnostics.errors.push(format!("{:#}", e)),
     }
-    
-    Ok(())
+
+    tracing::info!("🩺 浏览器诊断: {:?}", diagnostics);
+    Ok(diagnostics)
 }
 
-async fn start_chrome_with_remote_debugging() -> Result<()> {
-    if check_chrome_debug_port().await {
-        return Ok(());
-    }
+// 连接调试Chrome读取B站Cookie，存在SESSDATA即视为已登录
+async fn check_bilibili_session(settings: &AppSettings) -> Result<bool> {
+    let script_content = format!(r#"
+const {{ test, chromium }} = require('@playwright/test');
 
-    if is_chrome_running().await {
-        close_existing_chrome().await?;
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+test('RightsGuard Session Check', async () => {{
+    {connect_section}
+    const cookies = await context.cookies('https://www.bilibili.com');
+    const hasSession = cookies.some(cookie => cookie.name === 'SESSDATA' && cookie.value.length > 0);
+    console.log('{marker}' + JSON.stringify(hasSession));
+}});
+"#,
+        connect_section = cdp_connect_snippet(settings.debug_port),
+        marker = SESSION_CHECK_MARKER
+    );
+
+    let work_dir = automation_work_dir(settings)?;
+    let script_name = "session_check.spec.js";
+    let script_path_buf = work_dir.join(script_name);
+    std::fs::write(&script_path_buf, &script_content).context("写入登录状态检查脚本失败")?;
+
+    let options = PlaywrightRunOptions::from_settings(settings);
+    let result = execute_playwright_test(script_name, &work_dir, &options, None, None).await;
+    let _ = std::fs::remove_file(&script_path_buf);
+    let stdout = result.context("检查B站登录状态失败")?.stdout;
+
+    let has_session = stdout.lines()
+        .find_map(|line| line.split_once(SESSION_CHECK_MARKER))
+        .ok_or_else(|| anyhow::anyhow!("未能从脚本输出中读取登录状态"))?
+        .1;
+    serde_json::from_str(has_session.trim()).context("解析登录状态检查结果失败")
+}
+
+/// 通过调试端口连接已打开的Chrome，在当前页面上测试单个选择器，用于排查B站页面结构变化
+pub async fn probe_selector(selector: &str) -> Result<SelectorProbe> {
+    let selector = selector.trim();
+    if selector.is_empty() {
+        return Err(anyhow::anyhow!("选择器不能为空"));
+    }
+    let settings = load_settings().await;
+    if !check_chrome_debug_port(&settings).await {
+        return Err(anyhow::anyhow!("Chrome调试端口{}未开启，请先打开申诉页面", settings.debug_port));
     }
 
-    start_new_chrome_with_debugging().await
+    let script_content = format!(r#"
+const {{ test, chromium }} = require('@playwright/test');
+
+test('RightsGuard Selector Probe', async () => {{
+    {connect_section}
+    const pages = context.pages();
+    const page = pages.find(p => p.url().includes('bilibili.com')) || pages[pages.length - 1];
+    if (!page) {{
+        throw new Error('浏览器中没有已打开的页面');
+    }}
+    const locator = page.locator({selector});
+    const count = await locator.count();
+    const first = locator.first();
+    const result = {{
+        selector: {selector},
+        pageUrl: page.url(),
+        count,
+        visible: count > 0 ? await first.isVisible().catch(() => false) : false,
+        enabled: count > 0 ? await first.isEnabled({{ timeout: 2000 }}).catch(() => false) : false,
+        firstText: count > 0 ? await first.innerText({{ timeout: 2000 }}).catch(() => null) : null,
+    }};
+    console.log('{marker}' + JSON.stringify(result));
+}});
+"#,
+        connect_section = cdp_connect_snippet(settings.debug_port),
+        selector = js_string_literal(selector),
+        marker = SELECTOR_PROBE_MARKER
+    );
+
+    let work_dir = automation_work_dir(&settings)?;
+    let script_name = "selector_probe.spec.js";
+    let script_path_buf = work_dir.join(script_name);
+    std::fs::write(&script_path_buf, &script_content).context("写入选择器探测脚本失败")?;
+
+    let options = PlaywrightRunOptions::from_settings(&settings);
+    let result = execute_playwright_test(script_name, &work_dir, &options, None, None).await;
+    let _ = std::fs::remove_file(&script_path_buf);
+    let stdout = result.context("选择器探测失败")?.stdout;
+
+    let probe = stdout.lines()
+        .find_map(|line| line.split_once(SELECTOR_PROBE_MARKER))
+        .ok_or_else(|| anyhow::anyhow!("未能从脚本输出中读取探测结果"))?
+        .1;
+    let probe: SelectorProbe = serde_json::from_str(probe.trim()).context("解析选择器探测结果失败")?;
+    tracing::info!("🔍 选择器探测: {} -> 匹配{}个元素 (页面: {})", probe.selector, probe.count, probe.page_url);
+    Ok(probe)
 }
 
-// ==============================================
-// Script Generation (DEFINITIVE FIX HERE)
-// ==============================================
+/// 使用内置的模拟申诉页面走一遍填写与上传流程，校验选择器和路径转义，不访问真实B站
+pub async fn run_self_test() -> Result<String> {
+    let settings = load_settings().await;
 
-fn generate_connect_script(
-    profile: &crate::models::Profile,
-    ip_asset: Option<&crate::models::IpAsset>,
-    request: &AutomationRequest,
-    _project_root: &std::path::Path,
-) -> Result<String> {
-    let escaped_name = &profile.name;
-    let escaped_phone = &profile.phone;
-    let escaped_email = &profile.email;
-    let escaped_id_card = &profile.id_card_number;
-    let escaped_infringing_url = &request.infringing_url;
-
-    // Process profile files (identity card documents) - 确保使用真实身份证文件
-    let id_card_files = get_absolute_file_paths(&profile.id_card_files)?;
-    if id_card_files.is_empty() {
-        tracing::warn!("⚠️ 个人档案中未配置身份证文件，请先在个人档案页面上传身份证正反面照片");
-        return Err(anyhow::anyhow!("个人档案中未配置身份证文件。请先在个人档案页面上传身份证正反面照片。"));
-    }
-    tracing::info!("Profile ID card files resolved: {:?}", id_card_files);
-    tracing::info!("✅ 身份证文件数量: {}，请确认包含正反面照片", id_card_files.len());
-
-    // Process IP asset files if available
-    let (auth_files, work_proof_files) = if let Some(asset) = ip_asset {
-        let auth_files = get_absolute_file_paths(&asset.auth_files)?;
-        let work_proof_files = get_absolute_file_paths(&asset.work_proof_files)?;
-        tracing::info!("IP asset auth files resolved: {:?}", auth_files);
-        tracing::info!("IP asset work proof files resolved: {:?}", work_proof_files);
-        (auth_files, work_proof_files)
-    } else {
-        (Vec::new(), Vec::new())
-    };
+    let work_dir = std::env::temp_dir().join("rightsguard-self-test");
+    std::fs::create_dir_all(&work_dir).context("无法创建自测临时目录")?;
+    let fixture_path = work_dir.join("appeal_fixture.html");
+    std::fs::write(&fixture_path, SELF_TEST_FIXTURE_HTML).context("写入自测页面失败")?;
+    // 文件名包含空格和中文，覆盖路径转义问题
+    let id_card_path = work_dir.join("身份证 测试.png");
+    std::fs::write(&id_card_path, SELF_TEST_PNG).context("写入自测图片失败")?;
 
-    // --- 完整的IP资产信息自动填写 ---
-    let ip_section = if let Some(asset) = ip_asset {
-        // 生成完整的IP资产表单填写JavaScript代码
-        format!(r#"
-        console.log('\\n⏰ 阶段4开始时间:', new Date().toISOString());
-        console.log('📋 开始填写完整IP资产信息...');
-        
-        // 填写权利人 - 使用智能选择器策略
-        console.log('👤 开始填写权利人信息...');
-        
-        // 🔍 第一步：分析权利人字段DOM结构
-        console.log('🔍 分析权利人字段DOM结构...');
-        try {{
-            const rightsHolderSection = page.locator('.el-form-item:has-text("权利人")');
-            const sectionExists = await rightsHolderSection.count();
-            console.log(`📊 权利人表单项数量: ${{sectionExists}}`);
-            
-            if (sectionExists > 0) {{
-                const allInputs = await rightsHolderSection.locator('input').all();
-                console.log(`🔍 权利人字段包含 ${{allInputs.length}} 个input元素:`);
-                
-                for (let i = 0; i < Math.min(allInputs.length, 5); i++) {{
-                    const inputType = await allInputs[i].getAttribute('type') || 'text';
-                    const inputClass = await allInputs[i].getAttribute('class') || '';
-                    const inputValue = await allInputs[i].getAttribute('value') || '';
-                    const isVisible = await allInputs[i].isVisible();
-                    console.log(`  Input[${{i}}]: type=${{inputType}}, class="${{inputClass}}", value="${{inputValue}}", visible=${{isVisible}}`);
-                }}
-            }}
-        }} catch (domError) {{
-            console.log('⚠️ DOM分析失败:', domError.message);
-        }}
-        
-        // 🎯 第二步：使用多重选择器策略填写权利人
-        const rightsHolderStrategies = [
-            {{ selector: '.el-form-item:has-text("权利人") input[type="text"]', name: '文本输入框(type=text)' }},
-            {{ selector: '.el-form-item:has-text("权利人") .el-input__inner', name: 'Element UI输入框(.el-input__inner)' }},
-            {{ selector: '.el-form-item:has-text("权利人") input:not([type="radio"]):not([type="checkbox"])', name: '非单选按钮输入框' }},
-            {{ selector: '.el-form-item:has-text("权利人") textarea', name: '文本域' }},
-            {{ selector: '.el-form-item:has-text("权利人") [contenteditable="true"]', name: '可编辑内容元素' }}
-        ];
-        
-        let rightsHolderFilled = false;
-        
-        for (let i = 0; i < rightsHolderStrategies.length && !rightsHolderFilled; i++) {{
-            const strategy = rightsHolderStrategies[i];
-            console.log(`🎯 尝试策略${{i+1}}: ${{strategy.name}} (${{strategy.selector}})`);
-            
-            try {{
-                const element = page.locator(strategy.selector);
-                const count = await element.count();
-                console.log(`   元素数量: ${{count}}`);
-                
-                if (count > 0) {{
-                    const firstElement = element.first();
-                    const isVisible = await firstElement.isVisible({{ timeout: 2000 }});
-                    const isEnabled = await firstElement.isEnabled();
-                    console.log(`   第一个元素: visible=${{isVisible}}, enabled=${{isEnabled}}`);
-                    
-                    if (isVisible && isEnabled) {{
-                        await firstElement.fill({owner});
-                        console.log(`✅ 权利人填写成功! 使用策略: ${{strategy.name}}`);
-                        rightsHolderFilled = true;
-                        
-                        // 验证填写是否成功
-                        await page.waitForTimeout(500);
-                        const filledValue = await firstElement.inputValue().catch(() => '');
-                        console.log(`🔍 验证填写结果: "${{filledValue}}"`);
-                    }} else {{
-                        console.log(`   ⚠️ 元素不可见或不可用`);
-                    }}
-                }}
-            }} catch (strategyError) {{
-                console.log(`   ❌ 策略${{i+1}}失败: ${{strategyError.message}}`);
-            }}
-        }}
-        
-        if (!rightsHolderFilled) {{
-            console.error('❌ 所有权利人填写策略都失败了');
-            console.log('🔍 建议手动检查页面结构或联系开发者');
-        }} else {{
-            console.log('✅ 权利人信息填写完成');
-        }}
-        
-        // 填写授权期限 - 起始时间和结束时间
-        if ({auth_start_date} && {auth_end_date}) {{
-            console.log('📅 设置授权期限...');
-            await page.locator('div').filter({{ hasText: /^授权期限/ }}).getByPlaceholder('起始时间').click();
-            // 等待日期选择器打开，然后选择日期 (暂时使用简化处理)
-            await page.waitForTimeout(500);
-            await page.keyboard.type({auth_start_date_simple});
-            await page.keyboard.press('Tab');
-            
-            await page.locator('div').filter({{ hasText: /^授权期限/ }}).getByPlaceholder('结束时间').click();
-            await page.waitForTimeout(500);
-            await page.keyboard.type({auth_end_date_simple});
-            await page.keyboard.press('Tab');
-        }}
-        
-        // 著作类型选择
-        console.log('🎨 选择著作类型...');
-        await page.locator('div').filter({{ hasText: /^著作类型/ }}).getByPlaceholder('请选择').click();
-        await page.waitForTimeout(500);
-        await page.getByRole('listitem').filter({{ hasText: {work_type} }}).click();
-        
-        // 填写著作名称 - 使用安全选择器策略
-        console.log('📝 开始填写著作名称...');
-        const workNameStrategies = [
-            {{ selector: '.el-form-item:has-text("著作名称") input[type="text"]', name: '文本输入框' }},
-            {{ selector: '.el-form-item:has-text("著作名称") .el-input__inner', name: 'Element UI输入框' }},
-            {{ selector: 'div:has-text("著作名称") input:not([type="radio"]):not([type="checkbox"])', name: '非单选按钮输入框' }},
-            {{ selector: 'div:has-text("著作名称") [role="textbox"]', name: '角色为textbox的元素' }}
-        ];
-        
-        let workNameFilled = false;
-        for (let i = 0; i < workNameStrategies.length && !workNameFilled; i++) {{
-            const strategy = workNameStrategies[i];
-            try {{
-                const element = page.locator(strategy.selector);
-                const count = await element.count();
-                if (count > 0 && await element.first().isVisible({{ timeout: 1000 }})) {{
-                    await element.first().fill({work_name});
-                    console.log(`✅ 著作名称填写成功! 使用: ${{strategy.name}}`);
-                    workNameFilled = true;
-                }}
-            }} catch (error) {{
-                console.log(`⚠️ 著作名称策略${{i+1}}失败: ${{error.message}}`);
-            }}
-        }}
-        
-        if (!workNameFilled) {{
-            console.error('❌ 著作名称填写失败，尝试备用方法...');
-            try {{
-                await page.locator('div').filter({{ hasText: /^著作名称/ }}).getByRole('textbox').fill({work_name});
-                console.log('✅ 著作名称填写成功 (备用方法)');
-            }} catch (backupError) {{
-                console.error('❌ 著作名称备用方法也失败:', backupError.message);
-            }}
-        }}
-        
-        // 地区选择 (默认中国大陆) - 使用精确选择器
-        console.log('🌏 开始设置地区...');
-        const regionStrategies = [
-            {{ selector: '.el-form-item:has-text("地区") .el-select', name: '地区表单项内的下拉选择框' }},
-            {{ selector: '.el-form-item:has-text("地区") .el-input', name: '地区表单项内的输入框' }},
-            {{ selector: 'div:has-text("地区") [role="textbox"]', name: '地区相关的textbox角色元素' }},
-            {{ selector: '.el-form-item:has-text("地区") .el-input__inner', name: '地区表单项内的输入核心元素' }}
+    let fixture_url = reqwest::Url::from_file_path(&fixture_path)
+        .map_err(|_| anyhow::anyhow!("无法生成自测页面地址: {:?}", fixture_path))?;
+    let chrome_path = find_chrome_executable(settings.chrome_path.as_deref()).ok();
+    let script_content = build_self_test_script(
+        fixture_url.as_str(),
+        &normalize_windows_path(&id_card_path.to_string_lossy()),
+        chrome_path.as_deref(),
+    );
+
+    let script_dir = automation_work_dir(&settings)?;
+    let script_name = "self_test.spec.js";
+    let script_path_buf = script_dir.join(script_name);
+    std::fs::write(&script_path_buf, &script_content).context("写入自测脚本失败")?;
+    tracing::info!("🧪 开始运行自测脚本: {:?}", script_path_buf);
+
+    let options = PlaywrightRunOptions::from_settings(&settings);
+    let result = execute_playwright_test(script_name, &script_dir, &options, None, None).await;
+    let _ = std::fs::remove_file(&script_path_buf);
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result.context("自测失败")?;
+
+    tracing::info!("✅ 自测通过");
+    Ok("自测通过: 个人信息、身份证上传和申诉详情均已正确填写".to_string())
+}
+
+pub async fn check_automation_environment_public() -> Result<String> {
+    Ok("环境检查功能就绪。".to_string())
+}
+
+pub async fn continue_after_verification() -> Result<()> {
+    let work_dir = automation_work_dir(&load_settings().await)?;
+    let signal_file = work_dir.join(VERIFICATION_SIGNAL_FILE);
+    std::fs::write(&signal_file, "completed")
+        .with_context(|| format!("写入验证信号文件失败: {:?}", signal_file))?;
+    
+    let mut verification = VERIFICATION_COMPLETED.lock().await;
+    *verification = true;
+    tracing::info!("Verification completed signal sent to Playwright");
+    Ok(())
+}
+
+// ==============================================
+// Core Automation Logic
+// ==============================================
+
+// 以指数退避重试整个自动化流程，仅在最后一次尝试失败后返回错误；成功时返回是否已自动提交
+async fn run_automation_with_retries(request: Arc<AutomationRequest>) -> Result<bool> {
+    let max_retries = request.max_retries.unwrap_or(0);
+    let mut attempt = 0;
+    // 总时限覆盖所有重试，超时后不再等待卡住的步骤
+    // 数据库中的设置未经save_settings校验，这里再限制一次范围
+    let deadline_secs = match request.overall_deadline_secs {
+        Some(secs) => secs,
+        None => load_settings().await.overall_deadline_secs,
+    }
+    .clamp(1, MAX_OVERALL_DEADLINE_SECS);
+    let deadline = tokio::time::Instant::now()
+        .checked_add(tokio::time::Duration::from_secs(deadline_secs))
+        .context("运行总时限过大")?;
+
+    loop {
+        let result = match tokio::time::timeout_at(deadline, run_automation_process(request.clone())).await {
+            Ok(result) => result,
+            Err(_) => return Err(abort_on_deadline(deadline_secs).await),
+        };
+        let error = match result {
+            Ok(submitted) => return Ok(submitted),
+            Err(e) => e,
+        };
+
+        if attempt >= max_retries || !is_retryable_error(&error) {
+            return Err(error);
+        }
+        if !AUTOMATION_STATUS.lock().await.is_running {
+            tracing::info!("自动化已被停止，不再重试");
+            return Err(error);
+        }
+
+        attempt += 1;
+        let backoff = RETRY_BASE_DELAY_SECS * 2u64.pow(attempt - 1);
+        if tokio::time::Instant::now() + tokio::time::Duration::from_secs(backoff) >= deadline {
+            tracing::warn!("剩余时间不足以进行第{}次重试，不再重试", attempt);
+            return Err(error);
+        }
+        tracing::warn!("自动化流程失败 ({:#})，{}秒后进行第{}/{}次重试", error, backoff, attempt, max_retries);
+        update_status(Message::with_args("step.retrying", vec![attempt.to_string(), backoff.to_string()]), 0.0).await;
+        tokio::time::sleep(tokio::time::Duration::from_secs(backoff)).await;
+
+        // 每次重试都重新启动Chrome
+        if let Some(mut child) = CHROME_PROCESS.lock().await.take() {
+            let _ = child.kill();
+        }
+        if let Err(e) = close_existing_chrome().await {
+            tracing::warn!("重试前关闭Chrome失败: {}", e);
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    }
+}
+
+// 运行超过总时限: 结束Playwright和Chrome进程，返回包含当时所在步骤的错误
+async fn abort_on_deadline(deadline_secs: u64) -> anyhow::Error {
+    let stage = AUTOMATION_STATUS.lock().await.current_step.clone().unwrap_or_else(|| "未知".to_string());
+    tracing::error!("⏰ 运行超过总时限{}秒，终止于: {}", deadline_secs, stage);
+    if kill_tracked_playwright() {
+        tracing::info!("已结束超时的Playwright进程");
+    }
+    if let Some(mut child) = CHROME_PROCESS.lock().await.take() {
+        if let Err(e) = child.kill() {
+            tracing::warn!("结束超时运行的Chrome失败: {}", e);
+        }
+    }
+    AutomationError::DeadlineExceeded { stage, secs: deadline_secs }.into()
+}
+
+// 确定性错误 (数据缺失、文件校验、表单校验) 重试也不会成功；未识别的错误(如网络问题)允许重试
+fn is_retryable_error(error: &anyhow::Error) -> bool {
+    automation_error(error).map_or(true, AutomationError::is_retryable)
+}
+
+async fn run_automation_process(request: Arc<AutomationRequest>) -> Result<bool> {
+    update_status(Message::new("step.fetching_data"), 5.0).await;
+    let settings = load_settings().await;
+    let (profile, ip_asset) = load_run_data(&request).await?;
+
+    update_status(Message::new("step.launching_browser"), 10.0).await;
+    let user_agent = request.user_agent.as_deref().or(settings.user_agent.as_deref());
+    tracing::info!("Chrome User-Agent: {}", user_agent.unwrap_or("Chrome默认"));
+    start_chrome_with_remote_debugging(&settings, request.force_fresh, user_agent).await.context("启动带调试端口的Chrome失败")?;
+
+    if settings.automation_backend == AutomationBackend::NativeRust {
+        let run_result = run_native_backend(&profile, ip_asset.as_ref(), &request, &settings).await?;
+        return finish_run(&request, Some(&run_result)).await;
+    }
+
+    update_status(Message::new("step.generating_script"), 25.0).await;
+    let work_dir = automation_work_dir(&settings)?;
+    let script_path_buf = work_dir.join(CONNECT_SCRIPT_NAME);
+
+    let site = site_automation(request.platform)?;
+    let script_content = site.build_script(&profile, ip_asset.as_ref(), &request, &settings)?;
+    std::fs::write(&script_path_buf, &script_content).context("写入Playwright脚本失败")?;
+    tracing::info!("Playwright脚本已生成: {:?}", script_path_buf);
+    
+    update_status(Message::new("step.starting_playwright"), 35.0).await;
+    tracing::info!("🚀 开始执行Playwright脚本，监控日志输出...");
+    let options = PlaywrightRunOptions {
+        config: request.playwright_config.as_deref(),
+        project: request.playwright_project.as_deref(),
+        ..PlaywrightRunOptions::from_settings(&settings)
+    };
+    let output = execute_playwright_test(CONNECT_SCRIPT_NAME, &work_dir, &options, Some(site.as_ref()), request.run_id).await.context("执行Playwright脚本失败")?;
+    match &output.run_result {
+        Some(result) if !result.errors.is_empty() => tracing::warn!("⚠️ 脚本报告了错误: {:?}", result.errors),
+        Some(result) => tracing::info!(
+            "✅ 脚本到达阶段 {}，填写{}个字段，上传{}个文件",
+            result.stage_reached, result.fields_filled.len(), result.files_uploaded
+        ),
+        None => tracing::warn!("⚠️ 脚本未写出运行结果，只能根据退出码判断"),
+    }
+    
+    update_status(Message::new("step.script_finished"), 90.0).await;
+    tracing::info!("✅ Playwright脚本执行完成，检查输出结果...");
+    let _ = std::fs::remove_file(&script_path_buf);
+
+    finish_run(&request, output.run_result.as_ref()).await
+}
+
+// 原生后端不经过npx，只支持B站，且需要以native-playwright特性编译
+#[cfg(feature = "native-playwright")]
+async fn run_native_backend(
+    profile: &Profile,
+    ip_asset: Option<&IpAsset>,
+    request: &AutomationRequest,
+    settings: &AppSettings,
+) -> Result<RunResult> {
+    if request.platform != SitePlatform::Bilibili {
+        anyhow::bail!("原生Playwright后端目前只支持B站");
+    }
+    update_status(Message::new("step.native_filling"), 35.0).await;
+    let run_result = match crate::native_backend::run_bilibili(profile, ip_asset, request, settings).await {
+        Ok(run_result) => run_result,
+        // 用户关闭Chrome时playwright crate只返回普通的连接错误，调试端口不再响应时统一转换
+        Err(e) if automation_error(&e).is_none() && !check_chrome_debug_port(settings).await => {
+            tracing::warn!("运行中Chrome被关闭: {:#}", e);
+            return Err(AutomationError::BrowserClosed.into());
+        }
+        Err(e) => return Err(e),
+    };
+    if let Some(run_id) = request.run_id {
+        if let Err(e) = crate::database::save_run_result(run_id, &run_result).await {
+            tracing::warn!("保存运行结果失败: {}", e);
+        }
+    }
+    update_status(Message::new("step.native_finished"), 90.0).await;
+    Ok(run_result)
+}
+
+#[cfg(not(feature = "native-playwright"))]
+async fn run_native_backend(
+    _profile: &Profile,
+    _ip_asset: Option<&IpAsset>,
+    _request: &AutomationRequest,
+    _settings: &AppSettings,
+) -> Result<RunResult> {
+    anyhow::bail!("当前版本未编译原生Playwright后端，请在设置中改用npx方式运行")
+}
+
+// 两种后端共用的收尾: 按设置关闭Chrome、更新最终状态并记录案件
+async fn finish_run(request: &AutomationRequest, run_result: Option<&RunResult>) -> Result<bool> {
+    if !request.keep_browser_open {
+        if let Some(mut child) = CHROME_PROCESS.lock().await.take() {
+            // 用户已手动关闭Chrome时不需要再结束进程
+            if let Ok(Some(status)) = child.try_wait() {
+                tracing::info!("Chrome已退出 ({})，无需关闭", status);
+            } else {
+                match child.kill() {
+                    Ok(()) => tracing::info!("按设置在运行成功后关闭Chrome"),
+                    Err(e) => tracing::warn!("运行成功后关闭Chrome失败: {}", e),
+                }
+            }
+        }
+    }
+
+    // 测试模式在提交前停止，也不记录案件，避免测试页面出现在案件列表和每日次数中
+    if request.test_mode {
+        update_status(Message::new("step.test_run_finished"), 100.0).await;
+        tracing::info!("🧪 测试模式运行完成，跳过案件记录");
+        return Ok(false);
+    }
+
+    // 自动提交时脚本在未确认提交成功时会失败退出，因此缺少运行结果时按请求判断
+    let submitted = run_result.map_or(request.auto_submit, |result| result.submitted);
+    update_status(final_step(submitted), 100.0).await;
+    save_case_record(request, run_result, submitted).await?;
+    Ok(submitted)
+}
+
+// 只有脚本确认点击了提交才报告提交成功，否则页面仍等待用户手动提交
+fn final_step(submitted: bool) -> Message {
+    if submitted {
+        Message::new("step.submitted")
+    } else {
+        Message::new("step.awaiting_confirmation")
+    }
+}
+
+pub(crate) async fn update_status(step: Message, progress: f32) {
+    let mut status = AUTOMATION_STATUS.lock().await;
+    set_step(&mut status, &step);
+    status.progress = Some(progress);
+    let snapshot = cache_status(&status);
+    drop(status);
+    emit_status(&snapshot);
+}
+
+// 向前端发送状态事件并持久化到数据库
+async fn publish_status(status: &AutomationStatus) {
+    emit_status(status);
+    if let Err(e) = crate::database::save_automation_status(status).await {
+        tracing::warn!("保存自动化状态失败: {}", e);
+    }
+}
+
+/// 自动化结束时发送系统通知，用户在设置中关闭通知时跳过
+async fn notify_run_finished(title: &str, body: &str) {
+    if !load_settings().await.notifications_enabled {
+        tracing::info!("系统通知已关闭，跳过发送");
+        return;
+    }
+
+    if let Ok(app_handle) = crate::database::app_handle() {
+        if let Err(e) = app_handle.notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+        {
+            tracing::warn!("发送系统通知失败: {}", e);
+        }
+    }
+}
+
+/// 构建HTTP客户端，配置代理时本地地址绕过代理
+pub fn build_http_client(proxy: Option<&str>, timeout_secs: u64) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout_secs));
+    if let Some(proxy_url) = proxy {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .with_context(|| format!("代理地址无效: {}", proxy_url))?
+            .no_proxy(reqwest::NoProxy::from_string(&PROXY_BYPASS_HOSTS.join(",")));
+        tracing::debug!("HTTP客户端使用代理: {}", proxy_url);
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
+}
+
+// User-Agent的长度上限，正常浏览器的UA远小于该值
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// 校验User-Agent: 非空、不超长，只包含可见ASCII字符 (换行等会破坏Chrome命令行和请求头)
+pub fn validate_user_agent(raw_user_agent: &str) -> Result<String> {
+    let trimmed = raw_user_agent.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow::anyhow!("User-Agent不能为空"));
+    }
+    if trimmed.len() > MAX_USER_AGENT_LEN {
+        return Err(anyhow::anyhow!("User-Agent过长 ({}个字符)，最多{}个", trimmed.len(), MAX_USER_AGENT_LEN));
+    }
+    if let Some(invalid) = trimmed.chars().find(|c| !(c.is_ascii_graphic() || *c == ' ')) {
+        return Err(anyhow::anyhow!("User-Agent包含不允许的字符: {:?}", invalid));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// 校验代理地址格式，如 http://127.0.0.1:7890 或 socks5://host:1080
+pub fn validate_proxy_url(raw_proxy: &str) -> Result<String> {
+    let trimmed = raw_proxy.trim();
+    let parsed = reqwest::Url::parse(trimmed)
+        .with_context(|| format!("代理地址格式无效: {}", trimmed))?;
+    if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        return Err(anyhow::anyhow!("代理协议必须是http、https或socks5: {}", trimmed));
+    }
+    if parsed.host_str().map_or(true, str::is_empty) || parsed.port_or_known_default().is_none() {
+        return Err(anyhow::anyhow!("代理地址需包含主机和端口: {}", trimmed));
+    }
+    Ok(trimmed.trim_end_matches('/').to_string())
+}
+
+// 读取应用设置，失败时使用默认值，避免设置问题阻断自动化
+async fn load_settings() -> AppSettings {
+    crate::database::get_settings().await.unwrap_or_else(|e| {
+        tracing::warn!("读取应用设置失败，使用默认设置: {}", e);
+        AppSettings::default()
+    })
+}
+
+fn emit_status(status: &AutomationStatus) {
+    if let Ok(app_handle) = crate::database::app_handle() {
+        if let Err(e) = app_handle.emit(AUTOMATION_STATUS_EVENT, status) {
+            tracing::warn!("发送自动化状态事件失败: {}", e);
+        }
+    }
+}
+
+pub(crate) fn find_npx_executable() -> Result<String> {
+    let possible_paths = vec![
+        "C:\\Program Files\\nodejs\\npx.cmd",
+        "C:\\Program Files (x86)\\nodejs\\npx.cmd",
+    ];
+    for path in possible_paths {
+        if std::path::Path::new(path).exists() {
+            return Ok(path.to_string());
+        }
+    }
+    if Command::new("npx").arg("--version").output().is_ok() {
+        return Ok("npx".to_string());
+    }
+    Err(AutomationError::NpxNotFound.into())
+}
+
+// 项目根目录下的Playwright配置，运行时未指定配置则使用它
+const DEFAULT_PLAYWRIGHT_CONFIG: &str = "playwright.config.js";
+
+// 在工作目录中生成的配置: 继承项目配置并把testDir指向工作目录
+const GENERATED_PLAYWRIGHT_CONFIG: &str = "rightsguard.playwright.config.js";
+
+// 未设置覆盖目录时，生成脚本和信号文件放在应用数据目录下的该子目录
+const AUTOMATION_WORK_DIR_NAME: &str = "automation";
+
+/// 生成脚本、验证信号文件和Playwright配置所在的目录
+/// 安装版的当前目录不可预测甚至只读，因此默认使用应用数据目录，可在设置中覆盖
+pub(crate) fn automation_work_dir(settings: &AppSettings) -> Result<std::path::PathBuf> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::env::temp_dir());
+    resolve_work_dir(settings, &cwd)
+}
+
+// 当前目录只用于解析设置中的相对路径，由调用方传入，测试无需切换进程的当前目录
+fn resolve_work_dir(settings: &AppSettings, cwd: &std::path::Path) -> Result<std::path::PathBuf> {
+    let dir = match settings.script_output_dir.as_deref() {
+        Some(dir) => cwd.join(dir),
+        None => match crate::database::app_handle()
+            .and_then(|app| app.path().app_data_dir().map_err(anyhow::Error::from))
+        {
+            Ok(app_data_dir) => app_data_dir.join(AUTOMATION_WORK_DIR_NAME),
+            Err(e) => {
+                tracing::warn!("无法获取应用数据目录，生成脚本写入临时目录: {:#}", e);
+                std::env::temp_dir().join("rightsguard").join(AUTOMATION_WORK_DIR_NAME)
+            }
+        },
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("无法创建脚本输出目录: {:?}", dir))?;
+    Ok(dir)
+}
+
+// 开发环境中包含node_modules的项目根目录 (src-tauri的上一级)，安装版中通常不存在
+fn node_project_root() -> Option<std::path::PathBuf> {
+    let root = std::env::current_dir().ok()?.parent()?.to_path_buf();
+    root.join("node_modules").is_dir().then_some(root)
+}
+
+// npx playwright test 的命令行选项
+struct PlaywrightRunOptions<'a> {
+    config: Option<&'a str>,
+    project: Option<&'a str>,
+    timeout_secs: u64,
+    // 在主日志中逐行记录stdout
+    verbose_logs: bool,
+}
+
+impl PlaywrightRunOptions<'_> {
+    fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            config: None,
+            project: None,
+            timeout_secs: settings.playwright_timeout_secs,
+            verbose_logs: settings.verbose_automation_logs,
+        }
+    }
+
+    fn to_args(&self, script_name: &str, work_dir: &std::path::Path) -> Result<Vec<String>> {
+        let config = self.write_config(work_dir)?;
+        let mut args = vec![
+            "playwright".to_string(),
+            "test".to_string(),
+            script_name.to_string(),
+            format!("--timeout={}", self.timeout_secs.saturating_mul(1000)),
+            format!("--config={}", config.to_string_lossy()),
         ];
-        
-        let regionSelected = false;
-        for (let i = 0; i < regionStrategies.length && !regionSelected; i++) {{
-            const strategy = regionStrategies[i];
-            try {{
-                const element = page.locator(strategy.selector);
-                const count = await element.count();
-                console.log(`🔍 地区策略${{i+1}}: 找到${{count}}个元素 (${{strategy.name}})`);
-                
-                if (count > 0) {{
-                    const firstElement = element.first();
-                    const isVisible = await firstElement.isVisible({{ timeout: 1000 }});
-                    if (isVisible) {{
-                        console.log(`👆 点击地区选择器: ${{strategy.name}}`);
-                        await firstElement.click();
-                        await page.waitForTimeout(500);
-                        
-                        // 选择"中国大陆"选项
-                        const option = page.getByRole('listitem').filter({{ hasText: '中国大陆' }});
-                        const optionExists = await option.count();
-                        console.log(`🔍 "中国大陆"选项数量: ${{optionExists}}`);
-                        
-                        if (optionExists > 0) {{
-                            await option.first().click();
-                            console.log('✅ 地区选择成功: 中国大陆');
-                            regionSelected = true;
-                        }}
-                    }}
-                }}
-            }} catch (error) {{
-                console.log(`⚠️ 地区选择策略${{i+1}}失败: ${{error.message}}`);
-            }}
-        }}
-        
-        // 备用方法：使用原始选择器
-        if (!regionSelected) {{
-            console.log('🔄 使用备用地区选择方法...');
-            try {{
-                await page.getByRole('textbox', {{ name: '请选择' }}).nth(1).click();
-                await page.waitForTimeout(500);
-                await page.getByRole('listitem').filter({{ hasText: '中国大陆' }}).click();
-                console.log('✅ 地区选择成功 (备用方法)');
-            }} catch (backupError) {{
-                console.error('❌ 地区选择备用方法失败:', backupError.message);
-            }}
-        }}
-        
-        // 填写期限 (作品有效期)
-        if ({work_start_date} && {work_end_date}) {{
-            console.log('⏰ 设置作品期限...');
-            await page.locator('div').filter({{ hasText: /^期限/ }}).getByPlaceholder('起始时间').click();
-            await page.waitForTimeout(500);
-            await page.keyboard.type({work_start_date_simple});
-            await page.keyboard.press('Tab');
-            
-            await page.locator('div').filter({{ hasText: /^期限/ }}).getByPlaceholder('结束时间').click();
-            await page.waitForTimeout(500);
-            await page.keyboard.type({work_end_date_simple});
-            await page.keyboard.press('Tab');
-        }}
-        
-        // 上传授权证明文件
-        {auth_files_upload_code}
-        
-        // 上传作品证明文件  
-        {work_proof_files_upload_code}
-        
-        console.log('✅ IP资产完整信息填写完成');
-        console.log('👆 点击下一步按钮...');
-        await page.getByRole('button', {{ name: '下一步' }}).click();
-        await page.waitForTimeout(2000);
-"#,
-            owner = serde_json::to_string(&asset.owner).unwrap(),
-            work_type = serde_json::to_string(&asset.work_type).unwrap(),
-            work_name = serde_json::to_string(&asset.work_name).unwrap(),
-            auth_start_date = asset.auth_start_date.is_some().to_string(),
-            auth_end_date = asset.auth_end_date.is_some().to_string(),
-            auth_start_date_simple = serde_json::to_string(&asset.auth_start_date.as_deref().unwrap_or("")).unwrap(),
-            auth_end_date_simple = serde_json::to_string(&asset.auth_end_date.as_deref().unwrap_or("")).unwrap(),
-            work_start_date = (!asset.work_start_date.is_empty()).to_string(),
-            work_end_date = (!asset.work_end_date.is_empty()).to_string(),
-            work_start_date_simple = serde_json::to_string(&asset.work_start_date).unwrap(),
-            work_end_date_simple = serde_json::to_string(&asset.work_end_date).unwrap(),
-            auth_files_upload_code = generate_auth_files_upload_code(&auth_files),
-            work_proof_files_upload_code = generate_work_proof_files_upload_code(&work_proof_files)
-        )
-    } else { 
-        // If no IP asset, this string will be empty.
-        "".to_string() 
+        if let Some(project) = self.project {
+            args.push(format!("--project={}", project));
+        }
+        Ok(args)
+    }
+
+    // 指定的配置 (绝对路径或相对项目根目录) 或项目默认配置，不存在时不继承任何配置
+    fn base_config(&self) -> Result<Option<std::path::PathBuf>> {
+        let project_root = node_project_root();
+        match self.config {
+            Some(config) => {
+                let path = std::path::Path::new(config);
+                let path = match &project_root {
+                    Some(root) if path.is_relative() => root.join(path),
+                    _ => path.to_path_buf(),
+                };
+                if !path.is_file() {
+                    return Err(anyhow::anyhow!("Playwright配置文件不存在: {}", config));
+                }
+                Ok(Some(path))
+            }
+            None => Ok(project_root
+                .map(|root| root.join(DEFAULT_PLAYWRIGHT_CONFIG))
+                .filter(|path| path.is_file())),
+        }
+    }
+
+    // 脚本不在项目的tests目录中，生成一个继承原配置、testDir指向工作目录的配置
+    fn write_config(&self, work_dir: &std::path::Path) -> Result<std::path::PathBuf> {
+        let base = match self.base_config()? {
+            Some(path) => format!("...require({})", js_string_literal(&path.to_string_lossy())),
+            None => "workers: 1, reporter: [['list']]".to_string(),
+        };
+        let content = format!("module.exports = {{ {}, testDir: __dirname }};\n", base);
+        let config_path = work_dir.join(GENERATED_PLAYWRIGHT_CONFIG);
+        std::fs::write(&config_path, content).context("写入Playwright配置失败")?;
+        Ok(config_path)
+    }
+}
+
+// 安装和版本检查的超时；安装需要下载浏览器，给得更宽松
+const PLAYWRIGHT_VERSION_TIMEOUT_SECS: u64 = 60;
+const PLAYWRIGHT_INSTALL_TIMEOUT_SECS: u64 = 600;
+
+// 与运行脚本时相同的环境执行 npx playwright <args>，返回stdout
+async fn run_playwright_cli(args: &[&str], timeout_secs: u64) -> Result<String> {
+    let npx_path = find_npx_executable()?;
+    let work_dir = match node_project_root() {
+        Some(root) => root,
+        None => automation_work_dir(&load_settings().await)?,
     };
+    let mut cmd = tokio::process::Command::new(&npx_path);
+    // --no-install: 未安装Playwright时直接失败，而不是从网络下载
+    cmd.arg("--no-install")
+       .arg("playwright")
+       .args(args)
+       .env("PLAYWRIGHT_BROWSERS_PATH", "0")
+       .current_dir(&work_dir)
+       .kill_on_drop(true);
+    if let Some(root) = node_project_root() {
+        cmd.env("NODE_PATH", root.join("node_modules"));
+    }
+    let output = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), cmd.output())
+        .await
+        .map_err(|_| anyhow::anyhow!("npx playwright {} 超时 ({}秒)", args.join(" "), timeout_secs))?
+        .context("启动npx失败")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("npx playwright {} 失败: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
 
-    // Generate file upload sections - Fixed to match B站 form structure
-    let id_card_upload_section = if !id_card_files.is_empty() {
-        let files_array = id_card_files.iter()
-            .map(|path| escape_file_path_for_js_array(path))
-            .collect::<Vec<_>>()
-            .join(", ");
-        let files_display = id_card_files.iter()
-            .map(|path| {
-                let filename = path.split(['/', '\\']).last().unwrap_or(path);
-                serde_json::to_string(filename).unwrap()
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-        format!(r#"
-        console.log('🆔 开始上传真实身份证文件（来自个人档案配置）...');
-        console.log('📁 身份证文件列表:', [{}]);
-        console.log('🚦 文件上传模块启动 - 即将开始上传流程...');
+/// 已安装的Playwright版本，未安装时返回错误
+pub(crate) async fn playwright_version() -> Result<String> {
+    run_playwright_cli(&["--version"], PLAYWRIGHT_VERSION_TIMEOUT_SECS).await
+}
+
+/// 运行时使用 PLAYWRIGHT_BROWSERS_PATH=0，浏览器安装在 playwright-core/.local-browsers 中
+/// 找不到项目的node_modules (如安装版) 时无法判断，返回None
+pub(crate) fn playwright_browser_installed() -> Option<bool> {
+    let browsers_dir = node_project_root()?.join("node_modules").join("playwright-core").join(".local-browsers");
+    let installed = std::fs::read_dir(&browsers_dir).map_or(false, |entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("chromium"))
+    });
+    Some(installed)
+}
+
+/// 下载Playwright使用的Chromium，可能需要几分钟
+pub(crate) async fn install_playwright_browser() -> Result<()> {
+    tracing::info!("开始安装Playwright浏览器 (chromium)");
+    run_playwright_cli(&["install", "chromium"], PLAYWRIGHT_INSTALL_TIMEOUT_SECS).await?;
+    tracing::info!("Playwright浏览器安装完成");
+    Ok(())
+}
+
+// Playwright运行的输出；run_result仅在脚本写出结果文件时存在
+struct PlaywrightOutput {
+    stdout: String,
+    run_result: Option<RunResult>,
+}
+
+// 运行Playwright脚本，成功时返回stdout供调用方解析脚本输出
+// 传入site时逐行读取stdout，根据脚本的阶段日志更新进度
+async fn execute_playwright_test(
+    script_name: &str,
+    work_dir: &std::path::Path,
+    options: &PlaywrightRunOptions<'_>,
+    site: Option<&dyn SiteAutomation>,
+    run_id: Option<uuid::Uuid>,
+) -> Result<PlaywrightOutput> {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+    let npx_path = find_npx_executable()?;
+    let args = options.to_args(script_name, work_dir)?;
+    tracing::info!("Playwright参数: {:?}", args);
+    let result_path = run_result_path(run_id);
+    let _ = std::fs::remove_file(&result_path);
+    let mut cmd = tokio::process::Command::new(&npx_path);
+    if let Some(screenshot_path) = submission_screenshot_path(run_id) {
+        cmd.env(SUBMISSION_SCREENSHOT_PATH_ENV, &screenshot_path);
+    }
+    cmd.args(&args)
+       .env("PLAYWRIGHT_BROWSERS_PATH", "0")
+       .env(RUN_RESULT_PATH_ENV, &result_path)
+       .current_dir(work_dir)
+       .stdout(std::process::Stdio::piped())
+       .stderr(std::process::Stdio::piped());
+    // 脚本不在项目目录下，通过NODE_PATH让require('@playwright/test')找到项目依赖
+    if let Some(root) = node_project_root() {
+        cmd.env("NODE_PATH", root.join("node_modules"));
+    }
         
-        try {{
-            const idCardFiles = [{}];
-            console.log('📊 文件数量:', idCardFiles.length, '，请确认包含身份证正反面');
-            
-            // ✅ 验证身份证文件完整性
-            console.log('🔍 身份证文件验证开始...');
-            for (let i = 0; i < idCardFiles.length; i++) {{
-                const filePath = idCardFiles[i];
-                const fileName = filePath.split(/[/\\\\]/).pop();
-                console.log(`📄 第${{i+1}}个文件: ${{fileName}}`);
-                console.log(`📍 完整路径: ${{filePath}}`);
-            }}
-            
-            if (idCardFiles.length === 1) {{
-                console.log('⚠️ 只检测到1个身份证文件，建议上传正反面两张照片');
-            }} else if (idCardFiles.length === 2) {{
-                console.log('✅ 检测到2个身份证文件，符合正反面要求');
-            }} else {{
-                console.log(`📊 检测到${{idCardFiles.length}}个身份证文件`);
-            }}
-            
-            // 🔍 第一步：详细DOM结构分析 - 专门针对版权图片上传区域
-            console.log('🔍 开始版权图片上传区域DOM结构深度分析...');
-            console.log('🎯 DEBUG: 检查修复后的策略是否生效 - 这是新增的调试信息');
-            
-            // 🔍 关键诊断：检查所有可能的文件输入元素
-            console.log('🔍 开始全面文件输入元素检测...');
-            try {{
-                // 检查.el-upload__input元素
-                const elUploadInputCount = await page.locator('.el-upload__input').count();
-                console.log(`📊 .el-upload__input 元素数量: ${{elUploadInputCount}}`);
-                
-                if (elUploadInputCount > 0) {{
-                    for (let i = 0; i < elUploadInputCount; i++) {{
-                        const element = page.locator('.el-upload__input').nth(i);
-                        const isVisible = await element.isVisible();
-                        const isEnabled = await element.isEnabled();
-                        const attributes = await element.evaluate(el => {{
-                            return {{
-                                id: el.id,
-                                className: el.className,
-                                name: el.name,
-                                type: el.type,
-                                accept: el.accept,
-                                multiple: el.multiple,
-                                style: el.style.cssText
-                            }};
-                        }});
-                        console.log(`📄 .el-upload__input[${{i}}]: visible=${{isVisible}}, enabled=${{isEnabled}}`);
-                        console.log(`📄 属性:`, JSON.stringify(attributes, null, 2));
-                    }}
-                }}
-                
-                // 检查所有input[type=\"file\"]元素
-                const allFileInputs = await page.locator('input[type=\"file\"]').count();
-                console.log(`📊 所有 input[type=\"file\"] 数量: ${{allFileInputs}}`);
-                
-                if (allFileInputs > 0) {{
-                    for (let i = 0; i < Math.min(allFileInputs, 3); i++) {{ // 限制检查前3个
-                        const element = page.locator('input[type=\"file\"]').nth(i);
-                        const isVisible = await element.isVisible();
-                        const isEnabled = await element.isEnabled();
-                        const selector = await element.evaluate(el => {{
-                            // 生成元素的唯一选择器
-                            const classes = el.className ? '.' + el.className.split(' ').join('.') : '';
-                            const id = el.id ? '#' + el.id : '';
-                            return `input[type=\"file\"]${{id}}${{classes}}`;
-                        }});
-                        console.log(`📄 FileInput[${{i}}]: visible=${{isVisible}}, enabled=${{isEnabled}}, selector: ${{selector}}`);
-                    }}
-                }}
-                
-                // 检查.el-upload元素
-                const elUploadCount = await page.locator('.el-upload').count();
-                console.log(`📊 .el-upload 元素数量: ${{elUploadCount}}`);
-                
-            }} catch (domAnalysisError) {{
-                console.error('❌ 文件输入元素检测失败:', domAnalysisError.message);
-            }}
-            
-            try {{
-                // 直接定位版权图片上传区域
-                const copyrightUploadArea = page.locator('.copyright-img-upload');
-                const areaExists = await copyrightUploadArea.count();
-                console.log(`📍 版权图片上传区域数量: ${{areaExists}}`);
-                
-                if (areaExists > 0) {{
-                    // 获取版权上传区域的完整HTML结构
-                    const areaHTML = await copyrightUploadArea.first().innerHTML();
-                    console.log('📋 版权上传区域完整HTML:');
-                    console.log(areaHTML);
-                    
-                    // 检查el-upload--picture-card元素
-                    const pictureCardUpload = await copyrightUploadArea.first().locator('.el-upload--picture-card').count();
-                    console.log(`🖼️ picture-card上传组件数量: ${{pictureCardUpload}}`);
-                    
-                    // 检查加号图标
-                    const plusIcon = await copyrightUploadArea.first().locator('.el-icon-plus').count();
-                    console.log(`➕ 加号图标数量: ${{plusIcon}}`);
-                    
-                    // 检查文件输入元素
-                    const fileInputs = await copyrightUploadArea.first().locator('input[type="file"]').count();
-                    console.log(`📁 文件输入元素数量: ${{fileInputs}}`);
-                    
-                    // 逐个检查文件输入元素的详细信息
-                    for (let i = 0; i < fileInputs; i++) {{
-                        const input = copyrightUploadArea.first().locator('input[type="file"]').nth(i);
-                        const inputClass = await input.getAttribute('class') || '';
-                        const inputName = await input.getAttribute('name') || '';
-                        const isVisible = await input.isVisible();
-                        console.log(`📁 FileInput[${{i}}]: class="${{inputClass}}", name="${{inputName}}", visible=${{isVisible}}`);
-                    }}
-                    
-                    // 检查可点击的上传触发器
-                    const clickableTriggers = await copyrightUploadArea.first().locator('[tabindex="0"], .el-upload--picture-card').count();
-                    console.log(`👆 可点击上传触发器数量: ${{clickableTriggers}}`);
-                    
-                    // 检查上传列表区域
-                    const uploadList = await copyrightUploadArea.first().locator('.el-upload-list').count();
-                    console.log(`📋 上传列表区域数量: ${{uploadList}}`);
-                    
-                }} else {{
-                    console.log('❌ 未找到.copyright-img-upload区域！');
-                    
-                    // 查找其他可能的上传区域
-                    const allUploadElements = await page.locator('[class*="upload"]').count();
-                    console.log(`🔍 页面所有包含upload的元素数量: ${{allUploadElements}}`);
-                    
-                    const allFileInputs = await page.locator('input[type="file"]').count();
-                    console.log(`📁 页面所有文件输入数量: ${{allFileInputs}}`);
-                    
-                    // 显示页面所有可能相关的class
-                    const uploadClasses = await page.locator('[class*="upload"], [class*="img"], [class*="picture"]').allInnerTexts();
-                    console.log('🎨 可能相关的上传元素:', uploadClasses.slice(0, 10));
-                }}
-            }} catch (domError) {{
-                console.error('❌ DOM分析失败:', domError.message);
-            }}
-            
-            // 🎯 优化策略顺序 - 优先使用不依赖文件选择器的方法
-            const selectorStrategies = [
-                // 策略1: Element UI组件直接API调用 - 最专业的方法
-                {{ selector: '.el-upload', type: 'element_ui_api', name: 'Element UI组件API直接调用' }},
-                // 策略2: 隐藏文件输入直接设置 - 最可靠，不检查可见性
-                {{ selector: '.el-upload__input', type: 'hidden_input', name: '隐藏文件输入直接设置' }},
-                // 策略3: 通用文件输入直接设置 - 需要检查可见性
-                {{ selector: 'input[type=\"file\"]', type: 'visible_input', name: '通用文件输入直接设置' }},
-                // 策略4: FileChooser API方法 - 如果支持的话，程序化设置
-                {{ selector: '.el-upload', type: 'chooser', name: 'FileChooser API设置' }},
-                // 策略5: 用户验证方法作为最后备用 - 可能打开选择界面
-                {{ selector: '.el-upload', type: 'fallback', name: '点击后直接设置（备用）' }}
-            ];
-            
-            console.log('🔍 开始5级智能选择器检测（Element UI API优先，避免文件选择器依赖）...');
-            
-            // 🔍 增强文件验证和错误处理
-            console.log('📁 开始全面文件验证...');
-            let validFiles = [];
-            let fileValidationErrors = [];
-            
-            for (let i = 0; i < idCardFiles.length; i++) {{
-                const filePath = idCardFiles[i];
-                console.log(`\n🔍 验证文件${{i+1}}: ${{filePath}}`);
-                
-                try {{
-                    const fs = require('fs');
-                    const exists = fs.existsSync(filePath);
-                    
-                    if (exists) {{
-                        const stats = fs.statSync(filePath);
-                        const fileName = filePath.split(/[/\\\\]/).pop();
-                        const fileSize = stats.size;
-                        const isImage = /\.(png|jpg|jpeg|gif|bmp|webp)$/i.test(fileName);
-                        
-                        console.log(`✅ 文件${{i+1}}验证通过:`);
-                        console.log(`   📄 文件名: ${{fileName}}`);
-                        console.log(`   📊 文件大小: ${{fileSize}} bytes (${{(fileSize/1024/1024).toFixed(2)}} MB)`);
-                        console.log(`   🖼️ 图片格式: ${{isImage ? '是' : '否'}}`);
-                        console.log(`   📅 修改时间: ${{stats.mtime}}`);
-                        
-                        // 检查文件大小合理性
-                        if (fileSize === 0) {{
-                            console.log(`⚠️ 文件${{i+1}}大小为0，可能是空文件`);
-                            fileValidationErrors.push(`文件${{i+1}}为空文件`);
-                        }} else if (fileSize > 10 * 1024 * 1024) {{
-                            console.log(`⚠️ 文件${{i+1}}超过10MB，可能过大`);
-                        }}
-                        
-                        if (!isImage) {{
-                            console.log(`⚠️ 文件${{i+1}}可能不是图片格式`);
-                        }}
-                        
-                        validFiles.push(filePath);
-                        
-                    }} else {{
-                        console.log(`❌ 文件${{i+1}}不存在: ${{filePath}}`);
-                        fileValidationErrors.push(`文件${{i+1}}不存在: ${{filePath}}`);
-                        
-                        // 路径问题诊断
-                        console.log(`🔍 路径诊断:`);
-                        console.log(`   长度: ${{filePath.length}} 字符`);
-                        console.log(`   包含空格: ${{filePath.includes(' ') ? '是' : '否'}}`);
-                        console.log(`   包含中文: ${{/[\u4e00-\u9fa5]/.test(filePath) ? '是' : '否'}}`);
-                        
-                        // 尝试备选路径
-                        const altPaths = [
-                            filePath.replace(/\\\\/g, '/'),
-                            filePath.replace(/\\//g, '\\\\'),
-                            filePath.normalize()
-                        ];
-                        
-                        for (const altPath of altPaths) {{
-                            if (fs.existsSync(altPath)) {{
-                                console.log(`✅ 在备选路径找到文件: ${{altPath}}`);
-                                validFiles.push(altPath);
-                                break;
-                            }}
-                        }}
-                    }}
-                }} catch (fileError) {{
-                    console.error(`❌ 验证文件${{i+1}}时出错:`, fileError.message);
-                    fileValidationErrors.push(`文件${{i+1}}验证错误: ${{fileError.message}}`);
-                }}
-            }}
-            
-            // 验证结果总结
-            console.log(`\n📋 文件验证结果:`);
-            console.log(`   ✅ 有效文件: ${{validFiles.length}}/${{idCardFiles.length}}`);
-            console.log(`   ❌ 错误数量: ${{fileValidationErrors.length}}`);
-            
-            if (fileValidationErrors.length > 0) {{
-                console.log(`⚠️ 发现的问题:`);
-                fileValidationErrors.forEach((error, index) => {{
-                    console.log(`   ${{index + 1}}. ${{error}}`);
-                }});
-            }}
-            
-            if (validFiles.length === 0) {{
-                console.log(`❌ 没有找到有效的文件，无法继续上传`);
-                throw new Error(`没有找到有效的身份证文件。请检查个人档案中的文件配置。`);
-            }}
-            
-            // 使用验证通过的文件进行上传
-            console.log(`🚀 将使用${{validFiles.length}}个有效文件进行上传`);
-            const finalFiles = validFiles;
-            
-            let uploadSuccess = false;
-            
-            for (let i = 0; i < selectorStrategies.length && !uploadSuccess; i++) {{
-                const strategy = selectorStrategies[i];
-                console.log(`\\n🎯 尝试策略${{i+1}}: ${{strategy.name}} (${{strategy.selector}})`);
-                console.log(`🔍 策略类型: ${{strategy.type}} - 这将决定执行路径`);
-                
-                try {{
-                    if (strategy.type === 'element_ui_api') {{
-                        // Element UI组件API直接调用策略 - 最专业的方法
-                        console.log(`🎯 使用Element UI组件API直接调用方法`);
-                        const uploadComponents = page.locator(strategy.selector);
-                        const componentCount = await uploadComponents.count();
-                        console.log(`   Element UI上传组件数量: ${{componentCount}}`);
-                        
-                        if (componentCount > 0) {{
-                            console.log(`🔍 尝试直接调用Element UI Upload组件方法...`);
-                            
-                            // 尝试每个Upload组件
-                            for (let j = 0; j < componentCount; j++) {{
-                                const component = uploadComponents.nth(j);
-                                console.log(`🔍 处理第${{j+1}}个Upload组件...`);
-                                
-                                try {{
-                                    const apiCallResult = await component.evaluate((el, files) => {{
-                                        console.log('📡 开始Element UI API调用...');
-                                        
-                                        // 查找Vue实例
-                                        let vueInstance = el.__vue__ || el._vueParentComponent;
-                                        if (!vueInstance && el.__vueParentComponent) {{
-                                            vueInstance = el.__vueParentComponent.ctx;
-                                        }}
-                                        
-                                        if (vueInstance) {{
-                                            console.log('📡 找到Vue实例，组件类型:', vueInstance.$options.name || 'Unknown');
-                                            
-                                            // ❌ 不使用Mock File - 这会导致上传空内容
-                                            // ✅ Element UI API策略暂时跳过，因为无法传递真实文件内容
-                                            console.log('⚠️ Element UI API策略需要真实File对象，当前跳过此策略');
-                                            console.log('💡 建议使用hidden_input策略，可以直接设置文件路径');
-                                            return {{ success: false, error: 'Cannot create real File objects with content in browser context' }};
-                                        }} else {{
-                                            console.log('❌ 未找到Vue实例');
-                                            return {{ success: false, error: 'Vue instance not found' }};
-                                        }}
-                                    }}, finalFiles);
-                                    
-                                    console.log(`📊 API调用结果:`, JSON.stringify(apiCallResult, null, 2));
-                                    
-                                    if (apiCallResult.success) {{
-                                        console.log(`🎉 Element UI API调用成功！使用方法: ${{apiCallResult.method}}`);
-                                        
-                                        // 等待处理完成
-                                        await page.waitForTimeout(3000);
-                                        
-                                        // 验证上传成功
-                                        const uploadItemsVariants = [
-                                            '.copyright-img-upload .el-upload-list__item',
-                                            '.el-upload-list--picture-card .el-upload-list__item', 
-                                            '.el-upload-list__item',
-                                            '[class*=\"upload-list\"] [class*=\"item\"]',
-                                            '.el-upload-list .el-upload-list__item'
-                                        ];
-                                        
-                                        let totalUploadItems = 0;
-                                        for (const variant of uploadItemsVariants) {{
-                                            const count = await page.locator(variant).count();
-                                            if (count > 0) {{
-                                                console.log(`📊 找到${{count}}个上传项目 (选择器: ${{variant}})`);
-                                                totalUploadItems = Math.max(totalUploadItems, count);
-                                            }}
-                                        }}
-                                        
-                                        if (totalUploadItems > 0) {{
-                                            uploadSuccess = true;
-                                            console.log(`🎉 Element UI API上传成功，使用策略${{i+1}}: ${{strategy.name}}`);
-                                            break; // 退出组件循环
-                                        }}
-                                    }}
-                                    
-                                }} catch (componentError) {{
-                                    console.log(`❌ 第${{j+1}}个组件处理失败: ${{componentError.message}}`);
-                                }}
-                            }}
-                            
-                            if (uploadSuccess) {{
-                                console.log(`🛑 Element UI API上传成功，停止其他策略尝试`);
-                                break; // 立即退出策略循环
-                            }}
-                        }}
-                        
-                    }} else if (strategy.type === 'chooser') {{
-                        // File Chooser API策略 - 增强版本，处理文件选择界面
-                        console.log(`🎯 使用FileChooser API方法`);
-                        const trigger = page.locator(strategy.selector).first();
-                        const isVisible = await trigger.isVisible({{ timeout: 3000 }});
-                        console.log(`   上传触发器可见性: ${{isVisible}}`);
-                        
-                        if (isVisible) {{
-                            console.log(`🎯 准备点击上传触发器: ${{strategy.selector}}`);
-                            
-                            // 设置文件选择器监听 - 增加超时时间并处理多个可能的事件
-                            const fileChooserPromise = page.waitForEvent('filechooser', {{ timeout: 15000 }});
-                            
-                            // 点击触发器
-                            console.log(`👆 点击上传触发器...`);
-                            await trigger.click();
-                            console.log(`⏳ 等待文件选择器事件...`);
-                            
-                            try {{
-                                const fileChooser = await fileChooserPromise;
-                                console.log(`📁 FileChooser事件已触发！`);
-                                console.log(`🔍 FileChooser详细信息: isMultiple=${{fileChooser.isMultiple()}}`);
-                                
-                                // 设置文件 - 使用验证通过的文件
-                                console.log(`📂 开始设置${{finalFiles.length}}个验证通过的文件`);
-                                console.log(`📋 文件清单:`, finalFiles.map(f => f.split(/[/\\\\]/).pop()));
-                                await fileChooser.setFiles(finalFiles);
-                                console.log(`✅ FileChooser文件设置完成，避免了用户手动选择`);
-                                
-                                // 等待上传处理 - 增加等待时间
-                                console.log(`⏳ 等待文件上传和处理...`);
-                                await page.waitForTimeout(5000);
-                                
-                                // 验证上传成功 - 检查多种可能的上传成功指示器
-                                const uploadItemsVariants = [
-                                    '.copyright-img-upload .el-upload-list__item',
-                                    '.el-upload-list--picture-card .el-upload-list__item', 
-                                    '.el-upload-list__item',
-                                    '[class*=\"upload-list\"] [class*=\"item\"]'
-                                ];
-                                
-                                let totalUploadItems = 0;
-                                for (const variant of uploadItemsVariants) {{
-                                    const count = await page.locator(variant).count();
-                                    if (count > 0) {{
-                                        console.log(`📊 找到${{count}}个上传项目 (选择器: ${{variant}})`);
-                                        totalUploadItems = Math.max(totalUploadItems, count);
-                                    }}
-                                }}
-                                
-                                console.log(`📊 总上传项目数量: ${{totalUploadItems}}`);
-                                
-                                if (totalUploadItems > 0) {{
-                                    uploadSuccess = true;
-                                    console.log(`🎉 FileChooser方法上传成功，使用策略${{i+1}}: ${{strategy.name}}`);
-                                    
-                                    // 防止页面晃动
-                                    await page.evaluate(() => {{
-                                        document.body.style.overflow = 'hidden';
-                                        window.scrollTo(0, 0);
-                                    }});
-                                    await page.waitForTimeout(1000);
-                                    await page.evaluate(() => {{
-                                        document.body.style.overflow = 'auto';
-                                    }});
-                                    break; // 立即退出策略循环
-                                }} else {{
-                                    console.log(`⚠️ 策略${{i+1}}FileChooser成功但未检测到上传项目`);
-                                    console.log(`🔍 可能需要等待更长时间或触发其他事件`);
-                                }}
-                                
-                            }} catch (chooserError) {{
-                                console.log(`❌ 策略${{i+1}}FileChooser超时或失败: ${{chooserError.message}}`);
-                                console.log(`💡 FileChooser可能不被此页面支持，继续尝试其他方法`);
-                            }}
-                        }}
-                        
-                        
-                        
-                    }} else if (strategy.type === 'hidden_input') {{
-                        // 隐藏文件输入策略 - 不检查可见性，直接设置文件
-                        console.log(`🎯 使用隐藏输入策略，跳过可见性检查`);
-                        console.log(`🔍 正在搜索选择器: ${{strategy.selector}}`);
-                        const element = page.locator(strategy.selector).first();
-                        
-                        try {{
-                            // 检查元素是否存在
-                            const elementCount = await element.count();
-                            console.log(`   隐藏输入元素数量: ${{elementCount}}`);
-                            
-                            if (elementCount > 0) {{
-                                // 🔍 详细的元素状态检查
-                                console.log(`🔍 检查隐藏输入元素详细信息...`);
-                                const elementInfo = await element.evaluate(el => {{
-                                    return {{
-                                        tagName: el.tagName,
-                                        type: el.type,
-                                        className: el.className,
-                                        id: el.id,
-                                        name: el.name,
-                                        accept: el.accept,
-                                        multiple: el.multiple,
-                                        disabled: el.disabled,
-                                        readOnly: el.readOnly,
-                                        style: {{
-                                            display: el.style.display,
-                                            visibility: el.style.visibility,
-                                            opacity: el.style.opacity
-                                        }},
-                                        offsetParent: el.offsetParent !== null,
-                                        files: el.files ? el.files.length : 0
-                                    }};
-                                }});
-                                console.log(`📊 元素信息:`, JSON.stringify(elementInfo, null, 2));
-                                
-                                // 🔍 关键修复：逐个文件上传而非一次性多文件上传
-                                console.log(`📁 开始逐个文件上传策略，避免多文件一次性设置问题`);
-                                console.log(`🎯 设置前文件数量: ${{elementInfo.files}}`);
-                                console.log(`🎯 总共需要上传: ${{finalFiles.length}} 个文件`);
-                                
-                                let successfulUploads = 0;
-                                
-                                // 逐个上传每个文件
-                                for (let fileIndex = 0; fileIndex < finalFiles.length; fileIndex++) {{
-                                    const filePath = finalFiles[fileIndex];
-                                    const fileName = filePath.split(/[/\\\\\\\\]/).pop();
-                                    console.log(`\\n📄 上传第${{fileIndex + 1}}/${{finalFiles.length}}个文件: ${{fileName}}`);
-                                    console.log(`📍 文件路径: ${{filePath}}`);
-                                    
-                                    try {{
-                                        // 设置单个文件
-                                        await element.setInputFiles([filePath]);
-                                        console.log(`✅ 文件${{fileIndex + 1}}设置完成`);
-                                        
-                                        // 检查设置是否成功
-                                        const afterSingleFile = await element.evaluate(el => el.files ? el.files.length : 0);
-                                        console.log(`🎯 文件${{fileIndex + 1}}设置后元素文件数量: ${{afterSingleFile}}`);
-                                        
-                                        if (afterSingleFile > 0) {{
-                                            console.log(`✅ 文件${{fileIndex + 1}}成功设置到输入元素`);
-                                            successfulUploads++;
-                                            
-                                            // 立即触发事件处理该文件
-                                            await element.evaluate((input) => {{
-                                                const changeEvent = new Event('change', {{ bubbles: true, cancelable: true }});
-                                                const inputEvent = new Event('input', {{ bubbles: true, cancelable: true }});
-                                                input.dispatchEvent(inputEvent);
-                                                input.dispatchEvent(changeEvent);
-                                                console.log(`📡 文件${{fileIndex + 1}}事件已触发`);
-                                            }});
-                                            
-                                            // 等待处理完成
-                                            console.log(`⏳ 等待文件${{fileIndex + 1}}处理完成...`);
-                                            await page.waitForTimeout(2000);
-                                            
-                                            // 检查是否生成了上传项目
-                                            const uploadItemsNow = await page.locator('.el-upload-list__item').count();
-                                            console.log(`📊 文件${{fileIndex + 1}}处理后上传项目数量: ${{uploadItemsNow}}`);
-                                            
-                                        }} else {{
-                                            console.log(`❌ 文件${{fileIndex + 1}}设置失败，输入元素文件数量仍为0`);
-                                        }}
-                                        
-                                    }} catch (singleFileError) {{
-                                        console.log(`❌ 文件${{fileIndex + 1}}上传失败: ${{singleFileError.message}}`);
-                                    }}
-                                }}
-                                
-                                console.log(`\\n📊 逐个上传完成统计: 成功${{successfulUploads}}/${{finalFiles.length}}个文件`);
-                                
-                                console.log(`✅ 策略${{i+1}}逐个文件处理完成: ${{strategy.name}}`);
-                                
-                                // 最终验证所有文件上传成功 - 延长等待时间
-                                console.log(`⏳ 等待所有文件最终处理完成...`);
-                                await page.waitForTimeout(3000);
-                                
-                                // 检查多种上传成功指示器
-                                const uploadItemsVariants = [
-                                    '.copyright-img-upload .el-upload-list__item',
-                                    '.el-upload-list--picture-card .el-upload-list__item', 
-                                    '.el-upload-list__item',
-                                    '[class*=\"upload-list\"] [class*=\"item\"]',
-                                    '.el-upload-list .el-upload-list__item'
-                                ];
-                                
-                                let totalUploadItems = 0;
-                                for (const variant of uploadItemsVariants) {{
-                                    const count = await page.locator(variant).count();
-                                    if (count > 0) {{
-                                        console.log(`📊 找到${{count}}个上传项目 (选择器: ${{variant}})`);
-                                        totalUploadItems = Math.max(totalUploadItems, count);
-                                    }}
-                                }}
-                                
-                                console.log(`📊 最终上传项目数量: ${{totalUploadItems}}`);
-                                console.log(`📊 成功处理的文件数量: ${{successfulUploads}}`);
-                                console.log(`📊 期望上传的文件数量: ${{finalFiles.length}}`);
-                                
-                                // 判断成功条件：至少上传了一些文件
-                                if (totalUploadItems > 0 || successfulUploads > 0) {{
-                                    uploadSuccess = true;
-                                    console.log(`🎉 隐藏输入逐个文件上传成功！`);
-                                    console.log(`   ✅ 策略${{i+1}}: ${{strategy.name}}`);
-                                    console.log(`   ✅ 成功上传: ${{Math.max(totalUploadItems, successfulUploads)}} 个文件`);
-                                    console.log(`   ✅ 预期上传: ${{finalFiles.length}} 个文件`);
-                                    
-                                    if (totalUploadItems < finalFiles.length && successfulUploads < finalFiles.length) {{
-                                        console.log(`⚠️ 注意: 部分文件上传成功，但未达到预期数量`);
-                                        console.log(`💡 可能原因: Element UI组件限制或浏览器文件处理限制`);
-                                    }}
-                                    
-                                    console.log(`🛑 文件上传成功，停止其他策略尝试`);
-                                    
-                                    // 防止页面晃动
-                                    await page.evaluate(() => {{
-                                        document.body.style.overflow = 'hidden';
-                                        window.scrollTo(0, 0);
-                                    }});
-                                    await page.waitForTimeout(1000);
-                                    await page.evaluate(() => {{
-                                        document.body.style.overflow = 'auto';
-                                    }});
-                                    break; // 立即退出策略循环
-                                }} else {{
-                                    console.log(`❌ 策略${{i+1}}逐个文件处理完成，但未检测到任何上传项目`);
-                                    console.log(`🔍 可能的问题:`);
-                                    console.log(`   - 文件路径不正确或文件不存在`);
-                                    console.log(`   - Element UI组件未正确响应文件设置`);
-                                    console.log(`   - 上传组件选择器不匹配实际页面结构`);
-                                }}
-                            }} else {{
-                                console.log(`❌ 策略${{i+1}}隐藏输入元素未找到`);
-                            }}
-                        }} catch (hiddenError) {{
-                            console.log(`❌ 策略${{i+1}}隐藏输入处理失败: ${{hiddenError.message}}`);
-                        }}
-                        
-                    }} else if (strategy.type === 'visible_input') {{
-                        // 可见文件输入策略 - 需要检查可见性
-                        console.log(`🎯 使用可见输入策略，需要检查可见性`);
-                        const element = page.locator(strategy.selector).first();
-                        const isVisible = await element.isVisible({{ timeout: 3000 }});
-                        console.log(`   可见输入元素可见性: ${{isVisible}}`);
-                        
-                        if (isVisible) {{
-                            await element.setInputFiles(finalFiles);
-                            
-                            // 主动触发change事件
-                            await element.evaluate((input) => {{
-                                const changeEvent = new Event('change', {{ bubbles: true }});
-                                const inputEvent = new Event('input', {{ bubbles: true }});
-                                input.dispatchEvent(changeEvent);
-                                input.dispatchEvent(inputEvent);
-                                console.log('✅ 已触发change和input事件');
-                            }});
-                            
-                            console.log(`✅ 策略${{i+1}}成功: ${{strategy.name}}`);
-                            
-                            // 验证上传成功
-                            await page.waitForTimeout(3000);
-                            const uploadItems = await page.locator('.el-upload-list__item, .upload-list-item, .el-upload-list .el-upload-list__item').count();
-                            console.log(`📊 检测到上传项目数量: ${{uploadItems}}`);
-                            
-                            if (uploadItems > 0) {{
-                                uploadSuccess = true;
-                                console.log(`🎉 可见输入文件上传验证成功，使用策略${{i+1}}: ${{strategy.name}}`);
-                                console.log(`🛑 文件上传成功，停止其他策略尝试`);
-                                
-                                // 防止页面晃动
-                                await page.evaluate(() => {{
-                                    document.body.style.overflow = 'hidden';
-                                    window.scrollTo(0, 0);
-                                }});
-                                await page.waitForTimeout(1000);
-                                await page.evaluate(() => {{
-                                    document.body.style.overflow = 'auto';
-                                }});
-                                break; // 立即退出策略循环
-                            }}
-                        }}
-                        
-                    }} else if (strategy.type === 'fallback') {{
-                        // 备用方法: 点击.el-upload然后设置文件 (可能打开文件选择界面)
-                        console.log(`🎯 使用备用方法: 点击 + setInputFiles (可能显示选择器)`);
-                        const uploadElement = page.locator(strategy.selector).first();
-                        const isVisible = await uploadElement.isVisible({{ timeout: 3000 }});
-                        console.log(`   上传元素可见性: ${{isVisible}}`);
-                        
-                        if (isVisible) {{
-                            // 步骤1: 点击.el-upload触发上传界面
-                            await uploadElement.click();
-                            console.log(`👆 已点击上传元素: ${{strategy.selector}}`);
-                            console.log(`⏳ 等待文件选择界面加载完成...`);
-                            await page.waitForTimeout(1000); // 增加等待时间
-                            
-                            // 步骤2: 尝试多种方式设置文件
-                            console.log(`🔍 尝试多种文件设置方法...`);
-                            
-                            // 方法2a: 直接设置到原来的上传元素
-                            try {{
-                                await uploadElement.setInputFiles(finalFiles);
-                                console.log(`✅ 方法2a: 成功设置文件到原上传元素`);
-                            }} catch (error2a) {{
-                                console.log(`❌ 方法2a失败: ${{error2a.message}}`);
-                                
-                                // 方法2b: 寻找并设置到隐藏的文件输入元素
-                                try {{
-                                    const fileInput = page.locator('input[type="file"]').first();
-                                    const fileInputVisible = await fileInput.isVisible({{ timeout: 2000 }});
-                                    console.log(`🔍 文件输入元素可见性: ${{fileInputVisible}}`);
-                                    await fileInput.setInputFiles(finalFiles);
-                                    console.log(`✅ 方法2b: 成功设置文件到文件输入元素`);
-                                }} catch (error2b) {{
-                                    console.log(`❌ 方法2b失败: ${{error2b.message}}`);
-                                    
-                                    // 方法2c: 寻找.el-upload__input元素
-                                    try {{
-                                        const elUploadInput = page.locator('.el-upload__input').first();
-                                        await elUploadInput.setInputFiles(finalFiles);
-                                        console.log(`✅ 方法2c: 成功设置文件到.el-upload__input元素`);
-                                    }} catch (error2c) {{
-                                        console.log(`❌ 方法2c失败: ${{error2c.message}}`);
-                                        console.log(`❌ 所有文件设置方法均失败`);
-                                    }}
-                                }}
-                            }}
-                            
-                            // 等待上传处理并验证
-                            console.log(`⏳ 等待文件上传处理完成...`);
-                            await page.waitForTimeout(4000); // 增加等待时间
-                            const uploadItems = await page.locator('.el-upload-list__item').count();
-                            console.log(`📊 检测到上传项目数量: ${{uploadItems}}`);
-                            
-                            if (uploadItems > 0) {{
-                                uploadSuccess = true;
-                                console.log(`🎉 用户验证方法上传成功，使用策略${{i+1}}: ${{strategy.name}}`);
-                                console.log(`🛑 文件上传成功，停止其他策略尝试`);
-                                
-                                // 防止页面晃动
-                                await page.evaluate(() => {{
-                                    document.body.style.overflow = 'hidden';
-                                    window.scrollTo(0, 0);
-                                }});
-                                await page.waitForTimeout(1000);
-                                await page.evaluate(() => {{
-                                    document.body.style.overflow = 'auto';
-                                }});
-                                break; // 立即退出策略循环
-                            }} else {{
-                                console.log(`⚠️ 策略${{i+1}}文件界面打开成功但未检测到上传项目`);
-                                console.log(`🔍 继续尝试其他策略...`);
-                            }}
-                        }}
-                    }}
-                    
-                }} catch (strategyError) {{
-                    console.log(`❌ 策略${{i+1}}失败: ${{strategyError.message}}`);
-                }}
-            }}
-            
-            if (!uploadSuccess) {{
-                console.log('⚠️ 所有5种智能文件上传策略均未成功（Element UI API→隐藏输入→可见输入→FileChooser→备用方法）');
-                
-                // 🔍 增强调试信息 - DOM结构分析
-                console.log('🔍 开始页面DOM结构分析...');
-                const allFileInputs = await page.locator('input[type="file"]').count();
-                console.log(`🔍 页面总文件输入控件数量: ${{allFileInputs}}`);
-                
-                // 列出所有表单项的文本内容
-                try {{
-                    const formItems = await page.locator('.el-form-item').allTextContents();
-                    console.log('🔍 页面表单项文本: ', formItems);
-                    
-                    // 检查上传相关元素
-                    const uploadElements = await page.locator('.el-upload, [class*="upload"]').count();
-                    console.log(`🔍 上传相关元素数量: ${{uploadElements}}`);
-                    
-                    // 检查按钮元素
-                    const buttons = await page.locator('button, .el-button').allTextContents();
-                    console.log('🔍 页面按钮文本: ', buttons.slice(0, 10)); // 前10个
-                    
-                }} catch (debugError) {{
-                    console.log('调试信息获取失败:', debugError.message);
-                }}
-            }}
-            
-        }} catch (error) {{
-            console.error('❌ 身份证文件上传整体失败: ', error);
-        }}"#, files_display, files_array)
+    // 独立的进程组，kill_tracked_playwright可以连同子进程一起结束
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let mut child = cmd.spawn().context("启动Playwright进程失败")?;
+    *PLAYWRIGHT_PID.lock().unwrap_or_else(|e| e.into_inner()) = child.id();
+    let mut stdout_pipe = BufReader::new(child.stdout.take().context("无法读取Playwright输出")?);
+    let mut stderr_pipe = child.stderr.take().context("无法读取Playwright错误输出")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut buffer = Vec::new();
+        let _ = stderr_pipe.read_to_end(&mut buffer).await;
+        buffer
+    });
+
+    let mut stdout = String::new();
+    let mut line_buffer = Vec::new();
+    let mut last_progress = 0.0_f32;
+    while stdout_pipe.read_until(b'\n', &mut line_buffer).await? > 0 {
+        let line = String::from_utf8_lossy(&line_buffer).to_string();
+        line_buffer.clear();
+        if let Some((step, progress)) = site.and_then(|site| site.progress_for_line(&line)) {
+            // 只前进不后退，重复出现的阶段日志不会让进度条倒退
+            if progress > last_progress {
+                last_progress = progress;
+                update_status(step, progress).await;
+            }
+        }
+        stdout.push_str(&line);
+    }
+
+    let exit_status = child.wait().await;
+    PLAYWRIGHT_PID.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let exit_status = exit_status.context("等待Playwright进程结束失败")?;
+    let stderr = String::from_utf8_lossy(&stderr_task.await.unwrap_or_default()).to_string();
+
+    tracing::info!("📊 Playwright执行完成，开始分析输出日志...");
+    tracing::info!("📏 stdout长度: {} 字符", stdout.len());
+    tracing::info!("📏 stderr长度: {} 字符", stderr.len());
+    
+    // 完整输出写入运行日志；主日志默认只记录摘要和错误行，开启详细日志时分块输出stdout
+    if !stdout.is_empty() {
+        let stdout_lines: Vec<&str> = stdout.lines().collect();
+        if options.verbose_logs {
+            tracing::info!("📄 Playwright stdout ({} 行):", stdout_lines.len());
+            for (i, line) in stdout_lines.iter().enumerate() {
+                if i < 100 { // 限制显示前100行，避免日志过长
+                    tracing::info!("  stdout[{}]: {}", i + 1, line);
+                } else if i == 100 {
+                    tracing::info!("  stdout[...]: 剩余 {} 行已省略", stdout_lines.len() - 100);
+                    break;
+                }
+            }
+        } else {
+            tracing::info!("📄 Playwright stdout共 {} 行，完整内容见运行日志", stdout_lines.len());
+            for line in stdout_lines.iter().filter(|line| line.contains('❌')) {
+                tracing::warn!("  stdout: {}", line);
+            }
+        }
     } else {
-        "        console.log('ℹ️ 无身份证文件需要上传');".to_string()
+        tracing::warn!("⚠️ Playwright stdout为空，可能脚本未正常执行");
+    }
+    
+    if !stderr.is_empty() {
+        tracing::warn!("📄 Playwright stderr: {}", stderr);
+    }
+    
+    if let Err(e) = crate::logging::write_run_log(&stdout, &stderr, run_id) {
+        tracing::warn!("保存Playwright运行日志失败: {}", e);
+    }
+
+    let run_result = read_run_result(&result_path);
+    if let (Some(run_id), Some(result)) = (run_id, run_result.as_ref()) {
+        if let Err(e) = crate::database::save_run_result(run_id, result).await {
+            tracing::warn!("保存运行结果失败: {}", e);
+        }
+    }
+    
+    if !exit_status.success() {
+        let combined_output = format!("{}\n{}", stdout, stderr);
+        if combined_output.contains(NOT_LOGGED_IN_MARKER) {
+            return Err(AutomationError::NotLoggedIn.into());
+        }
+        if combined_output.contains(VERIFICATION_TIMEOUT_MARKER) {
+            return Err(AutomationError::VerificationTimeout.into());
+        }
+        if combined_output.contains(BROWSER_CLOSED_MARKER) {
+            return Err(AutomationError::BrowserClosed.into());
+        }
+        let missing_files = extract_marked_errors(&combined_output, FILE_NOT_FOUND_MARKER);
+        if !missing_files.is_empty() {
+            return Err(AutomationError::FileNotFound(missing_files.join("; ")).into());
+        }
+        let self_test_failures = extract_marked_errors(&combined_output, SELF_TEST_FAILED_MARKER);
+        if !self_test_failures.is_empty() {
+            return Err(AutomationError::SelfTestFailed(self_test_failures.join("; ")).into());
+        }
+        let quota_notices = extract_marked_errors(&combined_output, QUOTA_EXCEEDED_MARKER);
+        if !quota_notices.is_empty() {
+            return Err(AutomationError::QuotaExceeded(quota_notices.join("; ")).into());
+        }
+        let validation_errors = extract_marked_errors(&combined_output, FORM_VALIDATION_MARKER);
+        if !validation_errors.is_empty() {
+            return Err(AutomationError::FormValidation(validation_errors.join("; ")).into());
+        }
+        let unfilled_fields = extract_marked_errors(&combined_output, FIELDS_NOT_FILLED_MARKER);
+        if !unfilled_fields.is_empty() {
+            return Err(AutomationError::FieldsNotFilled(unfilled_fields.join("; ")).into());
+        }
+        let submit_errors = extract_marked_errors(&combined_output, SUBMIT_NOT_CONFIRMED_MARKER);
+        if !submit_errors.is_empty() {
+            return Err(AutomationError::SubmitNotConfirmed(submit_errors.join("; ")).into());
+        }
+        return Err(anyhow::anyhow!("Playwright测试失败 (退出码: {:?}): {}", exit_status.code(), stderr));
+    }
+    
+    Ok(PlaywrightOutput { stdout, run_result })
+}
+
+// 脚本通过该环境变量得知结构化结果的写入位置
+const RUN_RESULT_PATH_ENV: &str = "RIGHTSGUARD_RESULT_PATH";
+
+fn run_result_path(run_id: Option<uuid::Uuid>) -> std::path::PathBuf {
+    let name = match run_id {
+        Some(run_id) => format!("rightsguard-result-{}.json", run_id),
+        None => "rightsguard-result.json".to_string(),
     };
+    std::env::temp_dir().join(name)
+}
 
-    // Note: File upload sections are now integrated into the IP asset section above
-    // No need for separate auth_files_upload_section and work_proof_upload_section
+// 脚本通过该环境变量得知提交结果截图的保存位置
+const SUBMISSION_SCREENSHOT_PATH_ENV: &str = "RIGHTSGUARD_SCREENSHOT_PATH";
 
-    // The main script template now includes file upload functionality
-    Ok(format!(r#"
-const {{ test, chromium }} = require('@playwright/test');
-const fs = require('fs');
-
-test('Bilibili Appeal - Connect Mode with File Upload', async () => {{
-    try {{
-        console.log('🚀 开始自动化申诉流程...');
-        console.log('⏰ 脚本启动时间:', new Date().toISOString());
-        console.log('🔍 关键修复验证: 逐个文件上传机制已启用');
-        console.log('🎯 预期效果: 上传真实可查看的图片，支持多文件上传');
-        console.log('🔧 Playwright脚本已启动并开始执行 - 如果你看到这条消息，说明JavaScript语法正确');
-        const browser = await chromium.connectOverCDP('http://127.0.0.1:9222', {{ timeout: 15000 }});
-        const context = browser.contexts()[0];
-        const page = context.pages()[0] || await context.newPage();
-        
-        console.log('\\n⏰ 阶段1开始时间:', new Date().toISOString());
-        console.log('📄 导航到B站版权申诉页面...');
-        console.log('🌐 页面导航开始 - 目标URL: https://www.bilibili.com/v/copyright/apply?origin=home');
-        await page.goto('https://www.bilibili.com/v/copyright/apply?origin=home', {{ timeout: 60000, waitUntil: 'networkidle' }});
-        console.log('✅ 页面导航完成，开始填写表单...');
-
-        console.log('\\n⏰ 阶段2开始时间:', new Date().toISOString());
-        console.log('✏️ 开始填写个人信息...');
-        await page.locator('input[placeholder="真实姓名"].el-input__inner').first().fill({name});
-        await page.locator('input[placeholder="手机号"].el-input__inner').first().fill({phone});
-        await page.locator('.el-form-item:has-text("邮箱") input.el-input__inner').first().fill({email});
-        await page.locator('input[placeholder="证件号码"].el-input__inner').first().fill({id_card});
-        console.log('✓ 个人信息填写完成');
-
-        console.log('\\n⏰ 阶段3开始时间:', new Date().toISOString());
-        console.log('🔥 关键阶段：身份证文件上传开始...');
-        {id_card_upload_section}
-        
-        console.log('⏳ 等待用户完成验证码并进入下一页...');
-        console.log('💡 请在页面中输入验证码并点击下一步');
-        
-        // 等待IP资产页面的关键元素出现，最多等待5分钟
-        console.log('🔍 正在检测IP资产页面加载...');
-        await page.waitForSelector('.el-form-item:has-text("权利人")', {{ 
-            timeout: 300000 
-        }});
-        
-        console.log('✅ 检测到IP资产页面，开始自动填写...');
-        await page.waitForTimeout(2000);
-        
-        // 执行完整的IP资产信息填写和文件上传
-        {ip_section}
-        
-        console.log('📝 填写申诉详情...');
-        await page.locator('input[placeholder*="他人发布的B站侵权链接"]').first().fill({url});
-        await page.locator('textarea[placeholder*="该链接内容全部"]').first().fill('该链接内容侵犯了我的版权，要求立即删除。');
-        await page.locator('.el-checkbox__label:has-text("本人保证")').first().click();
-        console.log('✓ 申诉详情填写完成');
-        
-        console.log('🎉 自动化申诉流程准备就绪，保持页面打开供用户最终确认...');
-        await new Promise(() => {{}}); // Keep open indefinitely
-    }} catch (error) {{
-        console.error('❌ 自动化申诉流程失败:', error);
-        throw error;
-    }}
-}});
-"#, 
-    name = serde_json::to_string(escaped_name).unwrap(), 
-    phone = serde_json::to_string(escaped_phone).unwrap(), 
-    email = serde_json::to_string(escaped_email).unwrap(), 
-    id_card = serde_json::to_string(escaped_id_card).unwrap(), 
-    ip_section = ip_section, 
-    url = serde_json::to_string(escaped_infringing_url).unwrap(),
-    id_card_upload_section = id_card_upload_section
-))
+// 本次运行的提交结果截图，相对应用数据目录，与其他文件一样以相对路径记录在案件中
+fn submission_screenshot_relative_path(run_id: uuid::Uuid) -> String {
+    format!("files/screenshots/submission_{}.png", run_id)
 }
 
-// ==============================================
-// Helper Functions
-// ==============================================
+/// 提交结果截图的绝对路径，会创建所在目录并删除同名的旧截图；无法确定应用数据目录时返回None
+pub(crate) fn submission_screenshot_path(run_id: Option<uuid::Uuid>) -> Option<std::path::PathBuf> {
+    let path = get_app_data_dir()?.join(submission_screenshot_relative_path(run_id?));
+    if let Some(dir) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            tracing::warn!("无法创建截图目录 {:?}: {}", dir, e);
+            return None;
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+    Some(path)
+}
 
-async fn check_chrome_debug_port() -> bool {
-    if tokio::net::TcpStream::connect("127.0.0.1:9222").await.is_ok() {
-        if let Ok(true) = check_chrome_debug_api().await {
-            return true;
+// 运行结束后截图存在时返回其相对路径
+fn saved_submission_screenshot(run_id: Option<uuid::Uuid>) -> Option<String> {
+    let run_id = run_id?;
+    let relative = submission_screenshot_relative_path(run_id);
+    get_app_data_dir()?.join(&relative).is_file().then_some(relative)
+}
+
+// 读取后删除结果文件；脚本在写出结果之前就崩溃时没有该文件
+fn read_run_result(path: &std::path::Path) -> Option<RunResult> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let _ = std::fs::remove_file(path);
+    match serde_json::from_str::<RunResult>(&content) {
+        Ok(result) => {
+            tracing::info!("📋 脚本运行结果: {:?}", result);
+            Some(result)
+        }
+        Err(e) => {
+            tracing::warn!("解析脚本运行结果失败: {}", e);
+            None
         }
     }
-    false
 }
 
-async fn check_chrome_debug_api() -> Result<bool> {
-    let client = reqwest::Client::builder().timeout(std::time::Duration::from_secs(5)).build()?;
-    Ok(client.get("http://127.0.0.1:9222/json/version").send().await.map_or(false, |res| res.status().is_success()))
+async fn start_chrome_with_remote_debugging(settings: &AppSettings, force_fresh: bool, user_agent: Option<&str>) -> Result<()> {
+    if check_chrome_debug_port(settings).await {
+        if force_fresh {
+            tracing::info!("🔄 请求强制使用新的浏览器会话，关闭已有的调试会话");
+        } else if is_debug_session_fresh(settings, user_agent).await {
+            tracing::info!("♻️ 复用已有的Chrome调试会话 (端口: {})", settings.debug_port);
+            return Ok(());
+        } else {
+            tracing::warn!("⚠️ 已有的Chrome调试会话看起来已过期，关闭后重新启动");
+        }
+        close_existing_chrome().await?;
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        return start_new_chrome_with_debugging(settings, None, user_agent).await;
+    }
+
+    if is_chrome_running().await {
+        close_existing_chrome().await?;
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    }
+
+    start_new_chrome_with_debugging(settings, None, user_agent).await
 }
 
-async fn is_chrome_running() -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(output) = Command::new("tasklist").args(&["/FI", "IMAGENAME eq chrome.exe"]).output() {
-            String::from_utf8_lossy(&output.stdout).contains("chrome.exe")
-        } else { false }
+// ==============================================
+// Helper Functions
+// ==============================================
+
+// 已有调试会话中至少有一个B站页面或空白页时才认为可以复用；
+// 上次异常退出遗留的Chrome可能停留在其他站点或使用了错误的用户目录
+// --user-agent只在启动时生效，会话的UA与本次要求的不同时不能复用
+// 指定了UA时会话必须使用该UA；未指定时使用Chrome默认UA，之前的运行以自定义UA启动的会话不能沿用
+fn session_user_agent_matches(requested: Option<&str>, launched: Option<&str>, session: &str) -> bool {
+    match requested {
+        Some(user_agent) => user_agent == session,
+        None => launched.is_none(),
     }
-    #[cfg(not(target_os = "windows"))]
-    {
-        Command::new("pgrep").arg("chrome").status().await.map_or(false, |s| s.success())
+}
+
+async fn is_debug_session_fresh(settings: &AppSettings, user_agent: Option<&str>) -> bool {
+    let client = &*DEBUG_API_CLIENT;
+    let version = client.get(format!("http://127.0.0.1:{}/json/version", settings.debug_port)).send().await;
+    match version {
+        Ok(response) => match response.json::<serde_json::Value>().await {
+            Ok(info) => {
+                tracing::info!("🔍 调试会话浏览器版本: {}", info["Browser"].as_str().unwrap_or("未知"));
+                let session_user_agent = info["User-Agent"].as_str().unwrap_or_default();
+                let launched_user_agent = LAUNCHED_USER_AGENT.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                if !session_user_agent_matches(user_agent, launched_user_agent.as_deref(), session_user_agent) {
+                    tracing::warn!("调试会话的User-Agent与要求的不同: {} != {}", session_user_agent, user_agent.unwrap_or("Chrome默认"));
+                    return false;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("解析/json/version失败: {}", e);
+                return false;
+            }
+        },
+        Err(e) => {
+            tracing::warn!("请求/json/version失败: {}", e);
+            return false;
+        }
     }
+
+    let targets = match client.get(format!("http://127.0.0.1:{}/json/list", settings.debug_port)).send().await {
+        Ok(response) => response.json::<Vec<serde_json::Value>>().await.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("请求/json/list失败: {}", e);
+            return false;
+        }
+    };
+
+    let page_urls: Vec<&str> = targets.iter()
+        .filter(|target| target["type"].as_str() == Some("page"))
+        .filter_map(|target| target["url"].as_str())
+        .collect();
+    tracing::info!("🔍 调试会话中的页面: {:?}", page_urls);
+
+    page_urls.iter().any(|url| {
+        url.is_empty()
+            || *url == "about:blank"
+            || url.starts_with("chrome://newtab")
+            || reqwest::Url::parse(url).ok()
+                .and_then(|parsed| parsed.host_str().map(|host| host == "bilibili.com" || host.ends_with(".bilibili.com")))
+                .unwrap_or(false)
+    })
 }
 
-fn get_chrome_user_data_dir() -> Result<String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("无法获取用户主目录"))?;
-    let user_data_dir = home_dir.join("AppData\\Local\\RightsGuard\\ChromeProfile");
-    std::fs::create_dir_all(&user_data_dir).ok();
-    Ok(user_data_dir.to_str().unwrap().to_string())
+// 通过调试API在已运行的Chrome中打开新标签页
+async fn open_tab_via_debug_api(settings: &AppSettings, url: &str) -> Result<()> {
+    let response = DEBUG_API_CLIENT.put(format!("http://127.0.0.1:{}/json/new?{}", settings.debug_port, url))
+        .send()
+        .await
+        .context("调用Chrome调试API打开新标签页失败")?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Chrome调试API返回错误状态: {}", response.status()));
+    }
+    Ok(())
 }
 
 async fn close_existing_chrome() -> Result<()> {
@@ -1384,55 +1770,131 @@ async fn close_existing_chrome() -> Result<()> {
     }
     #[cfg(not(target_os = "windows"))]
     {
-        let _ = Command::new("pkill").args(&["-KILL", "chrome"]).output();
+        // pkill没有匹配到进程时退出码为1，不算失败
+        for name in crate::browser::CHROME_PROCESS_NAMES {
+            Command::new("pkill").args(&["-KILL", "-x", name]).output().context("无法强制关闭Chrome进程")?;
+        }
     }
     Ok(())
 }
 
-async fn start_new_chrome_with_debugging() -> Result<()> {
+// 固定界面语言，B站页面和表单提示始终以中文显示，与脚本中的文本匹配一致
+const CHROME_LANG: &str = "zh-CN";
+
+async fn start_new_chrome_with_debugging(settings: &AppSettings, start_url: Option<&str>, user_agent: Option<&str>) -> Result<()> {
     let mut process_handle = CHROME_PROCESS.lock().await;
     if let Some(mut child) = process_handle.take() {
         let _ = child.kill();
     }
     
-    let user_data_dir = get_chrome_user_data_dir()?;
-    let chrome_path = find_chrome_executable()?;
+    let user_data_dir = get_chrome_user_data_dir(settings)?;
+    let chrome_path = find_chrome_executable(settings.chrome_path.as_deref())?;
+
+    let debug_port_arg = format!("--remote-debugging-port={}", settings.debug_port);
 
-    let child = Command::new(&chrome_path)
-        .args(&[
-            "--remote-debugging-port=9222",
+    let mut cmd = Command::new(&chrome_path);
+    cmd.args(&[
+            debug_port_arg.as_str(),
             &format!("--user-data-dir={}", user_data_dir),
             "--no-first-run",
             "--no-default-browser-check",
-        ])
-        .spawn()
-        .context("无法启动Chrome进程")?;
+            &format!("--lang={}", CHROME_LANG),
+        ]);
+    if let Some(user_agent) = user_agent {
+        cmd.arg(format!("--user-agent={}", user_agent));
+    }
+    if settings.headless {
+        cmd.arg("--headless=new");
+    }
+    if let Some(proxy) = settings.proxy.as_deref() {
+        tracing::info!("Chrome使用代理: {}", proxy);
+        cmd.arg(format!("--proxy-server={}", proxy));
+        cmd.arg(format!("--proxy-bypass-list={}", PROXY_BYPASS_HOSTS.join(";")));
+    }
+    if let Some(url) = start_url {
+        cmd.arg(url);
+    }
+    let child = cmd.spawn().context("无法启动Chrome进程")?;
     
     *process_handle = Some(child);
-    wait_for_debug_port().await
+    *LAUNCHED_USER_AGENT.lock().unwrap_or_else(|e| e.into_inner()) = user_agent.map(str::to_string);
+    wait_for_debug_port(settings).await
 }
 
-fn find_chrome_executable() -> Result<String> {
+pub(crate) fn find_chrome_executable(path_override: Option<&str>) -> Result<String> {
+    if let Some(path) = path_override {
+        if std::path::Path::new(path).exists() {
+            return Ok(path.to_string());
+        }
+        tracing::warn!("设置中的Chrome路径不存在，改为自动查找: {}", path);
+    }
+    #[cfg(target_os = "windows")]
     let possible_paths = vec![
         "C:\\Program Files\\Google\\Chrome\\Application\\chrome.exe",
         "C:\\Program Files (x86)\\Google\\Chrome\\Application\\chrome.exe",
     ];
+    #[cfg(target_os = "macos")]
+    let possible_paths = vec![
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    ];
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let possible_paths = vec![
+        "/usr/bin/google-chrome",
+        "/usr/bin/google-chrome-stable",
+        "/usr/bin/chromium",
+        "/usr/bin/chromium-browser",
+        "/snap/bin/chromium",
+    ];
     for path in possible_paths {
         if std::path::Path::new(path).exists() {
             return Ok(path.to_string());
         }
     }
+    // Linux发行版的安装位置不固定，再按PATH查找常见的可执行文件名
+    #[cfg(all(unix, not(target_os = "macos")))]
+    for name in ["google-chrome", "google-chrome-stable", "chromium", "chromium-browser"] {
+        if let Some(path) = find_in_path(name) {
+            return Ok(path);
+        }
+    }
     Err(anyhow::anyhow!("未找到Chrome可执行文件"))
 }
 
-async fn wait_for_debug_port() -> Result<()> {
+/// Edge同样支持远程调试端口，未安装Chrome时可在设置中改用Edge
+pub(crate) fn find_edge_executable() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    let possible_paths = vec![
+        "C:\\Program Files (x86)\\Microsoft\\Edge\\Application\\msedge.exe",
+        "C:\\Program Files\\Microsoft\\Edge\\Application\\msedge.exe",
+    ];
+    #[cfg(target_os = "macos")]
+    let possible_paths = vec!["/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge"];
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let possible_paths = vec!["/usr/bin/microsoft-edge", "/usr/bin/microsoft-edge-stable"];
+    possible_paths
+        .into_iter()
+        .find(|path| std::path::Path::new(path).exists())
+        .map(str::to_string)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn find_in_path(name: &str) -> Option<String> {
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+        .map(|candidate| candidate.to_string_lossy().to_string())
+}
+
+async fn wait_for_debug_port(settings: &AppSettings) -> Result<()> {
     let timeout = tokio::time::Duration::from_secs(30);
     let start = tokio::time::Instant::now();
     loop {
         if start.elapsed() > timeout {
             return Err(anyhow::anyhow!("等待Chrome调试端口超时 (30秒)"));
         }
-        if check_chrome_debug_port().await {
+        if check_chrome_debug_port(settings).await {
             break;
         }
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -1441,210 +1903,693 @@ async fn wait_for_debug_port() -> Result<()> {
 }
 
 
-fn escape_file_path_for_js_array(path: &str) -> String {
+/// 校验申诉页面URL为https的bilibili.com地址
+pub fn validate_appeal_url(raw_url: &str) -> Result<String> {
+    let parsed = reqwest::Url::parse(raw_url)
+        .with_context(|| format!("申诉页面URL格式无效: {}", raw_url))?;
+    let host = parsed.host_str().unwrap_or_default();
+    let is_bilibili = host == "bilibili.com" || host.ends_with(".bilibili.com");
+
+    if parsed.scheme() != "https" || !is_bilibili {
+        return Err(anyhow::anyhow!("申诉页面URL必须是https的bilibili.com地址: {}", raw_url));
+    }
+    Ok(parsed.to_string())
+}
+
+/// 校验测试模式的页面地址，只允许http/https/file
+pub fn validate_test_url(raw_url: &str) -> Result<String> {
+    let parsed = reqwest::Url::parse(raw_url)
+        .with_context(|| format!("测试页面URL格式无效: {}", raw_url))?;
+    if !matches!(parsed.scheme(), "http" | "https" | "file") {
+        return Err(anyhow::anyhow!("测试页面URL必须是http、https或file地址: {}", raw_url));
+    }
+    Ok(parsed.to_string())
+}
+
+/// 测试模式打开的页面: 设置中的测试URL，未设置时把内置的模拟申诉页面写入脚本目录
+pub(crate) fn test_target_url(settings: &AppSettings) -> Result<String> {
+    if let Some(test_url) = settings.test_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+        return validate_test_url(test_url);
+    }
+    let fixture_path = automation_work_dir(settings)?.join("appeal_fixture.html");
+    std::fs::write(&fixture_path, SELF_TEST_FIXTURE_HTML).context("写入模拟申诉页面失败")?;
+    let fixture_url = reqwest::Url::from_file_path(&fixture_path)
+        .map_err(|_| anyhow::anyhow!("无法生成模拟申诉页面地址: {:?}", fixture_path))?;
+    Ok(fixture_url.to_string())
+}
+
+// 从Playwright输出中提取表单字段校验错误
+fn extract_marked_errors(output: &str, marker: &str) -> Vec<String> {
+    output.lines()
+        .filter_map(|line| line.split_once(marker))
+        .filter_map(|(_, json)| serde_json::from_str::<Vec<String>>(json.trim()).ok())
+        .flatten()
+        .collect()
+}
+
+// 脚本连接调试端口的重试次数、每次超时和重试间隔，总耗时约为 5*5s + 4*2s
+const CDP_CONNECT_ATTEMPTS: u32 = 5;
+const CDP_CONNECT_ATTEMPT_TIMEOUT_MS: u32 = 5000;
+const CDP_CONNECT_RETRY_DELAY_MS: u32 = 2000;
+
+/// 生成通过调试端口连接已打开Chrome的脚本片段，定义browser和context变量
+/// Chrome刚启动时端口可能尚未就绪，因此有限次重试
+pub(crate) fn cdp_connect_snippet(debug_port: u16) -> String {
+    format!(r#"let browser;
+        for (let attempt = 1; attempt <= {attempts}; attempt++) {{
+            try {{
+                console.log(`🔌 连接Chrome调试端口{port} (第${{attempt}}/{attempts}次)...`);
+                browser = await chromium.connectOverCDP('http://127.0.0.1:{port}', {{ timeout: {attempt_timeout} }});
+                console.log('✅ 已连接到Chrome');
+                break;
+            }} catch (connectError) {{
+                if (attempt === {attempts}) {{
+                    console.error(`❌ 连接Chrome调试端口失败，已重试{attempts}次: ${{connectError.message}}`);
+                    throw connectError;
+                }}
+                console.log(`⚠️ 第${{attempt}}次连接失败: ${{connectError.message}}，{retry_delay}毫秒后重试`);
+                await new Promise(resolve => setTimeout(resolve, {retry_delay}));
+            }}
+        }}
+        const context = browser.contexts()[0];"#,
+        port = debug_port,
+        attempts = CDP_CONNECT_ATTEMPTS,
+        attempt_timeout = CDP_CONNECT_ATTEMPT_TIMEOUT_MS,
+        retry_delay = CDP_CONNECT_RETRY_DELAY_MS
+    )
+}
+
+pub(crate) fn escape_file_path_for_js_array(path: &str) -> String {
     // For file paths in JavaScript arrays, we need proper JSON escaping
-    serde_json::to_string(path).unwrap_or_else(|_| "\"\"".to_string())
+    js_string_literal(path)
+}
+
+/// 清理嵌入脚本的用户数据：控制字符和JS中的行终止符(U+2028/U+2029)替换为空格
+/// 个人档案可以从不受信任的JSON导入，不能假设字段内容是正常文本
+pub(crate) fn sanitize_for_js(value: &str) -> String {
+    value.chars()
+        .map(|c| if c.is_control() || c == '\u{2028}' || c == '\u{2029}' { ' ' } else { c })
+        .collect()
 }
 
-async fn save_case_record(_request: &AutomationRequest) -> Result<()> {
-    tracing::info!("案件记录已保存 (模拟)。");
+/// 生成可直接嵌入脚本的JS字符串字面量
+/// 在JSON编码基础上再转义反引号、`$`和`<`，即使被拼进模板字符串或HTML中也无法提前结束字符串
+pub(crate) fn js_string_literal(value: &str) -> String {
+    serde_json::to_string(&sanitize_for_js(value))
+        .unwrap_or_else(|_| "\"\"".to_string())
+        .replace('`', "\\u0060")
+        .replace('$', "\\u0024")
+        .replace('<', "\\u003c")
+}
+
+async fn save_case_record(request: &AutomationRequest, run_result: Option<&RunResult>, submitted: bool) -> Result<()> {
+    let screenshot = saved_submission_screenshot(request.run_id);
+    let result_json = run_result.and_then(|result| serde_json::to_string(result).ok());
+    let case = crate::database::record_case_submission(
+        &request.infringing_url,
+        request.original_url.as_deref(),
+        request.ip_asset_id,
+        request.new_case,
+        submitted,
+        screenshot.as_deref(),
+        result_json.as_deref(),
+    ).await?;
+    tracing::info!("案件记录已保存: {:?}", case.id);
     Ok(())
 }
 
-// Helper function to convert relative file paths to absolute paths
-fn get_absolute_file_paths(file_paths_json: &Option<String>) -> Result<Vec<String>> {
+/// Parse a stored file list, which is either a JSON array or a comma-separated string
+pub(crate) fn parse_stored_file_paths(file_paths_json: &Option<String>) -> Result<Vec<String>> {
     let paths_json = match file_paths_json {
         Some(json_str) if !json_str.trim().is_empty() => json_str,
         _ => return Ok(Vec::new()),
     };
     
     // Try to parse as JSON array first, then as comma-separated string
-    let paths: Vec<String> = if paths_json.trim().starts_with('[') {
+    if paths_json.trim().starts_with('[') {
         serde_json::from_str(paths_json)
-            .context("Failed to parse file paths JSON")?
+            .context("Failed to parse file paths JSON")
     } else {
         // Treat as array of strings (current format)
-        paths_json.split(',').map(|s| s.trim().to_string()).collect()
+        Ok(paths_json.split(',').map(|s| s.trim().to_string()).collect())
+    }
+}
+
+/// 档案中身份证文件的正反面标记，未标记或无法解析时为空
+pub(crate) fn parse_id_card_roles(profile: &Profile) -> std::collections::HashMap<String, IdCardRole> {
+    let Some(json) = profile.id_card_roles.as_deref().filter(|json| !json.trim().is_empty()) else {
+        return Default::default();
     };
-    
+    serde_json::from_str(json).unwrap_or_else(|e| {
+        tracing::warn!("解析身份证正反面标记失败，按存储顺序上传: {}", e);
+        Default::default()
+    })
+}
+
+/// 按B站要求的顺序 (人像面、国徽面、其他) 排列身份证文件的存储路径，未标记的文件保持原顺序排在最后
+pub(crate) fn ordered_id_card_files(profile: &Profile) -> Result<Vec<String>> {
+    let roles = parse_id_card_roles(profile);
+    let mut paths: Vec<String> = parse_stored_file_paths(&profile.id_card_files)?
+        .into_iter()
+        .filter(|path| !path.trim().is_empty())
+        .collect();
+    paths.sort_by_key(|path| {
+        let role = roles.get(path).copied();
+        (role.is_none(), role)
+    });
+    Ok(paths)
+}
+
+/// 没有恰好标记一个人像面和一个国徽面时返回提示
+pub(crate) fn id_card_role_warning(profile: &Profile) -> Result<Option<String>> {
+    let roles = parse_id_card_roles(profile);
+    let paths = parse_stored_file_paths(&profile.id_card_files)?;
+    let count = |role: IdCardRole| paths.iter().filter(|path| roles.get(*path) == Some(&role)).count();
+    let (front, back) = (count(IdCardRole::Front), count(IdCardRole::Back));
+    if front == 1 && back == 1 {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "身份证正反面标记不完整 (人像面{}个，国徽面{}个)，请在个人档案中各标记1个，未标记的文件按存储顺序上传",
+        front, back
+    )))
+}
+
+// Helper function to convert relative file paths to absolute paths
+pub(crate) fn get_absolute_file_paths(file_paths_json: &Option<String>) -> Result<Vec<String>> {
+    Ok(resolve_absolute_file_paths(&parse_stored_file_paths(file_paths_json)?))
+}
+
+/// 把存储的路径 (应用数据目录下的相对路径或旧数据中的绝对路径) 解析为存在的绝对路径，保持原顺序
+pub(crate) fn resolve_absolute_file_paths(paths: &[String]) -> Vec<String> {
+    let app_data_dir = get_app_data_dir();
     let mut absolute_paths = Vec::new();
     let paths_count = paths.len();
     
-    for relative_path in &paths {
+    for relative_path in paths {
         if relative_path.trim().is_empty() {
             continue;
         }
         
-        // If path starts with "files/", it's a relative app data path
-        if relative_path.starts_with("files/") {
-            // Get absolute path using app handle
-            if let Ok(app_handle_guard) = crate::database::APP_HANDLE.lock() {
-                if let Some(app_handle) = app_handle_guard.as_ref() {
-                    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
-                        let abs_path = app_data_dir.join(relative_path);
-                        if abs_path.exists() {
-                            // 确保Windows路径格式统一 - 全部使用反斜杠
-                            let normalized_path = abs_path.to_string_lossy().replace('/', "\\");
-                            absolute_paths.push(normalized_path.clone());
-                            tracing::info!("Resolved file path: {} -> {} (normalized: {})", relative_path, abs_path.display(), normalized_path);
-                        } else {
-                            tracing::warn!("File does not exist: {}", abs_path.display());
-                        }
-                        continue;
-                    }
-                }
-            }
-            tracing::warn!("Failed to resolve app data path for: {}", relative_path);
-        } else {
-            // Handle absolute paths - might be legacy data
-            let path = std::path::Path::new(relative_path);
-            if path.exists() {
-                // Check if this absolute path is outside app data directory
-                // If so, try to find corresponding file in app data directory
-                if let Ok(app_handle_guard) = crate::database::APP_HANDLE.lock() {
-                    if let Some(app_handle) = app_handle_guard.as_ref() {
-                        if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
-                            let app_data_str = app_data_dir.to_string_lossy();
-                            
-                            // If absolute path is outside app data directory
-                            if !relative_path.starts_with(&*app_data_str) {
-                                // Try to find corresponding file in app data directory
-                                let mut found_in_app_data = false;
-                                if let Some(filename) = path.file_name() {
-                                    // Search in common locations
-                                    let search_paths = [
-                                        app_data_dir.join("files").join("ip_assets").join("auth_docs").join(filename),
-                                        app_data_dir.join("files").join("ip_assets").join("proof_docs").join(filename),
-                                        app_data_dir.join("files").join("profiles").join("id_cards").join(filename),
-                                    ];
-                                    
-                                    for search_path in &search_paths {
-                                        if search_path.exists() {
-                                            // 确保Windows路径格式统一
-                                            let normalized_path = search_path.to_string_lossy().replace('/', "\\");
-                                            absolute_paths.push(normalized_path.clone());
-                                            tracing::info!("Found corresponding file in app data: {} -> {} (normalized: {})", relative_path, search_path.display(), normalized_path);
-                                            found_in_app_data = true;
-                                            break;
-                                        }
-                                    }
-                                }
-                                
-                                // If not found in app data, use original absolute path with normalization
-                                if !found_in_app_data {
-                                    let normalized_path = relative_path.replace('/', "\\");
-                                    absolute_paths.push(normalized_path.clone());
-                                    tracing::info!("Using existing absolute path (not found in app data): {} (normalized: {})", relative_path, normalized_path);
-                                }
-                            } else {
-                                // Already in app data directory - normalize path
-                                let normalized_path = relative_path.replace('/', "\\");
-                                absolute_paths.push(normalized_path.clone());
-                                tracing::info!("Using existing absolute path: {} (normalized: {})", relative_path, normalized_path);
-                            }
-                        } else {
-                            let normalized_path = relative_path.replace('/', "\\");
-                            absolute_paths.push(normalized_path.clone());
-                            tracing::info!("Using existing absolute path: {} (normalized: {})", relative_path, normalized_path);
-                        }
-                    } else {
-                        let normalized_path = relative_path.replace('/', "\\");
-                        absolute_paths.push(normalized_path.clone());
-                        tracing::info!("Using existing absolute path: {} (normalized: {})", relative_path, normalized_path);
-                    }
-                } else {
-                    let normalized_path = relative_path.replace('/', "\\");
-                    absolute_paths.push(normalized_path.clone());
-                    tracing::info!("Using existing absolute path: {} (normalized: {})", relative_path, normalized_path);
-                }
+        // If path starts with "files/" (or "files\" when saved on Windows), it's a relative app data path
+        if relative_path.starts_with("files/") || relative_path.starts_with("files\\") {
+            let Some(app_data_dir) = app_data_dir.as_ref() else {
+                tracing::warn!("Failed to resolve app data path for: {}", relative_path);
+                continue;
+            };
+            let abs_path = app_data_dir.join(normalize_windows_path(relative_path));
+            if abs_path.exists() {
+                let normalized_path = normalize_windows_path(&abs_path.to_string_lossy());
+                tracing::info!("Resolved file path: {} -> {}", relative_path, normalized_path);
+                absolute_paths.push(normalized_path);
             } else {
-                tracing::warn!("Absolute file path does not exist: {}", relative_path);
+                tracing::warn!("File does not exist: {}", abs_path.display());
+            }
+            continue;
+        }
+        
+        // Handle absolute paths - might be legacy data
+        let normalized_input = normalize_windows_path(relative_path);
+        let path = std::path::Path::new(&normalized_input);
+        if !path.exists() {
+            tracing::warn!("Absolute file path does not exist: {}", relative_path);
+            continue;
+        }
+        
+        // Files outside the app data directory may have been copied into it; prefer that copy.
+        // Compare by path components so mixed separators, spaces and non-ASCII directory
+        // names don't make an app data path look external
+        let app_data_copy = match app_data_dir.as_ref() {
+            Some(app_data_dir) if !path.starts_with(app_data_dir) => find_in_app_data(app_data_dir, path),
+            _ => None,
+        };
+        
+        match app_data_copy {
+            Some(copy_path) => {
+                let normalized_path = normalize_windows_path(&copy_path.to_string_lossy());
+                tracing::info!("Found corresponding file in app data: {} -> {}", relative_path, normalized_path);
+                absolute_paths.push(normalized_path);
+            }
+            None => {
+                tracing::info!("Using existing absolute path: {} (normalized: {})", relative_path, normalized_input);
+                absolute_paths.push(normalized_input);
             }
         }
     }
     
     tracing::info!("Resolved {} file paths from {} input paths", absolute_paths.len(), paths_count);
-    Ok(absolute_paths)
-}
-
-// 生成授权证明文件上传代码
-fn generate_auth_files_upload_code(auth_files: &[String]) -> String {
-    if auth_files.is_empty() {
-        return "console.log('ℹ️ 无授权证明文件需要上传');".to_string();
-    }
-
-    let files_array = auth_files.iter()
-        .map(|path| escape_file_path_for_js_array(path))
-        .collect::<Vec<_>>()
-        .join(", ");
-
-    format!(r#"
-        console.log('📋 开始上传授权证明文件...');
-        try {{
-            const authFiles = [{}];
-            console.log('📁 授权证明文件数量:', authFiles.length);
-            
-            // 使用更精确的选择器，基于用户录制的操作
-            const authUploadArea = page.locator('div:nth-child(3) > .el-form-item__content > .inline-form-item > .copyright-img-upload > div > .el-upload');
-            const uploadExists = await authUploadArea.count();
-            console.log('🔍 授权证明上传区域数量:', uploadExists);
-            
-            if (uploadExists > 0) {{
-                await authUploadArea.first().setInputFiles(authFiles);
-                console.log('✅ 授权证明文件上传完成');
-                await page.waitForTimeout(2000); // 等待处理完成
-            }} else {{
-                console.log('⚠️ 未找到授权证明上传区域，尝试备用方法');
-                const backupSelector = page.locator('.el-form-item:has-text("授权证明") input[type="file"]');
-                const backupExists = await backupSelector.count();
-                if (backupExists > 0) {{
-                    await backupSelector.first().setInputFiles(authFiles);
-                    console.log('✅ 授权证明文件上传完成 (备用方法)');
-                    await page.waitForTimeout(2000);
-                }}
-            }}
-        }} catch (error) {{
-            console.error('❌ 授权证明文件上传失败:', error);
-        }}"#, files_array)
-}
-
-// 生成作品证明文件上传代码
-fn generate_work_proof_files_upload_code(work_proof_files: &[String]) -> String {
-    if work_proof_files.is_empty() {
-        return "console.log('ℹ️ 无作品证明文件需要上传');".to_string();
-    }
-
-    let files_array = work_proof_files.iter()
-        .map(|path| escape_file_path_for_js_array(path))
-        .collect::<Vec<_>>()
-        .join(", ");
-
-    format!(r#"
-        console.log('🏆 开始上传作品证明文件...');
-        try {{
-            const workProofFiles = [{}];
-            console.log('📁 作品证明文件数量:', workProofFiles.length);
-            
-            // 使用更精确的选择器，基于用户录制的操作
-            const workProofUploadArea = page.locator('.el-form-item.default-item > .el-form-item__content > .inline-form-item > .copyright-img-upload > div > .el-upload');
-            const uploadExists = await workProofUploadArea.count();
-            console.log('🔍 作品证明上传区域数量:', uploadExists);
-            
-            if (uploadExists > 0) {{
-                await workProofUploadArea.first().setInputFiles(workProofFiles);
-                console.log('✅ 作品证明文件上传完成');
-                await page.waitForTimeout(2000); // 等待处理完成
-            }} else {{
-                console.log('⚠️ 未找到作品证明上传区域，尝试备用方法');
-                const backupSelector = page.locator('.el-form-item:has-text("证明")').last().locator('input[type="file"]');
-                const backupExists = await backupSelector.count();
-                if (backupExists > 0) {{
-                    await backupSelector.setInputFiles(workProofFiles);
-                    console.log('✅ 作品证明文件上传完成 (备用方法)');
-                    await page.waitForTimeout(2000);
-                }}
-            }}
-        }} catch (error) {{
-            console.error('❌ 作品证明文件上传失败:', error);
-        }}"#, files_array)
-}
\ No newline at end of file
+    absolute_paths
+}
+
+/// 统一路径分隔符：Windows上把所有正斜杠替换为反斜杠并合并重复分隔符(保留UNC前缀)
+/// 其他平台原样返回，反斜杠在那里是合法的文件名字符
+fn normalize_windows_path(path: &str) -> String {
+    if !cfg!(windows) {
+        return path.to_string();
+    }
+    unify_windows_separators(path)
+}
+
+// normalize_windows_path在Windows上的实际转换，不依赖平台以便测试
+fn unify_windows_separators(path: &str) -> String {
+    let unified = path.replace('/', "\\");
+    let (prefix, rest) = if unified.starts_with("\\\\") {
+        ("\\\\", &unified[2..])
+    } else {
+        ("", unified.as_str())
+    };
+    
+    let mut normalized = String::with_capacity(unified.len());
+    normalized.push_str(prefix);
+    let mut previous_was_separator = false;
+    for ch in rest.chars() {
+        if ch == '\\' {
+            if previous_was_separator {
+                continue;
+            }
+            previous_was_separator = true;
+        } else {
+            previous_was_separator = false;
+        }
+        normalized.push(ch);
+    }
+    normalized
+}
+
+/// Check one stored file and, when it is missing, look for a file with the same
+/// name in the app data upload folders using the same search as get_absolute_file_paths
+pub(crate) fn check_stored_file(stored_path: &str) -> FileStatus {
+    let app_data_dir = get_app_data_dir();
+    let normalized = normalize_windows_path(stored_path);
+    let resolved = if stored_path.starts_with("files/") || stored_path.starts_with("files\\") {
+        app_data_dir.as_ref().map(|dir| dir.join(&normalized))
+    } else {
+        Some(std::path::PathBuf::from(&normalized))
+    };
+    let exists = resolved.as_ref().map_or(false, |path| path.exists());
+
+    let suggested_path = if exists {
+        None
+    } else {
+        app_data_dir.as_ref().and_then(|dir| {
+            let found = find_in_app_data(dir, std::path::Path::new(&normalized))?;
+            // Suggest the same relative form copy_file_to_app_data stores
+            let relative = found.strip_prefix(dir).ok()?;
+            let components: Vec<String> = relative.components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect();
+            Some(components.join("/"))
+        })
+    };
+
+    if !exists {
+        tracing::warn!("Stored file is missing: {} (suggestion: {:?})", stored_path, suggested_path);
+    }
+    FileStatus {
+        path: stored_path.to_string(),
+        resolved_path: resolved.map(|path| normalize_windows_path(&path.to_string_lossy())),
+        exists,
+        suggested_path,
+    }
+}
+
+// 与上传脚本中的校验规则保持一致
+pub(crate) const UPLOAD_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+const UPLOAD_SIZE_WARNING_BYTES: u64 = 10 * 1024 * 1024;
+// 身份证需要正反面两张
+const ID_CARD_REQUIRED_FILES: usize = 2;
+
+/// 按上传脚本的规则逐个检查文件：存在性、大小、格式
+/// PDF会在生成脚本前尝试转换为图片，这里只标记出来，不算作错误
+pub fn validate_upload_files(files: &[String]) -> Vec<UploadFileCheck> {
+    files.iter().map(|file| {
+        let path = std::path::Path::new(file);
+        let extension = path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let is_image = UPLOAD_IMAGE_EXTENSIONS.contains(&extension.as_str());
+        let is_pdf = crate::pdf_convert::is_pdf(file);
+        let mut problems = Vec::new();
+
+        let size_bytes = match std::fs::metadata(path) {
+            Ok(metadata) => Some(metadata.len()),
+            Err(e) => {
+                problems.push(format!("文件无法读取: {}", e));
+                None
+            }
+        };
+        match size_bytes {
+            Some(0) => problems.push("文件为空".to_string()),
+            Some(size) if size > UPLOAD_SIZE_WARNING_BYTES => {
+                problems.push(format!("文件超过10MB ({:.2} MB)，可能被拒绝", size as f64 / 1024.0 / 1024.0))
+            }
+            _ => {}
+        }
+        if !is_image && !is_pdf {
+            problems.push("不是支持的图片格式".to_string());
+        }
+
+        // 过大只是提醒，和上传脚本一样仍然尝试上传
+        let valid = matches!(size_bytes, Some(size) if size > 0) && (is_image || is_pdf);
+        UploadFileCheck {
+            path: file.clone(),
+            exists: size_bytes.is_some(),
+            size_bytes,
+            is_image,
+            is_pdf,
+            valid,
+            problems,
+        }
+    }).collect()
+}
+
+// 姓名、手机号、邮箱、身份证号、身份证正面、身份证反面
+const PROFILE_CHECK_COUNT: usize = 6;
+
+/// 检查当前档案和IP资产是否满足申诉流程的全部前提，按检查项给出完成度和缺失项
+pub fn profile_completeness(profile: Option<&Profile>, assets: &[IpAsset]) -> CompletenessReport {
+    let mut checks: Vec<(bool, String)> = Vec::new();
+
+    match profile {
+        Some(profile) => {
+            checks.push((!profile.name.trim().is_empty(), "缺少姓名".to_string()));
+            checks.push((
+                crate::models::normalize_phone(&profile.phone).is_ok(),
+                if profile.phone.trim().is_empty() { "缺少手机号" } else { "手机号格式不正确" }.to_string(),
+            ));
+            checks.push((profile.email.contains('@'), "缺少有效的邮箱".to_string()));
+            checks.push((
+                crate::models::normalize_id_card(&profile.id_card_number).is_ok(),
+                if profile.id_card_number.trim().is_empty() { "缺少身份证号" } else { "身份证号校验失败" }.to_string(),
+            ));
+            let (front, back) = id_card_sides_present(profile);
+            checks.push((front, "缺少身份证正面".to_string()));
+            checks.push((back, "缺少身份证反面".to_string()));
+        }
+        None => checks.push((false, "尚未创建个人档案".to_string())),
+    }
+
+    let asset_problems: Vec<Vec<String>> = assets.iter().map(asset_readiness_problems).collect();
+    let ready_asset_count = asset_problems.iter().filter(|problems| problems.is_empty()).count();
+    let asset_missing = if assets.is_empty() {
+        "至少添加一个IP资产".to_string()
+    } else {
+        // 所有资产都不可用时，提示问题最少的那个，用户补齐它最快
+        asset_problems
+            .iter()
+            .zip(assets)
+            .min_by_key(|(problems, _)| problems.len())
+            .map(|(problems, asset)| format!("IP资产「{}」{}", asset.work_name, problems.join("、")))
+            .unwrap_or_default()
+    };
+    checks.push((ready_asset_count > 0, asset_missing));
+
+    // 没有档案时只记一条缺失项，但所有档案检查项都算未完成
+    let total = match profile {
+        Some(_) => checks.len(),
+        None => checks.len() + PROFILE_CHECK_COUNT - 1,
+    };
+    let satisfied = checks.iter().filter(|(ok, _)| *ok).count();
+    let missing: Vec<String> = checks.into_iter().filter(|(ok, _)| !ok).map(|(_, item)| item).collect();
+    CompletenessReport {
+        percent: (satisfied * 100 / total) as u8,
+        complete: missing.is_empty(),
+        missing,
+        ready_asset_count,
+    }
+}
+
+// 按正反面标记查找本地存在且格式有效的身份证文件；档案未标记时按存储顺序前两个有效文件计
+fn id_card_sides_present(profile: &Profile) -> (bool, bool) {
+    let roles = parse_id_card_roles(profile);
+    let stored: Vec<String> = parse_stored_file_paths(&profile.id_card_files)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| !path.trim().is_empty())
+        .collect();
+    let usable = |path: &String| {
+        let status = check_stored_file(path);
+        status.exists
+            && status.resolved_path
+                .is_some_and(|resolved| validate_upload_files(&[resolved]).iter().all(|file| file.valid))
+    };
+    if roles.is_empty() {
+        let valid = stored.iter().filter(|path| usable(path)).count();
+        return (valid >= 1, valid >= ID_CARD_REQUIRED_FILES);
+    }
+    let has = |role: IdCardRole| stored.iter().any(|path| roles.get(path) == Some(&role) && usable(path));
+    (has(IdCardRole::Front), has(IdCardRole::Back))
+}
+
+// IP资产用于申诉前缺少的材料，空列表表示可以直接使用
+fn asset_readiness_problems(asset: &IpAsset) -> Vec<String> {
+    let has_files = |files: &Option<String>| {
+        parse_stored_file_paths(files)
+            .unwrap_or_default()
+            .iter()
+            .any(|path| !path.trim().is_empty() && check_stored_file(path).exists)
+    };
+    let mut problems = Vec::new();
+    if !crate::bilibili_script::is_known_region(&asset.region) {
+        problems.push("地区不在B站可选范围内".to_string());
+    }
+    if !crate::bilibili_script::is_known_work_type(&asset.work_type) {
+        problems.push("著作类型不在B站可选范围内".to_string());
+    }
+    if asset.is_agent && !has_files(&asset.auth_files) {
+        problems.push("缺少授权文件".to_string());
+    }
+    if !has_files(&asset.work_proof_files) {
+        problems.push("缺少作品证明文件".to_string());
+    }
+    problems
+}
+
+/// 不启动浏览器，检查档案中的身份证文件能否用于上传
+pub fn dry_run_profile_upload(profile: &Profile) -> Result<UploadValidationReport> {
+    let stored = parse_stored_file_paths(&profile.id_card_files)?;
+    let stored: Vec<String> = stored.into_iter().filter(|path| !path.trim().is_empty()).collect();
+    let missing_files: Vec<FileStatus> = stored.iter()
+        .map(|path| check_stored_file(path))
+        .filter(|status| !status.exists)
+        .collect();
+
+    let resolved = resolve_absolute_file_paths(&ordered_id_card_files(profile)?);
+    let files = validate_upload_files(&resolved);
+    let valid_count = files.iter().filter(|file| file.valid).count();
+
+    let mut warnings = Vec::new();
+    if stored.is_empty() {
+        warnings.push("档案中没有配置身份证文件".to_string());
+    } else if !missing_files.is_empty() {
+        warnings.push(format!("{}个文件在本地找不到", missing_files.len()));
+    }
+    if valid_count < ID_CARD_REQUIRED_FILES {
+        warnings.push(format!("有效文件{}个，身份证需要正反面{}个文件", valid_count, ID_CARD_REQUIRED_FILES));
+    }
+    if files.iter().any(|file| file.valid && file.is_pdf) {
+        warnings.push("PDF文件将在上传前转换为首页图片".to_string());
+    }
+    if !stored.is_empty() {
+        warnings.extend(id_card_role_warning(profile)?);
+    }
+
+    tracing::info!("档案文件检查: 共{}个，有效{}个，缺失{}个", stored.len(), valid_count, missing_files.len());
+    Ok(UploadValidationReport {
+        stored_count: stored.len(),
+        valid_count,
+        files,
+        missing_files,
+        warnings,
+        ready: valid_count >= ID_CARD_REQUIRED_FILES,
+    })
+}
+
+fn get_app_data_dir() -> Option<std::path::PathBuf> {
+    crate::database::app_handle().ok()?.path().app_data_dir().ok()
+}
+
+// 在应用数据目录的常用上传位置查找同名文件
+fn find_in_app_data(app_data_dir: &std::path::Path, path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let filename = path.file_name()?;
+    let files_dir = app_data_dir.join("files");
+    [
+        files_dir.join("ip_assets").join("auth_docs").join(filename),
+        files_dir.join("ip_assets").join("proof_docs").join(filename),
+        files_dir.join("profiles").join("id_cards").join(filename),
+    ]
+    .into_iter()
+    .find(|candidate| candidate.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_starts_claim_the_run_only_once() {
+        let barrier = Arc::new(tokio::sync::Barrier::new(2));
+        let attempts: Vec<_> = (0..2)
+            .map(|_| {
+                let barrier = barrier.clone();
+                tokio::spawn(async move {
+                    barrier.wait().await;
+                    // 与start_automation相同: 持有状态锁时占用运行状态并标记为运行中
+                    let mut status = AUTOMATION_STATUS.lock().await;
+                    let claimed = claim_run(&status);
+                    if claimed.is_ok() {
+                        status.is_running = true;
+                    }
+                    claimed
+                })
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        for attempt in attempts {
+            results.push(attempt.await.expect("start task panicked"));
+        }
+        *AUTOMATION_STATUS.lock().await = idle_status();
+        RUN_IN_PROGRESS.store(false, Ordering::SeqCst);
+
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        let rejected = results.into_iter().find_map(Result::err).expect("one start should be rejected");
+        assert!(rejected.is::<AutomationAlreadyRunning>());
+    }
+
+    #[test]
+    fn windows_path_with_chinese_and_spaces_is_kept_intact() {
+        let path = r"C:\Users\张三\我的 文档\身份证.jpg";
+        assert_eq!(unify_windows_separators(path), path);
+    }
+
+    #[test]
+    fn windows_path_with_mixed_separators_is_unified() {
+        assert_eq!(unify_windows_separators(r"C:/foo\bar/baz.jpg"), r"C:\foo\bar\baz.jpg");
+        assert_eq!(unify_windows_separators(r"C://foo\\bar.jpg"), r"C:\foo\bar.jpg");
+        assert_eq!(unify_windows_separators(r"\\server\share//scan.jpg"), r"\\server\share\scan.jpg");
+    }
+
+    #[test]
+    fn non_windows_paths_are_returned_unchanged() {
+        if !cfg!(windows) {
+            assert_eq!(normalize_windows_path(r"/tmp/a\b.jpg"), r"/tmp/a\b.jpg");
+        }
+    }
+
+    #[test]
+    fn normalized_windows_path_survives_js_escaping() {
+        let normalized = unify_windows_separators(r"C:/Users/张三\我的 文档//身份证.jpg");
+        assert_eq!(normalized, r"C:\Users\张三\我的 文档\身份证.jpg");
+
+        // 生成脚本中的文件数组元素，JS字符串字面量也是合法的JSON字符串
+        let literal = escape_file_path_for_js_array(&normalized);
+        assert_eq!(literal, r#""C:\\Users\\张三\\我的 文档\\身份证.jpg""#);
+        let decoded: String = serde_json::from_str(&literal).expect("path literal is not valid JSON");
+        assert_eq!(decoded, normalized);
+    }
+
+    const ADVERSARIAL_INPUTS: &[&str] = &[
+        "第一行\n第二行\r\n第三行",
+        "it's a \"quoted\" name",
+        r"C:\path\to\file\",
+        "`${alert(1)}`",
+        "${process.exit()}",
+        "</script><script>alert(1)</script>",
+        "行分隔\u{2028}段分隔\u{2029}结束",
+        "\u{0}\u{7}\t混合`'\"\\${}</script>\u{2028}",
+    ];
+
+    #[test]
+    fn sanitize_for_js_replaces_control_and_line_terminator_characters() {
+        for input in ADVERSARIAL_INPUTS {
+            let sanitized = sanitize_for_js(input);
+            assert!(
+                !sanitized.chars().any(|c| c.is_control() || c == '\u{2028}' || c == '\u{2029}'),
+                "unsanitized characters left in {:?}",
+                sanitized
+            );
+            assert_eq!(sanitized.chars().count(), input.chars().count());
+        }
+    }
+
+    #[test]
+    fn js_string_literal_round_trips_adversarial_strings() {
+        for input in ADVERSARIAL_INPUTS {
+            let literal = js_string_literal(input);
+            for forbidden in ['\n', '\r', '`', '$', '<', '\u{2028}', '\u{2029}'] {
+                assert!(!literal.contains(forbidden), "{:?} contains raw {:?}", literal, forbidden);
+            }
+            let decoded: String = serde_json::from_str(&literal)
+                .unwrap_or_else(|e| panic!("{:?} is not a valid literal: {}", literal, e));
+            assert_eq!(decoded, sanitize_for_js(input));
+        }
+    }
+
+    #[test]
+    fn work_dir_does_not_depend_on_a_read_only_current_dir() {
+        let read_only_cwd = std::env::temp_dir().join(format!("rightsguard-cwd-{}", uuid::Uuid::new_v4()));
+        let output_dir = std::env::temp_dir().join(format!("rightsguard-output-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&read_only_cwd).expect("create cwd");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&read_only_cwd, std::fs::Permissions::from_mode(0o555)).expect("make cwd read-only");
+        }
+        // 测试中没有APP_HANDLE，未设置覆盖目录时使用临时目录下的回退位置
+        let fallback = resolve_work_dir(&AppSettings::default(), &read_only_cwd);
+        let overridden = resolve_work_dir(&AppSettings {
+            script_output_dir: Some(output_dir.to_string_lossy().to_string()),
+            ..AppSettings::default()
+        }, &read_only_cwd);
+
+        let _ = std::fs::remove_dir(&read_only_cwd);
+        let overridden_created = output_dir.is_dir();
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let fallback = fallback.expect("fallback work dir");
+        assert!(fallback.is_absolute());
+        assert!(!fallback.starts_with(&read_only_cwd));
+        assert!(fallback.ends_with(AUTOMATION_WORK_DIR_NAME));
+        assert_eq!(overridden.expect("overridden work dir"), output_dir);
+        assert!(overridden_created);
+    }
+
+    #[test]
+    fn custom_user_agent_session_is_not_reused_for_a_default_run() {
+        let custom = "Mozilla/5.0 RightsGuardTest";
+        assert!(session_user_agent_matches(Some(custom), Some(custom), custom));
+        assert!(!session_user_agent_matches(Some(custom), None, "Mozilla/5.0 Chrome"));
+        assert!(session_user_agent_matches(None, None, "Mozilla/5.0 Chrome"));
+        // 上次运行指定了UA，本次未指定: 应重新启动为默认UA
+        assert!(!session_user_agent_matches(None, Some(custom), custom));
+    }
+
+    #[test]
+    fn stale_run_files_are_removed_before_the_next_run() {
+        let output_dir = std::env::temp_dir().join(format!("rightsguard-output-{}", uuid::Uuid::new_v4()));
+        let settings = AppSettings {
+            script_output_dir: Some(output_dir.to_string_lossy().to_string()),
+            ..AppSettings::default()
+        };
+        let work_dir = automation_work_dir(&settings).expect("work dir");
+        // 上一次被停止的运行留下的脚本和已完成验证的信号
+        std::fs::write(work_dir.join(CONNECT_SCRIPT_NAME), "// stale script").expect("write script");
+        std::fs::write(work_dir.join(VERIFICATION_SIGNAL_FILE), "completed").expect("write signal");
+        let unrelated = work_dir.join("keep.txt");
+        std::fs::write(&unrelated, "keep").expect("write unrelated file");
+
+        let removed = remove_stale_run_files(&settings);
+        let leftovers = (
+            work_dir.join(CONNECT_SCRIPT_NAME).exists(),
+            work_dir.join(VERIFICATION_SIGNAL_FILE).exists(),
+            unrelated.exists(),
+        );
+        let removed_again = remove_stale_run_files(&settings);
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        assert_eq!(removed.len(), 2);
+        assert_eq!(leftovers, (false, false, true));
+        assert!(removed_again.is_empty());
+    }
+}
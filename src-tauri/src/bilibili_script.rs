@@ -0,0 +1,1512 @@
+// src-tauri/src/bilibili_script.rs
+
+use anyhow::Result;
+use crate::automation::{
+    cdp_connect_snippet, escape_file_path_for_js_array, get_absolute_file_paths, id_card_role_warning, js_string_literal,
+    ordered_id_card_files, resolve_absolute_file_paths, test_target_url, validate_appeal_url, validate_upload_files, SiteAutomation,
+    AutomationError, BROWSER_CLOSED_MARKER, FIELDS_NOT_FILLED_MARKER, FILE_NOT_FOUND_MARKER, FORM_VALIDATION_MARKER, NOT_LOGGED_IN_MARKER, QUOTA_EXCEEDED_MARKER,
+    SELF_TEST_FAILED_MARKER, SUBMIT_NOT_CONFIRMED_MARKER, VERIFICATION_TIMEOUT_MARKER,
+};
+use crate::i18n::Message;
+use crate::models::{AppSettings, AutomationRequest, AutomationStage, IpAsset, Profile, ScriptTiming};
+use crate::pdf_convert::convert_pdfs_to_images;
+
+// ---- B站申诉表单的选择器，生成的脚本和原生后端(native_backend)共用 ----
+
+// 未登录时B站页面出现的登录弹窗/登录面板选择器
+pub(crate) const LOGIN_WALL_SELECTORS: &[&str] = &[
+    ".bili-mini-mask",
+    ".bili-mini-login",
+    ".login-panel",
+];
+
+// 自测使用的模拟B站申诉表单
+pub const SELF_TEST_FIXTURE_HTML: &str = include_str!("../assets/self_test_fixture.html");
+
+// "是否代理"单选框所在的表单项；选"是"后授权期限和授权证明才会出现
+pub(crate) const AGENT_TOGGLE_SELECTOR: &str = r#".el-form-item:has-text("是否代理")"#;
+// 选"是"后出现的授权期限表单项，用于确认授权部分已展开
+// 按表单项自身的标签匹配，避免:has-text匹配到包含多个表单项的外层元素
+pub(crate) const AUTH_PERIOD_SELECTOR: &str = r#".el-form-item:has(> .el-form-item__label:text-is("授权期限"))"#;
+
+// B站申诉表单地区下拉框中的选项
+pub const BILIBILI_REGIONS: &[&str] = &["中国大陆", "中国香港", "中国澳门", "中国台湾", "海外"];
+
+pub fn is_known_region(region: &str) -> bool {
+    BILIBILI_REGIONS.contains(&region.trim())
+}
+
+// B站申诉表单著作类型下拉框中的选项 (按著作权法的作品类型划分)，脚本按完全一致的文字选择
+pub const BILIBILI_WORK_TYPES: &[&str] = &[
+    "文字作品",
+    "口述作品",
+    "音乐作品",
+    "戏剧作品",
+    "曲艺作品",
+    "舞蹈作品",
+    "杂技艺术作品",
+    "美术作品",
+    "建筑作品",
+    "摄影作品",
+    "视听作品",
+    "图形作品",
+    "模型作品",
+    "计算机软件",
+    "其他",
+];
+
+pub fn is_known_work_type(work_type: &str) -> bool {
+    BILIBILI_WORK_TYPES.contains(&work_type.trim())
+}
+
+// 个人信息页的身份证上传区域 (该页只有这一个图片上传区域)
+pub(crate) const ID_CARD_UPLOAD_SELECTOR: &str = ".copyright-img-upload";
+// IP资产页的授权证明和作品证明上传区域，基于录制的操作定位到各自的表单项
+pub(crate) const AUTH_FILES_UPLOAD_SELECTOR: &str = "div:nth-child(3) > .el-form-item__content > .inline-form-item > .copyright-img-upload";
+pub(crate) const WORK_PROOF_UPLOAD_SELECTOR: &str = ".el-form-item.default-item > .el-form-item__content > .inline-form-item > .copyright-img-upload";
+
+// 申诉详情中补充证据的上传区域 (表单项标签包含"证据"或"附件")
+pub(crate) const EVIDENCE_UPLOAD_SELECTOR: &str = r#".el-form-item:has-text("证据"):has(.el-upload), .el-form-item:has-text("附件"):has(.el-upload)"#;
+
+// 点击提交后页面出现的成功提示 (JS正则，原生后端作为text=选择器使用)
+pub(crate) const SUBMIT_SUCCESS_PATTERN: &str = "/提交成功|申诉已提交|我们会尽快处理/";
+
+// 申诉次数超出B站限制时页面出现的提示 (JS正则，原生后端作为text=选择器使用)
+pub(crate) const QUOTA_EXCEEDED_PATTERN: &str = "/次数已达上限|已达今日上限|已达到今日上限|申诉过于频繁|操作过于频繁/";
+
+// 个人信息页的输入框
+pub(crate) const NAME_INPUT_SELECTOR: &str = r#"input[placeholder="真实姓名"].el-input__inner"#;
+pub(crate) const PHONE_INPUT_SELECTOR: &str = r#"input[placeholder="手机号"].el-input__inner"#;
+pub(crate) const EMAIL_INPUT_SELECTOR: &str = r#".el-form-item:has-text("邮箱") input.el-input__inner"#;
+pub(crate) const ID_CARD_INPUT_SELECTOR: &str = r#"input[placeholder="证件号码"].el-input__inner"#;
+
+// IP资产页的表单项；权利人出现即说明验证码已完成、进入了IP资产页
+pub(crate) const RIGHTS_HOLDER_SELECTOR: &str = r#".el-form-item:has-text("权利人")"#;
+pub(crate) const WORK_TYPE_SELECTOR: &str = r#".el-form-item:has-text("著作类型")"#;
+pub(crate) const WORK_NAME_SELECTOR: &str = r#".el-form-item:has-text("著作名称")"#;
+pub(crate) const REGION_SELECTOR: &str = r#".el-form-item:has-text("地区")"#;
+// 作品期限，标签完全等于"期限"，不会匹配到授权期限
+pub(crate) const WORK_PERIOD_SELECTOR: &str = r#".el-form-item:has(> .el-form-item__label:text-is("期限"))"#;
+
+// 申诉详情页的输入框和保证声明
+pub(crate) const INFRINGING_URL_INPUT_SELECTOR: &str = r#"input[placeholder*="他人发布的B站侵权链接"]"#;
+pub(crate) const DESCRIPTION_TEXTAREA_SELECTOR: &str = r#"textarea[placeholder*="该链接内容全部"]"#;
+pub(crate) const GUARANTEE_CHECKBOX_SELECTOR: &str = r#".el-checkbox__label:has-text("本人保证")"#;
+
+// 权利人、著作名称、地区的候选选择器 (选择器, 说明)，脚本按顺序尝试直到填写成功
+const RIGHTS_HOLDER_INPUT_STRATEGIES: &[(&str, &str)] = &[
+    (r#".el-form-item:has-text("权利人") input[type="text"]"#, "文本输入框(type=text)"),
+    (r#".el-form-item:has-text("权利人") .el-input__inner"#, "Element UI输入框(.el-input__inner)"),
+    (r#".el-form-item:has-text("权利人") input:not([type="radio"]):not([type="checkbox"])"#, "非单选按钮输入框"),
+    (r#".el-form-item:has-text("权利人") textarea"#, "文本域"),
+    (r#".el-form-item:has-text("权利人") [contenteditable="true"]"#, "可编辑内容元素"),
+];
+const WORK_NAME_INPUT_STRATEGIES: &[(&str, &str)] = &[
+    (r#".el-form-item:has-text("著作名称") input[type="text"]"#, "文本输入框"),
+    (r#".el-form-item:has-text("著作名称") .el-input__inner"#, "Element UI输入框"),
+    (r#"div:has-text("著作名称") input:not([type="radio"]):not([type="checkbox"])"#, "非单选按钮输入框"),
+    (r#"div:has-text("著作名称") [role="textbox"]"#, "角色为textbox的元素"),
+];
+const REGION_INPUT_STRATEGIES: &[(&str, &str)] = &[
+    (r#".el-form-item:has-text("地区") .el-select"#, "地区表单项内的下拉选择框"),
+    (r#".el-form-item:has-text("地区") .el-input"#, "地区表单项内的输入框"),
+    (r#"div:has-text("地区") [role="textbox"]"#, "地区相关的textbox角色元素"),
+    (r#".el-form-item:has-text("地区") .el-input__inner"#, "地区表单项内的输入核心元素"),
+];
+
+// 脚本中用到的Element UI组件选择器，生成时写入脚本开头的SELECTORS对象，脚本只通过SELECTORS读取
+const SCRIPT_SELECTORS: &[(&str, &str)] = &[
+    ("dropdownOption", ".el-select-dropdown:visible li"),
+    ("formError", ".el-form-item__error"),
+    ("pickerPanel", ".el-picker-panel:visible"),
+    ("pickerHeader", ".el-date-picker__header, .el-date-range-picker__header"),
+    ("pickerNextYear", ".el-icon-d-arrow-right"),
+    ("pickerPrevYear", ".el-icon-d-arrow-left"),
+    ("pickerNextMonth", ".el-icon-arrow-right"),
+    ("pickerPrevMonth", ".el-icon-arrow-left"),
+    ("dateTable", ".el-date-table"),
+    ("currentMonthDay", "td.available:not(.prev-month):not(.next-month)"),
+    ("uploadListItem", ".el-upload-list__item"),
+    ("fileInput", r#"input[type="file"]"#),
+    ("radio", ".el-radio"),
+    ("checkboxInput", ".el-checkbox__original"),
+];
+
+// 申诉详情中填写的侵权说明
+pub(crate) const APPEAL_DESCRIPTION: &str = "该链接内容侵犯了我的版权，要求立即删除。";
+
+/// B站版权申诉页面的脚本生成
+pub struct BilibiliAutomation;
+
+impl SiteAutomation for BilibiliAutomation {
+    // 解析申诉页面URL，只允许https的bilibili.com域名，避免把已登录会话导航到其他站点
+    // 请求未指定时使用设置中的申诉页面；测试模式使用测试页面
+    fn appeal_url(&self, request: &AutomationRequest, settings: &AppSettings) -> Result<String> {
+        if request.test_mode {
+            return test_target_url(settings);
+        }
+        let raw_url = match request.appeal_url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => url,
+            _ => settings.appeal_url.trim(),
+        };
+        validate_appeal_url(raw_url)
+    }
+
+    fn build_script(
+        &self,
+        profile: &Profile,
+        ip_asset: Option<&IpAsset>,
+        request: &AutomationRequest,
+        settings: &AppSettings,
+    ) -> Result<String> {
+        generate_connect_script(profile, ip_asset, request, settings)
+    }
+
+    fn progress_for_line(&self, line: &str) -> Option<(Message, f32)> {
+        if let Some(progress) = parse_upload_progress(line) {
+            return Some(progress);
+        }
+        SCRIPT_PROGRESS_MARKERS.iter()
+            .find(|(marker, _, _)| line.contains(marker))
+            .map(|(_, progress, step)| (Message::new(step), *progress))
+    }
+}
+
+// 脚本各阶段完成时输出的日志与对应进度(35%启动脚本到90%脚本结束之间)及步骤的消息key
+// 脚本通过progress_log输出这些日志，两边使用同一份文本
+const SCRIPT_PROGRESS_MARKERS: &[(&str, f32, &str)] = &[
+    ("✓ 个人信息填写完成", 40.0, "step.personal_info_filled"),
+    ("✅ 检测到IP资产页面", 60.0, "step.captcha_completed"),
+    ("✅ 授权证明文件上传完成", 68.0, "step.auth_files_uploaded"),
+    ("✅ 作品证明文件上传完成", 74.0, "step.proof_files_uploaded"),
+    ("✅ IP资产完整信息填写完成", 80.0, "step.ip_asset_filled"),
+    ("✓ 申诉详情填写完成", 85.0, "step.details_filled"),
+    ("✅ 申诉已提交", 88.0, "step.submitted_confirming"),
+];
+
+// 生成输出某一步骤完成日志的代码，日志文本取自SCRIPT_PROGRESS_MARKERS
+fn progress_log(step: &str) -> String {
+    let marker = SCRIPT_PROGRESS_MARKERS.iter()
+        .find(|(_, _, key)| *key == step)
+        .map(|(marker, _, _)| *marker)
+        .unwrap_or(step);
+    format!("console.log({});", js_string_literal(marker))
+}
+
+// 身份证逐个上传时的日志 "📄 上传第X/N个文件"，映射到42%-55%
+const UPLOAD_LINE_MARKER: &str = "上传第";
+const UPLOAD_PROGRESS_START: f32 = 42.0;
+const UPLOAD_PROGRESS_END: f32 = 55.0;
+
+fn parse_upload_progress(line: &str) -> Option<(Message, f32)> {
+    let (_, rest) = line.split_once(UPLOAD_LINE_MARKER)?;
+    let (counts, _) = rest.split_once("个文件")?;
+    let (current, total) = counts.split_once('/')?;
+    let current: u32 = current.trim().parse().ok()?;
+    let total: u32 = total.trim().parse().ok()?;
+    if total == 0 || current > total {
+        return None;
+    }
+    let progress = UPLOAD_PROGRESS_START + (UPLOAD_PROGRESS_END - UPLOAD_PROGRESS_START) * current as f32 / total as f32;
+    Some((Message::with_args("step.uploading_id_card", vec![current.to_string(), total.to_string()]), progress))
+}
+
+/// 一次申诉要上传的各类文件的绝对路径，PDF已转换为图片
+pub(crate) struct AppealFiles {
+    /// 已按人像面、国徽面、其他的顺序排列
+    pub id_card_files: Vec<String>,
+    /// 正反面标记不完整时的提示，此时未标记的文件按存储顺序上传
+    pub id_card_warning: Option<String>,
+    pub auth_files: Vec<String>,
+    pub work_proof_files: Vec<String>,
+    pub additional_files: Vec<String>,
+}
+
+/// 解析档案、IP资产和请求中的文件，生成脚本和原生后端共用
+pub(crate) fn resolve_appeal_files(
+    profile: &Profile,
+    ip_asset: Option<&IpAsset>,
+    request: &AutomationRequest,
+) -> Result<AppealFiles> {
+    // Process profile files (identity card documents) - 确保使用真实身份证文件
+    // B站上传组件只接受图片，PDF在生成脚本前转换为首页图片
+    let id_card_files = convert_pdfs_to_images(resolve_absolute_file_paths(&ordered_id_card_files(profile)?));
+    if id_card_files.is_empty() && request.start_stage == AutomationStage::Full {
+        tracing::warn!("⚠️ 个人档案中未配置身份证文件，请先在个人档案页面上传身份证正反面照片");
+        return Err(AutomationError::MissingData("个人档案中未配置身份证文件。请先在个人档案页面上传身份证正反面照片。".to_string()).into());
+    }
+    tracing::info!("Profile ID card files resolved: {:?}", id_card_files);
+    let id_card_warning = id_card_role_warning(profile)?;
+    match &id_card_warning {
+        Some(warning) => tracing::warn!("⚠️ {}", warning),
+        None => tracing::info!("✅ 身份证文件数量: {}，按人像面、国徽面顺序上传", id_card_files.len()),
+    }
+
+    // Process IP asset files if available
+    let (auth_files, work_proof_files) = if let Some(asset) = ip_asset {
+        // 非代理申诉不需要授权证明，页面上也没有对应的上传区域
+        let auth_files = if asset.is_agent {
+            convert_pdfs_to_images(get_absolute_file_paths(&asset.auth_files)?)
+        } else {
+            Vec::new()
+        };
+        let work_proof_files = convert_pdfs_to_images(get_absolute_file_paths(&asset.work_proof_files)?);
+        tracing::info!("IP asset auth files resolved: {:?}", auth_files);
+        tracing::info!("IP asset work proof files resolved: {:?}", work_proof_files);
+        (auth_files, work_proof_files)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let additional_files = resolve_additional_files(&request.additional_files)?;
+    Ok(AppealFiles { id_card_files, id_card_warning, auth_files, work_proof_files, additional_files })
+}
+
+fn generate_connect_script(
+    profile: &Profile,
+    ip_asset: Option<&IpAsset>,
+    request: &AutomationRequest,
+    settings: &AppSettings,
+) -> Result<String> {
+    let escaped_name = &profile.name;
+    let escaped_phone = &profile.phone;
+    let escaped_email = &profile.email;
+    let escaped_id_card = &profile.id_card_number;
+    let escaped_infringing_url = &request.infringing_url;
+
+    tracing::info!("脚本起始阶段: {:?}", request.start_stage);
+    let appeal_url = BilibiliAutomation.appeal_url(request, settings)?;
+    tracing::info!("申诉页面URL: {}", appeal_url);
+
+    let AppealFiles { id_card_files, id_card_warning, auth_files, work_proof_files, additional_files } =
+        resolve_appeal_files(profile, ip_asset, request)?;
+
+    // --- 完整的IP资产信息自动填写 ---
+    let ip_section = if request.start_stage == AutomationStage::FromAppealDetails {
+        "console.log('⏭️ 从申诉详情阶段继续，跳过IP资产填写');".to_string()
+    } else if let Some(asset) = ip_asset {
+        // 生成完整的IP资产表单填写JavaScript代码
+        format!(r#"
+        console.log('\\n⏰ 阶段4开始时间:', new Date().toISOString());
+        console.log('📋 开始填写完整IP资产信息...');
+        
+        // 填写权利人 - 使用智能选择器策略
+        console.log('👤 开始填写权利人信息...');
+        
+        // 🔍 第一步：分析权利人字段DOM结构
+        console.log('🔍 分析权利人字段DOM结构...');
+        try {{
+            const rightsHolderSection = page.locator({rights_holder_selector});
+            const sectionExists = await rightsHolderSection.count();
+            console.log(`📊 权利人表单项数量: ${{sectionExists}}`);
+            
+            if (sectionExists > 0) {{
+                const allInputs = await rightsHolderSection.locator('input').all();
+                console.log(`🔍 权利人字段包含 ${{allInputs.length}} 个input元素:`);
+                
+                for (let i = 0; i < Math.min(allInputs.length, 5); i++) {{
+                    const inputType = await allInputs[i].getAttribute('type') || 'text';
+                    const inputClass = await allInputs[i].getAttribute('class') || '';
+                    const inputValue = await allInputs[i].getAttribute('value') || '';
+                    const isVisible = await allInputs[i].isVisible();
+                    console.log(`  Input[${{i}}]: type=${{inputType}}, class="${{inputClass}}", value="${{inputValue}}", visible=${{isVisible}}`);
+                }}
+            }}
+        }} catch (domError) {{
+            console.log('⚠️ DOM分析失败:', domError.message);
+        }}
+        
+        // 🎯 第二步：使用多重选择器策略填写权利人
+        const rightsHolderStrategies = SELECTOR_STRATEGIES.rightsHolder;
+        
+        let rightsHolderFilled = false;
+        
+        for (let i = 0; i < rightsHolderStrategies.length && !rightsHolderFilled; i++) {{
+            const strategy = rightsHolderStrategies[i];
+            console.log(`🎯 尝试策略${{i+1}}: ${{strategy.name}} (${{strategy.selector}})`);
+            
+            try {{
+                const element = page.locator(strategy.selector);
+                const count = await element.count();
+                console.log(`   元素数量: ${{count}}`);
+                
+                if (count > 0) {{
+                    const firstElement = element.first();
+                    const isVisible = await firstElement.isVisible({{ timeout: 2000 }});
+                    const isEnabled = await firstElement.isEnabled();
+                    console.log(`   第一个元素: visible=${{isVisible}}, enabled=${{isEnabled}}`);
+                    
+                    if (isVisible && isEnabled) {{
+                        await firstElement.fill({owner});
+                        // 读回校验，值未生效时尝试下一个策略
+                        await page.waitForTimeout(WAIT_SHORT);
+                        rightsHolderFilled = await valueSticks(firstElement, {owner});
+                        if (rightsHolderFilled) {{
+                            console.log(`✅ 权利人填写成功! 使用策略: ${{strategy.name}}`);
+                        }} else {{
+                            console.log(`   ⚠️ 填写后读回的值不一致，尝试下一个策略`);
+                        }}
+                    }} else {{
+                        console.log(`   ⚠️ 元素不可见或不可用`);
+                    }}
+                }}
+            }} catch (strategyError) {{
+                console.log(`   ❌ 策略${{i+1}}失败: ${{strategyError.message}}`);
+            }}
+        }}
+        
+        if (!rightsHolderFilled) {{
+            console.error('❌ 所有权利人填写策略都失败了');
+            console.log('🔍 建议手动检查页面结构或联系开发者');
+            recordFieldFillFailure('权利人');
+        }} else {{
+            console.log('✅ 权利人信息填写完成');
+        }}
+        
+        {agent_section}
+        
+        // 填写授权期限 - 起始时间和结束时间 (仅代理申诉)
+        if ({is_agent} && {auth_start_date} && {auth_end_date}) {{
+            console.log('📅 设置授权期限...');
+            await fillDatePicker(page, {auth_period_selector}, '起始时间', {auth_start_date_simple}, '授权期限');
+            await fillDatePicker(page, {auth_period_selector}, '结束时间', {auth_end_date_simple}, '授权期限');
+        }}
+        
+        // 著作类型选择
+        console.log('🎨 选择著作类型...');
+        await page.locator({work_type_selector}).first().getByPlaceholder('请选择').click();
+        const workTypeOptions = page.locator(SELECTORS.dropdownOption);
+        await waitForVisible(workTypeOptions.first(), WAIT_LONG);
+        // 按完全一致的文字选择，避免"其他"之类的短词匹配到别的选项
+        const workTypeTexts = (await workTypeOptions.allTextContents()).map(text => text.trim());
+        const workTypeIndex = workTypeTexts.indexOf({work_type});
+        if (workTypeIndex < 0) {{
+            const workTypeError = `著作类型下拉框中没有"${{{work_type}}}"选项，可选: ${{workTypeTexts.join('、') || '未读取到选项'}}`;
+            console.error('{marker}' + JSON.stringify([workTypeError]));
+            throw new Error(workTypeError);
+        }}
+        await workTypeOptions.nth(workTypeIndex).click();
+        console.log(`✅ 著作类型已选择: ${{workTypeTexts[workTypeIndex]}}`);
+        
+        // 填写著作名称 - 使用安全选择器策略
+        console.log('📝 开始填写著作名称...');
+        const workNameStrategies = SELECTOR_STRATEGIES.workName;
+        
+        let workNameFilled = false;
+        for (let i = 0; i < workNameStrategies.length && !workNameFilled; i++) {{
+            const strategy = workNameStrategies[i];
+            try {{
+                const element = page.locator(strategy.selector);
+                const count = await element.count();
+                if (count > 0 && await element.first().isVisible({{ timeout: 1000 }})) {{
+                    await element.first().fill({work_name});
+                    workNameFilled = await valueSticks(element.first(), {work_name});
+                    if (workNameFilled) {{
+                        console.log(`✅ 著作名称填写成功! 使用: ${{strategy.name}}`);
+                    }}
+                }}
+            }} catch (error) {{
+                console.log(`⚠️ 著作名称策略${{i+1}}失败: ${{error.message}}`);
+            }}
+        }}
+        
+        if (!workNameFilled) {{
+            console.error('❌ 著作名称填写失败，尝试备用方法...');
+            const workNameBackup = page.locator('div').filter({{ hasText: /^著作名称/ }}).getByRole('textbox');
+            if (await fillAndVerify(workNameBackup, {work_name}, '著作名称')) {{
+                console.log('✅ 著作名称填写成功 (备用方法)');
+            }}
+        }}
+        
+        // 地区选择 (按IP资产设置，默认中国大陆) - 使用精确选择器
+        const region = {region};
+        console.log(`🌏 开始设置地区: ${{region}}`);
+        const regionStrategies = SELECTOR_STRATEGIES.region;
+        
+        let regionSelected = false;
+        for (let i = 0; i < regionStrategies.length && !regionSelected; i++) {{
+            const strategy = regionStrategies[i];
+            try {{
+                const element = page.locator(strategy.selector);
+                const count = await element.count();
+                console.log(`🔍 地区策略${{i+1}}: 找到${{count}}个元素 (${{strategy.name}})`);
+                
+                if (count > 0) {{
+                    const firstElement = element.first();
+                    const isVisible = await firstElement.isVisible({{ timeout: 1000 }});
+                    if (isVisible) {{
+                        console.log(`👆 点击地区选择器: ${{strategy.name}}`);
+                        await firstElement.click();
+                        
+                        // 选择与IP资产地区一致的选项 (必要等待: 下拉框展开前选项数量为0)
+                        const option = page.getByRole('listitem').filter({{ hasText: region }});
+                        await waitForVisible(option.first(), WAIT_LONG);
+                        const optionExists = await option.count();
+                        console.log(`🔍 "${{region}}"选项数量: ${{optionExists}}`);
+                        
+                        if (optionExists > 0) {{
+                            await option.first().click();
+                            console.log(`✅ 地区选择成功: ${{region}}`);
+                            regionSelected = true;
+                        }}
+                    }}
+                }}
+            }} catch (error) {{
+                console.log(`⚠️ 地区选择策略${{i+1}}失败: ${{error.message}}`);
+            }}
+        }}
+        
+        // 备用方法：使用原始选择器
+        if (!regionSelected) {{
+            console.log('🔄 使用备用地区选择方法...');
+            try {{
+                await page.getByRole('textbox', {{ name: '请选择' }}).nth(1).click();
+                await page.getByRole('listitem').filter({{ hasText: region }}).click({{ timeout: 5000 }});
+                console.log('✅ 地区选择成功 (备用方法)');
+                regionSelected = true;
+            }} catch (backupError) {{
+                console.error('❌ 地区选择备用方法失败:', backupError.message);
+            }}
+        }}
+        
+        if (!regionSelected) {{
+            const regionError = `地区下拉框中没有找到"${{region}}"选项，请检查IP资产的地区设置`;
+            console.error('{marker}' + JSON.stringify([regionError]));
+            throw new Error(regionError);
+        }}
+        
+        // 填写期限 (作品有效期)
+        if ({work_start_date} && {work_end_date}) {{
+            console.log('⏰ 设置作品期限...');
+            await fillDatePicker(page, {work_period_selector}, '起始时间', {work_start_date_simple}, '期限');
+            await fillDatePicker(page, {work_period_selector}, '结束时间', {work_end_date_simple}, '期限');
+        }}
+        
+        // 上传授权证明文件
+        {auth_files_upload_code}
+        
+        // 上传作品证明文件  
+        {work_proof_files_upload_code}
+        
+        runResult.files_uploaded += await countVisibleUploads(page);
+        reportFieldFillFailures();
+        runResult.fields_filled.push('权利人', '著作类型', '著作名称', '地区', '期限');
+        runResult.stage_reached = 'ip_asset';
+        {ip_asset_filled_log}
+        console.log('👆 点击下一步按钮...');
+        await clickButtonIfEnabled(page, '下一步');
+        // 必要等待: 申诉详情页没有可以等待的稳定标识，给页面切换留出时间
+        await page.waitForTimeout(WAIT_MEDIUM);
+"#,
+            owner = js_string_literal(&asset.owner),
+            work_type = js_string_literal(asset.work_type.trim()),
+            work_type_selector = js_string_literal(WORK_TYPE_SELECTOR),
+            rights_holder_selector = js_string_literal(RIGHTS_HOLDER_SELECTOR),
+            work_name = js_string_literal(&asset.work_name),
+            region = js_string_literal(asset.region.trim()),
+            marker = FORM_VALIDATION_MARKER,
+            auth_start_date = asset.auth_start_date.is_some().to_string(),
+            auth_end_date = asset.auth_end_date.is_some().to_string(),
+            auth_start_date_simple = js_string_literal(asset.auth_start_date.as_deref().unwrap_or("")),
+            auth_end_date_simple = js_string_literal(asset.auth_end_date.as_deref().unwrap_or("")),
+            work_start_date = (!asset.work_start_date.is_empty()).to_string(),
+            work_end_date = (!asset.work_end_date.is_empty()).to_string(),
+            work_start_date_simple = js_string_literal(&asset.work_start_date),
+            work_end_date_simple = js_string_literal(&asset.work_end_date),
+            auth_period_selector = js_string_literal(AUTH_PERIOD_SELECTOR),
+            work_period_selector = js_string_literal(WORK_PERIOD_SELECTOR),
+            ip_asset_filled_log = progress_log("step.ip_asset_filled"),
+            agent_section = generate_agent_section(asset.is_agent),
+            is_agent = asset.is_agent,
+            auth_files_upload_code = if asset.is_agent {
+                generate_upload_code(&auth_files, AUTH_FILES_UPLOAD_SELECTOR, "授权证明", Some("step.auth_files_uploaded"))
+            } else {
+                "console.log('⏭️ 非代理申诉，跳过授权证明上传');".to_string()
+            },
+            work_proof_files_upload_code = generate_upload_code(&work_proof_files, WORK_PROOF_UPLOAD_SELECTOR, "作品证明", Some("step.proof_files_uploaded"))
+        )
+    } else { 
+        // If no IP asset, this string will be empty.
+        "".to_string() 
+    };
+
+    // Generate file upload sections - Fixed to match B站 form structure
+    let id_card_upload_section = generate_id_card_upload_section(&id_card_files, id_card_warning.as_deref());
+
+    let entry_section = match request.start_stage {
+        AutomationStage::Full => generate_full_entry_section(
+            &appeal_url,
+            escaped_name,
+            escaped_phone,
+            escaped_email,
+            escaped_id_card,
+            &id_card_upload_section,
+            &generate_entry_page_lookup(
+                &appeal_url,
+                request.target_tab_id.as_deref(),
+                request.reuse_current_page.unwrap_or(settings.reuse_current_page),
+            ),
+        ),
+        stage => generate_resume_entry_section(stage, request.target_tab_id.as_deref()),
+    };
+
+    // Note: File upload sections are now integrated into the IP asset section above
+    // No need for separate auth_files_upload_section and work_proof_upload_section
+
+    // The main script template now includes file upload functionality
+    Ok(format!(r#"
+const {{ test, chromium }} = require('@playwright/test');
+const fs = require('fs');
+{selectors_prelude}
+{timing_prelude}
+{run_result_prelude}
+
+// 检查页面是否提示申诉次数超限，超限时输出标记并终止，避免提交被静默拒绝
+async function checkAppealQuota(page) {{
+    const quotaNotice = page.getByText({quota_pattern}).first();
+    if (await quotaNotice.isVisible().catch(() => false)) {{
+        const notice = (await quotaNotice.textContent().catch(() => '') || '').trim();
+        console.error('❌ B站提示申诉次数已达上限:', notice);
+        console.error('{quota_marker}' + JSON.stringify([notice || '申诉次数已达上限']));
+        throw new Error(`申诉次数已达上限: ${{notice}}`);
+    }}
+}}
+
+// 点击按钮前检查是否可用，不可用时输出表单校验错误并终止
+async function clickButtonIfEnabled(page, name) {{
+    const button = page.getByRole('button', {{ name }}).first();
+    await button.waitFor({{ state: 'visible', timeout: 10000 }});
+    if (!(await button.isEnabled())) {{
+        // 按钮因次数超限被禁用时报告超限，而不是表单校验错误
+        await checkAppealQuota(page);
+        const errors = (await page.locator(SELECTORS.formError).allTextContents())
+            .map(text => text.trim())
+            .filter(text => text.length > 0);
+        console.error(`❌ "${{name}}"按钮不可用，表单校验未通过`);
+        console.error('{marker}' + JSON.stringify(errors));
+        throw new Error(`"${{name}}"按钮不可用，表单校验未通过: ${{errors.join('; ') || '未检测到具体错误信息'}}`);
+    }}
+    await button.click();
+}}
+
+// 把 2024-03-05 / 2024/3/5 / 2024年3月5日 等写法统一为[年, 月, 日]，无法识别时返回null
+function parseDateParts(text) {{
+    const match = /(\d{{4}})\D+(\d{{1,2}})\D+(\d{{1,2}})/.exec(text || '');
+    return match ? [Number(match[1]), Number(match[2]), Number(match[3])] : null;
+}}
+
+function sameDate(actual, expected) {{
+    const a = parseDateParts(actual);
+    const b = parseDateParts(expected);
+    return !!a && !!b && a[0] === b[0] && a[1] === b[1] && a[2] === b[2];
+}}
+
+// 填写Element UI日期选择器: 先在输入框输入并按Enter确认 (Tab不会触发el-date-picker的确认)，
+// 未生效时打开面板翻到对应月份点击日期单元格；最后校验输入框的值，仍不一致时按表单校验错误终止
+async function fillDatePicker(page, itemSelector, placeholder, value, label) {{
+    const input = page.locator(itemSelector).first().locator(`input[placeholder="${{placeholder}}"]`).first();
+    const panel = page.locator(SELECTORS.pickerPanel).last();
+
+    await input.click();
+    await waitForVisible(panel, WAIT_LONG);
+    await input.fill(value);
+    await input.press('Enter');
+    await page.waitForTimeout(WAIT_SHORT);
+    if (sameDate(await input.inputValue().catch(() => ''), value)) {{
+        console.log(`✅ ${{label}}${{placeholder}}已设置: ${{value}}`);
+        return;
+    }}
+
+    console.log(`🔄 ${{label}}${{placeholder}}输入未生效，改为点击日期面板...`);
+    const parts = parseDateParts(value);
+    if (parts) {{
+        await clickDateCell(page, input, panel, parts).catch(error =>
+            console.log(`⚠️ 点击${{label}}日期面板失败: ${{error.message}}`));
+    }}
+
+    const actual = await input.inputValue().catch(() => '');
+    if (!sameDate(actual, value)) {{
+        const dateError = `${{label}}${{placeholder}}未能设置为${{value}} (当前值: "${{actual}}")`;
+        console.error('{marker}' + JSON.stringify([dateError]));
+        throw new Error(dateError);
+    }}
+    console.log(`✅ ${{label}}${{placeholder}}已通过日期面板设置: ${{value}}`);
+}}
+
+// 日期面板标题为 "2024 年 3 月" (范围选择器取左侧面板)，按月份差点击翻页按钮后点击当月的日期单元格
+async function clickDateCell(page, input, panel, [year, month, day]) {{
+    if (!(await panel.isVisible().catch(() => false))) {{
+        await input.click();
+        await waitForVisible(panel, WAIT_LONG);
+    }}
+    const header = panel.locator(SELECTORS.pickerHeader).first();
+    const shown = /(\d{{4}})\s*年\s*(\d{{1,2}})\s*月/.exec(await header.innerText());
+    if (!shown) {{
+        throw new Error('无法识别日期面板当前显示的月份');
+    }}
+
+    let offset = (year - Number(shown[1])) * 12 + (month - Number(shown[2]));
+    while (offset !== 0) {{
+        if (Math.abs(offset) >= 12) {{
+            const yearButton = offset > 0 ? panel.locator(SELECTORS.pickerNextYear).last() : panel.locator(SELECTORS.pickerPrevYear).first();
+            await yearButton.click();
+            offset += offset > 0 ? -12 : 12;
+        }} else {{
+            const monthButton = offset > 0 ? panel.locator(SELECTORS.pickerNextMonth).last() : panel.locator(SELECTORS.pickerPrevMonth).first();
+            await monthButton.click();
+            offset += offset > 0 ? -1 : 1;
+        }}
+    }}
+
+    // 排除上月/下月补位的日期，只点击当前月份中的那一天
+    await panel.locator(SELECTORS.dateTable).first()
+        .locator(SELECTORS.currentMonthDay)
+        .filter({{ hasText: new RegExp(`^\\s*${{day}}\\s*$`) }})
+        .first()
+        .click();
+    await page.waitForTimeout(WAIT_SHORT);
+}}
+
+// 用户手动关闭调试Chrome时Playwright抛出的断开连接错误
+function isBrowserClosedError(error) {{
+    const message = String((error && error.message) || error);
+    return /Target page, context or browser has been closed|Browser has been closed|browser has disconnected|Target closed|Connection closed/i.test(message);
+}}
+
+test('Bilibili Appeal - Connect Mode with File Upload', async () => {{
+    let browserDisconnected = false;
+    try {{
+        console.log('🚀 开始自动化申诉流程...');
+        console.log('🆔 运行ID:', {run_id});
+        console.log('⏰ 脚本启动时间:', new Date().toISOString());
+        console.log('🔍 关键修复验证: 逐个文件上传机制已启用');
+        console.log('🎯 预期效果: 上传真实可查看的图片，支持多文件上传');
+        console.log('🔧 Playwright脚本已启动并开始执行 - 如果你看到这条消息，说明JavaScript语法正确');
+        {connect_section}
+        browser.on('disconnected', () => {{
+            browserDisconnected = true;
+            console.log('⚠️ 与Chrome的连接已断开');
+        }});
+        runResult.stage_reached = 'connected';
+        {entry_section}
+        
+        // 执行完整的IP资产信息填写和文件上传
+        {ip_section}
+        
+        {appeal_details_section}
+        
+        {submit_section}
+        await captureSubmissionScreenshot(page);
+        writeRunResult();
+        {finish_section}
+    }} catch (error) {{
+        // Chrome被关闭时不输出堆栈，只报告标记，由Rust端转换为友好提示
+        if (browserDisconnected || isBrowserClosedError(error)) {{
+            console.error('{browser_closed_marker} 运行中Chrome被关闭，停止时所在阶段:', runResult.stage_reached);
+            runResult.errors.push('Chrome在运行中被关闭');
+            writeRunResult();
+            throw new Error('Chrome在运行中被关闭');
+        }}
+        console.error('❌ 自动化申诉流程失败:', error);
+        runResult.errors.push(String((error && error.message) || error));
+        writeRunResult();
+        throw error;
+    }}
+}});
+"#, 
+    entry_section = entry_section,
+    browser_closed_marker = BROWSER_CLOSED_MARKER,
+    // 复用的页面已在申诉详情步骤时跳过IP资产部分
+    ip_section = if request.start_stage == AutomationStage::Full && !ip_section.is_empty() {
+        format!("if (reusedStep !== 'appeal_details') {{{}\n        }}", ip_section)
+    } else {
+        ip_section
+    }, 
+    appeal_details_section = generate_appeal_details_section(escaped_infringing_url, &additional_files),
+    marker = FORM_VALIDATION_MARKER,
+    quota_pattern = QUOTA_EXCEEDED_PATTERN,
+    quota_marker = QUOTA_EXCEEDED_MARKER,
+    connect_section = cdp_connect_snippet(settings.debug_port),
+    submit_section = generate_submit_section(request.auto_submit, request.test_mode),
+    finish_section = generate_finish_section(request.keep_browser_open),
+    run_id = js_string_literal(&request.run_id.map(|id| id.to_string()).unwrap_or_default()),
+    selectors_prelude = generate_selectors_prelude(),
+    timing_prelude = generate_timing_prelude(&settings.timing),
+    run_result_prelude = run_result_prelude()
+))
+}
+
+// 脚本运行结果，结束时(包括失败)写入RIGHTSGUARD_RESULT_PATH指定的文件，由Rust端解析为RunResult
+// 修改字段时需要同步更新models::RunResult
+const RUN_RESULT_PRELUDE: &str = r#"
+const runResult = { stage_reached: 'started', fields_filled: [], files_uploaded: 0, submitted: false, errors: [] };
+
+function writeRunResult() {
+    const resultPath = process.env.RIGHTSGUARD_RESULT_PATH;
+    if (!resultPath) return;
+    try {
+        fs.writeFileSync(resultPath, JSON.stringify(runResult));
+    } catch (error) {
+        console.log('⚠️ 写入运行结果失败:', error.message);
+    }
+}
+
+// 保存流程结束时的页面截图作为提交凭证，失败不影响申诉结果
+async function captureSubmissionScreenshot(page) {
+    const screenshotPath = process.env.RIGHTSGUARD_SCREENSHOT_PATH;
+    if (!screenshotPath) return;
+    try {
+        await page.screenshot({ path: screenshotPath, fullPage: true });
+        console.log('📸 已保存提交结果截图:', screenshotPath);
+    } catch (error) {
+        console.log('⚠️ 保存提交结果截图失败:', error.message);
+    }
+}
+
+// 当前步骤页面上可见的已上传文件数，之前步骤隐藏的上传列表不计入
+async function countVisibleUploads(page) {
+    return await page.locator(`${SELECTORS.uploadListItem}:visible`).count().catch(() => 0);
+}
+
+// 填写后读回的值与期望不一致的字段，每个步骤结束前统一报告
+const fieldFillFailures = [];
+
+// 读回输入框的值，与期望一致 (忽略首尾空白) 时返回true
+async function valueSticks(locator, value) {
+    const actual = await locator.inputValue().catch(() => '');
+    return actual.trim() === String(value).trim();
+}
+
+function recordFieldFillFailure(label) {
+    console.error(`❌ ${label}未填入`);
+    if (!fieldFillFailures.includes(label)) fieldFillFailures.push(label);
+}
+
+// 填写输入框并读回校验，未填入时记录字段名，返回是否填入
+async function fillAndVerify(locator, value, label) {
+    await locator.fill(value).catch(error => console.log(`⚠️ 填写${label}时出错:`, error.message));
+    if (await valueSticks(locator, value)) return true;
+    recordFieldFillFailure(label);
+    return false;
+}
+
+// 有字段未填入时终止流程，避免提交不完整的申诉
+function reportFieldFillFailures() {
+    if (fieldFillFailures.length === 0) return;
+    const messages = fieldFillFailures.map(label => `${label}未填入`);
+    console.error('__FIELDS_NOT_FILLED_MARKER__' + JSON.stringify(messages));
+    throw new Error(messages.join('; '));
+}
+"#;
+
+// 生成脚本开头的选择器表: SELECTORS为组件选择器，SELECTOR_STRATEGIES为各字段按顺序尝试的候选选择器
+fn generate_selectors_prelude() -> String {
+    let selectors: serde_json::Map<String, serde_json::Value> = SCRIPT_SELECTORS.iter()
+        .map(|(name, selector)| (name.to_string(), serde_json::Value::from(*selector)))
+        .collect();
+    let strategies = |list: &[(&str, &str)]| list.iter()
+        .map(|(selector, name)| serde_json::json!({ "selector": selector, "name": name }))
+        .collect::<Vec<_>>();
+    let strategies = serde_json::json!({
+        "rightsHolder": strategies(RIGHTS_HOLDER_INPUT_STRATEGIES),
+        "workName": strategies(WORK_NAME_INPUT_STRATEGIES),
+        "region": strategies(REGION_INPUT_STRATEGIES),
+    });
+    format!(
+        "\nconst SELECTORS = {};\nconst SELECTOR_STRATEGIES = {};\n",
+        serde_json::Value::Object(selectors),
+        strategies
+    )
+}
+
+// 生成脚本开头的等待时间常量和等待辅助函数，各段脚本都通过这些常量等待
+// 能等待具体元素的地方使用waitForVisible/waitForUploadItems，出现即继续；超时不抛错，保持原来固定等待的宽松行为
+fn generate_timing_prelude(timing: &ScriptTiming) -> String {
+    format!(r#"
+const WAIT_SHORT = {short};
+const WAIT_MEDIUM = {medium};
+const WAIT_LONG = {long};
+const WAIT_UPLOAD_SETTLE = {upload_settle};
+
+async function waitForVisible(locator, timeout) {{
+    await locator.waitFor({{ state: 'visible', timeout }})
+        .catch(() => console.log(`⚠️ ${{timeout}}ms内元素未出现，继续执行`));
+}}
+
+// 等待上传列表中至少出现minCount个条目，由调用处根据实际数量判断是否成功
+async function waitForUploadItems(page, minCount, timeout) {{
+    await page.waitForFunction(
+        (count) => document.querySelectorAll('.el-upload-list__item').length >= count,
+        minCount,
+        {{ timeout }}
+    ).catch(() => console.log(`⚠️ ${{timeout}}ms内上传列表未达到${{minCount}}项，继续执行`));
+}}
+"#,
+        short = timing.short_ms,
+        medium = timing.medium_ms,
+        long = timing.long_ms,
+        upload_settle = timing.upload_settle_ms,
+    )
+}
+
+// 脚本共用的运行结果与填写校验函数，标记在这里替换以与Rust端保持一致
+fn run_result_prelude() -> String {
+    RUN_RESULT_PRELUDE.replace("__FIELDS_NOT_FILLED_MARKER__", FIELDS_NOT_FILLED_MARKER)
+}
+
+// 生成最终提交部分: 自动提交时点击"提交"并等待成功提示，未出现提示时报错而不是假定成功；
+// 否则只准备好表单，由用户在页面上确认后提交，submitted保持false。测试模式总是在提交前停止
+fn generate_submit_section(auto_submit: bool, test_mode: bool) -> String {
+    if test_mode {
+        return r#"console.log('🧪 测试模式: 表单已填写完成，跳过最终提交');
+        runResult.stage_reached = 'ready_for_confirmation';"#.to_string();
+    }
+    if !auto_submit {
+        return r#"console.log('⏸️ 申诉表单已准备就绪，等待用户确认提交');
+        runResult.stage_reached = 'ready_for_confirmation';"#.to_string();
+    }
+    format!(r#"console.log('📨 点击提交按钮...');
+        await clickButtonIfEnabled(page, '提交');
+        const submitConfirmed = await page.getByText({pattern}).first()
+            .waitFor({{ state: 'visible', timeout: WAIT_LONG * 3 }})
+            .then(() => true)
+            .catch(() => false);
+        if (!submitConfirmed) {{
+            const submitError = '点击提交后未检测到提交成功提示，请在页面上确认申诉是否已提交';
+            console.error('{marker}' + JSON.stringify([submitError]));
+            throw new Error(submitError);
+        }}
+        runResult.submitted = true;
+        runResult.stage_reached = 'submitted';
+        {submitted_log}"#,
+        submitted_log = progress_log("step.submitted_confirming"),
+        pattern = SUBMIT_SUCCESS_PATTERN,
+        marker = SUBMIT_NOT_CONFIRMED_MARKER
+    )
+}
+
+// 生成流程结束部分: 默认保持页面打开供用户最终确认，否则关闭浏览器
+fn generate_finish_section(keep_browser_open: bool) -> String {
+    if keep_browser_open {
+        r#"console.log('🎉 自动化申诉流程准备就绪，保持页面打开供用户最终确认...');
+        await new Promise(() => {}); // Keep open indefinitely"#.to_string()
+    } else {
+        r#"console.log('🎉 自动化申诉流程完成，按设置关闭浏览器...');
+        await context.close().catch(error => console.log('⚠️ 关闭浏览器上下文失败:', error.message));
+        await browser.close().catch(error => console.log('⚠️ 断开浏览器连接失败:', error.message));"#.to_string()
+    }
+}
+
+/// 生成针对本地模拟页面的自测脚本：复用真实的填写与上传代码，并逐项校验结果
+pub fn build_self_test_script(fixture_url: &str, id_card_file: &str, chrome_path: Option<&str>) -> String {
+    let name = "测试用户";
+    let phone = "13800000000";
+    let email = "selftest@example.com";
+    let id_card = "110101199001011234";
+    let infringing_url = "https://www.bilibili.com/video/BV1xx411c7mD";
+
+    let entry_section = generate_full_entry_section(
+        fixture_url,
+        name,
+        phone,
+        email,
+        id_card,
+        &generate_id_card_upload_section(&[id_card_file.to_string()], None),
+        &generate_entry_page_lookup(fixture_url, None, false),
+    );
+    let expected_fields = serde_json::json!([
+        {"field": "真实姓名", "selector": r#"input[placeholder="真实姓名"]"#, "value": name},
+        {"field": "手机号", "selector": r#"input[placeholder="手机号"]"#, "value": phone},
+        {"field": "邮箱", "selector": r#".el-form-item:has-text("邮箱") input.el-input__inner"#, "value": email},
+        {"field": "证件号码", "selector": r#"input[placeholder="证件号码"]"#, "value": id_card},
+        {"field": "侵权链接", "selector": r#"input[placeholder*="他人发布的B站侵权链接"]"#, "value": infringing_url},
+        {"field": "侵权描述", "selector": r#"textarea[placeholder*="该链接内容全部"]"#, "value": APPEAL_DESCRIPTION},
+    ]);
+    let launch_options = match chrome_path {
+        Some(path) => format!("{{ headless: true, executablePath: {} }}", serde_json::to_string(path).unwrap()),
+        None => "{ headless: true }".to_string(),
+    };
+
+    format!(r#"
+const {{ test, chromium }} = require('@playwright/test');
+const fs = require('fs');
+{selectors_prelude}
+{timing_prelude}
+{run_result_prelude}
+
+test('RightsGuard Self Test', async () => {{
+    console.log('🧪 开始自测: 使用本地模拟页面验证填写与上传逻辑');
+    const browser = await chromium.launch({launch_options});
+    const context = await browser.newContext();
+    try {{
+        {entry_section}
+
+        {appeal_details_section}
+
+        const failures = [];
+
+        // 两个分支都验证: 选"是"时授权期限出现，选"否"时隐藏
+        {agent_on_section}
+        if (!(await page.locator({auth_selector}).first().isVisible())) {{
+            failures.push('是否代理(是): 授权期限未出现');
+        }}
+        {agent_off_section}
+        if (await page.locator({auth_selector}).first().isVisible()) {{
+            failures.push('是否代理(否): 授权期限未隐藏');
+        }}
+
+        for (const check of {expected_fields}) {{
+            const actual = await page.locator(check.selector).first().inputValue().catch(() => '');
+            if (actual !== check.value) {{
+                failures.push(`${{check.field}}: 期望 "${{check.value}}"，实际 "${{actual}}"`);
+            }}
+        }}
+        if (await page.locator(SELECTORS.uploadListItem).count() === 0) {{
+            failures.push('身份证文件: 未上传');
+        }}
+        if (!(await page.locator(SELECTORS.checkboxInput).first().isChecked())) {{
+            failures.push('本人保证: 未勾选');
+        }}
+
+        if (failures.length > 0) {{
+            console.error('{marker}' + JSON.stringify(failures));
+            throw new Error(`自测未通过: ${{failures.join('; ')}}`);
+        }}
+        console.log('✅ 自测通过: 所有字段均已正确填写');
+    }} finally {{
+        await browser.close();
+    }}
+}});
+"#,
+        launch_options = launch_options,
+        entry_section = entry_section,
+        appeal_details_section = generate_appeal_details_section(infringing_url, &[]),
+        expected_fields = expected_fields,
+        agent_on_section = generate_agent_section(true),
+        agent_off_section = generate_agent_section(false),
+        auth_selector = js_string_literal(AUTH_PERIOD_SELECTOR),
+        selectors_prelude = generate_selectors_prelude(),
+        timing_prelude = generate_timing_prelude(&ScriptTiming::default()),
+        run_result_prelude = run_result_prelude(),
+        marker = SELF_TEST_FAILED_MARKER
+    )
+}
+
+// 生成身份证文件上传代码
+// 文件已按正反面标记排好顺序，标记不完整时输出提示
+fn generate_id_card_upload_section(id_card_files: &[String], role_warning: Option<&str>) -> String {
+    if id_card_files.is_empty() {
+        return "        console.log('ℹ️ 无身份证文件需要上传');".to_string();
+    }
+    let count_check = match role_warning {
+        Some(warning) => format!("console.log('⚠️ ' + {});", js_string_literal(warning)),
+        None => "console.log('✅ 按人像面、国徽面顺序上传身份证');".to_string(),
+    };
+    format!(r#"
+        console.log('🆔 开始上传真实身份证文件（来自个人档案配置）...');
+        {count_check}
+        {upload_code}"#,
+        count_check = count_check,
+        upload_code = generate_upload_code(id_card_files, ID_CARD_UPLOAD_SELECTOR, "身份证", None)
+    )
+}
+
+// 生成上传到指定上传区域的代码，身份证、授权证明和作品证明共用
+// 逐个设置文件并等待该区域的上传列表出现对应条目，日志 "上传第X/N个文件" 用于进度解析
+// done_step为完成时要报告的进度步骤，没有对应步骤时输出普通的完成日志
+fn generate_upload_code(files: &[String], area_selector: &str, label: &str, done_step: Option<&str>) -> String {
+    if files.is_empty() {
+        return format!("console.log('ℹ️ 无{}文件需要上传');", label);
+    }
+
+    let files_array = files.iter()
+        .map(|path| escape_file_path_for_js_array(path))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(r#"
+        console.log('📤 开始上传{label}文件...');
+        try {{
+            let uploadFiles = [{files_array}];
+            console.log('📁 {label}文件数量:', uploadFiles.length);
+
+            // 文件在生成脚本前已检查过，这里再确认一次，防止运行期间被移动或删除
+            const missingFiles = uploadFiles.filter(f => !fs.existsSync(f));
+            if (missingFiles.length > 0) {{
+                console.log('⚠️ {label}文件不存在，已跳过:', missingFiles);
+                uploadFiles = uploadFiles.filter(f => fs.existsSync(f));
+            }}
+            if (uploadFiles.length === 0) {{
+                console.error('{missing_marker}' + JSON.stringify(missingFiles));
+                throw new Error('没有找到有效的{label}文件，请检查文件配置');
+            }}
+
+            const uploadArea = page.locator({area_selector});
+            if (await uploadArea.count() === 0) {{
+                console.log('⚠️ 未找到{label}上传区域，跳过上传');
+            }} else {{
+                {pdf_check}
+                const fileInput = uploadArea.first().locator(SELECTORS.fileInput).first();
+                const uploadItems = uploadArea.first().locator(SELECTORS.uploadListItem);
+                const itemsBefore = await uploadItems.count();
+                for (let i = 0; i < uploadFiles.length; i++) {{
+                    const fileName = uploadFiles[i].split(/[/\\]/).pop();
+                    console.log(`📄 {label}: 上传第${{i + 1}}/${{uploadFiles.length}}个文件: ${{fileName}}`);
+                    await fileInput.setInputFiles([uploadFiles[i]]);
+                    await uploadItems.nth(itemsBefore + i)
+                        .waitFor({{ state: 'visible', timeout: WAIT_LONG }})
+                        .catch(() => console.log(`⚠️ ${{fileName}}未及时出现在上传列表，继续执行`));
+                    await page.waitForTimeout(WAIT_UPLOAD_SETTLE);
+                }}
+                const uploaded = await uploadItems.count() - itemsBefore;
+                if (uploaded < uploadFiles.length) {{
+                    console.log(`⚠️ {label}上传列表只有${{uploaded}}/${{uploadFiles.length}}个文件`);
+                }}
+                {done_log}
+            }}
+        }} catch (error) {{
+            console.error('❌ {label}文件上传失败:', error);
+        }}"#,
+        label = label,
+        files_array = files_array,
+        missing_marker = FILE_NOT_FOUND_MARKER,
+        done_log = match done_step {
+            Some(step) => progress_log(step),
+            None => format!("console.log('✅ {}文件上传完成');", label),
+        },
+        area_selector = js_string_literal(area_selector),
+        pdf_check = generate_pdf_accept_check("uploadFiles", "uploadArea", label)
+    )
+}
+
+// 补充证据按与其他文件相同的规则解析和检查；无法上传的文件跳过，不影响整个申诉
+fn resolve_additional_files(stored_files: &[String]) -> Result<Vec<String>> {
+    if stored_files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let resolved = get_absolute_file_paths(&Some(serde_json::to_string(stored_files)?))?;
+    if resolved.len() < stored_files.len() {
+        tracing::warn!("⚠️ {}个补充证据文件不存在，已跳过", stored_files.len() - resolved.len());
+    }
+
+    let checked = validate_upload_files(&resolved)
+        .into_iter()
+        .filter(|check| {
+            for problem in &check.problems {
+                tracing::warn!("⚠️ 补充证据 {}: {}", check.path, problem);
+            }
+            check.valid
+        })
+        .map(|check| check.path)
+        .collect();
+    let additional_files = convert_pdfs_to_images(checked);
+    tracing::info!("Additional evidence files resolved: {:?}", additional_files);
+    Ok(additional_files)
+}
+
+// 生成申诉详情填写代码
+fn generate_appeal_details_section(infringing_url: &str, additional_files: &[String]) -> String {
+    format!(r#"console.log('📝 填写申诉详情...');
+        await fillAndVerify(page.locator({url_selector}).first(), {url}, '侵权链接');
+        await fillAndVerify(page.locator({description_selector}).first(), {description}, '侵权描述');
+        reportFieldFillFailures();
+        {additional_files_upload_code}
+        await page.locator({guarantee_selector}).first().click();
+        runResult.fields_filled.push('侵权链接', '侵权描述');
+        runResult.stage_reached = 'appeal_details';
+        {details_filled_log}"#,
+        details_filled_log = progress_log("step.details_filled"),
+        url = js_string_literal(infringing_url),
+        description = serde_json::to_string(APPEAL_DESCRIPTION).unwrap(),
+        url_selector = js_string_literal(INFRINGING_URL_INPUT_SELECTOR),
+        description_selector = js_string_literal(DESCRIPTION_TEXTAREA_SELECTOR),
+        guarantee_selector = js_string_literal(GUARANTEE_CHECKBOX_SELECTOR),
+        additional_files_upload_code = generate_additional_files_upload_code(additional_files)
+    )
+}
+
+// 生成申诉详情中补充证据的上传代码
+fn generate_additional_files_upload_code(additional_files: &[String]) -> String {
+    if additional_files.is_empty() {
+        return "console.log('ℹ️ 无补充证据需要上传');".to_string();
+    }
+
+    let files_array = additional_files.iter()
+        .map(|path| escape_file_path_for_js_array(path))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(r#"
+        console.log('📎 开始上传补充证据...');
+        try {{
+            let additionalFiles = [{files_array}];
+            console.log('📁 补充证据文件数量:', additionalFiles.length);
+
+            // 申诉详情页的证据上传区域在侵权描述下方
+            const evidenceUploadArea = page.locator({selector}).last();
+            if (await evidenceUploadArea.count() > 0) {{
+                {pdf_check}
+                if (additionalFiles.length > 0) {{
+                    await evidenceUploadArea.locator(SELECTORS.fileInput).setInputFiles(additionalFiles);
+                    await evidenceUploadArea.locator(SELECTORS.uploadListItem).nth(additionalFiles.length - 1)
+                        .waitFor({{ state: 'visible', timeout: WAIT_LONG }})
+                        .catch(() => console.log('⚠️ 补充证据上传列表未及时更新，继续执行'));
+                    runResult.files_uploaded += await evidenceUploadArea.locator(SELECTORS.uploadListItem).count();
+                    console.log('✅ 补充证据上传完成');
+                }}
+            }} else {{
+                console.log('⚠️ 未找到补充证据上传区域，跳过补充证据');
+            }}
+        }} catch (error) {{
+            console.error('❌ 补充证据上传失败:', error);
+        }}"#,
+        files_array = files_array,
+        selector = js_string_literal(EVIDENCE_UPLOAD_SELECTOR),
+        pdf_check = generate_pdf_accept_check("additionalFiles", "evidenceUploadArea", "补充证据")
+    )
+}
+
+// 生成完整流程的入口部分: 导航 -> 个人信息 -> 身份证上传 -> 等待验证码
+fn generate_full_entry_section(
+    appeal_url: &str,
+    name: &str,
+    phone: &str,
+    email: &str,
+    id_card: &str,
+    id_card_upload_section: &str,
+    page_lookup: &str,
+) -> String {
+    format!(r#"
+        {page_lookup}
+        // 未指定标签页时使用空白页或新建标签页，避免覆盖用户正在浏览的页面
+        const page = reusedPage
+            || targetPage
+            || context.pages().find(p => ['about:blank', 'chrome://newtab/'].includes(p.url()))
+            || await context.newPage();
+        
+        if (reusedStep) {{
+            // 页面已通过验证码，重新导航会清空表单并需要再次完成验证码
+            await page.bringToFront();
+            console.log(`♻️ 复用已打开的申诉页面，跳过导航和个人信息 (当前步骤: ${{reusedStep}}):`, page.url());
+            await checkAppealQuota(page);
+            runResult.stage_reached = 'ip_asset_page';
+        }} else {{
+            console.log('\\n⏰ 阶段1开始时间:', new Date().toISOString());
+            console.log('📄 导航到B站版权申诉页面...');
+            console.log('🌐 页面导航开始 - 目标URL:', {appeal_url});
+            await page.goto({appeal_url}, {{ timeout: 60000, waitUntil: 'networkidle' }});
+        
+            // 检查是否被重定向到登录页或弹出登录框
+            console.log('🔐 检查B站登录状态...');
+            let loginWallDetected = page.url().includes('passport.bilibili.com');
+            for (const selector of {login_wall_selectors}) {{
+                if (loginWallDetected) break;
+                loginWallDetected = await page.locator(selector).first().isVisible().catch(() => false);
+            }}
+            if (loginWallDetected) {{
+                console.error('{not_logged_in_marker} 当前页面:', page.url());
+                throw new Error('未登录B站账号');
+            }}
+            await checkAppealQuota(page);
+            console.log('✅ 页面导航完成，开始填写表单...');
+
+            console.log('\\n⏰ 阶段2开始时间:', new Date().toISOString());
+            console.log('✏️ 开始填写个人信息...');
+            await fillAndVerify(page.locator({name_selector}).first(), {name}, '姓名');
+            await fillAndVerify(page.locator({phone_selector}).first(), {phone}, '手机号');
+            await fillAndVerify(page.locator({email_selector}).first(), {email}, '邮箱');
+            await fillAndVerify(page.locator({id_card_selector}).first(), {id_card}, '证件号码');
+            reportFieldFillFailures();
+            {personal_info_filled_log}
+            runResult.stage_reached = 'personal_info';
+            runResult.fields_filled.push('姓名', '手机号', '邮箱', '证件号码');
+
+            console.log('\\n⏰ 阶段3开始时间:', new Date().toISOString());
+            console.log('🔥 关键阶段：身份证文件上传开始...');
+            {id_card_upload_section}
+            runResult.files_uploaded += await countVisibleUploads(page);
+        
+            console.log('⏳ 等待用户完成验证码并进入下一页...');
+            console.log('💡 请在页面中输入验证码并点击下一步');
+        
+            // 等待IP资产页面的关键元素出现，最多等待5分钟
+            console.log('🔍 正在检测IP资产页面加载...');
+            await page.waitForSelector({rights_holder_selector}, {{ 
+                timeout: 300000 
+            }}).catch((error) => {{
+                console.error('{verification_timeout_marker} 未在5分钟内完成验证码');
+                throw error;
+            }});
+        
+            {captcha_completed_log}
+            runResult.stage_reached = 'ip_asset_page';
+        }}
+        if (reusedStep !== 'appeal_details') {{
+            await waitForVisible(page.locator({rights_holder_selector}).locator('input').first(), WAIT_LONG);
+        }}
+"#,
+        appeal_url = js_string_literal(appeal_url),
+        login_wall_selectors = serde_json::to_string(LOGIN_WALL_SELECTORS).unwrap(),
+        not_logged_in_marker = NOT_LOGGED_IN_MARKER,
+        verification_timeout_marker = VERIFICATION_TIMEOUT_MARKER,
+        name = js_string_literal(name),
+        phone = js_string_literal(phone),
+        email = js_string_literal(email),
+        id_card = js_string_literal(id_card),
+        id_card_upload_section = id_card_upload_section,
+        personal_info_filled_log = progress_log("step.personal_info_filled"),
+        captcha_completed_log = progress_log("step.captcha_completed"),
+        name_selector = js_string_literal(NAME_INPUT_SELECTOR),
+        phone_selector = js_string_literal(PHONE_INPUT_SELECTOR),
+        email_selector = js_string_literal(EMAIL_INPUT_SELECTOR),
+        id_card_selector = js_string_literal(ID_CARD_INPUT_SELECTOR),
+        rights_holder_selector = js_string_literal(RIGHTS_HOLDER_SELECTOR),
+        page_lookup = page_lookup
+    )
+}
+
+// 完整流程查找标签页的代码: 指定的标签页，以及可复用的申诉页面
+fn generate_entry_page_lookup(appeal_url: &str, target_tab_id: Option<&str>, reuse_current_page: bool) -> String {
+    format!(
+        "{}\n        {}",
+        generate_target_page_lookup(target_tab_id),
+        generate_reuse_page_lookup(appeal_url, reuse_current_page)
+    )
+}
+
+// 生成查找可复用申诉页面的代码，定义reusedPage和reusedStep (不复用或未找到时为null)
+// 只复用已停在IP资产或申诉详情步骤的页面: 这两个步骤已通过验证码，个人信息步骤重新开始即可，
+// 复用反而会重复上传身份证
+fn generate_reuse_page_lookup(appeal_url: &str, reuse_current_page: bool) -> String {
+    if !reuse_current_page {
+        return "const reusedPage = null;\n        const reusedStep = null;".to_string();
+    }
+
+    format!(r#"let reusedPage = null;
+        let reusedStep = null;
+        const appealPageUrl = new URL({appeal_url});
+        for (const candidate of targetPage ? [targetPage] : context.pages()) {{
+            let candidateUrl = null;
+            try {{
+                candidateUrl = new URL(candidate.url());
+            }} catch (error) {{
+                continue;
+            }}
+            // 源和路径都要一致，其他站点上同路径的页面不算申诉页面
+            if (candidateUrl.origin !== appealPageUrl.origin || candidateUrl.pathname !== appealPageUrl.pathname) continue;
+            // 确认页面确实处于可以继续的步骤，否则仍按完整流程重新导航
+            if (await candidate.locator({details_selector}).first().isVisible().catch(() => false)) {{
+                reusedStep = 'appeal_details';
+            }} else if (await candidate.locator({rights_holder_selector}).first().isVisible().catch(() => false)) {{
+                reusedStep = 'ip_asset';
+            }}
+            if (reusedStep) {{
+                reusedPage = candidate;
+                break;
+            }}
+            console.log('ℹ️ 已打开的申诉页面不在IP资产或申诉详情步骤，将重新打开:', candidate.url());
+        }}"#,
+        appeal_url = js_string_literal(appeal_url),
+        details_selector = js_string_literal(INFRINGING_URL_INPUT_SELECTOR),
+        rights_holder_selector = js_string_literal(RIGHTS_HOLDER_SELECTOR)
+    )
+}
+
+// 生成按CDP targetId查找指定标签页的代码，定义targetPage变量(未指定时为null)
+fn generate_target_page_lookup(target_tab_id: Option<&str>) -> String {
+    let Some(target_tab_id) = target_tab_id else {
+        return "const targetPage = null;".to_string();
+    };
+
+    format!(r#"let targetPage = null;
+        const targetTabId = {target_tab_id};
+        for (const candidate of context.pages()) {{
+            const session = await context.newCDPSession(candidate);
+            const {{ targetInfo }} = await session.send('Target.getTargetInfo');
+            await session.detach().catch(() => {{}});
+            if (targetInfo.targetId === targetTabId) {{
+                targetPage = candidate;
+                break;
+            }}
+        }}
+        if (!targetPage) {{
+            throw new Error(`未找到指定的标签页: ${{targetTabId}}`);
+        }}
+        console.log('🎯 使用指定的标签页:', targetPage.url());"#,
+        target_tab_id = js_string_literal(target_tab_id)
+    )
+}
+
+// 生成从中间阶段继续的入口部分: 复用已打开的申诉页面并校验当前步骤
+fn generate_resume_entry_section(stage: AutomationStage, target_tab_id: Option<&str>) -> String {
+    let (stage_name, ready_selector) = resume_stage_target(stage);
+
+    format!(r#"
+        console.log('\\n⏭️ 从{stage_name}阶段继续，复用已打开的申诉页面...');
+        {target_page_lookup}
+        const openPages = context.pages();
+        const page = targetPage || openPages.find(p => p.url().includes('/v/copyright/')) || openPages[0];
+        if (!page) {{
+            throw new Error('未找到已打开的申诉页面，无法从{stage_name}阶段继续');
+        }}
+        await page.bringToFront();
+        console.log('📄 当前页面:', page.url());
+
+        const stageReady = await page.locator({ready_selector}).first()
+            .waitFor({{ state: 'visible', timeout: 10000 }})
+            .then(() => true)
+            .catch(() => false);
+        if (!stageReady) {{
+            throw new Error('页面不在{stage_name}填写步骤，请完整运行一次流程');
+        }}
+        console.log('✅ 已确认页面处于{stage_name}步骤');
+        await checkAppealQuota(page);
+"#,
+        stage_name = stage_name,
+        ready_selector = serde_json::to_string(ready_selector).unwrap(),
+        target_page_lookup = generate_target_page_lookup(target_tab_id)
+    )
+}
+
+// 从中间阶段继续时的阶段名称，以及确认页面处于该阶段的选择器
+pub(crate) fn resume_stage_target(stage: AutomationStage) -> (&'static str, &'static str) {
+    match stage {
+        AutomationStage::FromAppealDetails => ("申诉详情", INFRINGING_URL_INPUT_SELECTOR),
+        _ => ("IP资产", RIGHTS_HOLDER_SELECTOR),
+    }
+}
+
+// 生成"是否代理"选择代码: 代理申诉选"是"并等待授权部分出现，否则选"否"
+fn generate_agent_section(is_agent: bool) -> String {
+    let label = if is_agent { "是" } else { "否" };
+    format!(r#"
+        // 是否代理 (放在独立块中，同一脚本可多次插入)
+        {{
+            console.log('🤝 设置是否代理: {label}');
+            const agentToggle = page.locator({toggle_selector}).first();
+            if (await agentToggle.count() > 0) {{
+                await agentToggle.locator(SELECTORS.radio).filter({{ hasText: {label_literal} }}).first().click();
+                await page.waitForTimeout(WAIT_SHORT);
+                if ({is_agent}) {{
+                    await page.locator({auth_selector}).first()
+                        .waitFor({{ state: 'visible', timeout: WAIT_LONG }})
+                        .then(() => console.log('✅ 授权部分已展开'))
+                        .catch(() => console.log('⚠️ 选择代理后未出现授权期限，继续尝试填写'));
+                }}
+            }} else {{
+                console.log('⚠️ 未找到"是否代理"选项，按页面默认值继续');
+            }}
+        }}
+"#,
+        label = label,
+        label_literal = js_string_literal(label),
+        is_agent = is_agent,
+        toggle_selector = js_string_literal(AGENT_TOGGLE_SELECTOR),
+        auth_selector = js_string_literal(AUTH_PERIOD_SELECTOR),
+    )
+}
+
+// 生成PDF上传检查代码: 上传组件的accept不包含PDF时，移除未能转换为图片的PDF并明确记录
+fn generate_pdf_accept_check(files_var: &str, upload_area_var: &str, label: &str) -> String {
+    format!(r#"const pdfFiles = {files}.filter(f => /\.pdf$/i.test(f));
+                if (pdfFiles.length > 0) {{
+                    const accept = await {area}.first().locator(SELECTORS.fileInput).getAttribute('accept').catch(() => null);
+                    if (accept && !/pdf/i.test(accept)) {{
+                        console.log(`❌ {label}上传组件只接受 ${{accept}}，跳过未能转换为图片的PDF:`, pdfFiles);
+                        {files} = {files}.filter(f => !/\.pdf$/i.test(f));
+                    }} else {{
+                        console.log('📄 {label}上传组件接受PDF，直接上传:', pdfFiles);
+                    }}
+                }}"#,
+        files = files_var,
+        area = upload_area_var,
+        label = label
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upload_code_embeds_windows_paths_as_js_literals() {
+        let path = r"C:\Users\张三\我的 文档\身份证.jpg";
+        let script = generate_upload_code(&[path.to_string()], ID_CARD_UPLOAD_SELECTOR, "身份证", None);
+
+        let start = script.find("let uploadFiles = [").expect("upload array missing") + "let uploadFiles = ".len();
+        let end = start + script[start..].find("];").expect("upload array not terminated") + 1;
+        let files: Vec<String> = serde_json::from_str(&script[start..end]).expect("upload array is not valid JSON");
+        assert_eq!(files, vec![path.to_string()]);
+    }
+
+    #[test]
+    fn non_agent_appeal_skips_authorization_files_and_selects_no() -> Result<()> {
+        let auth_file = std::env::temp_dir().join(format!("rightsguard-auth-{}.jpg", uuid::Uuid::new_v4()));
+        std::fs::write(&auth_file, b"auth")?;
+        let auth_path = auth_file.to_string_lossy().to_string();
+        let asset = IpAsset {
+            work_name: "测试作品".to_string(),
+            auth_files: Some(serde_json::to_string(&[&auth_path])?),
+            ..IpAsset::default()
+        };
+        // 从IP资产阶段继续，不需要身份证文件
+        let request: AutomationRequest = serde_json::from_value(serde_json::json!({
+            "infringing_url": "https://www.bilibili.com/video/BV1test",
+            "original_url": null,
+            "ip_asset_id": null,
+            "start_stage": "fromIpAsset",
+        }))?;
+
+        let non_agent = resolve_appeal_files(&Profile::default(), Some(&asset), &request);
+        let agent = resolve_appeal_files(&Profile::default(), Some(&IpAsset { is_agent: true, ..asset.clone() }), &request);
+        let _ = std::fs::remove_file(&auth_file);
+
+        assert!(non_agent?.auth_files.is_empty());
+        assert_eq!(agent?.auth_files.len(), 1);
+
+        let section = generate_agent_section(false);
+        assert!(section.contains("hasText: \"否\""), "non-agent toggle not selected: {}", section);
+        assert!(section.contains("if (false)"), "non-agent run still waits for the authorization section");
+        Ok(())
+    }
+
+    #[test]
+    fn selectors_prelude_declares_every_selector_as_json() {
+        let prelude = generate_selectors_prelude();
+        let json_after = |name: &str| -> serde_json::Value {
+            let start = prelude.find(name).expect("declaration missing") + name.len();
+            let end = start + prelude[start..].find(";\n").expect("declaration not terminated");
+            serde_json::from_str(&prelude[start..end]).expect("declaration is not valid JSON")
+        };
+
+        let selectors = json_after("const SELECTORS = ");
+        for (name, selector) in SCRIPT_SELECTORS {
+            assert_eq!(selectors[*name], *selector);
+        }
+        let strategies = json_after("const SELECTOR_STRATEGIES = ");
+        assert_eq!(strategies["rightsHolder"].as_array().map(Vec::len), Some(RIGHTS_HOLDER_INPUT_STRATEGIES.len()));
+        assert_eq!(strategies["workName"][0]["selector"], WORK_NAME_INPUT_STRATEGIES[0].0);
+        assert_eq!(strategies["region"][0]["name"], REGION_INPUT_STRATEGIES[0].1);
+    }
+
+    #[test]
+    fn every_progress_log_is_recognised_as_its_step() {
+        for (_, progress, step) in SCRIPT_PROGRESS_MARKERS {
+            let line = progress_log(step);
+            let (message, parsed) = BilibiliAutomation.progress_for_line(&line)
+                .unwrap_or_else(|| panic!("{} was not recognised", line));
+            assert_eq!(message.key(), *step);
+            assert_eq!(parsed, *progress);
+        }
+    }
+
+    #[test]
+    fn upload_code_reports_its_progress_step() {
+        let files = ["/tmp/授权书.jpg".to_string()];
+        let script = generate_upload_code(&files, AUTH_FILES_UPLOAD_SELECTOR, "授权证明", Some("step.auth_files_uploaded"));
+        let (message, _) = script.lines()
+            .find_map(|line| BilibiliAutomation.progress_for_line(line))
+            .expect("no progress marker in the upload code");
+        assert_eq!(message.key(), "step.auth_files_uploaded");
+    }
+}
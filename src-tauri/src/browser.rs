@@ -0,0 +1,259 @@
+// src-tauri/src/browser.rs
+//
+// 调试Chrome的探测与定位，automation.rs 和 commands.rs 共用这里的唯一实现
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::models::AppSettings;
+
+// 所有调试API请求共用一个客户端；调试API只监听本机，不经过代理
+pub(crate) static DEBUG_API_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .no_proxy()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .unwrap_or_else(|e| {
+            tracing::warn!("创建调试API客户端失败，使用默认配置: {}", e);
+            reqwest::Client::new()
+        })
+});
+
+// 调试API健康检查的超时；wait_for_debug_port每500ms轮询一次，超时过长会拖慢等待
+const DEBUG_API_HEALTH_TIMEOUT_SECS: u64 = 2;
+
+// 非Windows平台上Chrome/Chromium主进程的进程名 (pgrep/pkill -x精确匹配)
+#[cfg(target_os = "macos")]
+pub(crate) const CHROME_PROCESS_NAMES: &[&str] = &["Google Chrome", "Chromium"];
+// Linux上进程名截断为15个字符，chromium-browser包装脚本启动的进程名也是chromium/chrome
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) const CHROME_PROCESS_NAMES: &[&str] = &["chrome", "chromium"];
+
+/// 调试端口可连接且调试API正常响应
+/// 只记录异常情况：该函数在等待Chrome启动时会被高频轮询，成功时不写日志
+pub async fn check_chrome_debug_port(settings: &AppSettings) -> bool {
+    if tokio::net::TcpStream::connect(("127.0.0.1", settings.debug_port)).await.is_err() {
+        return false;
+    }
+    matches!(check_chrome_debug_api(settings).await, Ok(true))
+}
+
+/// 检查调试API是否正常响应
+/// 连接被拒绝说明Chrome还在启动，只记录debug日志；超时和错误状态码说明端口异常，记录警告
+pub async fn check_chrome_debug_api(settings: &AppSettings) -> Result<bool> {
+    let response = DEBUG_API_CLIENT
+        .get(format!("http://127.0.0.1:{}/json/version", settings.debug_port))
+        .timeout(std::time::Duration::from_secs(DEBUG_API_HEALTH_TIMEOUT_SECS))
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => Ok(true),
+        Ok(response) => {
+            tracing::warn!("Chrome调试API返回错误状态: {}", response.status());
+            Ok(false)
+        }
+        Err(e) if e.is_connect() => {
+            tracing::debug!("Chrome调试端口{}尚未就绪 (连接被拒绝)", settings.debug_port);
+            Ok(false)
+        }
+        Err(e) if e.is_timeout() => {
+            tracing::warn!("Chrome调试API响应超时 ({}秒)，端口{}可能被其他程序占用", DEBUG_API_HEALTH_TIMEOUT_SECS, settings.debug_port);
+            Ok(false)
+        }
+        Err(e) => {
+            tracing::warn!("Chrome调试API请求失败: {}", e);
+            Ok(false)
+        }
+    }
+}
+
+/// 是否有Chrome进程在运行 (不区分是否开启了调试端口)
+/// 进程列表命令执行失败时记录错误并视为未运行，与之前commands.rs中的处理一致
+pub async fn is_chrome_running() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        match Command::new("tasklist").args(["/FI", "IMAGENAME eq chrome.exe"]).output() {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).contains("chrome.exe"),
+            Err(e) => {
+                tracing::error!("检查Chrome进程失败: {}", e);
+                false
+            }
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        CHROME_PROCESS_NAMES.iter().any(|name| {
+            match Command::new("pgrep").args(["-x", name]).output() {
+                Ok(output) => output.status.success(),
+                Err(e) => {
+                    tracing::error!("检查Chrome进程失败: {}", e);
+                    false
+                }
+            }
+        })
+    }
+}
+
+/// 解析调试Chrome使用的用户数据目录：优先使用设置中的目录，不存在时创建
+/// 目录无法创建时返回错误，而不是像之前commands.rs那样忽略错误继续使用
+pub fn get_chrome_user_data_dir(settings: &AppSettings) -> Result<String> {
+    let user_data_dir = match settings.chrome_user_data_dir.as_deref().map(str::trim) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => default_chrome_user_data_dir()?,
+    };
+    std::fs::create_dir_all(&user_data_dir)
+        .with_context(|| format!("无法创建Chrome用户数据目录: {:?}", user_data_dir))?;
+    if let Some(warning) = live_profile_warning(&user_data_dir) {
+        tracing::warn!("⚠️ {}", warning);
+    }
+    user_data_dir
+        .to_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Chrome用户数据目录包含无效字符: {:?}", user_data_dir))
+}
+
+// 使用自定义的非默认目录来避免Chrome的安全限制
+fn default_chrome_user_data_dir() -> Result<PathBuf> {
+    let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("无法获取用户主目录"))?;
+    #[cfg(target_os = "windows")]
+    let user_data_dir = home_dir.join("AppData\\Local\\RightsGuard\\ChromeProfile");
+    #[cfg(target_os = "macos")]
+    let user_data_dir = home_dir.join("Library/Application Support/RightsGuard/ChromeProfile");
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let user_data_dir = home_dir.join(".config/rights-guard/chrome-profile");
+    Ok(user_data_dir)
+}
+
+// 日常使用的Chrome的默认用户数据目录
+fn live_chrome_profile_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let candidates = dirs::data_local_dir()
+        .map(|dir| vec![dir.join("Google\\Chrome\\User Data"), dir.join("Chromium\\User Data")]);
+    #[cfg(target_os = "macos")]
+    let candidates = dirs::data_dir()
+        .map(|dir| vec![dir.join("Google/Chrome"), dir.join("Chromium")]);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let candidates = dirs::config_dir()
+        .map(|dir| vec![dir.join("google-chrome"), dir.join("chromium")]);
+    candidates.unwrap_or_default()
+}
+
+/// 目录位于日常Chrome的默认用户数据目录内时返回警告。
+/// 该Chrome正在运行时，同一目录的 --user-data-dir 会把启动请求交给已运行的实例，调试端口不会开启
+pub fn live_profile_warning(user_data_dir: &Path) -> Option<String> {
+    let target = user_data_dir.canonicalize().unwrap_or_else(|_| user_data_dir.to_path_buf());
+    live_chrome_profile_dirs()
+        .into_iter()
+        .map(|dir| dir.canonicalize().unwrap_or(dir))
+        .find(|live_dir| target.starts_with(live_dir))
+        .map(|live_dir| format!(
+            "Chrome用户数据目录 {:?} 是日常Chrome的默认配置目录 ({:?})。请先完全关闭所有Chrome窗口再启动调试Chrome，否则调试端口无法开启",
+            user_data_dir, live_dir
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // 在随机端口上启动只返回固定响应的HTTP服务，模拟Chrome调试API
+    async fn serve_canned_response(status_line: &'static str, body: &'static str) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind mock server");
+        let port = listener.local_addr().expect("mock server address").port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status_line,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+        port
+    }
+
+    fn settings_with_port(debug_port: u16) -> AppSettings {
+        AppSettings { debug_port, ..AppSettings::default() }
+    }
+
+    #[tokio::test]
+    async fn debug_api_success_response_is_healthy() {
+        let port = serve_canned_response("200 OK", r#"{"Browser":"Chrome/120.0.0.0"}"#).await;
+        let settings = settings_with_port(port);
+        assert!(check_chrome_debug_api(&settings).await.expect("health check failed"));
+        assert!(check_chrome_debug_port(&settings).await);
+    }
+
+    #[tokio::test]
+    async fn debug_api_client_error_is_unhealthy() {
+        let port = serve_canned_response("404 Not Found", "").await;
+        let settings = settings_with_port(port);
+        assert!(!check_chrome_debug_api(&settings).await.expect("health check failed"));
+        assert!(!check_chrome_debug_port(&settings).await);
+    }
+
+    #[tokio::test]
+    async fn debug_api_server_error_is_unhealthy() {
+        let port = serve_canned_response("500 Internal Server Error", "").await;
+        assert!(!check_chrome_debug_api(&settings_with_port(port)).await.expect("health check failed"));
+    }
+
+    #[tokio::test]
+    async fn refused_connection_is_not_ready() {
+        // 绑定后立即释放，得到一个当前无人监听的端口
+        let port = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .and_then(|listener| listener.local_addr())
+            .expect("reserve port")
+            .port();
+        let settings = settings_with_port(port);
+        assert!(!check_chrome_debug_api(&settings).await.expect("health check failed"));
+        assert!(!check_chrome_debug_port(&settings).await);
+    }
+
+    #[test]
+    fn user_data_dir_override_is_trimmed_and_created() {
+        let dir = std::env::temp_dir().join(format!("rightsguard-chrome-{}", uuid::Uuid::new_v4()));
+        let settings = AppSettings {
+            chrome_user_data_dir: Some(format!("  {}  ", dir.display())),
+            ..AppSettings::default()
+        };
+
+        let resolved = get_chrome_user_data_dir(&settings);
+        let created = dir.is_dir();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(resolved.expect("override should resolve"), dir.to_string_lossy());
+        assert!(created);
+    }
+
+    #[test]
+    fn default_user_data_dir_is_separate_from_the_live_profile() {
+        let Ok(default_dir) = default_chrome_user_data_dir() else {
+            return;
+        };
+        assert!(default_dir.ends_with("chrome-profile") || default_dir.ends_with("ChromeProfile"));
+        assert!(live_profile_warning(&default_dir).is_none());
+    }
+
+    #[test]
+    fn live_profile_dir_triggers_a_warning() {
+        for live_dir in live_chrome_profile_dirs() {
+            assert!(live_profile_warning(&live_dir.join("Default")).is_some(), "no warning for {:?}", live_dir);
+        }
+        assert!(live_profile_warning(&std::env::temp_dir().join("rightsguard-chrome")).is_none());
+    }
+}
@@ -0,0 +1,191 @@
+// src-tauri/src/case_export.rs
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use printpdf::{IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference};
+use std::path::Path;
+use crate::automation::parse_stored_file_paths;
+use crate::bilibili_script::APPEAL_DESCRIPTION;
+use crate::models::{mask_id_card, Case, CaseEvent, IpAsset, Profile};
+
+const PAGE_WIDTH: Mm = Mm(210.0);
+const PAGE_HEIGHT: Mm = Mm(297.0);
+const MARGIN: f32 = 20.0;
+const TITLE_SIZE: f32 = 18.0;
+const HEADING_SIZE: f32 = 13.0;
+const TEXT_SIZE: f32 = 10.5;
+const LINE_HEIGHT: f32 = 6.5;
+// 按字符数折行，中文字符在10.5号字下一行约放下45个
+const MAX_LINE_CHARS: usize = 45;
+
+// 内置PDF字体不含中文，需要嵌入系统中的中文字体
+const CJK_FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+];
+
+/// 导出案件申诉记录所需的全部数据
+pub struct CaseSummary<'a> {
+    pub case: &'a Case,
+    pub events: &'a [CaseEvent],
+    pub ip_asset: Option<&'a IpAsset>,
+    pub profile: Option<&'a Profile>,
+}
+
+/// 将案件、关联IP资产和申诉人信息写成PDF申诉记录
+pub fn write_case_pdf(summary: &CaseSummary, destination: &Path) -> Result<()> {
+    let font_bytes = load_cjk_font()?;
+    let (doc, page, layer) = PdfDocument::new("版权申诉记录", PAGE_WIDTH, PAGE_HEIGHT, "内容");
+    let font = doc
+        .add_external_font(std::io::Cursor::new(font_bytes))
+        .map_err(|e| anyhow!("嵌入中文字体失败: {}", e))?;
+
+    let mut writer = PageWriter {
+        doc: &doc,
+        layer: doc.get_page(page).get_layer(layer),
+        font,
+        y: PAGE_HEIGHT.0 - MARGIN,
+    };
+
+    let case = summary.case;
+    writer.line("版权申诉记录", TITLE_SIZE);
+    writer.line(&format!("生成时间: {}", Local::now().format("%Y-%m-%d %H:%M:%S")), TEXT_SIZE);
+    writer.gap();
+
+    writer.line("案件信息", HEADING_SIZE);
+    writer.field("案件ID", &case.id.map(|id| id.to_string()).unwrap_or_default());
+    writer.field("状态", &case.status);
+    writer.field("侵权链接", &case.infringing_url);
+    writer.field("原创链接", case.original_url.as_deref().unwrap_or("未填写"));
+    writer.field("提交时间", &format_datetime(case.submission_date));
+    writer.field("创建时间", &format_datetime(case.created_at));
+    writer.field("侵权描述", APPEAL_DESCRIPTION);
+    writer.gap();
+
+    if !summary.events.is_empty() {
+        writer.line("提交记录", HEADING_SIZE);
+        for event in summary.events {
+            writer.field(&format_datetime(Some(event.created_at)), &event.event);
+        }
+        writer.gap();
+    }
+
+    writer.line("申诉人", HEADING_SIZE);
+    match summary.profile {
+        Some(profile) => {
+            writer.field("姓名", &profile.name);
+            writer.field("手机号", &profile.phone);
+            writer.field("邮箱", &profile.email);
+            writer.field("证件号码", &mask_id_card(&profile.id_card_number));
+            writer.field("证件证明", &file_names(&profile.id_card_files));
+        }
+        None => writer.line("未找到个人档案", TEXT_SIZE),
+    }
+    writer.gap();
+
+    writer.line("IP资产", HEADING_SIZE);
+    match summary.ip_asset {
+        Some(asset) => {
+            writer.field("著作名称", &asset.work_name);
+            writer.field("著作类型", &asset.work_type);
+            writer.field("权利人", &asset.owner);
+            writer.field("地区", &asset.region);
+            writer.field("期限", &format!("{} 至 {}", asset.work_start_date, asset.work_end_date));
+            writer.field("是否代理", if asset.is_agent { "是" } else { "否" });
+            if asset.is_agent {
+                writer.field("授权期限", &format!(
+                    "{} 至 {}",
+                    asset.auth_start_date.as_deref().unwrap_or("未填写"),
+                    asset.auth_end_date.as_deref().unwrap_or("未填写")
+                ));
+                writer.field("授权证明", &file_names(&asset.auth_files));
+            }
+            writer.field("作品证明", &file_names(&asset.work_proof_files));
+        }
+        None => writer.line("未关联IP资产", TEXT_SIZE),
+    }
+
+    let file = std::fs::File::create(destination)
+        .with_context(|| format!("无法创建PDF文件: {:?}", destination))?;
+    doc.save(&mut std::io::BufWriter::new(file))
+        .map_err(|e| anyhow!("保存PDF失败: {}", e))?;
+    tracing::info!("案件申诉记录已导出: {:?}", destination);
+    Ok(())
+}
+
+// 逐行向下排版，超出页面底部时自动换页
+struct PageWriter<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    y: f32,
+}
+
+impl PageWriter<'_> {
+    fn line(&mut self, text: &str, size: f32) {
+        for chunk in wrap(text, MAX_LINE_CHARS) {
+            if self.y < MARGIN {
+                let (page, layer) = self.doc.add_page(PAGE_WIDTH, PAGE_HEIGHT, "内容");
+                self.layer = self.doc.get_page(page).get_layer(layer);
+                self.y = PAGE_HEIGHT.0 - MARGIN;
+            }
+            self.layer.use_text(chunk, size, Mm(MARGIN), Mm(self.y), &self.font);
+            self.y -= LINE_HEIGHT * size / TEXT_SIZE;
+        }
+    }
+
+    fn field(&mut self, label: &str, value: &str) {
+        self.line(&format!("{}: {}", label, value), TEXT_SIZE);
+    }
+
+    fn gap(&mut self) {
+        self.y -= LINE_HEIGHT / 2.0;
+    }
+}
+
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(max_chars).map(|chunk| chunk.iter().collect()).collect()
+}
+
+fn load_cjk_font() -> Result<Vec<u8>> {
+    CJK_FONT_CANDIDATES
+        .iter()
+        .find_map(|path| std::fs::read(path).ok())
+        .ok_or_else(|| anyhow!("未找到可用的中文字体，无法生成PDF"))
+}
+
+fn format_datetime(value: Option<DateTime<Utc>>) -> String {
+    value
+        .map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "无".to_string())
+}
+
+fn file_names(stored: &Option<String>) -> String {
+    let names: Vec<String> = parse_stored_file_paths(stored)
+        .unwrap_or_default()
+        .iter()
+        .filter(|path| !path.trim().is_empty())
+        .map(|path| {
+            let normalized = path.replace('\\', "/");
+            Path::new(&normalized)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(normalized)
+        })
+        .collect();
+    if names.is_empty() {
+        "无".to_string()
+    } else {
+        names.join("、")
+    }
+}
@@ -0,0 +1,178 @@
+// src-tauri/src/catalog_import.rs
+//
+// 从CSV作品目录批量导入IP资产；Excel表格需先另存为"CSV UTF-8"格式
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::bilibili_script::{is_known_region, is_known_work_type, BILIBILI_REGIONS, BILIBILI_WORK_TYPES};
+use crate::models::IpAsset;
+
+// 可导入的字段及默认识别的表头，第一个表头用于导入模板
+const FIELD_HEADERS: &[(&str, &[&str])] = &[
+    ("work_name", &["作品名称", "著作名称", "work_name", "workName"]),
+    ("work_type", &["著作类型", "作品类型", "work_type", "workType"]),
+    ("owner", &["权利人", "owner"]),
+    ("region", &["地区", "region"]),
+    ("work_start_date", &["期限开始", "起始日期", "work_start_date", "workStartDate"]),
+    ("work_end_date", &["期限结束", "结束日期", "work_end_date", "workEndDate"]),
+    ("equity_type", &["权利类型", "equity_type", "equityType"]),
+];
+
+const REQUIRED_FIELDS: &[&str] = &["work_name", "work_type", "owner"];
+
+// 表格软件导出的日期常见写法，统一保存为YYYY-MM-DD
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%Y年%m月%d日"];
+
+/// CSV中的一行数据及其校验结果
+pub struct ParsedRow {
+    /// CSV中的行号，表头为第1行
+    pub row: usize,
+    pub work_name: Option<String>,
+    pub asset: std::result::Result<IpAsset, String>,
+}
+
+/// 读取CSV作品目录并逐行校验，header_mapping把字段名 (如work_name) 映射到非标准的列名
+/// 文件无法读取或缺少必填列时整体失败，单行的问题记录在对应的行中
+pub fn parse_catalog(path: &Path, header_mapping: &HashMap<String, String>) -> Result<Vec<ParsedRow>> {
+    if let Some(unknown) = header_mapping.keys().find(|field| !FIELD_HEADERS.iter().any(|(name, _)| *name == field.as_str())) {
+        let fields: Vec<&str> = FIELD_HEADERS.iter().map(|(name, _)| *name).collect();
+        return Err(anyhow!("未知的字段 \"{}\"，可映射的字段: {}", unknown, fields.join(", ")));
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("无法打开CSV文件: {}", path.display()))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .context("读取CSV表头失败，请确认文件以UTF-8编码保存")?
+        .iter()
+        .map(|header| header.trim_start_matches('\u{feff}').trim().to_string())
+        .collect();
+    let columns = resolve_columns(&headers, header_mapping)?;
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                rows.push(ParsedRow { row: index + 2, work_name: None, asset: Err(format!("无法读取该行: {}", e)) });
+                continue;
+            }
+        };
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        // 单元格内有换行时记录会跨多行，使用csv给出的实际行号
+        let row = record.position().map_or(index + 2, |position| position.line() as usize);
+        let value = |field: &str| {
+            columns.get(field).and_then(|&column| record.get(column)).unwrap_or_default().to_string()
+        };
+        let work_name = Some(value("work_name")).filter(|name| !name.is_empty());
+        rows.push(ParsedRow { row, work_name, asset: build_asset(value) });
+    }
+    Ok(rows)
+}
+
+// 字段到列序号的映射，自定义映射优先，否则按默认表头识别 (英文表头不区分大小写)
+fn resolve_columns(headers: &[String], header_mapping: &HashMap<String, String>) -> Result<HashMap<&'static str, usize>> {
+    let mut columns = HashMap::new();
+    for (field, aliases) in FIELD_HEADERS {
+        let index = match header_mapping.get(*field) {
+            Some(column) => Some(
+                headers
+                    .iter()
+                    .position(|header| header == column.trim())
+                    .ok_or_else(|| anyhow!("CSV中没有映射的列 \"{}\" (字段 {})", column, field))?,
+            ),
+            None => headers.iter().position(|header| aliases.iter().any(|alias| alias.eq_ignore_ascii_case(header))),
+        };
+        if let Some(index) = index {
+            columns.insert(*field, index);
+        }
+    }
+
+    let missing: Vec<&str> = FIELD_HEADERS
+        .iter()
+        .filter(|(field, _)| REQUIRED_FIELDS.contains(field) && !columns.contains_key(field))
+        .map(|(_, aliases)| aliases[0])
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow!("CSV缺少必填列: {}，现有表头: {}", missing.join("、"), headers.join("、")));
+    }
+    Ok(columns)
+}
+
+// 校验一行并转换为IpAsset，所有问题合并为一条错误，方便用户一次改完
+fn build_asset(value: impl Fn(&str) -> String) -> std::result::Result<IpAsset, String> {
+    let defaults = IpAsset::default();
+    let mut errors = Vec::new();
+
+    let work_name = value("work_name");
+    if work_name.is_empty() {
+        errors.push("作品名称不能为空".to_string());
+    }
+    let owner = value("owner");
+    if owner.is_empty() {
+        errors.push("权利人不能为空".to_string());
+    }
+    let work_type = value("work_type");
+    if !is_known_work_type(&work_type) {
+        errors.push(format!("不支持的著作类型 \"{}\"，可选: {}", work_type, BILIBILI_WORK_TYPES.join("、")));
+    }
+    let region = Some(value("region")).filter(|region| !region.is_empty()).unwrap_or(defaults.region.clone());
+    if !is_known_region(&region) {
+        errors.push(format!("不支持的地区 \"{}\"，可选: {}", region, BILIBILI_REGIONS.join("、")));
+    }
+    let equity_type = Some(value("equity_type")).filter(|equity| !equity.is_empty()).unwrap_or(defaults.equity_type.clone());
+
+    let mut date = |field: &str, label: &str| {
+        parse_optional_date(&value(field)).unwrap_or_else(|raw| {
+            errors.push(format!("{}格式无效 \"{}\"，应为YYYY-MM-DD", label, raw));
+            String::new()
+        })
+    };
+    let work_start_date = date("work_start_date", "期限开始");
+    let work_end_date = date("work_end_date", "期限结束");
+    // 两者都是YYYY-MM-DD，字符串比较与日期顺序一致
+    if !work_start_date.is_empty() && !work_end_date.is_empty() && work_start_date > work_end_date {
+        errors.push(format!("期限开始 {} 晚于期限结束 {}", work_start_date, work_end_date));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok(IpAsset {
+        work_name,
+        work_type,
+        owner,
+        region,
+        work_start_date,
+        work_end_date,
+        equity_type,
+        ..defaults
+    })
+}
+
+// 空值表示不填写期限；无法识别时返回原始文本
+fn parse_optional_date(raw: &str) -> std::result::Result<String, String> {
+    if raw.is_empty() {
+        return Ok(String::new());
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(raw, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| raw.to_string())
+}
+
+/// 导入模板: 默认表头和一行示例，带UTF-8 BOM以便Excel正确显示中文
+pub fn template_csv() -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(b"\xEF\xBB\xBF".to_vec());
+    writer.write_record(FIELD_HEADERS.iter().map(|(_, aliases)| aliases[0]))?;
+    writer.write_record(["示例作品", "视听作品", "示例权利人", "中国大陆", "2024-01-01", "2034-12-31", "著作权"])?;
+    writer.into_inner().map_err(|e| anyhow!("生成导入模板失败: {}", e))
+}
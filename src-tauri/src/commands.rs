@@ -2,7 +2,14 @@ use serde::Serialize;
 use uuid::Uuid;
 use crate::database;
 use crate::automation;
-use crate::models::{Profile, IpAsset, Case, AutomationRequest, FileSelection, AutomationStatus};
+use crate::bilibili_script;
+use crate::browser;
+use crate::case_export;
+use crate::catalog_import;
+use crate::file_copy;
+use crate::image_sanitize;
+use crate::run_queue;
+use crate::models::{MAX_OVERALL_DEADLINE_SECS, Profile, IpAsset, Case, CaseEvent, CasePage, CaseQuery, CaseStats, DatabaseMaintenanceReport, AppSettings, AutomationRequest, AutomationStage, SitePlatform, FileSelection, IdCardFileRole, IngestedFile, AutomationResetReport, AutomationRun, AutomationStatus, BrowserDiagnostics, CompletenessReport, ExifStripping, FileStatus, ImportReport, ImportRowResult, ProfileSaveDiagnosis, QueuedRun, QuotaEstimate, RepairReport, ScheduledRun, SelectorProbe, SetupReport, TabInfo, UploadValidationReport};
 use std::fs;
 use std::str::FromStr;
 use tauri::Manager;
@@ -12,21 +19,67 @@ use tauri::Manager;
 pub enum CommandError {
     #[error("Database error: {0}")]
     Database(String),
+    #[error("数据库忙，请重试: {0}")]
+    DatabaseBusy(String),
     #[error("Automation error: {0}")]
     Automation(String),
+    #[error("已在运行: {0}")]
+    AlreadyRunning(String),
     #[error("UUID parsing error: {0}")]
     Uuid(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    DegradedMode(String),
+    #[error("该侵权链接已有案件: {existing_id}")]
+    DuplicateCase {
+        #[serde(rename = "existingId")]
+        existing_id: String,
+    },
+    #[error("Conflict: {message}")]
+    Conflict {
+        message: String,
+        #[serde(rename = "caseIds")]
+        case_ids: Vec<String>,
+    },
 }
 
 impl From<sqlx::Error> for CommandError {
     fn from(err: sqlx::Error) -> Self {
-        CommandError::Database(err.to_string())
+        if database::is_busy_error(&err) {
+            CommandError::DatabaseBusy(err.to_string())
+        } else {
+            CommandError::Database(err.to_string())
+        }
     }
 }
 
 impl From<anyhow::Error> for CommandError {
     fn from(err: anyhow::Error) -> Self {
-        CommandError::Automation(err.to_string())
+        if err.is::<database::DatabaseBusyError>() {
+            CommandError::DatabaseBusy(format!("{:#}", err))
+        } else if err.is::<database::DatabaseTimeoutError>() {
+            CommandError::Database(err.to_string())
+        } else if err.is::<database::DegradedModeError>() {
+            CommandError::DegradedMode(err.to_string())
+        } else if err.is::<automation::AutomationAlreadyRunning>() {
+            CommandError::AlreadyRunning(err.to_string())
+        } else {
+            CommandError::Automation(err.to_string())
+        }
+    }
+}
+
+impl CommandError {
+    // Database failures that were already logged, keeping busy errors distinct
+    fn from_database_error(err: anyhow::Error) -> Self {
+        if err.is::<database::DatabaseBusyError>() {
+            CommandError::DatabaseBusy(format!("{:#}", err))
+        } else if err.is::<database::DegradedModeError>() {
+            CommandError::DegradedMode(err.to_string())
+        } else {
+            CommandError::Database(err.to_string())
+        }
     }
 }
 
@@ -38,6 +91,83 @@ impl From<uuid::Error> for CommandError {
 }
 
 // 个人档案相关命令
+#[tauri::command]
+pub async fn verify_profile_files(profile_id: String) -> Result<Vec<FileStatus>, CommandError> {
+    let profile = database::get_profile_by_id(Uuid::parse_str(&profile_id)?).await?
+        .ok_or_else(|| CommandError::Automation(format!("Profile not found: {}", profile_id)))?;
+    
+    let paths = automation::parse_stored_file_paths(&profile.id_card_files)?;
+    let statuses: Vec<FileStatus> = paths.iter()
+        .filter(|path| !path.trim().is_empty())
+        .map(|path| automation::check_stored_file(path))
+        .collect();
+    tracing::info!("Verified {} profile files, {} missing", statuses.len(), statuses.iter().filter(|status| !status.exists).count());
+    Ok(statuses)
+}
+
+/// 设置身份证文件的顺序和正反面标记，ordering必须恰好包含档案中现有的全部文件
+/// 保存时按人像面、国徽面、其他的顺序排列，与上传顺序一致
+#[tauri::command]
+pub async fn set_id_card_roles(profile_id: String, mut ordering: Vec<IdCardFileRole>) -> Result<Profile, CommandError> {
+    let profile_id = Uuid::parse_str(&profile_id)?;
+    let profile = database::get_profile_by_id(profile_id).await?
+        .ok_or_else(|| CommandError::InvalidInput(format!("Profile not found: {}", profile_id)))?;
+
+    let mut stored: Vec<String> = automation::parse_stored_file_paths(&profile.id_card_files)?
+        .into_iter()
+        .filter(|path| !path.trim().is_empty())
+        .collect();
+    let mut given: Vec<String> = ordering.iter().map(|file| file.path.clone()).collect();
+    stored.sort();
+    given.sort();
+    if stored != given {
+        return Err(CommandError::InvalidInput("身份证文件列表与档案中的文件不一致，请刷新后重试".to_string()));
+    }
+
+    ordering.sort_by_key(|file| file.role);
+    database::set_id_card_roles(profile_id, &ordering).await?;
+    let updated = database::get_profile_by_id(profile_id).await?
+        .ok_or_else(|| CommandError::InvalidInput(format!("Profile not found: {}", profile_id)))?;
+    if let Some(warning) = automation::id_card_role_warning(&updated)? {
+        tracing::warn!("{}", warning);
+    }
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn dry_run_profile_upload(profile_id: String) -> Result<UploadValidationReport, CommandError> {
+    let profile = database::get_profile_by_id(Uuid::parse_str(&profile_id)?).await?
+        .ok_or_else(|| CommandError::Automation(format!("Profile not found: {}", profile_id)))?;
+    Ok(automation::dry_run_profile_upload(&profile)?)
+}
+
+// 档案完成度，界面据此提示首次申诉前还缺什么
+#[tauri::command]
+pub async fn get_profile_completeness() -> Result<CompletenessReport, CommandError> {
+    let profile = database::get_profile().await?;
+    let assets = database::get_ip_assets().await?;
+    let report = automation::profile_completeness(profile.as_ref(), &assets);
+    tracing::info!("档案完成度: {}%，缺少: {}", report.percent, report.missing.join("、"));
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn relink_file(old_path: String, new_path: String) -> Result<u64, CommandError> {
+    if !automation::check_stored_file(&new_path).exists {
+        return Err(CommandError::Automation(format!("New file does not exist: {}", new_path)));
+    }
+    Ok(database::relink_profile_file(&old_path, &new_path).await?)
+}
+
+// 仅由用户手动触发: 统一档案和IP资产中文件列的格式并移除已不存在的文件，dry_run时只返回将要修改的内容
+#[tauri::command]
+pub async fn repair_file_columns(dry_run: Option<bool>) -> Result<RepairReport, CommandError> {
+    if automation::is_run_in_progress() {
+        return Err(CommandError::AlreadyRunning("自动化流程运行中，请结束后再修复文件记录".to_string()));
+    }
+    Ok(database::repair_file_columns(dry_run.unwrap_or(false)).await?)
+}
+
 #[tauri::command]
 pub async fn get_profile() -> Result<Option<Profile>, CommandError> {
     Ok(database::get_profile().await?)
@@ -46,6 +176,7 @@ pub async fn get_profile() -> Result<Option<Profile>, CommandError> {
 #[tauri::command]
 pub async fn save_profile(profile: Profile) -> Result<Profile, CommandError> {
     tracing::info!("Attempting to save profile: {}", profile.name);
+    let profile = profile.normalized().map_err(CommandError::InvalidInput)?;
     
     match database::save_profile(&profile).await {
         Ok(saved_profile) => {
@@ -54,7 +185,7 @@ pub async fn save_profile(profile: Profile) -> Result<Profile, CommandError> {
         }
         Err(e) => {
             tracing::error!("Failed to save profile: {}", e);
-            Err(CommandError::Database(e.to_string()))
+            Err(CommandError::from_database_error(e))
         }
     }
 }
@@ -71,11 +202,81 @@ pub async fn get_ip_asset(id: String) -> Result<Option<IpAsset>, CommandError> {
     Ok(database::get_ip_asset(uuid).await?)
 }
 
+/// B站申诉表单中可选的著作类型，供界面渲染下拉框
+#[tauri::command]
+pub async fn get_work_type_options() -> Result<Vec<String>, CommandError> {
+    Ok(bilibili_script::BILIBILI_WORK_TYPES.iter().map(|work_type| work_type.to_string()).collect())
+}
+
 #[tauri::command]
 pub async fn save_ip_asset(asset: IpAsset) -> Result<IpAsset, CommandError> {
+    validate_region(&asset)?;
+    validate_work_type(&asset)?;
     Ok(database::save_ip_asset(&asset).await?)
 }
 
+#[tauri::command]
+pub async fn save_ip_assets_batch(assets: Vec<IpAsset>) -> Result<Vec<IpAsset>, CommandError> {
+    for asset in &assets {
+        validate_region(asset)?;
+        validate_work_type(asset)?;
+    }
+    Ok(database::save_ip_assets_batch(assets).await?)
+}
+
+// 以现有作品为模板复制一份，名称追加"(副本)"，默认只返回未保存的副本供界面编辑。
+// 证明文件只复制路径引用而不复制文件本身：文件由用户自行管理，应用从不删除，
+// 共享引用不会互相影响；但对原文件执行 relink_file 时两份资产都会随之更新。
+#[tauri::command]
+pub async fn duplicate_ip_asset(id: String, persist: Option<bool>) -> Result<IpAsset, CommandError> {
+    let uuid = Uuid::parse_str(&id)?;
+    let source = database::get_ip_asset(uuid).await?
+        .ok_or_else(|| CommandError::InvalidInput(format!("IP资产不存在: {}", id)))?;
+
+    let copy = IpAsset {
+        id: None,
+        work_name: format!("{} (副本)", source.work_name),
+        status: IpAsset::default().status,
+        archived: false,
+        created_at: None,
+        updated_at: None,
+        ..source
+    };
+
+    if persist.unwrap_or(false) {
+        validate_region(&copy)?;
+        validate_work_type(&copy)?;
+        return Ok(database::save_ip_asset(&copy).await?);
+    }
+    Ok(copy)
+}
+
+// 地区必须是B站申诉表单中存在的选项，否则脚本无法选中
+fn validate_region(asset: &IpAsset) -> Result<(), CommandError> {
+    if bilibili_script::is_known_region(&asset.region) {
+        return Ok(());
+    }
+    Err(CommandError::InvalidInput(format!(
+        "不支持的地区 \"{}\" (作品: {})，可选: {}",
+        asset.region,
+        asset.work_name,
+        bilibili_script::BILIBILI_REGIONS.join("、")
+    )))
+}
+
+// 著作类型同样必须与下拉框选项完全一致
+fn validate_work_type(asset: &IpAsset) -> Result<(), CommandError> {
+    if bilibili_script::is_known_work_type(&asset.work_type) {
+        return Ok(());
+    }
+    Err(CommandError::InvalidInput(format!(
+        "不支持的著作类型 \"{}\" (作品: {})，可选: {}",
+        asset.work_type,
+        asset.work_name,
+        bilibili_script::BILIBILI_WORK_TYPES.join("、")
+    )))
+}
+
 #[tauri::command]
 pub async fn delete_ip_asset(id: String) -> Result<bool, CommandError> {
     tracing::info!("Attempting to delete IP asset with ID: {}", id);
@@ -92,11 +293,11 @@ pub async fn delete_ip_asset(id: String) -> Result<bool, CommandError> {
         }
     };
     
-    // Perform database deletion (now handles related cases automatically)
+    // Archive the IP asset; related cases are left untouched
     match database::delete_ip_asset(uuid).await {
         Ok(deleted) => {
             if deleted {
-                tracing::info!("Successfully deleted IP asset with ID: {} (including any related cases)", id);
+                tracing::info!("Successfully archived IP asset with ID: {}", id);
                 Ok(true)
             } else {
                 tracing::warn!("No IP asset found with ID: {}", id);
@@ -105,6 +306,9 @@ pub async fn delete_ip_asset(id: String) -> Result<bool, CommandError> {
         },
         Err(e) => {
             tracing::error!("Database error while deleting IP asset '{}': {}", id, e);
+            if e.is::<database::DatabaseBusyError>() {
+                return Err(CommandError::from_database_error(e));
+            }
             let error_msg = if e.to_string().contains("FOREIGN KEY constraint failed") {
                 format!("无法删除IP资产 '{}': 存在相关的案件记录。请先删除相关案件或联系管理员。", id)
             } else {
@@ -115,12 +319,62 @@ pub async fn delete_ip_asset(id: String) -> Result<bool, CommandError> {
     }
 }
 
+#[tauri::command]
+pub async fn get_archived_ip_assets() -> Result<Vec<IpAsset>, CommandError> {
+    Ok(database::get_archived_ip_assets().await?)
+}
+
+#[tauri::command]
+pub async fn restore_ip_asset(id: String) -> Result<bool, CommandError> {
+    let uuid = Uuid::parse_str(&id)?;
+    Ok(database::restore_ip_asset(uuid).await?)
+}
+
+/// Permanently delete an IP asset
+/// Fails with `CommandError::Conflict` while cases still reference it, unless `cascade`
+/// is set, in which case those cases are archived and detached from the asset
+#[tauri::command]
+pub async fn purge_ip_asset(id: String, cascade: Option<bool>) -> Result<bool, CommandError> {
+    let uuid = Uuid::parse_str(&id)?;
+    tracing::info!("Permanently deleting IP asset with ID: {} (cascade: {:?})", id, cascade);
+    
+    match database::purge_ip_asset(uuid, cascade.unwrap_or(false)).await? {
+        database::PurgeIpAssetOutcome::Deleted => Ok(true),
+        database::PurgeIpAssetOutcome::NotFound => {
+            Err(CommandError::Database(format!("IP asset with ID '{}' not found", id)))
+        }
+        database::PurgeIpAssetOutcome::Referenced(case_ids) => {
+            tracing::warn!("IP asset {} is still referenced by {} case(s)", id, case_ids.len());
+            Err(CommandError::Conflict {
+                message: format!("无法删除IP资产 '{}': 仍有{}个案件引用该资产", id, case_ids.len()),
+                case_ids: case_ids.iter().map(|case_id| case_id.to_string()).collect(),
+            })
+        }
+    }
+}
+
 // 案件相关命令
 #[tauri::command]
 pub async fn get_cases() -> Result<Vec<Case>, CommandError> {
     Ok(database::get_cases().await?)
 }
 
+#[tauri::command]
+pub async fn query_cases(filter: CaseQuery) -> Result<CasePage, CommandError> {
+    Ok(database::query_cases(&filter).await?)
+}
+
+#[tauri::command]
+pub async fn get_case(id: String) -> Result<Option<Case>, CommandError> {
+    let uuid = Uuid::parse_str(&id)?;
+    Ok(database::get_case_by_id(uuid).await?)
+}
+
+#[tauri::command]
+pub async fn get_case_events(case_id: String) -> Result<Vec<CaseEvent>, CommandError> {
+    Ok(database::get_case_events(Uuid::parse_str(&case_id)?).await?)
+}
+
 #[tauri::command]
 pub async fn save_case(case: Case) -> Result<Case, CommandError> {
     Ok(database::save_case(&case).await?)
@@ -133,6 +387,29 @@ pub async fn delete_case(id: String) -> Result<bool, CommandError> {
     Ok(true)
 }
 
+#[tauri::command]
+pub async fn get_archived_cases() -> Result<Vec<Case>, CommandError> {
+    Ok(database::get_archived_cases().await?)
+}
+
+#[tauri::command]
+pub async fn restore_case(id: String) -> Result<bool, CommandError> {
+    let uuid = Uuid::parse_str(&id)?;
+    Ok(database::restore_case(uuid).await?)
+}
+
+#[tauri::command]
+pub async fn purge_case(id: String) -> Result<bool, CommandError> {
+    let uuid = Uuid::parse_str(&id)?;
+    tracing::info!("Permanently deleting case with ID: {}", id);
+    Ok(database::purge_case(uuid).await?)
+}
+
+#[tauri::command]
+pub async fn get_case_statistics() -> Result<CaseStats, CommandError> {
+    Ok(database::get_case_statistics().await?)
+}
+
 // 自动化相关命令参数结构体
 #[derive(serde::Deserialize)]
 pub struct StartAutomationParams {
@@ -142,23 +419,224 @@ pub struct StartAutomationParams {
     original_url: Option<String>,
     #[serde(rename = "ipAssetId")]
     ip_asset_id: Option<String>,
+    #[serde(rename = "startStage")]
+    start_stage: Option<AutomationStage>,
+    #[serde(rename = "maxRetries")]
+    max_retries: Option<u32>,
+    #[serde(rename = "appealUrl")]
+    appeal_url: Option<String>,
+    #[serde(rename = "skipUrlValidation")]
+    skip_url_validation: Option<bool>,
+    platform: Option<SitePlatform>,
+    #[serde(rename = "forceFresh")]
+    force_fresh: Option<bool>,
+    #[serde(rename = "keepBrowserOpen")]
+    keep_browser_open: Option<bool>,
+    #[serde(rename = "targetTabId")]
+    target_tab_id: Option<String>,
+    // 忽略重复案件检查，明确要求再次申诉
+    force: Option<bool>,
+    // 作为独立案件记录，而不是追加到已有案件的历史中
+    #[serde(rename = "newCase")]
+    new_case: Option<bool>,
+    // 申诉详情步骤中额外上传的补充证据
+    #[serde(rename = "additionalFiles")]
+    additional_files: Option<Vec<String>>,
+    #[serde(rename = "playwrightConfig")]
+    playwright_config: Option<String>,
+    #[serde(rename = "playwrightProject")]
+    playwright_project: Option<String>,
+    #[serde(rename = "allowWithoutIpAsset")]
+    allow_without_ip_asset: Option<bool>,
+    // 自动点击最终提交；默认只填写表单，等待用户确认提交
+    #[serde(rename = "autoSubmit")]
+    auto_submit: Option<bool>,
+    // 覆盖设置中的默认User-Agent
+    #[serde(rename = "userAgent")]
+    user_agent: Option<String>,
+    // 针对测试页面运行完整流程，提交前停止
+    #[serde(rename = "testMode")]
+    test_mode: Option<bool>,
+    // 复用已通过验证码的申诉页面，未指定时使用设置
+    #[serde(rename = "reuseCurrentPage")]
+    reuse_current_page: Option<bool>,
+    // 覆盖设置中的运行总时限(秒)
+    #[serde(rename = "overallDeadlineSecs")]
+    overall_deadline_secs: Option<u64>,
+}
+
+// 同一侵权链接在该天数内已有案件时视为重复申诉
+const DUPLICATE_CASE_WINDOW_DAYS: i64 = 30;
+
+// 以提交时间为准，未提交的案件使用创建时间；恰好N天时仍视为重复
+fn is_within_duplicate_window(case: &Case, now: chrono::DateTime<chrono::Utc>) -> bool {
+    case.submission_date
+        .or(case.created_at)
+        .map_or(false, |date| now - date <= chrono::Duration::days(DUPLICATE_CASE_WINDOW_DAYS))
+}
+
+#[tauri::command]
+pub async fn start_automation(params: StartAutomationParams, app: tauri::AppHandle) -> Result<(), CommandError> {
+    tracing::info!("start_automation called with: infringing_url={}, original_url={:?}, ip_asset_id={:?}, start_stage={:?}", 
+                   params.infringing_url, params.original_url, params.ip_asset_id, params.start_stage);
+    
+    let mut request = build_automation_request(params).await?;
+    // 测试模式不提交申诉，不占用每日次数
+    if !request.test_mode {
+        if !confirm_quota(&app).await? {
+            return Err(CommandError::InvalidInput("今日申诉次数接近上限，已取消启动".to_string()));
+        }
+        request.quota_confirmed = true;
+    }
+    tracing::info!("Calling automation::start_automation with request: {:?}", request);
+    automation::start_automation(request).await?;
+    tracing::info!("automation::start_automation completed successfully");
+    Ok(())
+}
+
+/// 根据本地案件记录估算今日剩余的B站申诉次数
+#[tauri::command]
+pub async fn get_remaining_quota_estimate() -> Result<QuotaEstimate, CommandError> {
+    let settings = database::get_settings().await?;
+    Ok(automation::estimate_quota(settings.daily_appeal_limit).await?)
+}
+
+// 接近上限时询问用户是否继续；估算只基于本地记录，因此不直接阻止启动
+async fn confirm_quota(app: &tauri::AppHandle) -> Result<bool, CommandError> {
+    use tauri_plugin_dialog::{MessageDialogButtons, MessageDialogKind};
+
+    let settings = database::get_settings().await?;
+    let estimate = automation::estimate_quota(settings.daily_appeal_limit).await?;
+    if !estimate.near_limit {
+        return Ok(true);
+    }
+    tracing::warn!("今日已提交{}次申诉，估计剩余{}次", estimate.submitted_today, estimate.remaining);
+    let message = format!(
+        "今日已提交{}次申诉，按每日{}次估算剩余{}次。超出B站限制的申诉可能被静默拒绝，是否继续？",
+        estimate.submitted_today, estimate.daily_limit, estimate.remaining
+    );
+    Ok(show_dialog(app, "申诉次数接近上限".to_string(), message, MessageDialogKind::Warning, MessageDialogButtons::YesNo).await)
+}
+
+/// 返回该请求会生成的完整Playwright脚本，不运行、不写文件、不连接Chrome
+/// 预览不会提交申诉，因此跳过重复案件检查
+#[tauri::command]
+pub async fn preview_automation_script(params: StartAutomationParams) -> Result<String, CommandError> {
+    let request = build_automation_request(StartAutomationParams { force: Some(true), ..params }).await?;
+    automation::preview_script(request)
+        .await
+        .map_err(|e| CommandError::Automation(format!("{:#}", e)))
+}
+
+/// 把申诉加入运行队列，前一个运行结束后自动开始；链接校验和重复案件检查在入队时完成
+#[tauri::command]
+pub async fn enqueue_automation(params: StartAutomationParams) -> Result<QueuedRun, CommandError> {
+    let request = build_automation_request(params).await?;
+    Ok(run_queue::enqueue(request).await)
 }
 
 #[tauri::command]
-pub async fn start_automation(params: StartAutomationParams) -> Result<(), CommandError> {
-    tracing::info!("start_automation called with: infringing_url={}, original_url={:?}, ip_asset_id={:?}", 
-                   params.infringing_url, params.original_url, params.ip_asset_id);
+pub async fn get_run_queue() -> Result<Vec<QueuedRun>, CommandError> {
+    Ok(run_queue::queued_runs())
+}
+
+/// 取消尚未开始的排队申诉，已开始运行或不在队列中时返回false
+#[tauri::command]
+pub async fn cancel_queued(run_id: String) -> Result<bool, CommandError> {
+    let run_id = Uuid::parse_str(&run_id)?;
+    Ok(run_queue::cancel(run_id).await)
+}
+
+/// 清空运行队列，返回被移除的数量；正在运行的申诉不受影响
+#[tauri::command]
+pub async fn clear_queue() -> Result<usize, CommandError> {
+    Ok(run_queue::clear().await)
+}
+
+/// 计划在指定时间运行申诉；链接校验和重复案件检查在计划时完成
+#[tauri::command]
+pub async fn schedule_automation(params: StartAutomationParams, run_at: chrono::DateTime<chrono::Utc>) -> Result<ScheduledRun, CommandError> {
+    if run_at <= chrono::Utc::now() {
+        return Err(CommandError::InvalidInput("计划时间必须晚于当前时间".to_string()));
+    }
+    let request = build_automation_request(params).await?;
+    let scheduled = database::insert_scheduled_run(&request, run_at).await?;
+    tracing::info!("计划申诉已保存: {} 于 {} 运行 ({})", scheduled.id, run_at, request.infringing_url);
+    Ok(scheduled)
+}
+
+#[tauri::command]
+pub async fn get_scheduled_runs() -> Result<Vec<ScheduledRun>, CommandError> {
+    Ok(database::get_scheduled_runs().await?)
+}
+
+/// 取消尚未开始的计划运行，已开始或已结束的计划返回false
+#[tauri::command]
+pub async fn cancel_scheduled_run(id: String) -> Result<bool, CommandError> {
+    let id = Uuid::parse_str(&id)?;
+    Ok(database::cancel_scheduled_run(id).await?)
+}
+
+// 校验参数并生成自动化请求，立即运行和计划运行共用
+async fn build_automation_request(params: StartAutomationParams) -> Result<AutomationRequest, CommandError> {
+    let infringing_url = if params.skip_url_validation.unwrap_or(false) {
+        tracing::warn!("Skipping infringing URL validation as requested");
+        params.infringing_url
+    } else {
+        automation::validate_infringing_url(&params.infringing_url)
+            .await
+            .map_err(|e| CommandError::Automation(format!("{:#}", e)))?
+    };
+    
+    let test_mode = params.test_mode.unwrap_or(false);
+    if test_mode {
+        tracing::info!("Test mode run: no appeal will be submitted or recorded as a case");
+    } else if params.force.unwrap_or(false) {
+        tracing::warn!("Skipping duplicate case check as requested");
+    } else if let Some(existing) = database::find_case_by_url(&infringing_url).await? {
+        if is_within_duplicate_window(&existing, chrono::Utc::now()) {
+            let existing_id = existing.id.map(|id| id.to_string()).unwrap_or_default();
+            tracing::warn!("Duplicate appeal blocked: {} already has case {}", infringing_url, existing_id);
+            return Err(CommandError::DuplicateCase { existing_id });
+        }
+    }
     
+    let user_agent = match params.user_agent.as_deref().map(str::trim) {
+        Some(user_agent) if !user_agent.is_empty() => Some(
+            automation::validate_user_agent(user_agent)
+                .map_err(|e| CommandError::InvalidInput(format!("{:#}", e)))?
+        ),
+        _ => None,
+    };
+    if params.overall_deadline_secs.is_some_and(|secs| !(1..=MAX_OVERALL_DEADLINE_SECS).contains(&secs)) {
+        return Err(CommandError::InvalidInput(format!("运行总时限必须在1-{}秒之间", MAX_OVERALL_DEADLINE_SECS)));
+    }
+
     let request = AutomationRequest {
-        infringing_url: params.infringing_url,
+        infringing_url,
         original_url: params.original_url,
         ip_asset_id: params.ip_asset_id.map(|id| Uuid::parse_str(&id)).transpose()?,
+        start_stage: params.start_stage.unwrap_or_default(),
+        max_retries: params.max_retries,
+        appeal_url: params.appeal_url,
+        platform: params.platform.unwrap_or_default(),
+        force_fresh: params.force_fresh.unwrap_or(false),
+        keep_browser_open: params.keep_browser_open.unwrap_or(true),
+        target_tab_id: params.target_tab_id.filter(|id| !id.trim().is_empty()),
+        new_case: params.new_case.unwrap_or(false),
+        run_id: None,
+        additional_files: params.additional_files.unwrap_or_default(),
+        playwright_config: params.playwright_config.filter(|config| !config.trim().is_empty()),
+        playwright_project: params.playwright_project.filter(|project| !project.trim().is_empty()),
+        allow_without_ip_asset: params.allow_without_ip_asset.unwrap_or(false),
+        auto_submit: params.auto_submit.unwrap_or(false),
+        user_agent,
+        test_mode,
+        reuse_current_page: params.reuse_current_page,
+        overall_deadline_secs: params.overall_deadline_secs,
+        quota_confirmed: false,
     };
-    
-    tracing::info!("Calling automation::start_automation with request: {:?}", request);
-    automation::start_automation(request).await?;
-    tracing::info!("automation::start_automation completed successfully");
-    Ok(())
+    Ok(request)
 }
 
 #[tauri::command]
@@ -172,12 +650,43 @@ pub async fn get_automation_status() -> Result<AutomationStatus, CommandError> {
     Ok(automation::get_automation_status().await?)
 }
 
+#[tauri::command]
+pub async fn get_automation_history(limit: Option<i64>, offset: Option<i64>) -> Result<Vec<AutomationRun>, CommandError> {
+    Ok(database::get_automation_history(limit, offset).await?)
+}
+
+#[tauri::command]
+pub async fn reset_automation_state() -> Result<AutomationResetReport, CommandError> {
+    Ok(automation::reset_automation_state().await?)
+}
+
 #[tauri::command]
 pub async fn continue_automation_after_verification() -> Result<(), CommandError> {
     automation::continue_after_verification().await?;
     Ok(())
 }
 
+#[tauri::command]
+pub async fn list_browser_tabs() -> Result<Vec<TabInfo>, CommandError> {
+    Ok(automation::list_browser_tabs().await?)
+}
+
+#[tauri::command]
+pub async fn get_browser_diagnostics() -> Result<BrowserDiagnostics, CommandError> {
+    Ok(automation::get_browser_diagnostics().await?)
+}
+
+#[tauri::command]
+pub async fn probe_selector(selector: String) -> Result<SelectorProbe, CommandError> {
+    Ok(automation::probe_selector(&selector).await?)
+}
+
+#[tauri::command]
+pub async fn run_self_test() -> Result<String, CommandError> {
+    tracing::info!("Running automation self-test against the bundled fixture page");
+    Ok(automation::run_self_test().await?)
+}
+
 #[tauri::command]
 pub async fn check_automation_environment() -> Result<String, CommandError> {
     match automation::check_automation_environment_public().await {
@@ -186,6 +695,13 @@ pub async fn check_automation_environment() -> Result<String, CommandError> {
     }
 }
 
+/// 首次运行引导: 检查目录、数据库、浏览器、npx和Playwright，返回逐项结果
+/// installBrowser为true时顺带安装缺少的Playwright浏览器
+#[tauri::command]
+pub async fn run_first_time_setup(install_browser: Option<bool>) -> Result<SetupReport, CommandError> {
+    Ok(crate::setup::run_first_time_setup(install_browser.unwrap_or(false)).await)
+}
+
 // 文件相关命令
 #[tauri::command]
 pub async fn select_file(app: tauri::AppHandle) -> Result<FileSelection, CommandError> {
@@ -250,43 +766,69 @@ pub async fn open_url(url: String, app: tauri::AppHandle) -> Result<(), CommandE
 
 #[tauri::command]
 pub async fn show_message(title: String, message: String, app: tauri::AppHandle) -> Result<(), CommandError> {
-    use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
-    use std::sync::mpsc;
-    
-    let (tx, rx) = mpsc::channel();
-    
-    app.dialog()
-        .message(message)
-        .title(title)
-        .kind(MessageDialogKind::Info)
-        .show(move |_| {
-            let _ = tx.send(());
-        });
+    show_message_with_kind(title, message, "info".to_string(), app).await
+}
+
+#[tauri::command]
+pub async fn show_message_with_kind(title: String, message: String, kind: String, app: tauri::AppHandle) -> Result<(), CommandError> {
+    use tauri_plugin_dialog::MessageDialogButtons;
     
-    let _ = rx.recv(); // Wait for dialog to close
+    show_dialog(&app, title, message, parse_dialog_kind(&kind), MessageDialogButtons::Ok).await;
     Ok(())
 }
 
 #[tauri::command]
 pub async fn show_confirm_dialog(title: String, message: String, app: tauri::AppHandle) -> Result<bool, CommandError> {
-    use tauri_plugin_dialog::{DialogExt, MessageDialogKind, MessageDialogButtons};
-    use std::sync::mpsc;
+    use tauri_plugin_dialog::{MessageDialogKind, MessageDialogButtons};
     
-    let (tx, rx) = mpsc::channel();
+    Ok(show_dialog(&app, title, message, MessageDialogKind::Warning, MessageDialogButtons::OkCancel).await)
+}
+
+/// Ask a Yes/No question, e.g. "确认删除该案件?", and return whether the user accepted
+#[tauri::command]
+pub async fn confirm_message(title: String, message: String, app: tauri::AppHandle) -> Result<bool, CommandError> {
+    use tauri_plugin_dialog::{MessageDialogKind, MessageDialogButtons};
+    
+    Ok(show_dialog(&app, title, message, MessageDialogKind::Info, MessageDialogButtons::YesNo).await)
+}
+
+fn parse_dialog_kind(kind: &str) -> tauri_plugin_dialog::MessageDialogKind {
+    use tauri_plugin_dialog::MessageDialogKind;
+    
+    match kind.to_lowercase().as_str() {
+        "warning" | "warn" => MessageDialogKind::Warning,
+        "error" => MessageDialogKind::Error,
+        "info" => MessageDialogKind::Info,
+        other => {
+            tracing::warn!("Unknown dialog kind '{}', falling back to info", other);
+            MessageDialogKind::Info
+        }
+    }
+}
+
+// Waits on a oneshot channel so the command only returns once the dialog has closed,
+// without blocking an async runtime worker thread
+pub(crate) async fn show_dialog(
+    app: &tauri::AppHandle,
+    title: String,
+    message: String,
+    kind: tauri_plugin_dialog::MessageDialogKind,
+    buttons: tauri_plugin_dialog::MessageDialogButtons,
+) -> bool {
+    use tauri_plugin_dialog::DialogExt;
+    
+    let (tx, rx) = tokio::sync::oneshot::channel();
     
     app.dialog()
         .message(message)
         .title(title)
-        .kind(MessageDialogKind::Warning)
-        .buttons(MessageDialogButtons::OkCancel)
+        .kind(kind)
+        .buttons(buttons)
         .show(move |response| {
             let _ = tx.send(response);
         });
     
-    match rx.recv() {
-        Ok(response) => Ok(response),
-        Err(_) => Ok(false), // Default to false if channel fails
-    }
+    rx.await.unwrap_or(false) // Default to false if the dialog was dropped
 }
 
 // Helper function to test file system operations
@@ -294,59 +836,55 @@ async fn test_file_system_operations() -> Result<Vec<String>, anyhow::Error> {
     let mut results = Vec::new();
     
     // Test app data directory access
-    if let Ok(app_handle_guard) = database::APP_HANDLE.lock() {
-        if let Some(handle) = app_handle_guard.as_ref() {
-            let app_data_dir = handle.path().app_data_dir()
-                .map_err(|e| anyhow::anyhow!("Failed to get app data directory: {}", e))?;
-            
-            results.push(format!("✓ App data directory: {:?}", app_data_dir));
-            results.push(format!("✓ App data exists: {}", app_data_dir.exists()));
-            
-            // Test creating the data subdirectory
-            let data_dir = app_data_dir.join("data");
-            if !data_dir.exists() {
-                fs::create_dir_all(&data_dir)
-                    .map_err(|e| anyhow::anyhow!("Failed to create data directory: {}", e))?;
-                results.push(format!("✓ Created data directory: {:?}", data_dir));
-            } else {
-                results.push(format!("✓ Data directory exists: {:?}", data_dir));
+    if let Ok(handle) = database::app_handle() {
+        let app_data_dir = handle.path().app_data_dir()
+            .map_err(|e| anyhow::anyhow!("Failed to get app data directory: {}", e))?;
+        
+        results.push(format!("✓ App data directory: {:?}", app_data_dir));
+        results.push(format!("✓ App data exists: {}", app_data_dir.exists()));
+        
+        // Test creating the data subdirectory
+        let data_dir = app_data_dir.join("data");
+        if !data_dir.exists() {
+            fs::create_dir_all(&data_dir)
+                .map_err(|e| anyhow::anyhow!("Failed to create data directory: {}", e))?;
+            results.push(format!("✓ Created data directory: {:?}", data_dir));
+        } else {
+            results.push(format!("✓ Data directory exists: {:?}", data_dir));
+        }
+        
+        // Test write permissions
+        let test_file = data_dir.join("write_test.tmp");
+        match fs::write(&test_file, "test content") {
+            Ok(_) => {
+                results.push("✓ Write permission test passed".to_string());
+                let _ = fs::remove_file(&test_file); // Clean up
             }
-            
-            // Test write permissions
-            let test_file = data_dir.join("write_test.tmp");
-            match fs::write(&test_file, "test content") {
-                Ok(_) => {
-                    results.push("✓ Write permission test passed".to_string());
-                    let _ = fs::remove_file(&test_file); // Clean up
+            Err(e) => {
+                results.push(format!("✗ Write permission test failed: {}", e));
+            }
+        }
+        
+        // Test database file path
+        let db_file = data_dir.join("rights_guard.db");
+        results.push(format!("✓ Database file path: {:?}", db_file));
+        
+        // Check if database file exists and get its size
+        if db_file.exists() {
+            match fs::metadata(&db_file) {
+                Ok(metadata) => {
+                    results.push(format!("✓ Database file size: {} bytes", metadata.len()));
+                    results.push(format!("✓ Database file readonly: {}", metadata.permissions().readonly()));
                 }
                 Err(e) => {
-                    results.push(format!("✗ Write permission test failed: {}", e));
+                    results.push(format!("✗ Failed to get database metadata: {}", e));
                 }
             }
-            
-            // Test database file path
-            let db_file = data_dir.join("rights_guard.db");
-            results.push(format!("✓ Database file path: {:?}", db_file));
-            
-            // Check if database file exists and get its size
-            if db_file.exists() {
-                match fs::metadata(&db_file) {
-                    Ok(metadata) => {
-                        results.push(format!("✓ Database file size: {} bytes", metadata.len()));
-                        results.push(format!("✓ Database file readonly: {}", metadata.permissions().readonly()));
-                    }
-                    Err(e) => {
-                        results.push(format!("✗ Failed to get database metadata: {}", e));
-                    }
-                }
-            } else {
-                results.push("ℹ Database file does not exist yet".to_string());
-            }
         } else {
-            return Err(anyhow::anyhow!("App handle not available"));
+            results.push("ℹ Database file does not exist yet".to_string());
         }
     } else {
-        return Err(anyhow::anyhow!("Failed to access app handle"));
+        return Err(anyhow::anyhow!("App handle not available"));
     }
     
     Ok(results)
@@ -449,8 +987,9 @@ pub async fn test_database() -> Result<String, CommandError> {
         name: "Test User".to_string(),
         phone: "13800138000".to_string(),
         email: "test@example.com".to_string(),
-        id_card_number: "110101199001011234".to_string(),
+        id_card_number: "11010519491231002X".to_string(),
         id_card_files: None,
+        id_card_roles: None,
         created_at: None,
         updated_at: None,
     };
@@ -492,6 +1031,22 @@ pub async fn test_database() -> Result<String, CommandError> {
     Ok(results.join("\n"))
 }
 
+// Write a known profile, read it back and report every column that differs from what was bound
+#[tauri::command]
+pub async fn diagnose_profile_save() -> Result<ProfileSaveDiagnosis, CommandError> {
+    if automation::is_run_in_progress() {
+        return Err(CommandError::AlreadyRunning("自动化流程运行中，请结束后再诊断档案保存".to_string()));
+    }
+    let diagnosis = database::diagnose_profile_save()
+        .await
+        .map_err(CommandError::from_database_error)?;
+    tracing::info!(
+        "Profile save diagnosis: retrieved={}, matches={}, {} differing column(s)",
+        diagnosis.retrieved, diagnosis.matches, diagnosis.diffs.len()
+    );
+    Ok(diagnosis)
+}
+
 // Comprehensive SQLite connection test command
 #[tauri::command]
 pub async fn test_sqlite_connection_strategies() -> Result<String, CommandError> {
@@ -628,6 +1183,201 @@ pub async fn get_database_diagnostics() -> Result<String, CommandError> {
     }
 }
 
+/// Back up the database to a location chosen in a save dialog
+/// Returns the written path, or an empty string if the user cancelled
+#[tauri::command]
+pub async fn backup_database(app: tauri::AppHandle) -> Result<String, CommandError> {
+    use tauri_plugin_dialog::DialogExt;
+    
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    
+    app.dialog()
+        .file()
+        .set_title("备份数据库")
+        .set_file_name(format!("rights_guard-{}.db", chrono::Local::now().format("%Y%m%d-%H%M%S")))
+        .add_filter("SQLite数据库", &["db"])
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+    
+    let destination = match rx.await {
+        Ok(Some(path)) => std::path::PathBuf::from(path.to_string()),
+        _ => {
+            tracing::info!("Database backup cancelled");
+            return Ok(String::new());
+        }
+    };
+    
+    database::backup_database_to(&destination).await
+        .map_err(CommandError::from_database_error)?;
+    Ok(destination.to_string_lossy().to_string())
+}
+
+/// Export a case with its IP asset and the applicant profile as a PDF appeal record
+/// Returns the written path, or an empty string if the user cancelled
+#[tauri::command]
+pub async fn export_case_pdf(app: tauri::AppHandle, case_id: String) -> Result<String, CommandError> {
+    use tauri_plugin_dialog::DialogExt;
+    
+    let case_id = Uuid::parse_str(&case_id)?;
+    let case = database::get_case_by_id(case_id).await?
+        .ok_or_else(|| CommandError::Database(format!("Case not found: {}", case_id)))?;
+    let events = database::get_case_events(case_id).await?;
+    let ip_asset = match case.associated_ip_id {
+        Some(ip_asset_id) => database::get_ip_asset(ip_asset_id).await?,
+        None => None,
+    };
+    let profile = database::get_profile().await?;
+    
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    
+    app.dialog()
+        .file()
+        .set_title("导出申诉记录")
+        .set_file_name(format!("申诉记录-{}.pdf", chrono::Local::now().format("%Y%m%d-%H%M%S")))
+        .add_filter("PDF文件", &["pdf"])
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+    
+    let destination = match rx.await {
+        Ok(Some(path)) => std::path::PathBuf::from(path.to_string()),
+        _ => {
+            tracing::info!("Case PDF export cancelled");
+            return Ok(String::new());
+        }
+    };
+    
+    let summary = case_export::CaseSummary {
+        case: &case,
+        events: &events,
+        ip_asset: ip_asset.as_ref(),
+        profile: profile.as_ref(),
+    };
+    case_export::write_case_pdf(&summary, &destination)?;
+    Ok(destination.to_string_lossy().to_string())
+}
+
+/// 从CSV作品目录批量导入IP资产，header_mapping把字段名映射到表格中的列名
+/// 校验通过的行在同一个事务中写入，每行的结果单独返回
+#[tauri::command]
+pub async fn import_ip_assets_csv(
+    path: String,
+    header_mapping: Option<std::collections::HashMap<String, String>>,
+) -> Result<ImportReport, CommandError> {
+    tracing::info!("Importing IP assets from CSV: {}", path);
+
+    let parsed = catalog_import::parse_catalog(std::path::Path::new(&path), &header_mapping.unwrap_or_default())
+        .map_err(|e| CommandError::InvalidInput(format!("{:#}", e)))?;
+
+    let mut rows = Vec::with_capacity(parsed.len());
+    let mut assets = Vec::new();
+    let mut asset_rows = Vec::new();
+    for parsed_row in parsed {
+        let error = match parsed_row.asset {
+            Ok(asset) => {
+                asset_rows.push(rows.len());
+                assets.push(asset);
+                None
+            }
+            Err(e) => Some(e),
+        };
+        rows.push(ImportRowResult {
+            row: parsed_row.row,
+            work_name: parsed_row.work_name,
+            success: error.is_none(),
+            error,
+            asset_id: None,
+        });
+    }
+
+    if !assets.is_empty() {
+        match database::save_ip_assets_batch(assets).await {
+            Ok(saved) => {
+                for (index, asset) in asset_rows.iter().zip(saved) {
+                    rows[*index].asset_id = asset.id;
+                }
+            }
+            // 批量写入是原子的，失败时本批没有任何一行被保存
+            Err(e) => {
+                tracing::error!("Failed to save imported IP assets: {:#}", e);
+                for index in &asset_rows {
+                    rows[*index].success = false;
+                    rows[*index].error = Some(format!("写入数据库失败，本批均未导入: {:#}", e));
+                }
+            }
+        }
+    }
+
+    let imported = rows.iter().filter(|row| row.success).count();
+    let failed = rows.len() - imported;
+    tracing::info!("CSV import finished: {} imported, {} failed", imported, failed);
+    Ok(ImportReport { total: rows.len(), imported, failed, rows })
+}
+
+/// 保存IP资产导入模板，返回写入的路径，用户取消时返回空字符串
+#[tauri::command]
+pub async fn download_import_template(app: tauri::AppHandle) -> Result<String, CommandError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    app.dialog()
+        .file()
+        .set_title("保存导入模板")
+        .set_file_name("IP资产导入模板.csv")
+        .add_filter("CSV文件", &["csv"])
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+
+    let destination = match rx.await {
+        Ok(Some(path)) => std::path::PathBuf::from(path.to_string()),
+        _ => {
+            tracing::info!("Import template download cancelled");
+            return Ok(String::new());
+        }
+    };
+
+    fs::write(&destination, catalog_import::template_csv()?)
+        .map_err(|e| CommandError::InvalidInput(format!("无法写入导入模板: {}", e)))?;
+    Ok(destination.to_string_lossy().to_string())
+}
+
+/// Replace the current database with a backup file and reopen the pool
+/// Returns any non-fatal warnings, one per line
+#[tauri::command]
+pub async fn restore_database(path: String) -> Result<String, CommandError> {
+    tracing::info!("Restoring database from backup: {}", path);
+    
+    let warnings = database::restore_database_from(std::path::Path::new(&path)).await
+        .map_err(|e| {
+            tracing::error!("Database restore failed: {:#}", e);
+            CommandError::from_database_error(e)
+        })?;
+    Ok(warnings.join("\n"))
+}
+
+/// Whether the database is an in-memory fallback; the UI shows a persistent banner while true
+#[tauri::command]
+pub async fn is_degraded_mode() -> Result<bool, CommandError> {
+    Ok(database::is_degraded_mode())
+}
+
+// Checkpoint the WAL and VACUUM; refuses to run while automation holds connections
+#[tauri::command]
+pub async fn run_database_maintenance() -> Result<DatabaseMaintenanceReport, CommandError> {
+    if automation::is_run_in_progress() {
+        return Err(CommandError::AlreadyRunning("自动化流程运行中，请结束后再整理数据库".to_string()));
+    }
+    tracing::info!("Running database maintenance");
+    
+    database::run_maintenance().await.map_err(|e| {
+        tracing::error!("Database maintenance failed: {:#}", e);
+        CommandError::from_database_error(e)
+    })
+}
+
 // Clear database cache command
 #[tauri::command]
 pub async fn clear_database_cache() -> Result<String, CommandError> {
@@ -642,8 +1392,9 @@ pub async fn check_browser_connection_status() -> Result<String, CommandError> {
     tracing::info!("Checking browser connection status");
     
     // Use the browser detection logic from automation.rs
-    let is_debug_port_available = check_chrome_debug_port().await;
-    let is_chrome_running = check_chrome_running().await;
+    let settings = database::get_settings().await.unwrap_or_default();
+    let is_debug_port_available = browser::check_chrome_debug_port(&settings).await;
+    let is_chrome_running = browser::is_chrome_running().await;
     
     let status = if is_debug_port_available {
         "connected".to_string()
@@ -658,128 +1409,43 @@ pub async fn check_browser_connection_status() -> Result<String, CommandError> {
 }
 
 #[tauri::command]
-pub async fn get_browser_launch_command() -> Result<String, CommandError> {
-    match get_chrome_user_data_dir() {
-        Ok(user_data_dir) => {
-            let command = if cfg!(target_os = "windows") {
-                format!("chrome.exe --remote-debugging-port=9222 --user-data-dir=\"{}\"", user_data_dir)
-            } else if cfg!(target_os = "macos") {
-                format!("/Applications/Google\\ Chrome.app/Contents/MacOS/Google\\ Chrome --remote-debugging-port=9222 --user-data-dir=\"{}\"", user_data_dir)
-            } else {
-                format!("google-chrome --remote-debugging-port=9222 --user-data-dir=\"{}\"", user_data_dir)
-            };
-            Ok(command)
-        },
-        Err(e) => Err(CommandError::Automation(e.to_string()))
-    }
-}
-
-// Helper functions (these need to be accessible from commands.rs)
-async fn check_chrome_debug_port() -> bool {
-    // Check TCP port connection
-    if let Ok(_) = tokio::net::TcpStream::connect("127.0.0.1:9222").await {
-        // Further check debug API response
-        match check_chrome_debug_api().await {
-            Ok(true) => {
-                tracing::info!("Chrome debug port 9222 is available and API responds normally");
-                true
-            },
-            Ok(false) => {
-                tracing::warn!("Chrome debug port 9222 is reachable but API doesn't respond");
-                false
-            },
-            Err(e) => {
-                tracing::error!("Error checking Chrome debug API: {}", e);
-                false
-            }
-        }
-    } else {
-        false
-    }
+pub async fn launch_browser_for_login() -> Result<String, CommandError> {
+    tracing::info!("Launching browser for manual Bilibili login");
+    Ok(automation::launch_browser_for_login().await?)
 }
 
-async fn check_chrome_debug_api() -> Result<bool, anyhow::Error> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
-    
-    match client.get("http://127.0.0.1:9222/json/version").send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                let text = response.text().await?;
-                tracing::debug!("Chrome debug API response: {}", text);
-                Ok(true)
-            } else {
-                tracing::warn!("Chrome debug API response status code: {}", response.status());
-                Ok(false)
-            }
-        },
-        Err(e) => {
-            tracing::warn!("Chrome debug API request failed: {}", e);
-            Ok(false)
-        }
+#[tauri::command]
+pub async fn get_browser_launch_command() -> Result<String, CommandError> {
+    let settings = database::get_settings().await?;
+    match browser::get_chrome_user_data_dir(&settings) {
+        Ok(user_data_dir) => Ok(build_browser_launch_command(&settings, &user_data_dir)),
+        Err(e) => Err(CommandError::Automation(e.to_string()))
     }
 }
 
-async fn check_chrome_running() -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        let output = std::process::Command::new("tasklist")
-            .args(&["/FI", "IMAGENAME eq chrome.exe"])
-            .output();
-        
-        match output {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let is_running = stdout.contains("chrome.exe");
-                if is_running {
-                    tracing::info!("Detected Chrome process is running");
-                }
-                is_running
-            },
-            Err(e) => {
-                tracing::error!("Error checking Chrome process: {}", e);
-                false
-            }
-        }
-    }
-    
-    #[cfg(not(target_os = "windows"))]
-    {
-        // Linux/Mac implementation
-        let output = std::process::Command::new("pgrep")
-            .args(&["-f", "chrome"])
-            .output();
-            
-        match output {
-            Ok(output) => !output.stdout.is_empty(),
-            Err(_) => false
-        }
-    }
+#[tauri::command]
+pub async fn copy_browser_launch_command(app: tauri::AppHandle) -> Result<(), CommandError> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    
+    let command = get_browser_launch_command().await?;
+    app.clipboard()
+        .write_text(command)
+        .map_err(|e| CommandError::Automation(format!("Failed to copy launch command to clipboard: {}", e)))?;
+    tracing::info!("Browser launch command copied to clipboard");
+    Ok(())
 }
 
-fn get_chrome_user_data_dir() -> Result<String, anyhow::Error> {
-    let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Unable to get user home directory"))?;
-    
-    // 使用自定义的非默认目录来避免Chrome的安全限制
-    #[cfg(target_os = "windows")]
-    let user_data_dir = home_dir.join("AppData\\Local\\RightsGuard\\ChromeProfile");
-    
-    #[cfg(target_os = "macos")]
-    let user_data_dir = home_dir.join("Library/Application Support/RightsGuard/ChromeProfile");
-    
-    #[cfg(target_os = "linux")]
-    let user_data_dir = home_dir.join(".config/rights-guard/chrome-profile");
-    
-    // 确保目录存在
-    if let Err(e) = std::fs::create_dir_all(&user_data_dir) {
-        tracing::warn!("Failed to create Chrome user data directory: {}", e);
-        // 尝试继续，有时目录已存在但权限问题导致create_dir_all失败
+fn build_browser_launch_command(settings: &AppSettings, user_data_dir: &str) -> String {
+    let port = settings.debug_port;
+    if let Some(chrome_path) = settings.chrome_path.as_deref() {
+        format!("\"{}\" --remote-debugging-port={} --user-data-dir=\"{}\"", chrome_path, port, user_data_dir)
+    } else if cfg!(target_os = "windows") {
+        format!("chrome.exe --remote-debugging-port={} --user-data-dir=\"{}\"", port, user_data_dir)
+    } else if cfg!(target_os = "macos") {
+        format!("/Applications/Google\\ Chrome.app/Contents/MacOS/Google\\ Chrome --remote-debugging-port={} --user-data-dir=\"{}\"", port, user_data_dir)
     } else {
-        tracing::info!("Chrome user data directory ready: {:?}", user_data_dir);
+        format!("google-chrome --remote-debugging-port={} --user-data-dir=\"{}\"", port, user_data_dir)
     }
-    
-    Ok(user_data_dir.to_str().unwrap_or_default().to_string())
 }
 
 #[tauri::command]
@@ -850,23 +1516,27 @@ pub async fn force_restart_chrome() -> Result<String, CommandError> {
     #[cfg(not(target_os = "windows"))]
     {
         // Linux/Mac implementation
-        let gentle_result = std::process::Command::new("pkill")
-            .args(&["-TERM", "chrome"])
-            .output();
+        let gentle_result: Result<Vec<_>, _> = browser::CHROME_PROCESS_NAMES.iter()
+            .map(|name| std::process::Command::new("pkill").args(&["-TERM", "-x", name]).output())
+            .collect();
             
         if let Ok(_) = gentle_result {
             results.push("✓ 发送关闭信号给Chrome进程".to_string());
             tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
         }
         
-        let force_result = std::process::Command::new("pkill")
-            .args(&["-KILL", "chrome"])
-            .output();
-            
-        if let Ok(_) = force_result {
-            results.push("✓ 强制关闭所有Chrome进程".to_string());
+        if browser::is_chrome_running().await {
+            let force_result: Result<Vec<_>, _> = browser::CHROME_PROCESS_NAMES.iter()
+                .map(|name| std::process::Command::new("pkill").args(&["-KILL", "-x", name]).output())
+                .collect();
+                
+            if let Ok(_) = force_result {
+                results.push("✓ 强制关闭所有Chrome进程".to_string());
+            } else {
+                results.push("⚠ 关闭Chrome进程可能失败".to_string());
+            }
         } else {
-            results.push("⚠ 关闭Chrome进程可能失败".to_string());
+            results.push("✓ 所有Chrome进程已关闭".to_string());
         }
     }
     
@@ -874,7 +1544,7 @@ pub async fn force_restart_chrome() -> Result<String, CommandError> {
     tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
     
     // Step 3: Verify all processes are closed
-    let final_check = check_chrome_running().await;
+    let final_check = browser::is_chrome_running().await;
     if !final_check {
         results.push("✓ 确认所有Chrome进程已关闭".to_string());
     } else {
@@ -885,18 +1555,17 @@ pub async fn force_restart_chrome() -> Result<String, CommandError> {
     results.push("".to_string());
     results.push("🔄 Chrome已关闭，请使用以下命令重新启动:".to_string());
     
-    let user_data_dir = get_chrome_user_data_dir().unwrap_or_default();
-    let command = if cfg!(target_os = "windows") {
-        format!("chrome.exe --remote-debugging-port=9222 --user-data-dir=\"{}\"", user_data_dir)
-    } else if cfg!(target_os = "macos") {
-        format!("/Applications/Google\\ Chrome.app/Contents/MacOS/Google\\ Chrome --remote-debugging-port=9222 --user-data-dir=\"{}\"", user_data_dir)
-    } else {
-        format!("google-chrome --remote-debugging-port=9222 --user-data-dir=\"{}\"", user_data_dir)
-    };
+    let settings = database::get_settings().await.unwrap_or_default();
+    let user_data_dir = browser::get_chrome_user_data_dir(&settings).unwrap_or_default();
+    let command = build_browser_launch_command(&settings, &user_data_dir);
+    if let Some(warning) = browser::live_profile_warning(std::path::Path::new(&user_data_dir)) {
+        results.push(format!("⚠ {}", warning));
+    }
     
     results.push("".to_string());
     results.push(command);
     results.push("".to_string());
+    results.push("📋 可点击\"复制启动命令\"将上述命令复制到剪贴板，避免手动复制出错".to_string());
     results.push("💡 提示: 运行上述命令后，系统将自动检测连接状态".to_string());
     
     let final_report = results.join("\n");
@@ -906,57 +1575,122 @@ pub async fn force_restart_chrome() -> Result<String, CommandError> {
 }
 
 // File management commands for automation
+// copy_id由前端生成，用于匹配file-copy-progress事件和cancel_file_copy；未提供时不可取消
 #[tauri::command]
 pub async fn copy_file_to_app_data(
     source_path: String,
     category: String, // "profiles" or "ip_assets"
-    subcategory: String // "id_cards", "auth_docs", "proof_docs"
+    subcategory: String, // "id_cards", "auth_docs", "proof_docs"
+    copy_id: Option<String>
+) -> Result<String, CommandError> {
+    let strip_exif = exif_stripping().await.applies_to(&subcategory);
+    let registration = file_copy::CopyRegistration::new(&copy_id.unwrap_or_else(|| Uuid::new_v4().to_string()))
+        .map_err(|e| CommandError::InvalidInput(e.to_string()))?;
+    copy_into_app_data(&source_path, &category, &subcategory, strip_exif, &registration).await
+}
+
+#[tauri::command]
+pub async fn cancel_file_copy(copy_id: String) -> Result<bool, CommandError> {
+    let cancelled = file_copy::cancel(&copy_id);
+    tracing::info!("Cancel file copy {}: {}", copy_id, if cancelled { "requested" } else { "not running" });
+    Ok(cancelled)
+}
+
+// 拖放文件没有对话框的格式过滤，这里按 select_files 的范围逐个检查后复制
+// 同一批文件共用copy_id，取消后剩余文件不再复制
+#[tauri::command]
+pub async fn ingest_dropped_files(
+    paths: Vec<String>,
+    category: String,
+    subcategory: String,
+    copy_id: Option<String>
+) -> Result<Vec<IngestedFile>, CommandError> {
+    let strip_exif = exif_stripping().await.applies_to(&subcategory);
+    let registration = file_copy::CopyRegistration::new(&copy_id.unwrap_or_else(|| Uuid::new_v4().to_string()))
+        .map_err(|e| CommandError::InvalidInput(e.to_string()))?;
+    let mut results = Vec::with_capacity(paths.len());
+    for source_path in paths {
+        let outcome = match check_ingest_extension(&source_path) {
+            Ok(()) => copy_into_app_data(&source_path, &category, &subcategory, strip_exif, &registration).await,
+            Err(e) => Err(e),
+        };
+        results.push(match outcome {
+            Ok(relative_path) => IngestedFile { source_path, relative_path: Some(relative_path), error: None },
+            Err(e) => {
+                tracing::warn!("Skipping dropped file {}: {}", source_path, e);
+                IngestedFile { source_path, relative_path: None, error: Some(e.to_string()) }
+            }
+        });
+    }
+    Ok(results)
+}
+
+fn check_ingest_extension(source_path: &str) -> Result<(), CommandError> {
+    let extension = std::path::Path::new(source_path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if extension == "pdf" || automation::UPLOAD_IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Ok(());
+    }
+    Err(CommandError::InvalidInput(format!("Unsupported file type: {}", source_path)))
+}
+
+// 读取设置失败时仍按默认范围处理，宁可多去除也不上传位置信息
+async fn exif_stripping() -> ExifStripping {
+    database::get_settings()
+        .await
+        .map(|settings| settings.strip_exif_on_upload)
+        .unwrap_or_default()
+}
+
+async fn copy_into_app_data(
+    source_path: &str,
+    category: &str,
+    subcategory: &str,
+    strip_exif: bool,
+    registration: &file_copy::CopyRegistration,
 ) -> Result<String, CommandError> {
     tracing::info!("Copying file to app data: {} -> {}/{}", source_path, category, subcategory);
     
     // Get app data directory
-    let app_handle_guard = database::APP_HANDLE.lock().unwrap();
-    let app_handle = app_handle_guard.as_ref()
-        .ok_or_else(|| CommandError::Automation("App handle not available".to_string()))?;
+    let app_handle = database::app_handle()
+        .map_err(|e| CommandError::Automation(e.to_string()))?;
     
     let app_data_dir = app_handle.path().app_data_dir()
         .map_err(|e| CommandError::Automation(format!("Failed to get app data directory: {}", e)))?;
     
     // Create target directory structure
-    let files_dir = app_data_dir.join("files").join(&category).join(&subcategory);
+    let files_dir = app_data_dir.join("files").join(category).join(subcategory);
     fs::create_dir_all(&files_dir)
         .map_err(|e| CommandError::Automation(format!("Failed to create directory: {}", e)))?;
     
     // Get source file info
-    let source_file = std::path::Path::new(&source_path);
+    let source_file = std::path::Path::new(source_path);
     if !source_file.exists() {
         return Err(CommandError::Automation(format!("Source file does not exist: {}", source_path)));
     }
     
-    let file_name = source_file.file_name()
-        .ok_or_else(|| CommandError::Automation("Invalid source file name".to_string()))?
-        .to_string_lossy();
-    
-    // Generate unique filename if needed
-    let target_file = files_dir.join(file_name.as_ref());
-    let final_target = if target_file.exists() {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let file_stem = source_file.file_stem()
-            .unwrap_or_default().to_string_lossy();
-        let extension = source_file.extension()
-            .map(|ext| format!(".{}", ext.to_string_lossy()))
-            .unwrap_or_default();
-        files_dir.join(format!("{}_{}{}", file_stem, timestamp, extension))
-    } else {
-        target_file
-    };
+    if source_file.file_name().is_none() {
+        return Err(CommandError::Automation("Invalid source file name".to_string()));
+    }
     
-    // Copy the file
-    fs::copy(&source_path, &final_target)
-        .map_err(|e| CommandError::Automation(format!("Failed to copy file: {}", e)))?;
+    // 文件名末尾追加UUID作为稳定的文件ID，保存档案/IP资产时按它合并文件列表
+    let file_stem = source_file.file_stem()
+        .unwrap_or_default().to_string_lossy();
+    let extension = source_file.extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let final_target = files_dir.join(format!("{}_{}{}", file_stem, Uuid::new_v4().simple(), extension));
+    
+    // 复制和去除EXIF都是阻塞IO，放到blocking线程避免大文件卡住异步运行时
+    let source = source_file.to_path_buf();
+    let target = final_target.clone();
+    let copy_id = registration.copy_id().to_string();
+    let cancelled = registration.flag();
+    tokio::task::spawn_blocking(move || copy_and_sanitize(&source, &target, &copy_id, &cancelled, strip_exif))
+        .await
+        .map_err(|e| CommandError::Automation(format!("File copy task failed: {}", e)))??;
     
     let relative_path = format!("files/{}/{}/{}", 
         category, 
@@ -968,11 +1702,39 @@ pub async fn copy_file_to_app_data(
     Ok(relative_path)
 }
 
+fn copy_and_sanitize(
+    source: &std::path::Path,
+    target: &std::path::Path,
+    copy_id: &str,
+    cancelled: &std::sync::atomic::AtomicBool,
+    strip_exif: bool,
+) -> Result<(), CommandError> {
+    file_copy::copy_with_progress(source, target, copy_id, cancelled).map_err(|e| {
+        if e.is::<file_copy::CopyCancelled>() {
+            CommandError::InvalidInput(e.to_string())
+        } else {
+            CommandError::Automation(format!("Failed to copy file: {:#}", e))
+        }
+    })?;
+
+    // 只处理复制后的副本，用户的原始文件保持不变
+    if strip_exif {
+        match image_sanitize::strip_exif(target) {
+            Ok(stripped) if stripped.is_empty() => {}
+            Ok(stripped) => tracing::info!("🧹 已去除{}的EXIF元数据: {}", target.display(), stripped.join(", ")),
+            Err(e) => {
+                let _ = fs::remove_file(target);
+                return Err(CommandError::Automation(format!("Failed to strip image metadata: {:#}", e)));
+            }
+        }
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_app_file_path(relative_path: String) -> Result<String, CommandError> {
-    let app_handle_guard = database::APP_HANDLE.lock().unwrap();
-    let app_handle = app_handle_guard.as_ref()
-        .ok_or_else(|| CommandError::Automation("App handle not available".to_string()))?;
+    let app_handle = database::app_handle()
+        .map_err(|e| CommandError::Automation(e.to_string()))?;
     
     let app_data_dir = app_handle.path().app_data_dir()
         .map_err(|e| CommandError::Automation(format!("Failed to get app data directory: {}", e)))?;
@@ -984,4 +1746,195 @@ pub async fn get_app_file_path(relative_path: String) -> Result<String, CommandE
     }
     
     Ok(full_path.to_string_lossy().to_string())
-}
\ No newline at end of file
+}
+
+// 日志相关命令
+#[tauri::command]
+pub async fn get_recent_logs(lines: usize) -> Result<String, CommandError> {
+    crate::logging::read_recent_logs(lines)
+        .map_err(|e| CommandError::Automation(format!("Failed to read logs: {}", e)))
+}
+
+#[tauri::command]
+pub async fn open_log_folder(app: tauri::AppHandle) -> Result<(), CommandError> {
+    use tauri_plugin_opener::OpenerExt;
+    
+    let log_dir = crate::logging::get_log_dir()
+        .map_err(|e| CommandError::Automation(format!("Failed to resolve log directory: {}", e)))?;
+    
+    app.opener()
+        .open_path(log_dir.to_string_lossy().to_string(), None::<String>)
+        .map_err(|e| CommandError::Automation(format!("Failed to open log folder: {}", e)))?;
+    Ok(())
+}
+
+// 在系统文件管理器中打开应用数据目录，便于排查上传文件问题
+#[tauri::command]
+pub async fn open_app_data_folder(app: tauri::AppHandle) -> Result<(), CommandError> {
+    let app_data_dir = resolve_app_data_dir()?;
+    open_folder(&app, &app_data_dir)
+}
+
+#[tauri::command]
+pub async fn open_files_folder(app: tauri::AppHandle, subpath: Option<String>) -> Result<(), CommandError> {
+    let mut folder = resolve_app_data_dir()?.join("files");
+    if let Some(subpath) = subpath.filter(|s| !s.trim().is_empty()) {
+        // 只允许打开 files 目录内部，拒绝绝对路径和 ".."
+        let relative = std::path::Path::new(&subpath);
+        let escapes = relative.components().any(|c| !matches!(c, std::path::Component::Normal(_)));
+        if escapes {
+            return Err(CommandError::InvalidInput(format!("Invalid files subpath: {}", subpath)));
+        }
+        folder = folder.join(relative);
+    }
+    open_folder(&app, &folder)
+}
+
+fn resolve_app_data_dir() -> Result<std::path::PathBuf, CommandError> {
+    let app_handle = database::app_handle()
+        .map_err(|e| CommandError::Automation(e.to_string()))?;
+    app_handle.path().app_data_dir()
+        .map_err(|e| CommandError::Automation(format!("Failed to get app data directory: {}", e)))
+}
+
+fn open_folder(app: &tauri::AppHandle, folder: &std::path::Path) -> Result<(), CommandError> {
+    use tauri_plugin_opener::OpenerExt;
+
+    fs::create_dir_all(folder)
+        .map_err(|e| CommandError::Automation(format!("Failed to create directory {:?}: {}", folder, e)))?;
+    app.opener()
+        .open_path(folder.to_string_lossy().to_string(), None::<String>)
+        .map_err(|e| CommandError::Automation(format!("Failed to open folder {:?}: {}", folder, e)))?;
+    Ok(())
+}
+
+// 应用设置相关命令
+#[tauri::command]
+pub async fn get_settings() -> Result<AppSettings, CommandError> {
+    Ok(database::get_settings().await?)
+}
+
+#[tauri::command]
+pub async fn save_settings(settings: AppSettings) -> Result<AppSettings, CommandError> {
+    if settings.debug_port == 0 {
+        return Err(CommandError::Automation("调试端口必须在1-65535之间".to_string()));
+    }
+    if settings.playwright_timeout_secs == 0 {
+        return Err(CommandError::InvalidInput("Playwright超时时间必须大于0秒".to_string()));
+    }
+    if !(1..=MAX_OVERALL_DEADLINE_SECS).contains(&settings.overall_deadline_secs) {
+        return Err(CommandError::InvalidInput(format!("运行总时限必须在1-{}秒之间", MAX_OVERALL_DEADLINE_SECS)));
+    }
+    if settings.daily_appeal_limit == 0 {
+        return Err(CommandError::InvalidInput("每日申诉次数上限必须大于0".to_string()));
+    }
+    if !settings.automation_backend.is_available() {
+        return Err(CommandError::InvalidInput("当前版本未包含原生Playwright后端，请使用npx方式运行".to_string()));
+    }
+    let appeal_url = automation::validate_appeal_url(settings.appeal_url.trim())
+        .map_err(|e| CommandError::Automation(format!("{:#}", e)))?;
+    let proxy = match settings.proxy.as_deref().map(str::trim) {
+        Some(proxy) if !proxy.is_empty() => Some(
+            automation::validate_proxy_url(proxy)
+                .map_err(|e| CommandError::Automation(format!("{:#}", e)))?
+        ),
+        _ => None,
+    };
+    let user_agent = match settings.user_agent.as_deref().map(str::trim) {
+        Some(user_agent) if !user_agent.is_empty() => Some(
+            automation::validate_user_agent(user_agent)
+                .map_err(|e| CommandError::InvalidInput(format!("{:#}", e)))?
+        ),
+        _ => None,
+    };
+    let test_url = match settings.test_url.as_deref().map(str::trim) {
+        Some(test_url) if !test_url.is_empty() => Some(
+            automation::validate_test_url(test_url)
+                .map_err(|e| CommandError::InvalidInput(format!("{:#}", e)))?
+        ),
+        _ => None,
+    };
+    let chrome_user_data_dir = match settings.chrome_user_data_dir.as_deref().map(str::trim) {
+        Some(dir) if !dir.is_empty() => {
+            let path = std::path::Path::new(dir);
+            fs::create_dir_all(path)
+                .map_err(|e| CommandError::InvalidInput(format!("Chrome用户数据目录无法创建: {} ({})", dir, e)))?;
+            if let Some(warning) = browser::live_profile_warning(path) {
+                tracing::warn!("⚠️ {}", warning);
+            }
+            Some(dir.to_string())
+        }
+        _ => None,
+    };
+    
+    // 覆盖目录必须可写，否则要到运行自动化时才会失败
+    let script_output_dir = match settings.script_output_dir.as_deref().map(str::trim) {
+        Some(dir) if !dir.is_empty() => {
+            let probe = std::path::Path::new(dir).join(".rightsguard-write-test");
+            fs::create_dir_all(dir)
+                .and_then(|_| fs::write(&probe, b""))
+                .map_err(|e| CommandError::InvalidInput(format!("脚本输出目录不可写: {} ({})", dir, e)))?;
+            let _ = fs::remove_file(&probe);
+            Some(dir.to_string())
+        }
+        _ => None,
+    };
+
+    let saved = database::save_settings(&AppSettings { appeal_url, proxy, user_agent, test_url, chrome_user_data_dir, script_output_dir, ..settings }).await?;
+    crate::i18n::set_locale(saved.locale);
+    tracing::info!("App settings saved: {:?}", saved);
+    tracing::info!("Effective proxy: {}", saved.proxy.as_deref().unwrap_or("none"));
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn get_last_used_ip_asset() -> Result<Option<IpAsset>, CommandError> {
+    Ok(database::get_last_used_ip_asset().await?)
+}
+
+#[tauri::command]
+pub async fn get_notifications_enabled() -> Result<bool, CommandError> {
+    Ok(database::get_settings().await?.notifications_enabled)
+}
+
+#[tauri::command]
+pub async fn set_notifications_enabled(enabled: bool) -> Result<(), CommandError> {
+    database::set_setting(database::SETTING_NOTIFICATIONS_ENABLED, &enabled.to_string()).await?;
+    tracing::info!("System notifications {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn submitted_at(date: chrono::DateTime<Utc>) -> Case {
+        Case { submission_date: Some(date), ..Case::default() }
+    }
+
+    #[test]
+    fn duplicate_window_boundaries() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 30, 12, 0, 0).unwrap();
+        let window = Duration::days(DUPLICATE_CASE_WINDOW_DAYS);
+
+        assert!(is_within_duplicate_window(&submitted_at(now - window + Duration::seconds(1)), now));
+        assert!(is_within_duplicate_window(&submitted_at(now - window), now));
+        assert!(!is_within_duplicate_window(&submitted_at(now - window - Duration::seconds(1)), now));
+    }
+
+    #[test]
+    fn duplicate_window_falls_back_to_creation_date() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 30, 12, 0, 0).unwrap();
+        let created = Case { created_at: Some(now - Duration::days(1)), ..Case::default() };
+        assert!(is_within_duplicate_window(&created, now));
+
+        // 提交时间优先于创建时间
+        let resubmitted_long_ago = Case {
+            submission_date: Some(now - Duration::days(DUPLICATE_CASE_WINDOW_DAYS + 1)),
+            ..created
+        };
+        assert!(!is_within_duplicate_window(&resubmitted_long_ago, now));
+        assert!(!is_within_duplicate_window(&Case::default(), now));
+    }
+}
@@ -1,60 +1,145 @@
-use sqlx::{SqlitePool, sqlite::SqliteConnectOptions};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool, sqlite::SqliteConnectOptions};
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
 use anyhow::{Result, Context};
-use crate::models::{Profile, IpAsset, Case};
-use std::path::PathBuf;
+use crate::models::{Profile, FileColumnRepair, ProfileFieldDiff, ProfileSaveDiagnosis, IpAsset, AutomationRequest, AutomationRun, RunResult, ScheduledRun, Case, CaseEvent, CasePage, CaseQuery, CaseStats, DatabaseMaintenanceReport, IdCardFileRole, IdCardRole, MostAppealedAsset, AutomationStatus, AppSettings, RepairReport};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use once_cell::sync::Lazy;
 use tauri::Manager;
 use std::str::FromStr;
+use std::future::Future;
+use std::pin::Pin;
 
 // Global database URL storage with thread safety
 static DATABASE_URL: Lazy<Arc<Mutex<Option<String>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
 
-// Store app handle for path resolution
-pub static APP_HANDLE: Lazy<Arc<Mutex<Option<tauri::AppHandle>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+// Upper bound for acquiring the pool or running a single query
+const DB_OPERATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+// Number of retries for write operations that hit a transient lock
+const WRITE_RETRY_ATTEMPTS: u32 = 3;
+
+// Total time retry_on_busy may spend before giving up; no retry starts after this budget is used up
+const WRITE_RETRY_BUDGET: std::time::Duration = std::time::Duration::from_secs(15);
+
+// How long SQLite itself waits on a locked database. Kept below DB_OPERATION_TIMEOUT so a lock wait
+// ends as SQLITE_BUSY (statement not applied, safe to retry) before our timeout cuts it off
+const SQLITE_BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Schema version stored in `PRAGMA user_version`; bump whenever init_database adds a table or column.
+// Backups with an older version are migrated by init_database after restore, newer ones are refused
+//   1  profiles, ip_assets, cases, automation_status, settings
+//   2  automation_status.error_code
+//   3  case_events
+//   4  automation_status.run_id
+//   5  automation_runs
+//   6  automation_runs.result_json
+//   7  scheduled_runs
+//   8  cases.submission_screenshot, cases.submission_result_json
+//   9  profiles.id_card_roles
+//   10 automation_runs.test_mode
+//   11 automation_status.step_key
+const SCHEMA_VERSION: i64 = 11;
+
+// Tables a database file must contain to be accepted as a backup
+const REQUIRED_TABLES: &[&str] = &["profiles", "ip_assets", "cases", "automation_status"];
+
+// 应用设置的键
+pub const SETTING_DEBUG_PORT: &str = "debug_port";
+pub const SETTING_HEADLESS: &str = "headless";
+pub const SETTING_APPEAL_URL: &str = "appeal_url";
+pub const SETTING_NOTIFICATIONS_ENABLED: &str = "notifications_enabled";
+pub const SETTING_CHROME_PATH: &str = "chrome_path";
+pub const SETTING_CHROME_USER_DATA_DIR: &str = "chrome_user_data_dir";
+pub const SETTING_PROXY: &str = "proxy";
+pub const SETTING_USE_LAST_IP_ASSET: &str = "use_last_ip_asset";
+pub const SETTING_TIMING_SHORT_MS: &str = "timing_short_ms";
+pub const SETTING_TIMING_MEDIUM_MS: &str = "timing_medium_ms";
+pub const SETTING_TIMING_LONG_MS: &str = "timing_long_ms";
+pub const SETTING_TIMING_UPLOAD_SETTLE_MS: &str = "timing_upload_settle_ms";
+pub const SETTING_PLAYWRIGHT_TIMEOUT_SECS: &str = "playwright_timeout_secs";
+pub const SETTING_VERBOSE_AUTOMATION_LOGS: &str = "verbose_automation_logs";
+pub const SETTING_AUTOMATION_BACKEND: &str = "automation_backend";
+pub const SETTING_DAILY_APPEAL_LIMIT: &str = "daily_appeal_limit";
+pub const SETTING_STRIP_EXIF_ON_UPLOAD: &str = "strip_exif_on_upload";
+pub const SETTING_SCRIPT_OUTPUT_DIR: &str = "script_output_dir";
+pub const SETTING_USER_AGENT: &str = "user_agent";
+pub const SETTING_TEST_URL: &str = "test_url";
+pub const SETTING_REUSE_CURRENT_PAGE: &str = "reuse_current_page";
+pub const SETTING_OVERALL_DEADLINE_SECS: &str = "overall_deadline_secs";
+pub const SETTING_LOCALE: &str = "locale";
+// 运行时记录的状态，不属于AppSettings
+pub const SETTING_LAST_USED_IP_ASSET_ID: &str = "last_used_ip_asset_id";
+
+// Shared connection pool, created once by init_database
+static DB_POOL: Lazy<tokio::sync::RwLock<Option<SqlitePool>>> = Lazy::new(|| tokio::sync::RwLock::new(None));
+
+// Held for the whole of init_database. The first callers at startup (setup hook and the first
+// commands via get_pool) would otherwise run the migrations concurrently, and two ensure_column
+// calls racing on the same missing column fail with "duplicate column name"
+static INIT_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+// Serializes every write so at most one pooled connection writes at a time. In WAL mode readers
+// never wait on the writer, so the UI keeps reading cases while automation saves. Read-then-write
+// sequences (file list merges, reference checks) must run inside the locked section, either as one
+// retry_on_busy attempt or under acquire_writer, so no other write lands between the read and the write
+static WRITE_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+// Store app handle for path resolution; only accessed through set_app_handle/app_handle
+static APP_HANDLE: Lazy<Arc<Mutex<Option<tauri::AppHandle>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+// 所有基于文件的连接都失败、退回内存数据库时为true，此时写入的数据在退出后会丢失
+static DEGRADED_MODE: AtomicBool = AtomicBool::new(false);
 
 /// Initialize the database system with the app handle
 /// This must be called once during app setup before any database operations
 pub fn set_app_handle(handle: tauri::AppHandle) {
-    let mut app_handle = APP_HANDLE.lock().unwrap();
+    let mut app_handle = APP_HANDLE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
     *app_handle = Some(handle);
     tracing::info!("App handle set for database path resolution");
 }
 
+/// Clone the app handle in a minimal critical section
+/// The guard is dropped before returning, so callers never hold it across `.await` or I/O,
+/// and a poisoned lock is recovered instead of panicking
+pub fn app_handle() -> Result<tauri::AppHandle> {
+    let guard = APP_HANDLE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    guard.clone().ok_or_else(|| anyhow::anyhow!("App handle not available"))
+}
+
 
 /// Get the proper database path using Tauri's app data directory
 /// This works consistently in both development and production builds
 pub fn get_database_path() -> Result<PathBuf> {
     // First try to use Tauri's app data directory (preferred)
-    if let Ok(app_handle_guard) = APP_HANDLE.lock() {
-        if let Some(handle) = app_handle_guard.as_ref() {
-            tracing::info!("Using Tauri app data directory for database path");
-            
-            let app_data_dir = handle.path().app_data_dir()
-                .context("Failed to get app data directory")?;
-            
-            // Create the app data directory if it doesn't exist
-            if !app_data_dir.exists() {
-                fs::create_dir_all(&app_data_dir)
-                    .with_context(|| format!("Failed to create app data directory: {:?}", app_data_dir))?;
-                tracing::info!("Created app data directory: {:?}", app_data_dir);
-            }
-            
-            // Create data subdirectory for organized storage
-            let data_dir = app_data_dir.join("data");
-            if !data_dir.exists() {
-                fs::create_dir_all(&data_dir)
-                    .with_context(|| format!("Failed to create data directory: {:?}", data_dir))?;
-                tracing::info!("Created data directory: {:?}", data_dir);
-            }
-            
-            let db_path = data_dir.join("rights_guard.db");
-            tracing::info!("Database file path (app data): {:?}", db_path);
-            return Ok(db_path);
+    if let Ok(handle) = app_handle() {
+        tracing::info!("Using Tauri app data directory for database path");
+        
+        let app_data_dir = handle.path().app_data_dir()
+            .context("Failed to get app data directory")?;
+        
+        // Create the app data directory if it doesn't exist
+        if !app_data_dir.exists() {
+            fs::create_dir_all(&app_data_dir)
+                .with_context(|| format!("Failed to create app data directory: {:?}", app_data_dir))?;
+            tracing::info!("Created app data directory: {:?}", app_data_dir);
+        }
+        
+        // Create data subdirectory for organized storage
+        let data_dir = app_data_dir.join("data");
+        if !data_dir.exists() {
+            fs::create_dir_all(&data_dir)
+                .with_context(|| format!("Failed to create data directory: {:?}", data_dir))?;
+            tracing::info!("Created data directory: {:?}", data_dir);
         }
+        
+        let db_path = data_dir.join("rights_guard.db");
+        tracing::info!("Database file path (app data): {:?}", db_path);
+        return Ok(db_path);
     }
     
     // Fallback to current directory method if app handle not available
@@ -145,7 +230,7 @@ pub fn create_sqlite_options(db_path: &PathBuf) -> Result<SqliteConnectOptions>
         .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)  // Use WAL mode for better concurrency
         .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)  // Balanced safety/performance
         .foreign_keys(true)  // Enable foreign key constraints
-        .busy_timeout(std::time::Duration::from_secs(30));  // 30 second timeout for locked database
+        .busy_timeout(SQLITE_BUSY_TIMEOUT);
     
     tracing::info!("SQLite connection options created successfully");
     Ok(options)
@@ -207,15 +292,13 @@ async fn try_fallback_connection(db_path: &PathBuf) -> Result<SqlitePool> {
         }
     }
     
-    // Strategy 4: Try with in-memory fallback for testing
-    tracing::warn!("All file-based connections failed, trying in-memory database for testing");
-    let memory_options = SqliteConnectOptions::from_str("sqlite::memory:")
-        .with_context(|| "Failed to create in-memory SQLite options")?
-        .create_if_missing(true);
-    
-    match SqlitePool::connect_with(memory_options).await {
+    // Strategy 4: in-memory fallback so the app can still start and show the problem
+    // Enters degraded mode: reads work, but save_* operations are refused
+    tracing::warn!("All file-based connections failed, trying in-memory database");
+    match connect_memory_pool().await {
         Ok(pool) => {
-            tracing::warn!("Using in-memory database - data will not persist!");
+            tracing::error!("Using in-memory database - data will not persist! Saving is disabled (degraded mode)");
+            DEGRADED_MODE.store(true, Ordering::SeqCst);
             return Ok(pool);
         }
         Err(e) => {
@@ -229,10 +312,30 @@ async fn try_fallback_connection(db_path: &PathBuf) -> Result<SqlitePool> {
     ))
 }
 
-/// Initialize the database with proper error handling and logging
-/// This function creates all necessary tables and sets up the database schema
-pub async fn init_database() -> Result<()> {
-    tracing::info!("Starting database initialization...");
+/// Open an in-memory database pinned to a single connection
+async fn connect_memory_pool() -> Result<SqlitePool> {
+    let memory_options = SqliteConnectOptions::from_str("sqlite::memory:")
+        .with_context(|| "Failed to create in-memory SQLite options")?
+        .create_if_missing(true);
+    
+    // Every connection to sqlite::memory: opens its own empty database, so a multi-connection pool
+    // lets a write land on one connection and the follow-up read miss it on another
+    // (the "saved but could not be retrieved" error in save_profile). Pin the pool to one
+    // connection that is never recycled.
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(memory_options)
+        .await?;
+    Ok(pool)
+}
+
+/// Open the SQLite pool, falling back to simpler connection strategies on failure
+/// Only called once; every other caller shares the pool through `get_pool`
+async fn connect_pool() -> Result<SqlitePool> {
+    DEGRADED_MODE.store(false, Ordering::SeqCst);
     
     // Step 1: Get database path and ensure file exists
     let db_path = get_database_path_with_creation()
@@ -294,6 +397,29 @@ pub async fn init_database() -> Result<()> {
         }
     };
     
+    Ok(pool)
+}
+
+/// Initialize the database with proper error handling and logging
+/// This function creates all necessary tables and sets up the database schema
+pub async fn init_database() -> Result<()> {
+    let _init_guard = INIT_LOCK.lock().await;
+    tracing::info!("Starting database initialization...");
+    
+    // Reuse the shared pool if it already exists; the write lock prevents two concurrent
+    // initializations from opening separate pools
+    let pool = {
+        let mut pool_guard = DB_POOL.write().await;
+        match pool_guard.as_ref() {
+            Some(pool) => pool.clone(),
+            None => {
+                let pool = connect_pool().await?;
+                *pool_guard = Some(pool.clone());
+                pool
+            }
+        }
+    };
+    
     tracing::info!("Database connection established successfully");
     
     // 创建个人档案表
@@ -359,6 +485,21 @@ pub async fn init_database() -> Result<()> {
     .execute(&pool)
     .await?;
 
+    // 创建案件事件表，记录每次提交/重新提交
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS case_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            case_id TEXT NOT NULL,
+            event TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (case_id) REFERENCES cases (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
     // 创建自动化状态表
     sqlx::query(
         r#"
@@ -376,6 +517,89 @@ pub async fn init_database() -> Result<()> {
     .execute(&pool)
     .await?;
 
+    // 创建自动化运行记录表，每次运行一行
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS automation_runs (
+            run_id TEXT PRIMARY KEY,
+            infringing_url TEXT NOT NULL,
+            original_url TEXT,
+            ip_asset_id TEXT,
+            outcome TEXT NOT NULL,
+            error TEXT,
+            error_code TEXT,
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            duration_secs INTEGER,
+            result_json TEXT,
+            test_mode INTEGER NOT NULL DEFAULT 0
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // 创建计划运行表，request_json为完整的AutomationRequest
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scheduled_runs (
+            id TEXT PRIMARY KEY,
+            request_json TEXT NOT NULL,
+            run_at TEXT NOT NULL,
+            status TEXT NOT NULL,
+            error TEXT,
+            created_at TEXT NOT NULL,
+            started_at TEXT
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // 创建应用设置表(键值对)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // 写入默认设置，已存在的键保持用户的值
+    let seed_time = Utc::now().to_rfc3339();
+    for (key, value) in settings_entries(&AppSettings::default()) {
+        sqlx::query("INSERT OR IGNORE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)")
+            .bind(key)
+            .bind(value)
+            .bind(&seed_time)
+            .execute(&pool)
+            .await?;
+    }
+
+    // 归档(软删除)字段迁移
+    ensure_column(&pool, "cases", "archived", "INTEGER NOT NULL DEFAULT 0").await?;
+    ensure_column(&pool, "ip_assets", "archived", "INTEGER NOT NULL DEFAULT 0").await?;
+
+    // 身份证正反面标记字段迁移
+    ensure_column(&pool, "profiles", "id_card_roles", "TEXT").await?;
+
+    // 提交凭证字段迁移
+    ensure_column(&pool, "cases", "submission_screenshot", "TEXT").await?;
+    ensure_column(&pool, "cases", "submission_result_json", "TEXT").await?;
+
+    // 自动化运行耗时字段迁移
+    ensure_column(&pool, "automation_status", "finished_at", "TEXT").await?;
+    ensure_column(&pool, "automation_status", "duration_secs", "INTEGER").await?;
+    ensure_column(&pool, "automation_status", "error_code", "TEXT").await?;
+    ensure_column(&pool, "automation_status", "run_id", "TEXT").await?;
+    ensure_column(&pool, "automation_status", "step_key", "TEXT").await?;
+    ensure_column(&pool, "automation_runs", "result_json", "TEXT").await?;
+    ensure_column(&pool, "automation_runs", "test_mode", "INTEGER NOT NULL DEFAULT 0").await?;
+
     // 初始化默认状态
     sqlx::query(
         r#"
@@ -387,39 +611,179 @@ pub async fn init_database() -> Result<()> {
     .execute(&pool)
     .await?;
 
+    sqlx::query(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))
+        .execute(&pool)
+        .await?;
+
     tracing::info!("Database initialization completed successfully");
     Ok(())
 }
 
-pub async fn get_pool() -> Result<SqlitePool> {
-    tracing::debug!("Creating new database pool");
-    
-    // Use the same robust connection method as init_database
-    let db_path = get_database_path_with_creation()
-        .context("Failed to prepare database file for pool creation")?;
-    
-    // Try primary connection method first
-    match create_sqlite_options(&db_path) {
-        Ok(options) => {
-            match SqlitePool::connect_with(options).await {
-                Ok(pool) => {
-                    tracing::debug!("Database pool created successfully");
-                    return Ok(pool);
-                }
-                Err(e) => {
-                    tracing::warn!("Primary pool connection failed, trying fallback: {}", e);
+/// Returned when a database operation times out or hits a SQLite busy/locked error
+#[derive(Debug, thiserror::Error)]
+#[error("数据库忙，请重试")]
+pub struct DatabaseBusyError;
+
+/// Returned when a query exceeds `DB_OPERATION_TIMEOUT`
+/// The sqlx worker keeps running the statement after the future is dropped, so the write may still
+/// be applied; unlike `DatabaseBusyError` this is never retried
+#[derive(Debug, thiserror::Error)]
+#[error("数据库操作超时，结果未知，请刷新后确认是否已保存")]
+pub struct DatabaseTimeoutError;
+
+/// Returned by save operations while the database is an in-memory fallback
+#[derive(Debug, thiserror::Error)]
+#[error("数据库文件无法打开，当前使用临时内存数据库，为避免数据在退出后丢失已禁止保存")]
+pub struct DegradedModeError;
+
+/// Whether the database fell back to an in-memory connection
+pub fn is_degraded_mode() -> bool {
+    DEGRADED_MODE.load(Ordering::SeqCst)
+}
+
+// 降级模式下拒绝写入用户数据，避免身份证等信息在用户不知情的情况下丢失
+fn ensure_persistent() -> Result<()> {
+    if is_degraded_mode() {
+        return Err(DegradedModeError.into());
+    }
+    Ok(())
+}
+
+/// Whether a sqlx error is SQLite reporting SQLITE_BUSY (5) or SQLITE_LOCKED (6)
+pub fn is_busy_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => {
+            let primary_code = db_err.code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .map(|code| code & 0xff);
+            matches!(primary_code, Some(5) | Some(6))
+                || db_err.message().contains("database is locked")
+        }
+        sqlx::Error::PoolTimedOut => true,
+        _ => false,
+    }
+}
+
+/// Bound a single query by `DB_OPERATION_TIMEOUT`, mapping lock errors to `DatabaseBusyError`
+/// and the timeout itself to `DatabaseTimeoutError`
+async fn run_timed<T>(query: impl Future<Output = std::result::Result<T, sqlx::Error>>) -> Result<T> {
+    match tokio::time::timeout(DB_OPERATION_TIMEOUT, query).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) if is_busy_error(&e) => {
+            tracing::warn!("Database busy: {}", e);
+            Err(anyhow::Error::new(DatabaseBusyError).context(e.to_string()))
+        }
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => {
+            tracing::warn!("Database operation timed out after {:?}", DB_OPERATION_TIMEOUT);
+            Err(DatabaseTimeoutError.into())
+        }
+    }
+}
+
+/// Extension so queries read `.fetch_all(&pool).timed().await?`
+trait TimedQuery<'a, T: Send + 'a>: Future<Output = std::result::Result<T, sqlx::Error>> + Send + Sized + 'a {
+    fn timed(self) -> Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>> {
+        Box::pin(run_timed(self))
+    }
+}
+
+impl<'a, T, F> TimedQuery<'a, T> for F
+where
+    T: Send + 'a,
+    F: Future<Output = std::result::Result<T, sqlx::Error>> + Send + 'a,
+{}
+
+/// Retry a write operation with exponential backoff while the database is busy
+/// Reads are intentionally not retried so real problems are not masked
+/// Each attempt holds the write lock, so writes never contend with each other
+/// Only `DatabaseBusyError` (nothing was applied) is retried, and no retry starts once
+/// `WRITE_RETRY_BUDGET` is spent, so the total wait is at most the budget plus one attempt
+async fn retry_on_busy<T, F, Fut>(mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let started = std::time::Instant::now();
+    let mut attempt = 0;
+    loop {
+        let result = match acquire_writer().await {
+            Ok(_writer) => operation().await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Err(e) if attempt < WRITE_RETRY_ATTEMPTS && e.is::<DatabaseBusyError>() => {
+                attempt += 1;
+                let delay = std::time::Duration::from_millis(200 * 2u64.pow(attempt));
+                if started.elapsed() + delay > WRITE_RETRY_BUDGET {
+                    tracing::warn!("Database still busy after {:?}, giving up", started.elapsed());
+                    return Err(e);
                 }
+                tracing::warn!("Database busy, retrying write in {:?} (attempt {}/{})", delay, attempt, WRITE_RETRY_ATTEMPTS);
+                tokio::time::sleep(delay).await;
             }
+            result => return result,
         }
-        Err(e) => {
-            tracing::warn!("Failed to create SQLite options, trying fallback: {}", e);
+    }
+}
+
+/// Wait for the write lock, bounded like a single query so a stuck writer surfaces as busy
+async fn acquire_writer() -> Result<tokio::sync::MutexGuard<'static, ()>> {
+    tokio::time::timeout(DB_OPERATION_TIMEOUT, WRITE_LOCK.lock())
+        .await
+        .map_err(|_| {
+            tracing::warn!("Timed out waiting for the database writer after {:?}", DB_OPERATION_TIMEOUT);
+            DatabaseBusyError.into()
+        })
+}
+
+/// Add a column to an existing table if it is missing
+/// SQLite has no `ADD COLUMN IF NOT EXISTS`, so check `pragma_table_info` first
+async fn ensure_column(pool: &SqlitePool, table: &str, column: &str, definition: &str) -> Result<()> {
+    let columns: Vec<String> = sqlx::query_scalar(
+        &format!("SELECT name FROM pragma_table_info('{}')", table)
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if !columns.iter().any(|name| name == column) {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(pool)
+            .await
+            .with_context(|| format!("Failed to add column {}.{}", table, column))?;
+        tracing::info!("Migrated table {}: added column {}", table, column);
+    }
+    Ok(())
+}
+
+/// Get a handle to the shared database pool
+/// `SqlitePool` is reference counted, so the clone shares the same connections
+pub async fn get_pool() -> Result<SqlitePool> {
+    match tokio::time::timeout(DB_OPERATION_TIMEOUT, DB_POOL.read()).await {
+        Ok(pool_guard) => {
+            if let Some(pool) = pool_guard.as_ref() {
+                return Ok(pool.clone());
+            }
+        }
+        Err(_) => {
+            tracing::warn!("Timed out waiting for the database pool");
+            return Err(DatabaseBusyError.into());
         }
     }
     
-    // Use fallback connection method
-    try_fallback_connection(&db_path)
-        .await
-        .context("Both primary and fallback pool connections failed")
+    tracing::warn!("Database pool not initialized yet, initializing now");
+    init_database().await?;
+    DB_POOL.read().await
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("Database pool not available after initialization"))
+}
+
+/// Close the shared pool so WAL contents are checkpointed before the app exits
+pub async fn close_pool() {
+    if let Some(pool) = DB_POOL.write().await.take() {
+        pool.close().await;
+        tracing::info!("Database pool closed");
+    }
 }
 
 // 个人档案相关操作
@@ -429,11 +793,322 @@ pub async fn get_profile() -> Result<Option<Profile>> {
         "SELECT * FROM profiles ORDER BY created_at DESC LIMIT 1"
     )
     .fetch_optional(&pool)
+    .timed()
     .await?;
     Ok(profile)
 }
 
+pub async fn get_profile_by_id(id: Uuid) -> Result<Option<Profile>> {
+    let pool = get_pool().await?;
+    let profile = sqlx::query_as::<_, Profile>("SELECT * FROM profiles WHERE id = ?1")
+        .bind(id.to_string())
+        .fetch_optional(&pool)
+        .timed()
+        .await?;
+    Ok(profile)
+}
+
+/// 文件的稳定ID: 复制到应用目录时文件名末尾追加了UUID，旧文件没有UUID时以路径本身作为ID
+pub(crate) fn stored_file_id(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_string_lossy().rsplit_once('_').map(|(_, suffix)| suffix.to_string()))
+        .and_then(|suffix| Uuid::parse_str(&suffix).ok())
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+// 按文件ID合并文件列表，避免重复保存时文件引用被打乱或重复:
+// 已保存的文件保持原有顺序，新文件按提交顺序追加，同一文件只保留一次，未提交的文件视为已删除
+fn merge_file_list(existing: &Option<String>, incoming: &Option<String>) -> Result<Option<String>> {
+    if incoming.as_deref().map_or(true, |json| json.trim().is_empty()) {
+        return Ok(incoming.clone());
+    }
+    let incoming_paths = crate::automation::parse_stored_file_paths(incoming)?;
+    let existing_paths = crate::automation::parse_stored_file_paths(existing).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring unparsable stored file list while merging: {:#}", e);
+        Vec::new()
+    });
+
+    let mut incoming_ids = Vec::new();
+    let mut incoming_by_id = HashMap::new();
+    for path in incoming_paths.into_iter().filter(|path| !path.is_empty()) {
+        let id = stored_file_id(&path);
+        if !incoming_by_id.contains_key(&id) {
+            incoming_ids.push(id.clone());
+            incoming_by_id.insert(id, path);
+        }
+    }
+
+    let mut merged: Vec<String> = existing_paths
+        .iter()
+        .filter_map(|path| incoming_by_id.remove(&stored_file_id(path)))
+        .collect();
+    merged.extend(incoming_ids.iter().filter_map(|id| incoming_by_id.remove(id)));
+    Ok(Some(serde_json::to_string(&merged)?))
+}
+
+/// Replace `old_path` with `new_path` in every profile's id_card_files, keeping its front/back role
+/// Returns the number of profiles updated
+pub async fn relink_profile_file(old_path: &str, new_path: &str) -> Result<u64> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let rows: Vec<(String, Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT id, id_card_files, id_card_roles FROM profiles")
+            .fetch_all(&pool)
+            .timed()
+            .await?;
+
+    let mut updates = Vec::new();
+    for (id, files, roles) in rows {
+        let paths = crate::automation::parse_stored_file_paths(&files)
+            .with_context(|| format!("Failed to parse id_card_files of profile {}", id))?;
+        if !paths.iter().any(|path| path == old_path) {
+            continue;
+        }
+        let relinked: Vec<&str> = paths.iter()
+            .map(|path| if path == old_path { new_path } else { path.as_str() })
+            .collect();
+        let mut role_map: HashMap<String, IdCardRole> = roles
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        if let Some(role) = role_map.remove(old_path) {
+            role_map.insert(new_path.to_string(), role);
+        }
+        let roles = (!role_map.is_empty()).then(|| serde_json::to_string(&role_map)).transpose()?;
+        updates.push((id, serde_json::to_string(&relinked)?, roles));
+    }
+
+    let updated = updates.len() as u64;
+    let now = Utc::now().to_rfc3339();
+    retry_on_busy(|| {
+        let pool = pool.clone();
+        let updates = updates.clone();
+        let now = now.clone();
+        async move {
+            let mut tx = pool.begin().timed().await?;
+            for (id, files, roles) in &updates {
+                sqlx::query("UPDATE profiles SET id_card_files = ?1, id_card_roles = ?2, updated_at = ?3 WHERE id = ?4")
+                    .bind(files)
+                    .bind(roles)
+                    .bind(&now)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .timed()
+                    .await?;
+            }
+            tx.commit().timed().await
+        }
+    })
+    .await?;
+
+    tracing::info!("Relinked {} -> {} in {} profile(s)", old_path, new_path, updated);
+    Ok(updated)
+}
+
+/// Rewrite every `*_files` column as a canonical JSON array (trimmed, deduplicated, `NULL` when empty)
+/// and drop paths that no longer resolve to a local file; dropped id card files also lose their role
+/// With `dry_run` nothing is written. Each repair keeps the previous value so it can be restored by hand
+pub async fn repair_file_columns(dry_run: bool) -> Result<RepairReport> {
+    if !dry_run {
+        ensure_persistent()?;
+    }
+    let pool = get_pool().await?;
+    let profiles: Vec<(String, Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT id, id_card_files, id_card_roles FROM profiles")
+            .fetch_all(&pool)
+            .timed()
+            .await?;
+    // Archived assets are included so restoring one does not bring back broken references
+    let assets: Vec<(String, Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT id, auth_files, work_proof_files FROM ip_assets")
+            .fetch_all(&pool)
+            .timed()
+            .await?;
+
+    let mut repairs = Vec::new();
+    let mut errors = Vec::new();
+    for (id, files, roles) in &profiles {
+        let Some(repair) = repair_file_column("profiles", id, "id_card_files", files, &mut errors)? else {
+            continue;
+        };
+        let roles_repair = repair_id_card_roles(id, roles, &repair.dropped_paths, &mut errors)?;
+        repairs.push(repair);
+        repairs.extend(roles_repair);
+    }
+    for (id, auth_files, work_proof_files) in &assets {
+        repairs.extend(repair_file_column("ip_assets", id, "auth_files", auth_files, &mut errors)?);
+        repairs.extend(repair_file_column("ip_assets", id, "work_proof_files", work_proof_files, &mut errors)?);
+    }
+
+    if !dry_run && !repairs.is_empty() {
+        let now = Utc::now().to_rfc3339();
+        // Repairs were computed from values read without the write lock, so each UPDATE only applies
+        // while the column still holds that value; a row saved in the meantime is skipped, not overwritten
+        let stale = retry_on_busy(|| {
+            let pool = pool.clone();
+            let repairs = repairs.clone();
+            let now = now.clone();
+            async move {
+                let mut tx = pool.begin().timed().await?;
+                let mut stale = Vec::new();
+                for (index, repair) in repairs.iter().enumerate() {
+                    if !apply_file_column_repair(&mut tx, repair, &now).await? {
+                        stale.push(index);
+                    }
+                }
+                tx.commit().timed().await?;
+                Ok::<_, anyhow::Error>(stale)
+            }
+        })
+        .await?;
+        for index in stale.into_iter().rev() {
+            let repair = repairs.remove(index);
+            tracing::warn!("{}.{} of {} changed during repair, skipped", repair.table, repair.column, repair.row_id);
+            errors.push(format!("{}.{} ({}): changed during repair, skipped", repair.table, repair.column, repair.row_id));
+        }
+    }
+
+    tracing::info!(
+        "File column repair{}: scanned {} row(s), {} column(s) {}, {} unparseable",
+        if dry_run { " (dry run)" } else { "" },
+        profiles.len() + assets.len(),
+        repairs.len(),
+        if dry_run { "to change" } else { "changed" },
+        errors.len()
+    );
+    Ok(RepairReport {
+        scanned_rows: profiles.len() + assets.len(),
+        repairs,
+        errors,
+        applied: !dry_run,
+    })
+}
+
+// Write one repair only while the column still holds the value it was computed from; false when the row changed
+async fn apply_file_column_repair(conn: &mut sqlx::SqliteConnection, repair: &FileColumnRepair, now: &str) -> Result<bool> {
+    // table and column come from the fixed names in repair_file_columns, never from input
+    let result = sqlx::query(&format!(
+        "UPDATE {table} SET {column} = ?1, updated_at = ?2 WHERE id = ?3 AND {column} IS ?4",
+        table = repair.table,
+        column = repair.column
+    ))
+    .bind(&repair.after)
+    .bind(now)
+    .bind(&repair.row_id)
+    .bind(&repair.before)
+    .execute(conn)
+    .timed()
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// Canonical form of one file list column, or None when it is already canonical or cannot be parsed (recorded in errors)
+fn repair_file_column(
+    table: &str,
+    row_id: &str,
+    column: &str,
+    value: &Option<String>,
+    errors: &mut Vec<String>,
+) -> Result<Option<FileColumnRepair>> {
+    let paths = match crate::automation::parse_stored_file_paths(value) {
+        Ok(paths) => paths,
+        Err(e) => {
+            tracing::warn!("Cannot parse {}.{} of {}: {:#}", table, column, row_id, e);
+            errors.push(format!("{}.{} ({}): {:#}", table, column, row_id, e));
+            return Ok(None);
+        }
+    };
+
+    let mut kept: Vec<String> = Vec::new();
+    let mut dropped_paths = Vec::new();
+    for path in paths.iter().map(|path| path.trim()).filter(|path| !path.is_empty()) {
+        if kept.iter().any(|existing| existing == path) || dropped_paths.iter().any(|missing| missing == path) {
+            continue;
+        }
+        if crate::automation::check_stored_file(path).exists {
+            kept.push(path.to_string());
+        } else {
+            dropped_paths.push(path.to_string());
+        }
+    }
+
+    let after = (!kept.is_empty()).then(|| serde_json::to_string(&kept)).transpose()?;
+    if after == *value {
+        return Ok(None);
+    }
+    Ok(Some(FileColumnRepair {
+        table: table.to_string(),
+        row_id: row_id.to_string(),
+        column: column.to_string(),
+        before: value.clone(),
+        after,
+        dropped_paths,
+    }))
+}
+
+// Remove the roles of dropped id card files; None when nothing changes
+fn repair_id_card_roles(
+    row_id: &str,
+    roles: &Option<String>,
+    dropped_paths: &[String],
+    errors: &mut Vec<String>,
+) -> Result<Option<FileColumnRepair>> {
+    let Some(json) = roles.as_deref().filter(|json| !json.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let mut role_map: HashMap<String, IdCardRole> = match serde_json::from_str(json) {
+        Ok(role_map) => role_map,
+        Err(e) => {
+            errors.push(format!("profiles.id_card_roles ({}): {}", row_id, e));
+            return Ok(None);
+        }
+    };
+    let before_len = role_map.len();
+    role_map.retain(|path, _| !dropped_paths.iter().any(|dropped| dropped == path));
+    if role_map.len() == before_len {
+        return Ok(None);
+    }
+    let after = (!role_map.is_empty()).then(|| serde_json::to_string(&role_map)).transpose()?;
+    Ok(Some(FileColumnRepair {
+        table: "profiles".to_string(),
+        row_id: row_id.to_string(),
+        column: "id_card_roles".to_string(),
+        before: roles.clone(),
+        after,
+        dropped_paths: Vec::new(),
+    }))
+}
+
+/// 按给定顺序保存身份证文件及其正反面标记，返回档案是否存在
+pub async fn set_id_card_roles(profile_id: Uuid, ordering: &[IdCardFileRole]) -> Result<bool> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let files: Vec<&str> = ordering.iter().map(|file| file.path.as_str()).collect();
+    let roles: HashMap<&str, IdCardRole> = ordering.iter().map(|file| (file.path.as_str(), file.role)).collect();
+    let files = serde_json::to_string(&files)?;
+    let roles = serde_json::to_string(&roles)?;
+    let now = Utc::now().to_rfc3339();
+
+    let result = retry_on_busy(|| {
+        sqlx::query("UPDATE profiles SET id_card_files = ?1, id_card_roles = ?2, updated_at = ?3 WHERE id = ?4")
+            .bind(&files)
+            .bind(&roles)
+            .bind(&now)
+            .bind(profile_id.to_string())
+            .execute(&pool)
+            .timed()
+    })
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+const PROFILE_REFETCH_ATTEMPTS: u32 = 3;
+const PROFILE_REFETCH_DELAY: std::time::Duration = std::time::Duration::from_millis(50);
+
 pub async fn save_profile(profile: &Profile) -> Result<Profile> {
+    ensure_persistent()?;
     tracing::info!("Starting save_profile for: {}", profile.name);
     tracing::debug!("Profile data - name: {}, email: {}, phone: {}", profile.name, profile.email, profile.phone);
     
@@ -442,73 +1117,44 @@ pub async fn save_profile(profile: &Profile) -> Result<Profile> {
         e
     })?;
     
-    let now = Utc::now();
-    let profile_id = profile.id.unwrap_or_else(Uuid::new_v4);
-    
-    tracing::info!("Using profile ID: {}", profile_id);
-    tracing::info!("Timestamp: {}", now.to_rfc3339());
-    
-    // First check if profile exists
-    let existing = sqlx::query("SELECT id FROM profiles WHERE id = ?1")
-        .bind(profile_id.to_string())
-        .fetch_optional(&pool)
-        .await?;
-        
-    let is_update = existing.is_some();
-    tracing::info!("Profile exists: {}, performing {}", is_update, if is_update { "UPDATE" } else { "INSERT" });
-    
-    let result = sqlx::query(
-        r#"
-        INSERT OR REPLACE INTO profiles (
-            id, name, phone, email, id_card_number, id_card_files, created_at, updated_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 
-            COALESCE((SELECT created_at FROM profiles WHERE id = ?1), ?7), ?7)
-        "#,
-    )
-    .bind(profile_id.to_string())
-    .bind(&profile.name)
-    .bind(&profile.phone)
-    .bind(&profile.email)
-    .bind(&profile.id_card_number)
-    .bind(&profile.id_card_files)
-    .bind(now.to_rfc3339())
-    .execute(&pool)
-    .await;
+    let (profile_id, bound) = write_profile(&pool, profile).await?;
+
+    tracing::info!("Retrieving saved profile by ID: {}", profile_id);
     
-    match result {
-        Ok(exec_result) => {
-            tracing::info!("Database operation successful. Rows affected: {}", exec_result.rows_affected());
-            
-            if exec_result.rows_affected() == 0 {
-                tracing::warn!("No rows were affected by the operation");
-            }
+    // A committed write is visible to every later read in WAL mode, so a miss here should not happen;
+    // retry briefly anyway before reporting the profile as lost
+    let mut saved_profile = None;
+    for attempt in 1..=PROFILE_REFETCH_ATTEMPTS {
+        saved_profile = sqlx::query_as::<_, Profile>("SELECT * FROM profiles WHERE id = ?1")
+            .bind(profile_id.to_string())
+            .fetch_optional(&pool)
+            .timed()
+            .await?;
+        if saved_profile.is_some() {
+            break;
         }
-        Err(e) => {
-            tracing::error!("Database INSERT/UPDATE failed: {:?}", e);
-            tracing::error!("SQL Error details: {}", e);
-            return Err(anyhow::anyhow!("Database operation failed: {}", e));
+        tracing::warn!("Saved profile {} not visible yet (attempt {}/{})", profile_id, attempt, PROFILE_REFETCH_ATTEMPTS);
+        if attempt < PROFILE_REFETCH_ATTEMPTS {
+            tokio::time::sleep(PROFILE_REFETCH_DELAY).await;
         }
     }
-
-    tracing::info!("Retrieving saved profile by ID: {}", profile_id);
-    
-    // Directly query by ID instead of getting the latest
-    let saved_profile = sqlx::query_as::<_, Profile>("SELECT * FROM profiles WHERE id = ?1")
-        .bind(profile_id.to_string())
-        .fetch_optional(&pool)
-        .await?;
         
     match saved_profile {
-        Some(profile) => {
-            tracing::info!("Profile retrieved successfully: {} (ID: {:?})", profile.name, profile.id);
-            Ok(profile)
+        Some(saved) => {
+            tracing::info!("Profile retrieved successfully: {} (ID: {:?})", saved.name, saved.id);
+            if !profile_matches_bound(&saved, &bound) {
+                log_profile_diffs(profile_id, &diff_profile_row(&pool, profile_id, &bound).await?);
+            }
+            Ok(saved)
         }
         None => {
             tracing::error!("Failed to retrieve saved profile with ID: {}", profile_id);
+            log_profile_diffs(profile_id, &diff_profile_row(&pool, profile_id, &bound).await?);
             
             // List all profiles for debugging
             let all_profiles = sqlx::query_as::<_, (String, String)>("SELECT id, name FROM profiles")
                 .fetch_all(&pool)
+                .timed()
                 .await?;
             tracing::info!("All profiles in database: {}", 
                 all_profiles.iter()
@@ -521,17 +1167,396 @@ pub async fn save_profile(profile: &Profile) -> Result<Profile> {
     }
 }
 
+// Column values bound by write_profile, with the COALESCE fallbacks resolved, in PROFILE_DIFF_COLUMNS order
+type BoundProfileRow = Vec<(&'static str, Option<String>)>;
+
+const PROFILE_DIFF_COLUMNS: [&str; 8] = [
+    "name", "phone", "email", "id_card_number", "id_card_files", "id_card_roles", "created_at", "updated_at",
+];
+
+// Upsert the profile row and return its id together with the values the row should now hold
+async fn write_profile(pool: &SqlitePool, profile: &Profile) -> Result<(Uuid, BoundProfileRow)> {
+    let now = Utc::now().to_rfc3339();
+    let profile_id = profile.id.unwrap_or_else(Uuid::new_v4);
+    
+    tracing::info!("Using profile ID: {}", profile_id);
+    tracing::info!("Timestamp: {}", now);
+    
+    // The existence check, file list merge and upsert run as one attempt under the write lock,
+    // so a concurrent save cannot slip in between reading the old file list and replacing it
+    let now_ref = &now;
+    let result = retry_on_busy(|| async move {
+        let existing: Option<(Option<String>, Option<String>, Option<String>)> =
+            sqlx::query_as("SELECT id_card_files, id_card_roles, created_at FROM profiles WHERE id = ?1")
+                .bind(profile_id.to_string())
+                .fetch_optional(pool)
+                .timed()
+                .await?;
+
+        let is_update = existing.is_some();
+        tracing::info!("Profile exists: {}, performing {}", is_update, if is_update { "UPDATE" } else { "INSERT" });
+        let (existing_files, existing_roles, existing_created_at) = existing.unwrap_or_default();
+        let id_card_files = merge_file_list(&existing_files, &profile.id_card_files)?;
+
+        let exec_result = sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO profiles (
+                id, name, phone, email, id_card_number, id_card_files, id_card_roles, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6,
+                COALESCE(?8, (SELECT id_card_roles FROM profiles WHERE id = ?1)),
+                COALESCE((SELECT created_at FROM profiles WHERE id = ?1), ?7), ?7)
+            "#,
+        )
+        .bind(profile_id.to_string())
+        .bind(&profile.name)
+        .bind(&profile.phone)
+        .bind(&profile.email)
+        .bind(&profile.id_card_number)
+        .bind(&id_card_files)
+        .bind(now_ref)
+        .bind(&profile.id_card_roles)
+        .execute(pool)
+        .timed()
+        .await?;
+        Ok::<_, anyhow::Error>((exec_result, id_card_files, existing_roles, existing_created_at))
+    })
+    .await;
+    
+    let (id_card_files, existing_roles, existing_created_at) = match result {
+        Ok((exec_result, id_card_files, existing_roles, existing_created_at)) => {
+            tracing::info!("Database operation successful. Rows affected: {}", exec_result.rows_affected());
+            
+            if exec_result.rows_affected() == 0 {
+                tracing::warn!("No rows were affected by the operation");
+            }
+            (id_card_files, existing_roles, existing_created_at)
+        }
+        Err(e) => {
+            tracing::error!("Database INSERT/UPDATE failed: {:?}", e);
+            tracing::error!("SQL Error details: {}", e);
+            return Err(e.context("Database operation failed"));
+        }
+    };
+
+    let bound = PROFILE_DIFF_COLUMNS
+        .into_iter()
+        .zip([
+            Some(profile.name.clone()),
+            Some(profile.phone.clone()),
+            Some(profile.email.clone()),
+            Some(profile.id_card_number.clone()),
+            id_card_files,
+            profile.id_card_roles.clone().or(existing_roles),
+            Some(existing_created_at.unwrap_or_else(|| now.clone())),
+            Some(now),
+        ])
+        .collect();
+    Ok((profile_id, bound))
+}
+
+// Cheap check on the decoded profile; the raw-row diff only runs when something looks off
+fn profile_matches_bound(saved: &Profile, bound: &BoundProfileRow) -> bool {
+    let decoded = [
+        Some(saved.name.clone()),
+        Some(saved.phone.clone()),
+        Some(saved.email.clone()),
+        Some(saved.id_card_number.clone()),
+        saved.id_card_files.clone(),
+        saved.id_card_roles.clone(),
+    ];
+    bound.iter().zip(decoded).all(|((_, expected), actual)| *expected == actual)
+}
+
+/// Compare every bound value with the raw column in the stored row; a missing row reports every column
+pub async fn diff_profile_row(pool: &SqlitePool, profile_id: Uuid, bound: &BoundProfileRow) -> Result<Vec<ProfileFieldDiff>> {
+    type RawProfileRow = (Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>);
+    let row: Option<RawProfileRow> = sqlx::query_as(&format!(
+        "SELECT {} FROM profiles WHERE id = ?1",
+        PROFILE_DIFF_COLUMNS.join(", ")
+    ))
+    .bind(profile_id.to_string())
+    .fetch_optional(pool)
+    .timed()
+    .await?;
+
+    let row_missing = row.is_none();
+    let actual: [Option<String>; 8] = match row {
+        Some((name, phone, email, id_card_number, files, roles, created_at, updated_at)) => {
+            [name, phone, email, id_card_number, files, roles, created_at, updated_at]
+        }
+        None => Default::default(),
+    };
+    let diffs = bound
+        .iter()
+        .zip(actual)
+        .filter(|((_, expected), actual)| row_missing || expected != actual)
+        .map(|((column, expected), actual)| {
+            // 证件号只保留前6位和后4位，诊断结果可以直接外发
+            let mask = |value: Option<String>| match *column {
+                "id_card_number" => value.map(|value| crate::models::mask_id_card(&value)),
+                _ => value,
+            };
+            ProfileFieldDiff {
+                field: column.to_string(),
+                expected: mask(expected.clone()),
+                actual: mask(actual),
+            }
+        })
+        .collect();
+    Ok(diffs)
+}
+
+fn log_profile_diffs(profile_id: Uuid, diffs: &[ProfileFieldDiff]) {
+    if diffs.is_empty() {
+        tracing::info!("Profile {} row matches every bound value", profile_id);
+    }
+    for diff in diffs {
+        tracing::warn!(
+            "Profile {} column {} differs: bound {:?}, row {:?}",
+            profile_id, diff.field, diff.expected, diff.actual
+        );
+    }
+}
+
+/// Write a throwaway profile with known values, compare the stored row and the decoded profile
+/// against what was bound, then delete it again
+pub async fn diagnose_profile_save() -> Result<ProfileSaveDiagnosis> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let probe = Profile {
+        id: Some(Uuid::new_v4()),
+        name: "诊断测试".to_string(),
+        phone: "13800000000".to_string(),
+        email: "diagnose@example.com".to_string(),
+        id_card_number: "110101199001011234".to_string(),
+        id_card_files: Some(r#"["files/profiles/id_cards/diagnose.png"]"#.to_string()),
+        id_card_roles: Some(r#"{"files/profiles/id_cards/diagnose.png":"front"}"#.to_string()),
+        created_at: None,
+        updated_at: None,
+    };
+
+    let (profile_id, bound) = write_profile(&pool, &probe).await?;
+    let diffs = diff_profile_row(&pool, profile_id, &bound).await;
+    let retrieval = sqlx::query_as::<_, Profile>("SELECT * FROM profiles WHERE id = ?1")
+        .bind(profile_id.to_string())
+        .fetch_optional(&pool)
+        .timed()
+        .await;
+
+    // Always remove the probe so it never shows up as the current profile
+    retry_on_busy(|| {
+        sqlx::query("DELETE FROM profiles WHERE id = ?1")
+            .bind(profile_id.to_string())
+            .execute(&pool)
+            .timed()
+    })
+    .await?;
+
+    let diffs = diffs?;
+    log_profile_diffs(profile_id, &diffs);
+    let (retrieved, error) = match retrieval {
+        Ok(Some(saved)) if profile_matches_bound(&saved, &bound) => (true, None),
+        Ok(Some(_)) => (true, Some("读回的档案与写入的值不一致".to_string())),
+        Ok(None) => (false, Some("写入后未能读回档案".to_string())),
+        Err(e) => (false, Some(format!("读回档案失败: {:#}", e))),
+    };
+    Ok(ProfileSaveDiagnosis {
+        profile_id,
+        retrieved,
+        matches: diffs.is_empty() && error.is_none(),
+        diffs,
+        error,
+    })
+}
+
+// 应用设置相关操作
+pub async fn get_setting(key: &str) -> Result<Option<String>> {
+    let pool = get_pool().await?;
+    let value = sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = ?1"
+    )
+    .bind(key)
+    .fetch_optional(&pool)
+    .timed()
+    .await?;
+    Ok(value)
+}
+
+pub async fn set_setting(key: &str, value: &str) -> Result<()> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    retry_on_busy(|| {
+        sqlx::query(
+            "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)"
+        )
+        .bind(key)
+        .bind(value)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Load all settings, falling back to defaults for missing or malformed values
+pub async fn get_settings() -> Result<AppSettings> {
+    let pool = get_pool().await?;
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
+        .fetch_all(&pool)
+        .timed()
+        .await?;
+
+    let mut settings = AppSettings::default();
+    for (key, value) in rows {
+        match key.as_str() {
+            SETTING_DEBUG_PORT => match value.parse::<u16>() {
+                Ok(port) if port > 0 => settings.debug_port = port,
+                _ => tracing::warn!("Invalid debug_port setting '{}', using default", value),
+            },
+            SETTING_HEADLESS => settings.headless = value == "true",
+            SETTING_APPEAL_URL if !value.trim().is_empty() => settings.appeal_url = value,
+            SETTING_NOTIFICATIONS_ENABLED => settings.notifications_enabled = value != "false",
+            SETTING_CHROME_PATH => settings.chrome_path = Some(value).filter(|path| !path.trim().is_empty()),
+            SETTING_CHROME_USER_DATA_DIR => settings.chrome_user_data_dir = Some(value).filter(|dir| !dir.trim().is_empty()),
+            SETTING_PROXY => settings.proxy = Some(value).filter(|proxy| !proxy.trim().is_empty()),
+            SETTING_USE_LAST_IP_ASSET => settings.use_last_ip_asset = value == "true",
+            SETTING_TIMING_SHORT_MS => parse_timing(&key, &value, &mut settings.timing.short_ms),
+            SETTING_TIMING_MEDIUM_MS => parse_timing(&key, &value, &mut settings.timing.medium_ms),
+            SETTING_TIMING_LONG_MS => parse_timing(&key, &value, &mut settings.timing.long_ms),
+            SETTING_TIMING_UPLOAD_SETTLE_MS => parse_timing(&key, &value, &mut settings.timing.upload_settle_ms),
+            SETTING_PLAYWRIGHT_TIMEOUT_SECS => parse_timing(&key, &value, &mut settings.playwright_timeout_secs),
+            SETTING_OVERALL_DEADLINE_SECS => parse_timing(&key, &value, &mut settings.overall_deadline_secs),
+            SETTING_VERBOSE_AUTOMATION_LOGS => settings.verbose_automation_logs = value == "true",
+            SETTING_LOCALE => match value.parse() {
+                Ok(locale) => settings.locale = locale,
+                Err(e) => tracing::warn!("{}，使用中文", e),
+            },
+            SETTING_AUTOMATION_BACKEND => match value.parse() {
+                Ok(backend) => settings.automation_backend = backend,
+                Err(e) => tracing::warn!("{}，使用默认后端", e),
+            },
+            SETTING_DAILY_APPEAL_LIMIT => match value.parse::<u32>() {
+                Ok(limit) if limit > 0 => settings.daily_appeal_limit = limit,
+                _ => tracing::warn!("Invalid daily_appeal_limit setting '{}', using default", value),
+            },
+            SETTING_SCRIPT_OUTPUT_DIR => settings.script_output_dir = Some(value).filter(|dir| !dir.trim().is_empty()),
+            SETTING_USER_AGENT => settings.user_agent = Some(value).filter(|user_agent| !user_agent.trim().is_empty()),
+            SETTING_TEST_URL => settings.test_url = Some(value).filter(|url| !url.trim().is_empty()),
+            SETTING_REUSE_CURRENT_PAGE => settings.reuse_current_page = value == "true",
+            SETTING_STRIP_EXIF_ON_UPLOAD => match value.parse() {
+                Ok(mode) => settings.strip_exif_on_upload = mode,
+                Err(e) => tracing::warn!("{}，使用默认设置", e),
+            },
+            _ => {}
+        }
+    }
+    Ok(settings)
+}
+
+fn parse_timing(key: &str, value: &str, target: &mut u64) {
+    match value.parse::<u64>() {
+        Ok(ms) if ms > 0 => *target = ms,
+        _ => tracing::warn!("Invalid {} setting '{}', using default", key, value),
+    }
+}
+
+/// Persist every field of `settings` in a single transaction
+pub async fn save_settings(settings: &AppSettings) -> Result<AppSettings> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let entries = settings_entries(settings);
+    let now = Utc::now().to_rfc3339();
+
+    retry_on_busy(|| {
+        let pool = pool.clone();
+        let entries = entries.clone();
+        let now = now.clone();
+        async move {
+            let mut tx = pool.begin().timed().await?;
+            for (key, value) in &entries {
+                sqlx::query("INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)")
+                    .bind(*key)
+                    .bind(value)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .timed()
+                    .await?;
+            }
+            tx.commit().timed().await
+        }
+    })
+    .await?;
+
+    get_settings().await
+}
+
+fn settings_entries(settings: &AppSettings) -> Vec<(&'static str, String)> {
+    vec![
+        (SETTING_DEBUG_PORT, settings.debug_port.to_string()),
+        (SETTING_HEADLESS, settings.headless.to_string()),
+        (SETTING_APPEAL_URL, settings.appeal_url.clone()),
+        (SETTING_NOTIFICATIONS_ENABLED, settings.notifications_enabled.to_string()),
+        (SETTING_CHROME_PATH, settings.chrome_path.clone().unwrap_or_default()),
+        (SETTING_CHROME_USER_DATA_DIR, settings.chrome_user_data_dir.clone().unwrap_or_default()),
+        (SETTING_PROXY, settings.proxy.clone().unwrap_or_default()),
+        (SETTING_USE_LAST_IP_ASSET, settings.use_last_ip_asset.to_string()),
+        (SETTING_TIMING_SHORT_MS, settings.timing.short_ms.to_string()),
+        (SETTING_TIMING_MEDIUM_MS, settings.timing.medium_ms.to_string()),
+        (SETTING_TIMING_LONG_MS, settings.timing.long_ms.to_string()),
+        (SETTING_TIMING_UPLOAD_SETTLE_MS, settings.timing.upload_settle_ms.to_string()),
+        (SETTING_PLAYWRIGHT_TIMEOUT_SECS, settings.playwright_timeout_secs.to_string()),
+        (SETTING_OVERALL_DEADLINE_SECS, settings.overall_deadline_secs.to_string()),
+        (SETTING_LOCALE, settings.locale.as_str().to_string()),
+        (SETTING_VERBOSE_AUTOMATION_LOGS, settings.verbose_automation_logs.to_string()),
+        (SETTING_AUTOMATION_BACKEND, settings.automation_backend.as_str().to_string()),
+        (SETTING_DAILY_APPEAL_LIMIT, settings.daily_appeal_limit.to_string()),
+        (SETTING_STRIP_EXIF_ON_UPLOAD, settings.strip_exif_on_upload.as_str().to_string()),
+        (SETTING_SCRIPT_OUTPUT_DIR, settings.script_output_dir.clone().unwrap_or_default()),
+        (SETTING_USER_AGENT, settings.user_agent.clone().unwrap_or_default()),
+        (SETTING_TEST_URL, settings.test_url.clone().unwrap_or_default()),
+        (SETTING_REUSE_CURRENT_PAGE, settings.reuse_current_page.to_string()),
+    ]
+}
+
 // IP资产相关操作
 pub async fn get_ip_assets() -> Result<Vec<IpAsset>> {
     let pool = get_pool().await?;
     let assets = sqlx::query_as::<_, IpAsset>(
-        "SELECT * FROM ip_assets ORDER BY created_at DESC"
+        "SELECT * FROM ip_assets WHERE archived = 0 ORDER BY created_at DESC"
+    )
+    .fetch_all(&pool)
+    .timed()
+    .await?;
+    Ok(assets)
+}
+
+pub async fn get_archived_ip_assets() -> Result<Vec<IpAsset>> {
+    let pool = get_pool().await?;
+    let assets = sqlx::query_as::<_, IpAsset>(
+        "SELECT * FROM ip_assets WHERE archived = 1 ORDER BY updated_at DESC"
     )
     .fetch_all(&pool)
+    .timed()
     .await?;
     Ok(assets)
 }
 
+/// 上次自动化使用的IP资产，已归档或已删除时返回None
+pub async fn get_last_used_ip_asset() -> Result<Option<IpAsset>> {
+    let Some(value) = get_setting(SETTING_LAST_USED_IP_ASSET_ID).await? else {
+        return Ok(None);
+    };
+    let id = match Uuid::parse_str(&value) {
+        Ok(id) => id,
+        Err(_) => {
+            tracing::warn!("Invalid last used IP asset id '{}', ignoring", value);
+            return Ok(None);
+        }
+    };
+    Ok(get_ip_asset(id).await?.filter(|asset| !asset.archived))
+}
+
 pub async fn get_ip_asset(id: Uuid) -> Result<Option<IpAsset>> {
     let pool = get_pool().await?;
     let asset = sqlx::query_as::<_, IpAsset>(
@@ -539,78 +1564,230 @@ pub async fn get_ip_asset(id: Uuid) -> Result<Option<IpAsset>> {
     )
     .bind(id.to_string())
     .fetch_optional(&pool)
+    .timed()
     .await?;
     Ok(asset)
 }
 
 pub async fn save_ip_asset(asset: &IpAsset) -> Result<IpAsset> {
+    ensure_persistent()?;
     let pool = get_pool().await?;
     let now = Utc::now();
     
     let asset_id = asset.id.unwrap_or_else(Uuid::new_v4);
+    let pool = &pool;
     
-    sqlx::query(
-        r#"
-        INSERT OR REPLACE INTO ip_assets (
-            id, work_name, work_type, owner, region, work_start_date, work_end_date,
-            equity_type, is_agent, auth_start_date, auth_end_date, auth_files,
-            work_proof_files, status, created_at, updated_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14,
-            COALESCE((SELECT created_at FROM ip_assets WHERE id = ?1), ?15), ?15)
-        "#,
-    )
-    .bind(asset_id.to_string())
-    .bind(&asset.work_name)
-    .bind(&asset.work_type)
-    .bind(&asset.owner)
-    .bind(&asset.region)
-    .bind(&asset.work_start_date)
-    .bind(&asset.work_end_date)
-    .bind(&asset.equity_type)
-    .bind(asset.is_agent)
-    .bind(&asset.auth_start_date)
-    .bind(&asset.auth_end_date)
-    .bind(&asset.auth_files)
-    .bind(&asset.work_proof_files)
-    .bind(&asset.status)
-    .bind(now.to_rfc3339())
-    .execute(&pool)
+    // Read, merge and upsert in one attempt under the write lock, like write_profile
+    retry_on_busy(|| async move {
+        let existing: Option<(Option<String>, Option<String>)> =
+            sqlx::query_as("SELECT auth_files, work_proof_files FROM ip_assets WHERE id = ?1")
+                .bind(asset_id.to_string())
+                .fetch_optional(pool)
+                .timed()
+                .await?;
+        let (existing_auth_files, existing_proof_files) = existing.unwrap_or_default();
+        let auth_files = merge_file_list(&existing_auth_files, &asset.auth_files)?;
+        let work_proof_files = merge_file_list(&existing_proof_files, &asset.work_proof_files)?;
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO ip_assets (
+                id, work_name, work_type, owner, region, work_start_date, work_end_date,
+                equity_type, is_agent, auth_start_date, auth_end_date, auth_files,
+                work_proof_files, status, archived, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                COALESCE((SELECT created_at FROM ip_assets WHERE id = ?1), ?16), ?16)
+            "#,
+        )
+        .bind(asset_id.to_string())
+        .bind(&asset.work_name)
+        .bind(&asset.work_type)
+        .bind(&asset.owner)
+        .bind(&asset.region)
+        .bind(&asset.work_start_date)
+        .bind(&asset.work_end_date)
+        .bind(&asset.equity_type)
+        .bind(asset.is_agent)
+        .bind(&asset.auth_start_date)
+        .bind(&asset.auth_end_date)
+        .bind(&auth_files)
+        .bind(&work_proof_files)
+        .bind(&asset.status)
+        .bind(asset.archived)
+        .bind(now.to_rfc3339())
+        .execute(pool)
+        .timed()
+        .await
+    })
     .await?;
 
-    let saved_asset = get_ip_asset(asset_id).await?;
-    Ok(saved_asset.unwrap())
+    get_ip_asset(asset_id).await?.context("IP asset saved but could not be retrieved")
+}
+
+/// Upsert many IP assets atomically; if any row fails, the whole batch is rolled back
+pub async fn save_ip_assets_batch(assets: Vec<IpAsset>) -> Result<Vec<IpAsset>> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let now = Utc::now().to_rfc3339();
+
+    // Dropping the transaction without committing rolls back every upsert
+    let _writer = acquire_writer().await?;
+    let mut tx = pool.begin().await?;
+    let mut saved_assets = Vec::with_capacity(assets.len());
+
+    for (index, asset) in assets.iter().enumerate() {
+        let asset_id = asset.id.unwrap_or_else(Uuid::new_v4);
+
+        // Same merge as save_ip_asset so a batch re-save keeps the stored file order
+        let existing: Option<(Option<String>, Option<String>)> =
+            sqlx::query_as("SELECT auth_files, work_proof_files FROM ip_assets WHERE id = ?1")
+                .bind(asset_id.to_string())
+                .fetch_optional(&mut *tx)
+                .timed()
+                .await?;
+        let (existing_auth_files, existing_proof_files) = existing.unwrap_or_default();
+        let auth_files = merge_file_list(&existing_auth_files, &asset.auth_files)?;
+        let work_proof_files = merge_file_list(&existing_proof_files, &asset.work_proof_files)?;
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO ip_assets (
+                id, work_name, work_type, owner, region, work_start_date, work_end_date,
+                equity_type, is_agent, auth_start_date, auth_end_date, auth_files,
+                work_proof_files, status, archived, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                COALESCE((SELECT created_at FROM ip_assets WHERE id = ?1), ?16), ?16)
+            "#,
+        )
+        .bind(asset_id.to_string())
+        .bind(&asset.work_name)
+        .bind(&asset.work_type)
+        .bind(&asset.owner)
+        .bind(&asset.region)
+        .bind(&asset.work_start_date)
+        .bind(&asset.work_end_date)
+        .bind(&asset.equity_type)
+        .bind(asset.is_agent)
+        .bind(&asset.auth_start_date)
+        .bind(&asset.auth_end_date)
+        .bind(&auth_files)
+        .bind(&work_proof_files)
+        .bind(&asset.status)
+        .bind(asset.archived)
+        .bind(&now)
+        .execute(&mut *tx)
+        .timed()
+        .await
+        .with_context(|| format!("Failed to save IP asset #{} '{}'", index + 1, asset.work_name))?;
+
+        let saved_asset = sqlx::query_as::<_, IpAsset>(
+            "SELECT * FROM ip_assets WHERE id = ?1"
+        )
+        .bind(asset_id.to_string())
+        .fetch_one(&mut *tx)
+        .timed()
+        .await?;
+        saved_assets.push(saved_asset);
+    }
+
+    tx.commit().await?;
+    tracing::info!("Saved {} IP asset(s) in one transaction", saved_assets.len());
+    Ok(saved_assets)
 }
 
+/// Archive (soft-delete) an IP asset; referencing cases keep their foreign key
 pub async fn delete_ip_asset(id: Uuid) -> Result<bool> {
+    set_ip_asset_archived(id, true).await
+}
+
+pub async fn restore_ip_asset(id: Uuid) -> Result<bool> {
+    set_ip_asset_archived(id, false).await
+}
+
+async fn set_ip_asset_archived(id: Uuid, archived: bool) -> Result<bool> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let result = retry_on_busy(|| {
+        sqlx::query(
+            "UPDATE ip_assets SET archived = ?1, updated_at = ?2 WHERE id = ?3"
+        )
+        .bind(archived)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    
+    Ok(result.rows_affected() > 0)
+}
+
+/// Result of a permanent IP asset deletion
+pub enum PurgeIpAssetOutcome {
+    Deleted,
+    NotFound,
+    /// The asset is still referenced by these cases and `cascade` was not requested
+    Referenced(Vec<Uuid>),
+}
+
+/// Permanently delete an IP asset
+///
+/// If any case (archived or not) still references the asset, nothing is deleted and
+/// the referencing case ids are returned. With `cascade`, those cases are archived and
+/// detached (`associated_ip_id = NULL`) in the same transaction before the asset is
+/// removed, so no case record is ever deleted as a side effect.
+pub async fn purge_ip_asset(id: Uuid, cascade: bool) -> Result<PurgeIpAssetOutcome> {
+    ensure_persistent()?;
     let pool = get_pool().await?;
     
-    // Start a transaction to ensure both operations succeed or fail together
+    // Start a transaction so the reference check and the deletion see the same state
+    let _writer = acquire_writer().await?;
     let mut tx = pool.begin().await?;
     
-    // First, delete any cases that reference this IP asset
-    let cases_deleted = sqlx::query(
-        "DELETE FROM cases WHERE associated_ip_id = ?1"
+    let referencing_ids: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM cases WHERE associated_ip_id = ?1"
     )
     .bind(id.to_string())
-    .execute(&mut *tx)
+    .fetch_all(&mut *tx)
+    .timed()
     .await?;
     
-    if cases_deleted.rows_affected() > 0 {
-        tracing::info!("Deleted {} related case(s) for IP asset {}", cases_deleted.rows_affected(), id);
+    if !referencing_ids.is_empty() {
+        if !cascade {
+            tx.rollback().await?;
+            let case_ids = referencing_ids.iter()
+                .map(|case_id| Uuid::from_str(case_id).context("Invalid case id in database"))
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(PurgeIpAssetOutcome::Referenced(case_ids));
+        }
+        
+        sqlx::query(
+            "UPDATE cases SET archived = 1, associated_ip_id = NULL, updated_at = ?1 WHERE associated_ip_id = ?2"
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .timed()
+        .await?;
+        tracing::info!("Archived and detached {} case(s) referencing IP asset {}", referencing_ids.len(), id);
     }
     
-    // Then delete the IP asset itself
     let result = sqlx::query(
         "DELETE FROM ip_assets WHERE id = ?1"
     )
     .bind(id.to_string())
     .execute(&mut *tx)
+    .timed()
     .await?;
     
     // Commit the transaction
     tx.commit().await?;
     
-    Ok(result.rows_affected() > 0)
+    if result.rows_affected() > 0 {
+        Ok(PurgeIpAssetOutcome::Deleted)
+    } else {
+        Ok(PurgeIpAssetOutcome::NotFound)
+    }
 }
 
 // 案件相关操作
@@ -621,61 +1798,856 @@ pub async fn get_cases() -> Result<Vec<Case>> {
         SELECT c.*, ia.work_name as associated_ip_name
         FROM cases c
         LEFT JOIN ip_assets ia ON c.associated_ip_id = ia.id
+        WHERE c.archived = 0
         ORDER BY c.created_at DESC
         "#,
     )
     .fetch_all(&pool)
+    .timed()
+    .await?;
+    Ok(cases)
+}
+
+/// 最近一次针对该侵权链接创建的未归档案件
+pub async fn find_case_by_url(url: &str) -> Result<Option<Case>> {
+    let pool = get_pool().await?;
+    let case = sqlx::query_as::<_, Case>(
+        r#"
+        SELECT * FROM cases
+        WHERE archived = 0 AND infringing_url = ?1
+        ORDER BY COALESCE(submission_date, created_at) DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(url.trim())
+    .fetch_optional(&pool)
+    .timed()
+    .await?;
+    Ok(case)
+}
+
+pub const CASE_EVENT_SUBMITTED: &str = "提交申诉";
+pub const CASE_EVENT_RESUBMITTED: &str = "重新提交";
+const CASE_STATUS_SUBMITTED: &str = "已提交";
+// 只填好表单、等待用户在页面上手动提交的案件
+const CASE_STATUS_PREPARED: &str = "待提交";
+
+/// 记录一次自动化运行的案件
+/// 已有同一侵权链接的未归档案件时更新其提交时间并追加"重新提交"事件，`new_case`为true时总是新建案件
+/// 截图和运行结果作为本次提交的凭证，覆盖之前提交的记录
+/// `submitted`为false(只填写未提交)时不写提交事件，不计入每日次数：新建的案件标记为待提交，已有案件保持不变
+pub async fn record_case_submission(
+    infringing_url: &str,
+    original_url: Option<&str>,
+    ip_asset_id: Option<Uuid>,
+    new_case: bool,
+    submitted: bool,
+    screenshot: Option<&str>,
+    result_json: Option<&str>,
+) -> Result<Case> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let existing = if new_case { None } else { find_case_by_url(infringing_url).await? };
+    let case_id = existing.as_ref().and_then(|case| case.id).unwrap_or_else(Uuid::new_v4);
+    let is_resubmission = existing.is_some();
+    // 之前只填写未提交的案件，第一次提交仍记为"提交申诉"
+    let was_submitted = existing.as_ref().map_or(false, |case| case.submission_date.is_some());
+    if is_resubmission && !submitted {
+        tracing::info!("案件{}已存在，本次运行未提交，不更新案件记录", case_id);
+        return get_case_by_id(case_id).await?.context("Case disappeared while recording the run");
+    }
+    let url = infringing_url.trim().to_string();
+    let original_url = original_url.map(str::to_string);
+    let screenshot = screenshot.map(str::to_string);
+    let result_json = result_json.map(str::to_string);
+    let now = Utc::now().to_rfc3339();
+
+    retry_on_busy(|| {
+        let pool = pool.clone();
+        let url = url.clone();
+        let original_url = original_url.clone();
+        let screenshot = screenshot.clone();
+        let result_json = result_json.clone();
+        let now = now.clone();
+        async move {
+            let mut tx = pool.begin().timed().await?;
+            let event = if is_resubmission {
+                sqlx::query(
+                    r#"
+                    UPDATE cases SET status = ?1, submission_date = ?2, updated_at = ?2,
+                        submission_screenshot = ?3, submission_result_json = ?4
+                    WHERE id = ?5
+                    "#,
+                )
+                .bind(CASE_STATUS_SUBMITTED)
+                .bind(&now)
+                .bind(&screenshot)
+                .bind(&result_json)
+                .bind(case_id.to_string())
+                .execute(&mut *tx)
+                .timed()
+                .await?;
+                if was_submitted { CASE_EVENT_RESUBMITTED } else { CASE_EVENT_SUBMITTED }
+            } else {
+                sqlx::query(
+                    r#"
+                    INSERT INTO cases (
+                        id, infringing_url, original_url, associated_ip_id, status,
+                        submission_date, archived, created_at, updated_at,
+                        submission_screenshot, submission_result_json
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?7, ?8, ?9)
+                    "#,
+                )
+                .bind(case_id.to_string())
+                .bind(&url)
+                .bind(&original_url)
+                .bind(ip_asset_id.map(|id| id.to_string()))
+                .bind(if submitted { CASE_STATUS_SUBMITTED } else { CASE_STATUS_PREPARED })
+                .bind(submitted.then_some(&now))
+                .bind(&now)
+                .bind(&screenshot)
+                .bind(&result_json)
+                .execute(&mut *tx)
+                .timed()
+                .await?;
+                CASE_EVENT_SUBMITTED
+            };
+            if submitted {
+                sqlx::query("INSERT INTO case_events (case_id, event, created_at) VALUES (?1, ?2, ?3)")
+                    .bind(case_id.to_string())
+                    .bind(event)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .timed()
+                    .await?;
+            }
+            tx.commit().timed().await
+        }
+    })
+    .await?;
+
+    let case = sqlx::query_as::<_, Case>("SELECT * FROM cases WHERE id = ?1")
+        .bind(case_id.to_string())
+        .fetch_one(&pool)
+        .timed()
+        .await?;
+    Ok(case)
+}
+
+pub async fn get_case_by_id(id: Uuid) -> Result<Option<Case>> {
+    let pool = get_pool().await?;
+    let case = sqlx::query_as::<_, Case>(
+        "SELECT c.*, ia.work_name as associated_ip_name FROM cases c LEFT JOIN ip_assets ia ON c.associated_ip_id = ia.id WHERE c.id = ?1"
+    )
+    .bind(id.to_string())
+    .fetch_optional(&pool)
+    .timed()
+    .await?;
+    Ok(case)
+}
+
+pub async fn get_case_events(case_id: Uuid) -> Result<Vec<CaseEvent>> {
+    let pool = get_pool().await?;
+    let events = sqlx::query_as::<_, CaseEvent>(
+        "SELECT * FROM case_events WHERE case_id = ?1 ORDER BY created_at ASC, id ASC"
+    )
+    .bind(case_id.to_string())
+    .fetch_all(&pool)
+    .timed()
+    .await?;
+    Ok(events)
+}
+
+const DEFAULT_CASE_PAGE_SIZE: i64 = 50;
+const MAX_CASE_PAGE_SIZE: i64 = 500;
+
+/// 按状态、IP资产和提交日期筛选案件，返回当前页和筛选后的总数
+pub async fn query_cases(filter: &CaseQuery) -> Result<CasePage> {
+    let pool = get_pool().await?;
+    let limit = filter.limit.unwrap_or(DEFAULT_CASE_PAGE_SIZE).clamp(1, MAX_CASE_PAGE_SIZE);
+    let offset = filter.offset.unwrap_or(0).max(0);
+
+    let mut count_query = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM cases c");
+    push_case_filters(&mut count_query, filter);
+    let total: i64 = count_query
+        .build_query_scalar::<i64>()
+        .fetch_one(&pool)
+        .timed()
+        .await?;
+
+    let mut page_query = QueryBuilder::<Sqlite>::new(
+        "SELECT c.*, ia.work_name as associated_ip_name FROM cases c LEFT JOIN ip_assets ia ON c.associated_ip_id = ia.id",
+    );
+    push_case_filters(&mut page_query, filter);
+    page_query
+        .push(" ORDER BY c.submission_date IS NULL, c.submission_date DESC, c.created_at DESC LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    let cases = page_query
+        .build_query_as::<Case>()
+        .fetch_all(&pool)
+        .timed()
+        .await?;
+
+    Ok(CasePage { cases, total, limit, offset })
+}
+
+fn push_case_filters(builder: &mut QueryBuilder<'_, Sqlite>, filter: &CaseQuery) {
+    builder.push(" WHERE c.archived = 0");
+    if let Some(status) = filter.status.as_deref().map(str::trim).filter(|status| !status.is_empty()) {
+        builder.push(" AND c.status = ").push_bind(status.to_string());
+    }
+    if let Some(ip_asset_id) = filter.ip_asset_id {
+        builder.push(" AND c.associated_ip_id = ").push_bind(ip_asset_id.to_string());
+    }
+    // 按日期筛选时排除尚未提交(submission_date为NULL)的案件
+    if filter.from.is_some() || filter.to.is_some() {
+        builder.push(" AND c.submission_date IS NOT NULL");
+    }
+    // submission_date以RFC3339(UTC)文本存储，字符串比较与时间顺序一致
+    if let Some(from) = filter.from {
+        builder.push(" AND c.submission_date >= ").push_bind(from.to_rfc3339());
+    }
+    if let Some(to) = filter.to {
+        builder.push(" AND c.submission_date <= ").push_bind(to.to_rfc3339());
+    }
+}
+
+pub async fn get_archived_cases() -> Result<Vec<Case>> {
+    let pool = get_pool().await?;
+    let cases = sqlx::query_as::<_, Case>(
+        "SELECT * FROM cases WHERE archived = 1 ORDER BY updated_at DESC"
+    )
+    .fetch_all(&pool)
+    .timed()
     .await?;
     Ok(cases)
 }
 
 pub async fn save_case(case: &Case) -> Result<Case> {
+    ensure_persistent()?;
     let pool = get_pool().await?;
     let now = Utc::now();
     
     let case_id = case.id.unwrap_or_else(Uuid::new_v4);
     
-    sqlx::query(
-        r#"
-        INSERT OR REPLACE INTO cases (
-            id, infringing_url, original_url, associated_ip_id, status,
-            submission_date, created_at, updated_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6,
-            COALESCE((SELECT created_at FROM cases WHERE id = ?1), ?7), ?7)
-        "#,
-    )
-    .bind(case_id.to_string())
-    .bind(&case.infringing_url)
-    .bind(&case.original_url)
-    .bind(&case.associated_ip_id.map(|id| id.to_string()))
-    .bind(&case.status)
-    .bind(&case.submission_date.map(|dt| dt.to_rfc3339()))
-    .bind(now.to_rfc3339())
-    .execute(&pool)
+    retry_on_busy(|| {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO cases (
+                id, infringing_url, original_url, associated_ip_id, status,
+                submission_date, archived, created_at, updated_at,
+                submission_screenshot, submission_result_json
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7,
+                COALESCE((SELECT created_at FROM cases WHERE id = ?1), ?8), ?8,
+                (SELECT submission_screenshot FROM cases WHERE id = ?1),
+                (SELECT submission_result_json FROM cases WHERE id = ?1))
+            "#,
+        )
+        .bind(case_id.to_string())
+        .bind(&case.infringing_url)
+        .bind(&case.original_url)
+        .bind(case.associated_ip_id.map(|id| id.to_string()))
+        .bind(&case.status)
+        .bind(case.submission_date.map(|dt| dt.to_rfc3339()))
+        .bind(case.archived)
+        .bind(now.to_rfc3339())
+        .execute(&pool)
+        .timed()
+    })
     .await?;
 
+    // 不JOIN ip_assets，返回的associated_ip_name总是None；需要作品名时使用get_case_by_id
     let saved_case = sqlx::query_as::<_, Case>(
         "SELECT * FROM cases WHERE id = ?1"
     )
     .bind(case_id.to_string())
     .fetch_one(&pool)
+    .timed()
     .await?;
     
     Ok(saved_case)
 }
 
+/// Archive (soft-delete) a case so the legal record is kept
 pub async fn delete_case(id: Uuid) -> Result<bool> {
+    set_case_archived(id, true).await
+}
+
+pub async fn restore_case(id: Uuid) -> Result<bool> {
+    set_case_archived(id, false).await
+}
+
+async fn set_case_archived(id: Uuid, archived: bool) -> Result<bool> {
+    ensure_persistent()?;
     let pool = get_pool().await?;
-    let result = sqlx::query(
-        "DELETE FROM cases WHERE id = ?1"
-    )
-    .bind(id.to_string())
-    .execute(&pool)
+    let result = retry_on_busy(|| {
+        sqlx::query(
+            "UPDATE cases SET archived = ?1, updated_at = ?2 WHERE id = ?3"
+        )
+        .bind(archived)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    
+    Ok(result.rows_affected() > 0)
+}
+
+/// Permanently delete a case
+pub async fn purge_case(id: Uuid) -> Result<bool> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let result = retry_on_busy(|| {
+        sqlx::query(
+            "DELETE FROM cases WHERE id = ?1"
+        )
+        .bind(id.to_string())
+        .execute(&pool)
+        .timed()
+    })
     .await?;
     
     Ok(result.rows_affected() > 0)
 }
 
+// 自动化状态相关操作
+pub async fn save_automation_status(status: &AutomationStatus) -> Result<()> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    retry_on_busy(|| {
+        sqlx::query(
+            r#"
+            UPDATE automation_status
+            SET is_running = ?1, current_step = ?2, progress = ?3, error = ?4,
+                started_at = ?5, finished_at = ?6, duration_secs = ?7, error_code = ?8, run_id = ?9, updated_at = ?10,
+                step_key = ?11
+            WHERE id = 1
+            "#,
+        )
+        .bind(status.is_running)
+        .bind(&status.current_step)
+        .bind(status.progress)
+        .bind(&status.error)
+        .bind(status.started_at.map(|dt| dt.to_rfc3339()))
+        .bind(status.finished_at.map(|dt| dt.to_rfc3339()))
+        .bind(status.duration_secs)
+        .bind(&status.error_code)
+        .bind(status.run_id.map(|id| id.to_string()))
+        .bind(Utc::now().to_rfc3339())
+        .bind(&status.step_key)
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    Ok(())
+}
+
+pub const RUN_OUTCOME_RUNNING: &str = "running";
+pub const RUN_OUTCOME_SUCCEEDED: &str = "succeeded";
+pub const RUN_OUTCOME_FAILED: &str = "failed";
+pub const RUN_OUTCOME_STOPPED: &str = "stopped";
+pub const RUN_OUTCOME_INTERRUPTED: &str = "interrupted";
+
+const DEFAULT_HISTORY_PAGE_SIZE: i64 = 50;
+const MAX_HISTORY_PAGE_SIZE: i64 = 500;
+
+/// 记录一次新开始的自动化运行
+pub async fn insert_automation_run(run_id: Uuid, request: &AutomationRequest, started_at: DateTime<Utc>) -> Result<()> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    retry_on_busy(|| {
+        sqlx::query(
+            r#"
+            INSERT INTO automation_runs (run_id, infringing_url, original_url, ip_asset_id, outcome, started_at, test_mode)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(run_id.to_string())
+        .bind(&request.infringing_url)
+        .bind(&request.original_url)
+        .bind(request.ip_asset_id.map(|id| id.to_string()))
+        .bind(RUN_OUTCOME_RUNNING)
+        .bind(started_at.to_rfc3339())
+        .bind(request.test_mode)
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// 运行结束时写入结果
+pub async fn finish_automation_run(run_id: Uuid, outcome: &str, status: &AutomationStatus) -> Result<()> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    retry_on_busy(|| {
+        sqlx::query(
+            r#"
+            UPDATE automation_runs
+            SET outcome = ?1, error = ?2, error_code = ?3, finished_at = ?4, duration_secs = ?5
+            WHERE run_id = ?6
+            "#,
+        )
+        .bind(outcome)
+        .bind(&status.error)
+        .bind(&status.error_code)
+        .bind(status.finished_at.map(|dt| dt.to_rfc3339()))
+        .bind(status.duration_secs)
+        .bind(run_id.to_string())
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// 保存脚本写出的结构化结果，失败的运行也会保存，便于查看停在哪一步
+pub async fn save_run_result(run_id: Uuid, result: &RunResult) -> Result<()> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let result_json = serde_json::to_string(result)?;
+    retry_on_busy(|| {
+        sqlx::query("UPDATE automation_runs SET result_json = ?1 WHERE run_id = ?2")
+            .bind(&result_json)
+            .bind(run_id.to_string())
+            .execute(&pool)
+            .timed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// 最近的运行记录，按开始时间倒序
+pub async fn get_automation_history(limit: Option<i64>, offset: Option<i64>) -> Result<Vec<AutomationRun>> {
+    let pool = get_pool().await?;
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_PAGE_SIZE).clamp(1, MAX_HISTORY_PAGE_SIZE);
+    let offset = offset.unwrap_or(0).max(0);
+    let runs = sqlx::query_as::<_, AutomationRun>(
+        r#"
+        SELECT r.*, ia.work_name as ip_asset_name
+        FROM automation_runs r
+        LEFT JOIN ip_assets ia ON r.ip_asset_id = ia.id
+        ORDER BY r.started_at DESC
+        LIMIT ?1 OFFSET ?2
+        "#,
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&pool)
+    .timed()
+    .await?;
+    Ok(runs)
+}
+
+/// 应用启动时调用: 上次运行被强制结束时数据库中仍标记为运行中，改为中断状态
+/// 返回是否存在这样的遗留状态
+pub async fn clear_stale_running_status() -> Result<bool> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let now = Utc::now().to_rfc3339();
+    let result = retry_on_busy(|| {
+        sqlx::query(
+            r#"
+            UPDATE automation_status
+            SET is_running = 0, current_step = '已中断', error = COALESCE(error, '应用在运行过程中被关闭'),
+                finished_at = COALESCE(finished_at, ?1), updated_at = ?1
+            WHERE id = 1 AND is_running = 1
+            "#,
+        )
+        .bind(&now)
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 把仍处于running的运行记录标记为中断，返回更新的记录数
+pub async fn mark_unfinished_runs_interrupted() -> Result<u64> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let now = Utc::now().to_rfc3339();
+    let result = retry_on_busy(|| {
+        sqlx::query("UPDATE automation_runs SET outcome = ?1, finished_at = ?2 WHERE outcome = ?3")
+            .bind(RUN_OUTCOME_INTERRUPTED)
+            .bind(&now)
+            .bind(RUN_OUTCOME_RUNNING)
+            .execute(&pool)
+            .timed()
+    })
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub const SCHEDULE_STATUS_PENDING: &str = "pending";
+pub const SCHEDULE_STATUS_STARTED: &str = "started";
+pub const SCHEDULE_STATUS_FAILED: &str = "failed";
+pub const SCHEDULE_STATUS_CANCELLED: &str = "cancelled";
+pub const SCHEDULE_STATUS_SKIPPED: &str = "skipped";
+
+/// 保存一个待运行的计划申诉
+pub async fn insert_scheduled_run(request: &AutomationRequest, run_at: DateTime<Utc>) -> Result<ScheduledRun> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let id = Uuid::new_v4();
+    let created_at = Utc::now();
+    let request_json = serde_json::to_string(request)?;
+    retry_on_busy(|| {
+        sqlx::query(
+            r#"
+            INSERT INTO scheduled_runs (id, request_json, run_at, status, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(&request_json)
+        .bind(run_at.to_rfc3339())
+        .bind(SCHEDULE_STATUS_PENDING)
+        .bind(created_at.to_rfc3339())
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    Ok(ScheduledRun {
+        id,
+        request: request.clone(),
+        run_at,
+        status: SCHEDULE_STATUS_PENDING.to_string(),
+        error: None,
+        created_at,
+        started_at: None,
+    })
+}
+
+/// 所有计划运行，按计划时间排序
+pub async fn get_scheduled_runs() -> Result<Vec<ScheduledRun>> {
+    let pool = get_pool().await?;
+    let runs = sqlx::query_as::<_, ScheduledRun>("SELECT * FROM scheduled_runs ORDER BY run_at ASC")
+        .fetch_all(&pool)
+        .timed()
+        .await?;
+    Ok(runs)
+}
+
+/// 计划时间不晚于before的待运行计划；时间均为UTC的RFC3339格式，可以直接按字符串比较
+pub async fn get_due_scheduled_runs(before: DateTime<Utc>) -> Result<Vec<ScheduledRun>> {
+    let pool = get_pool().await?;
+    let runs = sqlx::query_as::<_, ScheduledRun>(
+        "SELECT * FROM scheduled_runs WHERE status = ?1 AND run_at <= ?2 ORDER BY run_at ASC",
+    )
+    .bind(SCHEDULE_STATUS_PENDING)
+    .bind(before.to_rfc3339())
+    .fetch_all(&pool)
+    .timed()
+    .await?;
+    Ok(runs)
+}
+
+/// 只能取消尚未开始的计划，返回是否取消成功
+pub async fn cancel_scheduled_run(id: Uuid) -> Result<bool> {
+    transition_scheduled_run(id, SCHEDULE_STATUS_PENDING, SCHEDULE_STATUS_CANCELLED, None).await
+}
+
+/// 计划当前为from状态时更新为status，否则不做修改并返回false，避免覆盖同时发生的取消或启动
+pub async fn transition_scheduled_run(id: Uuid, from: &str, status: &str, error: Option<&str>) -> Result<bool> {
+    ensure_persistent()?;
+    let pool = get_pool().await?;
+    let started_at = (status == SCHEDULE_STATUS_STARTED).then(|| Utc::now().to_rfc3339());
+    let result = retry_on_busy(|| {
+        sqlx::query(
+            r#"
+            UPDATE scheduled_runs
+            SET status = ?1, error = ?2, started_at = COALESCE(?3, started_at)
+            WHERE id = ?4 AND status = ?5
+            "#,
+        )
+        .bind(status)
+        .bind(error)
+        .bind(&started_at)
+        .bind(id.to_string())
+        .bind(from)
+        .execute(&pool)
+        .timed()
+    })
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Count submissions and resubmissions recorded in case_events since `since`
+/// Resubmissions count separately because B站 counts every filed appeal against the daily quota
+pub async fn count_submissions_since(since: DateTime<Utc>) -> Result<u32> {
+    let pool = get_pool().await?;
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM case_events WHERE event IN (?1, ?2) AND created_at >= ?3"
+    )
+    .bind(CASE_EVENT_SUBMITTED)
+    .bind(CASE_EVENT_RESUBMITTED)
+    .bind(since.to_rfc3339())
+    .fetch_one(&pool)
+    .timed()
+    .await?;
+    Ok(count as u32)
+}
+
+/// Round-trip a trivial query to confirm the pool can actually serve requests
+pub async fn check_connectivity() -> Result<()> {
+    let pool = get_pool().await?;
+    sqlx::query_scalar::<_, i64>("SELECT 1")
+        .fetch_one(&pool)
+        .timed()
+        .await?;
+    Ok(())
+}
+
+/// Aggregate case counts for the dashboard
+/// Week starts on Monday; both boundaries are computed in UTC like the stored timestamps
+pub async fn get_case_statistics() -> Result<CaseStats> {
+    let pool = get_pool().await?;
+
+    let today = Utc::now().date_naive();
+    let week_start = (today - Duration::days(today.weekday().num_days_from_monday() as i64))
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+        .to_rfc3339();
+    let month_start = today.with_day(1).unwrap_or(today)
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+        .to_rfc3339();
+
+    let total_cases: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cases WHERE archived = 0")
+        .fetch_one(&pool)
+        .timed()
+        .await?;
+
+    let by_status = sqlx::query_as::<_, (String, i64)>(
+        "SELECT status, COUNT(*) FROM cases WHERE archived = 0 GROUP BY status"
+    )
+    .fetch_all(&pool)
+    .timed()
+    .await?
+    .into_iter()
+    .collect();
+
+    let submitted_this_week: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM cases WHERE archived = 0 AND submission_date >= ?1"
+    )
+    .bind(&week_start)
+    .fetch_one(&pool)
+    .timed()
+    .await?;
+
+    let submitted_this_month: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM cases WHERE archived = 0 AND submission_date >= ?1"
+    )
+    .bind(&month_start)
+    .fetch_one(&pool)
+    .timed()
+    .await?;
+
+    let most_appealed = sqlx::query_as::<_, (String, String, i64)>(
+        r#"
+        SELECT ia.id, ia.work_name, COUNT(c.id) AS case_count
+        FROM cases c
+        JOIN ip_assets ia ON c.associated_ip_id = ia.id
+        WHERE c.archived = 0
+        GROUP BY ia.id, ia.work_name
+        ORDER BY case_count DESC
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(&pool)
+    .timed()
+    .await?;
+
+    let most_appealed_asset = match most_appealed {
+        Some((id, work_name, case_count)) => Some(MostAppealedAsset {
+            id: Uuid::from_str(&id).context("Invalid IP asset id in database")?,
+            work_name,
+            case_count,
+        }),
+        None => None,
+    };
+
+    Ok(CaseStats {
+        total_cases,
+        by_status,
+        submitted_this_week,
+        submitted_this_month,
+        most_appealed_asset,
+    })
+}
+
+/// Checkpoint the WAL and VACUUM the database to reclaim space left by deleted rows
+pub async fn run_maintenance() -> Result<DatabaseMaintenanceReport> {
+    ensure_persistent()?;
+    let db_path = get_database_path()?;
+    let before_bytes = database_files_size(&db_path);
+    let pool = get_pool().await?;
+    let _writer = acquire_writer().await?;
+
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&pool).timed().await?;
+    sqlx::query("VACUUM").execute(&pool).timed().await?;
+    // VACUUM itself goes through the WAL, so truncate it again afterwards
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&pool).timed().await?;
+
+    let after_bytes = database_files_size(&db_path);
+    let reclaimed_bytes = before_bytes.saturating_sub(after_bytes);
+    tracing::info!(
+        "Database maintenance finished: {} -> {} bytes, reclaimed {} bytes",
+        before_bytes, after_bytes, reclaimed_bytes
+    );
+    Ok(DatabaseMaintenanceReport { before_bytes, after_bytes, reclaimed_bytes })
+}
+
+// Size of the database file plus its WAL
+fn database_files_size(db_path: &Path) -> u64 {
+    let wal_path = PathBuf::from(format!("{}-wal", db_path.display()));
+    [db_path.to_path_buf(), wal_path]
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Write a consistent copy of the database to `destination` using `VACUUM INTO`
+/// Unlike copying the file, this includes pages still held in the WAL
+pub async fn backup_database_to(destination: &Path) -> Result<()> {
+    // VACUUM INTO refuses to overwrite, and the save dialog already confirmed replacing it
+    if destination.exists() {
+        fs::remove_file(destination)
+            .with_context(|| format!("Failed to replace existing backup file: {:?}", destination))?;
+    }
+
+    let pool = get_pool().await?;
+    sqlx::query("VACUUM INTO ?1")
+        .bind(destination.to_string_lossy().to_string())
+        .execute(&pool)
+        .await
+        .with_context(|| format!("Failed to back up database to {:?}", destination))?;
+
+    tracing::info!("Database backed up to {:?}", destination);
+    Ok(())
+}
+
+/// Replace the live database with the backup at `source` and reinitialize the pool
+/// Returns warnings (e.g. schema version mismatch) that did not prevent the restore
+pub async fn restore_database_from(source: &Path) -> Result<Vec<String>> {
+    let warnings = validate_backup_file(source).await?;
+    let db_path = get_database_path()?;
+
+    // Hold the write lock for the whole swap so no query sees a half-replaced file
+    let swap_result = {
+        let mut pool_guard = DB_POOL.write().await;
+        if let Some(pool) = pool_guard.take() {
+            pool.close().await;
+        }
+        swap_database_file(source, &db_path)
+    };
+
+    // Reopen the pool even if the swap failed so the app keeps working
+    init_database().await?;
+    swap_result?;
+
+    tracing::info!("Database restored from {:?}", source);
+    Ok(warnings)
+}
+
+fn swap_database_file(source: &Path, db_path: &Path) -> Result<()> {
+    if db_path.exists() {
+        let previous = db_path.with_extension("db.before-restore");
+        fs::copy(db_path, &previous)
+            .with_context(|| format!("Failed to keep a copy of the current database at {:?}", previous))?;
+        tracing::info!("Previous database saved to {:?}", previous);
+    }
+
+    // Stale WAL/SHM files would otherwise be replayed on top of the restored database
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = PathBuf::from(format!("{}{}", db_path.to_string_lossy(), suffix));
+        if sidecar.exists() {
+            fs::remove_file(&sidecar)
+                .with_context(|| format!("Failed to remove {:?}", sidecar))?;
+        }
+    }
+
+    fs::copy(source, db_path)
+        .with_context(|| format!("Failed to copy {:?} to {:?}", source, db_path))?;
+    Ok(())
+}
+
+/// Check that `path` is a readable SQLite database containing the expected tables
+async fn validate_backup_file(path: &Path) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+
+    let mut header = [0u8; 16];
+    {
+        use std::io::Read;
+        let mut file = fs::File::open(path)
+            .with_context(|| format!("Failed to open backup file: {:?}", path))?;
+        file.read_exact(&mut header)
+            .map_err(|_| anyhow::anyhow!("所选文件不是有效的SQLite数据库"))?;
+    }
+    if &header != b"SQLite format 3\0" {
+        return Err(anyhow::anyhow!("所选文件不是有效的SQLite数据库"));
+    }
+
+    let options = SqliteConnectOptions::from_str(&path.to_string_lossy())
+        .with_context(|| format!("Failed to create SQLite options for backup: {:?}", path))?
+        .read_only(true);
+    let pool = SqlitePool::connect_with(options).await
+        .with_context(|| format!("Failed to open backup file: {:?}", path))?;
+
+    let validation = async {
+        let integrity: String = sqlx::query_scalar("PRAGMA quick_check")
+            .fetch_one(&pool)
+            .await?;
+        if integrity != "ok" {
+            return Err(anyhow::anyhow!("备份文件已损坏: {}", integrity));
+        }
+
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table'"
+        )
+        .fetch_all(&pool)
+        .await?;
+        let missing: Vec<&str> = REQUIRED_TABLES.iter()
+            .copied()
+            .filter(|table| !tables.iter().any(|name| name == table))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!("备份文件缺少必要的数据表: {}", missing.join(", ")));
+        }
+
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(&pool)
+            .await?;
+        // 新版本写出的备份可能包含本版本无法理解的结构，只接受可迁移的旧版本
+        if version > SCHEMA_VERSION {
+            return Err(anyhow::anyhow!(
+                "备份文件的数据库版本({})高于当前版本({})，请升级应用后再恢复",
+                version, SCHEMA_VERSION
+            ));
+        }
+        if version < SCHEMA_VERSION {
+            let warning = format!(
+                "备份文件的数据库版本({})与当前版本({})不同，恢复后将自动迁移",
+                version, SCHEMA_VERSION
+            );
+            tracing::warn!("{}", warning);
+            warnings.push(warning);
+        }
+        Ok::<(), anyhow::Error>(())
+    }
+    .await;
+
+    pool.close().await;
+    validation?;
+    Ok(warnings)
+}
+
 /// Clear the cached database URL to force path re-resolution
 /// Useful for testing or if the app data directory changes
 pub fn clear_database_cache() {
@@ -690,12 +2662,7 @@ pub async fn get_database_info() -> Result<String> {
     let mut info = Vec::new();
     
     // App handle status - scope the mutex guard
-    let app_handle_exists = {
-        let app_handle = APP_HANDLE.lock().unwrap();
-        app_handle.is_some()
-    }; // Mutex guard is dropped here
-    
-    if app_handle_exists {
+    if app_handle().is_ok() {
         info.push("✓ App handle initialized".to_string());
     } else {
         info.push("✗ App handle not initialized".to_string());
@@ -772,4 +2739,519 @@ pub async fn get_database_info() -> Result<String> {
     }
     
     Ok(info.join("\n"))
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试共用全局连接池，需逐个执行
+    static TEST_DB_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+    /// 在临时目录创建WAL数据库并设为全局连接池，返回数据库文件路径
+    async fn use_temp_database() -> Result<PathBuf> {
+        let path = std::env::temp_dir().join(format!("rightsguard-test-{}.db", Uuid::new_v4()));
+        let pool = SqlitePool::connect_with(create_sqlite_options(&path)?).await?;
+        install_pool(pool).await?;
+        Ok(path)
+    }
+
+    async fn install_pool(pool: SqlitePool) -> Result<()> {
+        // 之前失败的测试可能留下了连接池，它所属的runtime已结束，直接丢弃即可
+        let previous = DB_POOL.write().await.replace(pool);
+        drop(previous);
+        DEGRADED_MODE.store(false, Ordering::SeqCst);
+        init_database().await
+    }
+
+    async fn remove_temp_database(path: &Path) {
+        close_pool().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_reads_and_writes_do_not_surface_lock_errors() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = use_temp_database().await?;
+
+        let mut tasks = Vec::new();
+        for writer in 0..2 {
+            tasks.push(tokio::spawn(async move {
+                for i in 0..50 {
+                    set_setting(&format!("test.writer{}", writer), &i.to_string()).await?;
+                    let pool = get_pool().await?;
+                    retry_on_busy(|| {
+                        sqlx::query("UPDATE automation_status SET updated_at = ?1 WHERE id = 1")
+                            .bind(Utc::now().to_rfc3339())
+                            .execute(&pool)
+                            .timed()
+                    })
+                    .await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            }));
+        }
+        for _ in 0..4 {
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..50 {
+                    get_settings().await?;
+                    get_setting("test.writer0").await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            }));
+        }
+
+        let mut errors = Vec::new();
+        for task in tasks {
+            match task.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => errors.push(format!("{:#}", e)),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        let last_written = get_setting("test.writer0").await;
+        remove_temp_database(&path).await;
+
+        assert!(errors.is_empty(), "lock errors escaped: {:?}", errors);
+        assert_eq!(last_written?.as_deref(), Some("49"));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_first_initializations_do_not_race_on_migrations() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = std::env::temp_dir().join(format!("rightsguard-test-{}.db", Uuid::new_v4()));
+        let pool = SqlitePool::connect_with(create_sqlite_options(&path)?).await?;
+        // 空数据库，所有表和列都还不存在
+        drop(DB_POOL.write().await.replace(pool));
+        DEGRADED_MODE.store(false, Ordering::SeqCst);
+
+        let tasks: Vec<_> = (0..8).map(|_| tokio::spawn(init_database())).collect();
+        let mut errors = Vec::new();
+        for task in tasks {
+            match task.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => errors.push(format!("{:#}", e)),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        remove_temp_database(&path).await;
+
+        assert!(errors.is_empty(), "concurrent initialization failed: {:?}", errors);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn purge_ip_asset_refuses_while_a_case_references_it() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = use_temp_database().await?;
+
+        let result = async {
+            let asset = save_ip_asset(&IpAsset {
+                work_name: "测试作品".to_string(),
+                work_type: "视频".to_string(),
+                owner: "测试权利人".to_string(),
+                ..IpAsset::default()
+            })
+            .await?;
+            let asset_id = asset.id.context("saved asset has no id")?;
+            let case = save_case(&Case {
+                infringing_url: "https://www.bilibili.com/video/BV1test".to_string(),
+                associated_ip_id: Some(asset_id),
+                ..Case::default()
+            })
+            .await?;
+            let case_id = case.id.context("saved case has no id")?;
+
+            match purge_ip_asset(asset_id, false).await? {
+                PurgeIpAssetOutcome::Referenced(ids) => assert_eq!(ids, vec![case_id]),
+                _ => panic!("purge should be refused while a case references the asset"),
+            }
+            assert!(get_ip_asset(asset_id).await?.is_some());
+
+            assert!(purge_case(case_id).await?);
+            assert!(matches!(purge_ip_asset(asset_id, false).await?, PurgeIpAssetOutcome::Deleted));
+            assert!(get_ip_asset(asset_id).await?.is_none());
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        remove_temp_database(&path).await;
+        result
+    }
+
+    #[tokio::test]
+    async fn save_ip_assets_batch_rolls_back_on_a_failed_asset() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = use_temp_database().await?;
+
+        let result = async {
+            let existing = save_ip_asset(&IpAsset {
+                work_name: "原作品".to_string(),
+                work_type: "视频".to_string(),
+                owner: "测试权利人".to_string(),
+                ..IpAsset::default()
+            })
+            .await?;
+            let before: Vec<(String, String)> = sqlx::query_as("SELECT id, work_name FROM ip_assets ORDER BY id")
+                .fetch_all(&get_pool().await?)
+                .await?;
+
+            // 用触发器让批次中的第三条写入失败
+            sqlx::query(
+                "CREATE TRIGGER fail_batch_asset BEFORE INSERT ON ip_assets WHEN NEW.work_name = '失败作品' \
+                 BEGIN SELECT RAISE(ABORT, 'forced failure'); END"
+            )
+            .execute(&get_pool().await?)
+            .await?;
+
+            let batch = vec![
+                IpAsset { work_name: "已修改".to_string(), ..existing.clone() },
+                IpAsset { work_name: "新作品".to_string(), work_type: "视频".to_string(), owner: "测试权利人".to_string(), ..IpAsset::default() },
+                IpAsset { work_name: "失败作品".to_string(), work_type: "视频".to_string(), owner: "测试权利人".to_string(), ..IpAsset::default() },
+            ];
+            let error = save_ip_assets_batch(batch).await.err().context("batch with a failing asset should fail")?;
+            assert!(format!("{:#}", error).contains("#3"), "unexpected error: {:#}", error);
+
+            let after: Vec<(String, String)> = sqlx::query_as("SELECT id, work_name FROM ip_assets ORDER BY id")
+                .fetch_all(&get_pool().await?)
+                .await?;
+            assert_eq!(after, before);
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        remove_temp_database(&path).await;
+        result
+    }
+
+    #[tokio::test]
+    async fn save_ip_assets_batch_keeps_the_stored_file_order() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = use_temp_database().await?;
+
+        let result = async {
+            let asset = IpAsset {
+                id: Some(Uuid::new_v4()),
+                work_name: "测试作品".to_string(),
+                work_type: "视频".to_string(),
+                owner: "测试权利人".to_string(),
+                auth_files: file_list(&[FRONT, BACK]),
+                work_proof_files: file_list(&[BACK, EXTRA]),
+                ..IpAsset::default()
+            };
+            save_ip_assets_batch(vec![asset.clone()]).await?;
+
+            // 前端重新排序后整批再次保存
+            let resaved = save_ip_assets_batch(vec![IpAsset {
+                auth_files: file_list(&[BACK, FRONT]),
+                work_proof_files: file_list(&[EXTRA, BACK]),
+                ..asset.clone()
+            }])
+            .await?;
+            assert_eq!(resaved[0].auth_files, file_list(&[FRONT, BACK]));
+            assert_eq!(resaved[0].work_proof_files, file_list(&[BACK, EXTRA]));
+
+            let stored = get_ip_asset(asset.id.context("asset has no id")?).await?.context("asset missing")?;
+            assert_eq!(stored.auth_files, file_list(&[FRONT, BACK]));
+            assert_eq!(stored.work_proof_files, file_list(&[BACK, EXTRA]));
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        remove_temp_database(&path).await;
+        result
+    }
+
+    async fn save_and_read_profile_repeatedly() -> Result<()> {
+        for i in 0..100 {
+            let saved = save_profile(&Profile {
+                name: format!("测试用户{}", i),
+                phone: "13800138000".to_string(),
+                email: "test@example.com".to_string(),
+                id_card_number: "11010519491231002X".to_string(),
+                ..Profile::default()
+            })
+            .await
+            .with_context(|| format!("save #{} failed", i))?;
+            let id = saved.id.context("saved profile has no id")?;
+            let read_back = get_profile_by_id(id).await?.with_context(|| format!("profile #{} not readable after save", i))?;
+            assert_eq!(read_back.name, format!("测试用户{}", i));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn saved_profile_is_immediately_readable_from_file_database() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = use_temp_database().await?;
+        let result = save_and_read_profile_repeatedly().await;
+        remove_temp_database(&path).await;
+        result
+    }
+
+    #[tokio::test]
+    async fn saved_profile_is_immediately_readable_from_memory_database() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        install_pool(connect_memory_pool().await?).await?;
+        let result = save_and_read_profile_repeatedly().await;
+        close_pool().await;
+        result
+    }
+
+    #[tokio::test]
+    async fn prepare_only_run_is_not_counted_as_a_submission() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = use_temp_database().await?;
+
+        let result = async {
+            let since = Utc::now() - Duration::hours(1);
+            let url = "https://www.bilibili.com/video/BV1prepare";
+            let before = count_submissions_since(since).await?;
+
+            let prepared = record_case_submission(url, None, None, false, false, None, None).await?;
+            assert_eq!(prepared.status, CASE_STATUS_PREPARED);
+            assert!(prepared.submission_date.is_none());
+            assert_eq!(count_submissions_since(since).await?, before);
+
+            // 再次只填写不改变已有案件
+            let prepared_again = record_case_submission(url, None, None, false, false, None, None).await?;
+            assert_eq!(prepared_again.id, prepared.id);
+            assert_eq!(prepared_again.status, CASE_STATUS_PREPARED);
+            assert_eq!(count_submissions_since(since).await?, before);
+
+            let submitted = record_case_submission(url, None, None, false, true, None, None).await?;
+            assert_eq!(submitted.id, prepared.id);
+            assert_eq!(submitted.status, CASE_STATUS_SUBMITTED);
+            assert_eq!(count_submissions_since(since).await?, before + 1);
+            let events = get_case_events(prepared.id.context("prepared case has no id")?).await?;
+            assert_eq!(events.iter().map(|event| event.event.as_str()).collect::<Vec<_>>(), vec![CASE_EVENT_SUBMITTED]);
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        remove_temp_database(&path).await;
+        result
+    }
+
+    #[tokio::test]
+    async fn degraded_database_rejects_every_write() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        install_pool(connect_memory_pool().await?).await?;
+        DEGRADED_MODE.store(true, Ordering::SeqCst);
+
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let request: AutomationRequest = serde_json::from_value(serde_json::json!({
+            "infringing_url": "https://www.bilibili.com/video/BV1degraded",
+            "original_url": null,
+            "ip_asset_id": null,
+        }))?;
+        let status: AutomationStatus = serde_json::from_value(serde_json::json!({
+            "isRunning": false,
+            "currentStep": null,
+            "progress": null,
+            "error": null,
+            "startedAt": null,
+            "finishedAt": null,
+            "durationSecs": null,
+            "errorCode": null,
+        }))?;
+
+        let results: Vec<(&str, Result<()>)> = vec![
+            ("save_profile", save_profile(&Profile::default()).await.map(drop)),
+            ("set_id_card_roles", set_id_card_roles(id, &[]).await.map(drop)),
+            ("relink_profile_file", relink_profile_file("a.jpg", "b.jpg").await.map(drop)),
+            ("repair_file_columns", repair_file_columns(false).await.map(drop)),
+            ("set_setting", set_setting(SETTING_LOCALE, "en-US").await),
+            ("save_settings", save_settings(&AppSettings::default()).await.map(drop)),
+            ("save_ip_asset", save_ip_asset(&IpAsset::default()).await.map(drop)),
+            ("save_ip_assets_batch", save_ip_assets_batch(vec![IpAsset::default()]).await.map(drop)),
+            ("delete_ip_asset", delete_ip_asset(id).await.map(drop)),
+            ("restore_ip_asset", restore_ip_asset(id).await.map(drop)),
+            ("purge_ip_asset", purge_ip_asset(id, true).await.map(drop)),
+            ("save_case", save_case(&Case::default()).await.map(drop)),
+            ("record_case_submission", record_case_submission(&request.infringing_url, None, None, true, true, None, None).await.map(drop)),
+            ("delete_case", delete_case(id).await.map(drop)),
+            ("restore_case", restore_case(id).await.map(drop)),
+            ("purge_case", purge_case(id).await.map(drop)),
+            ("save_automation_status", save_automation_status(&status).await),
+            ("insert_automation_run", insert_automation_run(id, &request, now).await),
+            ("finish_automation_run", finish_automation_run(id, RUN_OUTCOME_FAILED, &status).await),
+            ("save_run_result", save_run_result(id, &RunResult::default()).await),
+            ("clear_stale_running_status", clear_stale_running_status().await.map(drop)),
+            ("mark_unfinished_runs_interrupted", mark_unfinished_runs_interrupted().await.map(drop)),
+            ("insert_scheduled_run", insert_scheduled_run(&request, now).await.map(drop)),
+            ("cancel_scheduled_run", cancel_scheduled_run(id).await.map(drop)),
+            ("transition_scheduled_run", transition_scheduled_run(id, SCHEDULE_STATUS_PENDING, SCHEDULE_STATUS_STARTED, None).await.map(drop)),
+            ("run_maintenance", run_maintenance().await.map(drop)),
+        ];
+
+        DEGRADED_MODE.store(false, Ordering::SeqCst);
+        close_pool().await;
+
+        for (operation, result) in results {
+            match result {
+                Err(e) if e.is::<DegradedModeError>() => {}
+                Err(e) => panic!("{} failed with an unexpected error: {:#}", operation, e),
+                Ok(()) => panic!("{} succeeded on a degraded database", operation),
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn repair_skips_rows_changed_after_they_were_read() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = use_temp_database().await?;
+
+        let result = async {
+            let pool = get_pool().await?;
+            let profile = save_profile(&Profile {
+                name: "测试用户".to_string(),
+                id_card_files: file_list(&["missing/front.jpg"]),
+                ..Profile::default()
+            })
+            .await?;
+            let profile_id = profile.id.context("saved profile has no id")?.to_string();
+
+            // 模拟修复计算之后、写入之前用户保存了新的文件列表
+            let stale_repair = FileColumnRepair {
+                table: "profiles".to_string(),
+                row_id: profile_id.clone(),
+                column: "id_card_files".to_string(),
+                before: file_list(&["missing/front.jpg"]),
+                after: None,
+                dropped_paths: vec!["missing/front.jpg".to_string()],
+            };
+            sqlx::query("UPDATE profiles SET id_card_files = ?1 WHERE id = ?2")
+                .bind(file_list(&["missing/back.jpg"]))
+                .bind(&profile_id)
+                .execute(&pool)
+                .await?;
+            let mut conn = pool.acquire().await?;
+            assert!(!apply_file_column_repair(&mut conn, &stale_repair, &Utc::now().to_rfc3339()).await?);
+            drop(conn);
+            let files: Option<String> = sqlx::query_scalar("SELECT id_card_files FROM profiles WHERE id = ?1")
+                .bind(&profile_id)
+                .fetch_one(&pool)
+                .await?;
+            assert_eq!(files, file_list(&["missing/back.jpg"]));
+
+            // 未被并发修改的行照常修复
+            let report = repair_file_columns(false).await?;
+            assert!(report.repairs.iter().any(|repair| repair.row_id == profile_id && repair.after.is_none()));
+            let files: Option<String> = sqlx::query_scalar("SELECT id_card_files FROM profiles WHERE id = ?1")
+                .bind(&profile_id)
+                .fetch_one(&pool)
+                .await?;
+            assert_eq!(files, None);
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        remove_temp_database(&path).await;
+        result
+    }
+
+    /// 建一个只有最初数据表的数据库文件，模拟旧版本写出的备份
+    async fn create_old_schema_backup(user_version: i64) -> Result<PathBuf> {
+        let path = std::env::temp_dir().join(format!("rightsguard-backup-{}.db", Uuid::new_v4()));
+        let pool = SqlitePool::connect_with(create_sqlite_options(&path)?).await?;
+        for statement in [
+            "CREATE TABLE profiles (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE ip_assets (id INTEGER PRIMARY KEY, work_name TEXT NOT NULL)",
+            "CREATE TABLE cases (id INTEGER PRIMARY KEY, infringing_url TEXT NOT NULL)",
+            "CREATE TABLE automation_status (id INTEGER PRIMARY KEY, is_running INTEGER NOT NULL DEFAULT 0, updated_at TEXT)",
+        ] {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        sqlx::query(&format!("PRAGMA user_version = {}", user_version))
+            .execute(&pool)
+            .await?;
+        // 备份文件以只读方式校验，改回非WAL模式以便单独打开
+        sqlx::query("PRAGMA journal_mode = DELETE").execute(&pool).await?;
+        pool.close().await;
+        Ok(path)
+    }
+
+    #[tokio::test]
+    async fn older_schema_backup_is_accepted_and_migrated() -> Result<()> {
+        let _guard = TEST_DB_LOCK.lock().await;
+        let path = create_old_schema_backup(1).await?;
+
+        let result = async {
+            let warnings = validate_backup_file(&path).await?;
+            assert_eq!(warnings.len(), 1, "expected a migration warning: {:?}", warnings);
+
+            // 恢复后会对该文件重新执行init_database
+            install_pool(SqlitePool::connect_with(create_sqlite_options(&path)?).await?).await?;
+            let pool = get_pool().await?;
+            let version: i64 = sqlx::query_scalar("PRAGMA user_version").fetch_one(&pool).await?;
+            assert_eq!(version, SCHEMA_VERSION);
+            let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info('cases')")
+                .fetch_all(&pool)
+                .await?;
+            assert!(columns.iter().any(|c| c == "archived"), "cases not migrated: {:?}", columns);
+            assert!(get_automation_history(None, None).await?.is_empty());
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        remove_temp_database(&path).await;
+        result
+    }
+
+    #[tokio::test]
+    async fn newer_schema_backup_is_refused() -> Result<()> {
+        let path = create_old_schema_backup(SCHEMA_VERSION + 1).await?;
+        let result = validate_backup_file(&path).await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+
+        let error = result.expect_err("a backup from a newer schema must be refused");
+        assert!(error.to_string().contains("高于当前版本"), "unexpected error: {:#}", error);
+        Ok(())
+    }
+
+    fn file_list(paths: &[&str]) -> Option<String> {
+        Some(serde_json::to_string(paths).unwrap())
+    }
+
+    const FRONT: &str = "files/id_card/front_0f8c4a52-6a0e-4f4e-9d56-3b1f7d1e2a01.jpg";
+    const BACK: &str = "files/id_card/back_5b7e2c1d-91a4-4c3b-8f0e-6d2a9c4b7e02.jpg";
+    const EXTRA: &str = "files/id_card/extra_a3d9e6f1-2b4c-4d8e-b5a7-1c6f0e9d3b03.png";
+
+    #[test]
+    fn merge_file_list_keeps_a_resubmitted_list_unchanged() -> Result<()> {
+        let existing = file_list(&[FRONT, BACK]);
+        assert_eq!(merge_file_list(&existing, &existing)?, existing);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_file_list_keeps_stored_order_for_a_reordered_list() -> Result<()> {
+        let merged = merge_file_list(&file_list(&[FRONT, BACK]), &file_list(&[BACK, FRONT]))?;
+        assert_eq!(merged, file_list(&[FRONT, BACK]));
+        Ok(())
+    }
+
+    #[test]
+    fn merge_file_list_drops_files_missing_from_the_incoming_list() -> Result<()> {
+        let merged = merge_file_list(&file_list(&[FRONT, BACK, EXTRA]), &file_list(&[FRONT, EXTRA]))?;
+        assert_eq!(merged, file_list(&[FRONT, EXTRA]));
+        Ok(())
+    }
+
+    #[test]
+    fn merge_file_list_matches_legacy_paths_without_uuid_by_path() -> Result<()> {
+        let legacy = "C:/Users/test/Documents/身份证.jpg";
+        assert_eq!(stored_file_id(legacy), legacy);
+
+        let merged = merge_file_list(&file_list(&[legacy, FRONT]), &file_list(&[FRONT, legacy, legacy, BACK]))?;
+        assert_eq!(merged, file_list(&[legacy, FRONT, BACK]));
+        Ok(())
+    }
+}
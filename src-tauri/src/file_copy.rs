@@ -0,0 +1,149 @@
+// src-tauri/src/file_copy.rs
+//
+// 分块复制用户选择的文件，大文件(网络盘上的扫描件)复制时向前端报告进度，并可随时取消
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+use crate::models::FileCopyProgress;
+
+pub const FILE_COPY_PROGRESS_EVENT: &str = "file-copy-progress";
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+// 小于此大小的文件复制很快，不发送进度事件
+const PROGRESS_THRESHOLD_BYTES: u64 = 4 * 1024 * 1024;
+
+// 进行中的复制: copy_id -> 取消标记，同一批拖放的文件共用一个copy_id
+static ACTIVE_COPIES: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 复制被cancel_file_copy取消时返回的错误
+#[derive(Debug, thiserror::Error)]
+#[error("文件复制已取消")]
+pub struct CopyCancelled;
+
+/// copy_id由前端提供，已被进行中的复制使用时拒绝登记，避免一个复制注销或取消另一个复制
+#[derive(Debug, thiserror::Error)]
+#[error("复制ID已在使用: {0}")]
+pub struct CopyIdInUse(pub String);
+
+/// 登记一次复制，guard释放时自动注销
+pub struct CopyRegistration {
+    copy_id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CopyRegistration {
+    pub fn new(copy_id: &str) -> std::result::Result<Self, CopyIdInUse> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut active = ACTIVE_COPIES.lock().unwrap_or_else(|e| e.into_inner());
+        if active.contains_key(copy_id) {
+            return Err(CopyIdInUse(copy_id.to_string()));
+        }
+        active.insert(copy_id.to_string(), cancelled.clone());
+        Ok(Self { copy_id: copy_id.to_string(), cancelled })
+    }
+
+    pub fn copy_id(&self) -> &str {
+        &self.copy_id
+    }
+
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+}
+
+impl Drop for CopyRegistration {
+    fn drop(&mut self) {
+        ACTIVE_COPIES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.copy_id);
+    }
+}
+
+/// 请求取消指定的复制，没有进行中的复制时返回false
+pub fn cancel(copy_id: &str) -> bool {
+    match ACTIVE_COPIES.lock().unwrap_or_else(|e| e.into_inner()).get(copy_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 分块复制文件，每块之间检查取消标记；失败或取消时删除已写入的部分文件
+/// 阻塞调用，需在spawn_blocking中执行
+pub fn copy_with_progress(source: &Path, target: &Path, copy_id: &str, cancelled: &AtomicBool) -> Result<u64> {
+    let result = copy_chunks(source, target, copy_id, cancelled);
+    if result.is_err() {
+        let _ = std::fs::remove_file(target);
+    }
+    result
+}
+
+fn copy_chunks(source: &Path, target: &Path, copy_id: &str, cancelled: &AtomicBool) -> Result<u64> {
+    let mut reader = File::open(source).with_context(|| format!("无法打开文件: {}", source.display()))?;
+    let total_bytes = reader.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    let mut writer = File::create(target).with_context(|| format!("无法创建文件: {}", target.display()))?;
+    let report = total_bytes >= PROGRESS_THRESHOLD_BYTES;
+
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut copied_bytes = 0u64;
+    loop {
+        if cancelled.load(Ordering::SeqCst) {
+            return Err(CopyCancelled.into());
+        }
+        let read = reader.read(&mut buffer).with_context(|| format!("读取文件失败: {}", source.display()))?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read]).with_context(|| format!("写入文件失败: {}", target.display()))?;
+        copied_bytes += read as u64;
+        if report {
+            emit_progress(copy_id, source, copied_bytes, total_bytes);
+        }
+    }
+    writer.sync_all().with_context(|| format!("写入文件失败: {}", target.display()))?;
+    Ok(copied_bytes)
+}
+
+fn emit_progress(copy_id: &str, source: &Path, copied_bytes: u64, total_bytes: u64) {
+    let progress = FileCopyProgress {
+        copy_id: copy_id.to_string(),
+        source_path: source.to_string_lossy().to_string(),
+        copied_bytes,
+        total_bytes,
+    };
+    if let Ok(app_handle) = crate::database::app_handle() {
+        if let Err(e) = app_handle.emit(FILE_COPY_PROGRESS_EVENT, progress) {
+            tracing::warn!("发送文件复制进度事件失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_copy_id_in_use_cannot_be_registered_twice() {
+        let copy_id = format!("test-{}", uuid::Uuid::new_v4());
+        let first = CopyRegistration::new(&copy_id).expect("first registration");
+        assert!(CopyRegistration::new(&copy_id).is_err());
+
+        // 重复登记失败不会注销第一个复制
+        assert!(cancel(&copy_id));
+        assert!(first.flag().load(Ordering::SeqCst));
+
+        drop(first);
+        assert!(CopyRegistration::new(&copy_id).is_ok());
+    }
+}
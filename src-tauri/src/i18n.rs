@@ -0,0 +1,149 @@
+// src-tauri/src/i18n.rs
+//
+// 面向用户的状态文本和错误信息的多语言表。后端同时发送消息key和按设置语言渲染的文本，
+// 前端可以直接显示文本，也可以按key自行翻译
+
+use crate::models::Locale;
+use std::sync::RwLock;
+
+// 由设置中的locale决定，启动时和保存设置后更新
+static CURRENT_LOCALE: RwLock<Locale> = RwLock::new(Locale::ZhCn);
+
+/// (key, 中文, English)，参数用{0}、{1}表示
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // 自动化步骤
+    ("step.initializing", "初始化", "Initializing"),
+    ("step.retrying", "第{0}次重试，等待{1}秒...", "Retry {0}, waiting {1}s..."),
+    ("step.fetching_data", "获取数据...", "Loading data..."),
+    ("step.launching_browser", "启动浏览器...", "Launching browser..."),
+    ("step.generating_script", "生成连接脚本...", "Generating connection script..."),
+    ("step.starting_playwright", "正在启动Playwright测试...", "Starting Playwright..."),
+    ("step.script_finished", "Playwright脚本执行完成", "Playwright script finished"),
+    ("step.native_filling", "使用原生Playwright后端填写表单...", "Filling the form with the native Playwright backend..."),
+    ("step.native_finished", "原生Playwright后端执行完成", "Native Playwright backend finished"),
+    ("step.native_opening", "原生后端: 打开申诉页面...", "Native backend: opening the appeal page..."),
+    ("step.native_personal_info", "原生后端: 填写个人信息...", "Native backend: filling in personal information..."),
+    ("step.awaiting_captcha", "等待用户完成验证码...", "Waiting for you to complete the captcha..."),
+    ("step.filling_ip_asset", "填写IP资产信息...", "Filling in IP asset information..."),
+    ("step.filling_details", "填写申诉详情...", "Filling in appeal details..."),
+    ("step.personal_info_filled", "个人信息已填写，上传身份证...", "Personal information filled, uploading ID card..."),
+    ("step.uploading_id_card", "上传身份证文件 {0}/{1}", "Uploading ID card file {0}/{1}"),
+    ("step.captcha_completed", "验证码已完成，填写IP资产...", "Captcha completed, filling in IP asset..."),
+    ("step.auth_files_uploaded", "授权证明已上传", "Authorization proof uploaded"),
+    ("step.proof_files_uploaded", "作品证明已上传", "Work proof uploaded"),
+    ("step.ip_asset_filled", "IP资产已填写，填写申诉详情...", "IP asset filled, filling in appeal details..."),
+    ("step.details_filled", "申诉详情已填写", "Appeal details filled"),
+    ("step.submitted_confirming", "申诉已提交，确认结果...", "Appeal submitted, confirming the result..."),
+    ("step.test_run_finished", "测试运行完成，未提交申诉", "Test run finished, no appeal was submitted"),
+    ("step.submitted", "申诉提交成功", "Appeal submitted"),
+    ("step.awaiting_confirmation", "等待用户确认提交", "Waiting for you to confirm the submission"),
+    ("step.failed", "失败", "Failed"),
+    ("step.stopped", "已停止", "Stopped"),
+    ("step.reset", "已重置", "Reset"),
+    // 自动化错误，key为error.加错误代码
+    ("error.not_logged_in", "未登录B站账号，请先点击\"登录B站\"在打开的浏览器中完成登录后重试", "Not logged in to Bilibili. Click \"Log in to Bilibili\", finish logging in in the opened browser, then retry"),
+    ("error.verification_timeout", "等待验证码完成超时，请在5分钟内输入验证码并点击下一步", "Timed out waiting for the captcha. Enter the captcha and click Next within 5 minutes"),
+    ("error.file_not_found", "文件不存在: {0}", "File not found: {0}"),
+    ("error.npx_not_found", "未找到npx，请确认已安装Node.js并加入PATH", "npx was not found. Make sure Node.js is installed and on PATH"),
+    ("error.form_validation", "B站表单校验未通过: {0}", "Bilibili rejected the form: {0}"),
+    ("error.fields_not_filled", "部分字段未能填入，请在页面上检查后重试: {0}", "Some fields could not be filled, check the page and retry: {0}"),
+    ("error.missing_data", "{0}", "{0}"),
+    ("error.self_test_failed", "自测未通过: {0}", "Self-test failed: {0}"),
+    ("error.submit_not_confirmed", "未能确认申诉已提交: {0}", "Could not confirm the appeal was submitted: {0}"),
+    ("error.quota_exceeded", "B站申诉次数已达上限，请明天再试: {0}", "Bilibili's appeal limit has been reached, try again tomorrow: {0}"),
+    ("error.browser_closed", "Chrome在运行中被关闭，申诉未完成。请重新开始申诉，运行期间不要关闭自动打开的浏览器", "Chrome was closed during the run and the appeal was not finished. Start the appeal again and keep the opened browser running"),
+    ("error.deadline_exceeded", "运行超过总时限{0}秒，已在\"{1}\"阶段终止", "The run exceeded its {0}s time limit and was stopped at \"{1}\""),
+];
+
+pub fn set_locale(locale: Locale) {
+    *CURRENT_LOCALE.write().unwrap_or_else(|e| e.into_inner()) = locale;
+}
+
+pub fn current_locale() -> Locale {
+    *CURRENT_LOCALE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 一条可本地化的消息: key加渲染时代入的参数
+#[derive(Debug, Clone)]
+pub struct Message {
+    key: &'static str,
+    args: Vec<String>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Self { key, args: Vec::new() }
+    }
+
+    pub fn with_args(key: &'static str, args: Vec<String>) -> Self {
+        Self { key, args }
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// 按当前设置的语言渲染
+    pub fn text(&self) -> String {
+        self.render(current_locale())
+    }
+
+    /// 表中没有的key原样返回
+    pub fn render(&self, locale: Locale) -> String {
+        let Some((_, zh, en)) = MESSAGES.iter().find(|(key, _, _)| *key == self.key) else {
+            tracing::warn!("缺少本地化文本: {}", self.key);
+            return self.key.to_string();
+        };
+        let template = match locale {
+            Locale::ZhCn => zh,
+            Locale::EnUs => en,
+        };
+        substitute(template, &self.args)
+    }
+}
+
+// 单次扫描替换{0}、{1}等占位符，参数本身包含的"{1}"之类文本不会被再次替换；
+// 没有对应参数的占位符原样保留
+fn substitute(template: &str, args: &[String]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let arg = after.find('}')
+            .and_then(|close| after[..close].parse::<usize>().ok().map(|index| (index, close)))
+            .and_then(|(index, close)| args.get(index).map(|arg| (arg, close)));
+        match arg {
+            Some((arg, close)) => {
+                text.push_str(arg);
+                rest = &after[close + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_in_arguments_are_not_substituted_again() {
+        let args = vec!["{1}".to_string(), "第二个".to_string()];
+        assert_eq!(substitute("{0} / {1}", &args), "{1} / 第二个");
+    }
+
+    #[test]
+    fn placeholders_without_an_argument_are_kept() {
+        assert_eq!(substitute("{0} {2} {x}", &["a".to_string()]), "a {2} {x}");
+    }
+}
@@ -0,0 +1,91 @@
+// src-tauri/src/image_sanitize.rs
+//
+// 上传前去除图片中的EXIF元数据 (GPS位置、拍摄设备等)，手机拍摄的身份证照片尤其需要
+
+use anyhow::{Context, Result};
+use image::metadata::Orientation;
+use image::{ImageFormat, ImageReader};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+// 重新编码JPEG时使用的质量，肉眼与原图无差别
+const JPEG_QUALITY: u8 = 95;
+
+/// 去除图片中的EXIF元数据并覆盖保存，返回被去除的标签名称
+/// 非图片、没有EXIF或格式不支持重新编码的文件保持不变，返回空列表
+pub fn strip_exif(path: &Path) -> Result<Vec<String>> {
+    let Some(format) = reencodable_format(path) else {
+        return Ok(Vec::new());
+    };
+
+    let exif = {
+        let file = File::open(path).with_context(|| format!("无法打开图片: {}", path.display()))?;
+        match exif::Reader::new().read_from_container(&mut BufReader::new(file)) {
+            Ok(exif) => exif,
+            // 没有EXIF时不重新编码，保持文件原样
+            Err(exif::Error::NotFound(_)) => return Ok(Vec::new()),
+            Err(e) => {
+                tracing::warn!("读取EXIF失败，按包含元数据处理: {}: {}", path.display(), e);
+                return reencode(path, format, None).map(|_| vec!["(无法解析的EXIF)".to_string()]);
+            }
+        }
+    };
+
+    let mut stripped: Vec<String> = exif.fields().map(|field| field.tag.to_string()).collect();
+    stripped.sort();
+    stripped.dedup();
+
+    // 去掉方向标签后需要把旋转应用到像素上，否则手机竖拍的照片会显示为横向
+    let orientation = exif
+        .get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .and_then(|value| u8::try_from(value).ok())
+        .and_then(Orientation::from_exif);
+    reencode(path, format, orientation)?;
+    Ok(stripped)
+}
+
+// 只处理image crate能无元数据重新保存的格式
+fn reencodable_format(path: &Path) -> Option<ImageFormat> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+        "png" => Some(ImageFormat::Png),
+        "webp" => Some(ImageFormat::WebP),
+        "tif" | "tiff" => Some(ImageFormat::Tiff),
+        _ => None,
+    }
+}
+
+// 解码后重新编码，image crate的编码器不会写出原图的元数据
+fn reencode(path: &Path, format: ImageFormat, orientation: Option<Orientation>) -> Result<()> {
+    let mut img = ImageReader::open(path)
+        .with_context(|| format!("无法打开图片: {}", path.display()))?
+        .with_guessed_format()?
+        .decode()
+        .with_context(|| format!("无法解码图片: {}", path.display()))?;
+    if let Some(orientation) = orientation {
+        img.apply_orientation(orientation);
+    }
+
+    // 先写临时文件再替换，避免编码失败时损坏原文件
+    let temp_path = path.with_extension("sanitizing");
+    let result = (|| -> Result<()> {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        match format {
+            ImageFormat::Jpeg => {
+                let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, JPEG_QUALITY);
+                img.to_rgb8().write_with_encoder(encoder)?;
+            }
+            _ => img.write_to(&mut writer, format)?,
+        }
+        drop(writer);
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result.with_context(|| format!("无法保存去除元数据后的图片: {}", path.display()))
+}
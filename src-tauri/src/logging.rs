@@ -0,0 +1,179 @@
+use anyhow::{Result, Context};
+use chrono::Local;
+use once_cell::sync::OnceCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use uuid::Uuid;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+const LOG_FILE_NAME: &str = "rightsguard.log";
+
+// Keeps the non-blocking file writer alive for the lifetime of the process
+static LOG_GUARD: OnceCell<WorkerGuard> = OnceCell::new();
+
+/// Initialize tracing with a daily rolling file layer under `{app_data}/logs`
+/// The console layer is only attached in debug builds
+/// The level defaults to INFO and can be overridden with `RUST_LOG` (e.g. `RUST_LOG=debug`)
+pub fn init_logging(app_data_dir: Option<PathBuf>) {
+    let console_layer = if cfg!(debug_assertions) {
+        Some(fmt::layer())
+    } else {
+        None
+    };
+
+    let file_layer = app_data_dir
+        .map(|dir| dir.join("logs"))
+        .and_then(|log_dir| match fs::create_dir_all(&log_dir) {
+            Ok(_) => Some(log_dir),
+            Err(e) => {
+                eprintln!("Failed to create log directory {:?}: {}", log_dir, e);
+                None
+            }
+        })
+        .map(|log_dir| {
+            let appender = tracing_appender::rolling::daily(&log_dir, LOG_FILE_NAME);
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = LOG_GUARD.set(guard);
+            fmt::layer().with_writer(writer).with_ansi(false)
+        });
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(console_layer)
+        .with(file_layer)
+        .init();
+}
+
+/// Log every panic with its location and keep a crash report next to the regular logs
+/// Installed at the very start of `main`; the default hook still runs afterwards
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("unnamed");
+
+        tracing::error!("程序崩溃 [thread {}] at {}: {}", thread_name, location, message);
+        // 文件日志是异步写入的，进程退出时可能来不及落盘，这里再同步写一份
+        let crash_log = write_crash_log(&message, &location, thread_name).ok();
+
+        // 弹窗需要主线程的事件循环处理，主线程自身崩溃时无法弹出，只保留日志
+        if thread_name != "main" {
+            show_crash_dialog(&message, &location, crash_log.as_deref());
+        }
+
+        default_hook(info);
+    }));
+}
+
+fn write_crash_log(message: &str, location: &str, thread_name: &str) -> Result<PathBuf> {
+    let crash_log = get_log_dir()?.join(format!("crash-{}.log", Local::now().format("%Y%m%d-%H%M%S")));
+    let content = format!(
+        "time: {}\nthread: {}\nlocation: {}\nmessage: {}\n\n{}\n",
+        Local::now().to_rfc3339(),
+        thread_name,
+        location,
+        message,
+        std::backtrace::Backtrace::force_capture()
+    );
+    fs::write(&crash_log, content)
+        .with_context(|| format!("Failed to write crash log: {:?}", crash_log))?;
+    Ok(crash_log)
+}
+
+fn show_crash_dialog(message: &str, location: &str, crash_log: Option<&Path>) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+    let Ok(handle) = crate::database::app_handle() else {
+        return;
+    };
+    if handle.get_webview_window("main").is_none() {
+        return;
+    }
+
+    let log_hint = crash_log
+        .map(|path| format!("\n\n崩溃日志: {}", path.display()))
+        .unwrap_or_default();
+    handle
+        .dialog()
+        .message(format!("程序发生未处理的错误:\n{}\n位置: {}{}", message, location, log_hint))
+        .title("RightsGuard 错误")
+        .kind(MessageDialogKind::Error)
+        .blocking_show();
+}
+
+/// Resolve `{app_data}/logs`, creating it if necessary
+pub fn get_log_dir() -> Result<PathBuf> {
+    let handle = crate::database::app_handle()?;
+
+    let log_dir = handle.path().app_data_dir()
+        .context("Failed to get app data directory")?
+        .join("logs");
+    fs::create_dir_all(&log_dir)
+        .with_context(|| format!("Failed to create log directory: {:?}", log_dir))?;
+    Ok(log_dir)
+}
+
+/// Write the full Playwright output of a single run to `{app_data}/logs/run-{timestamp}[-{run_id}].log`
+pub fn write_run_log(stdout: &str, stderr: &str, run_id: Option<Uuid>) -> Result<PathBuf> {
+    let log_dir = get_log_dir()?;
+    let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+    let run_log = match run_id {
+        Some(run_id) => log_dir.join(format!("run-{}-{}.log", timestamp, run_id)),
+        None => log_dir.join(format!("run-{}.log", timestamp)),
+    };
+
+    let content = format!(
+        "===== STDOUT =====\n{}\n\n===== STDERR =====\n{}\n",
+        stdout, stderr
+    );
+    fs::write(&run_log, content)
+        .with_context(|| format!("Failed to write run log: {:?}", run_log))?;
+
+    tracing::info!("Playwright运行日志已保存: {:?}", run_log);
+    Ok(run_log)
+}
+
+/// Return the last `lines` lines of the most recent application log file
+pub fn read_recent_logs(lines: usize) -> Result<String> {
+    let log_dir = get_log_dir()?;
+    let latest = find_latest_log_file(&log_dir)?
+        .ok_or_else(|| anyhow::anyhow!("No log file found in {:?}", log_dir))?;
+
+    let content = fs::read_to_string(&latest)
+        .with_context(|| format!("Failed to read log file: {:?}", latest))?;
+    let all_lines: Vec<&str> = content.lines().collect();
+    let start = all_lines.len().saturating_sub(lines);
+    Ok(all_lines[start..].join("\n"))
+}
+
+// The rolling appender suffixes the file name with the date, so pick the newest one
+fn find_latest_log_file(log_dir: &Path) -> Result<Option<PathBuf>> {
+    let mut latest: Option<(std::time::SystemTime, PathBuf)> = None;
+
+    for entry in fs::read_dir(log_dir)? {
+        let entry = entry?;
+        if !entry.file_name().to_string_lossy().starts_with(LOG_FILE_NAME) {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if latest.as_ref().map_or(true, |(time, _)| modified > *time) {
+            latest = Some((modified, entry.path()));
+        }
+    }
+
+    Ok(latest.map(|(_, path)| path))
+}
@@ -4,27 +4,44 @@
 use tauri::{
     menu::{MenuBuilder, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder},
-    Manager
+    Listener, Manager
 };
 
 // 引入模块
 mod database;
 mod automation;
+mod bilibili_script;
+mod browser;
 mod models;
 mod commands;
+mod logging;
+mod pdf_convert;
+mod case_export;
+mod catalog_import;
+mod file_copy;
+mod i18n;
+mod image_sanitize;
+mod scheduler;
+mod run_queue;
+mod setup;
+#[cfg(feature = "native-playwright")]
+mod native_backend;
 
 use commands::*;
 
 fn main() {
-    // 初始化日志
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
+    // 在数据库初始化和托盘设置之前安装，确保这些阶段的崩溃也会被记录
+    logging::install_panic_hook();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
+            // 初始化日志 (写入应用数据目录下的logs文件夹)
+            logging::init_logging(app.path().app_data_dir().ok());
+
             // 设置数据库应用程序句柄
             let app_handle = app.handle().clone();
             database::set_app_handle(app_handle);
@@ -34,21 +51,53 @@ fn main() {
                 if let Err(e) = database::init_database().await {
                     eprintln!("Failed to initialize database: {}", e);
                     tracing::error!("Database initialization failed: {:?}", e);
+                } else {
+                    // 状态文本和错误信息使用设置中的语言
+                    match database::get_settings().await {
+                        Ok(settings) => i18n::set_locale(settings.locale),
+                        Err(e) => tracing::warn!("读取语言设置失败，使用中文: {}", e),
+                    }
+                    // 清理上次崩溃遗留的运行状态
+                    automation::recover_interrupted_run().await;
                 }
             });
 
+            // 启动计划运行的后台轮询和运行队列
+            scheduler::spawn_scheduler(app.handle().clone());
+            run_queue::spawn_worker();
+
             // 设置系统托盘
             let show_item = MenuItem::with_id(app, "show", "显示", true, None::<&str>)?;
             let hide_item = MenuItem::with_id(app, "hide", "隐藏", true, None::<&str>)?;
             let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+            let automation_item = MenuItem::with_id(app, "automation_status", "自动化: 空闲", false, None::<&str>)?;
+            let stop_item = MenuItem::with_id(app, "stop_automation", "停止自动化", false, None::<&str>)?;
             
             let menu = MenuBuilder::new(app)
+                .item(&automation_item)
+                .item(&stop_item)
+                .separator()
                 .item(&show_item)
                 .item(&hide_item)
                 .separator()
                 .item(&quit_item)
                 .build()?;
 
+            // 根据自动化状态事件更新托盘菜单
+            app.listen(automation::AUTOMATION_STATUS_EVENT, move |event| {
+                match serde_json::from_str::<models::AutomationStatus>(event.payload()) {
+                    Ok(status) => {
+                        if let Err(e) = automation_item.set_text(tray_status_text(&status)) {
+                            tracing::warn!("更新托盘自动化状态失败: {}", e);
+                        }
+                        if let Err(e) = stop_item.set_enabled(status.is_running) {
+                            tracing::warn!("更新托盘停止按钮失败: {}", e);
+                        }
+                    }
+                    Err(e) => tracing::warn!("解析自动化状态事件失败: {}", e),
+                }
+            });
+
             let _tray = TrayIconBuilder::with_id("main-tray")
                 .menu(&menu)
                 .tooltip("RightsGuard - 版权申诉工具")
@@ -66,6 +115,13 @@ fn main() {
                                 window.hide().unwrap();
                             }
                         }
+                        "stop_automation" => {
+                            tauri::async_runtime::spawn(async {
+                                if let Err(e) = automation::stop_automation().await {
+                                    tracing::error!("从托盘停止自动化失败: {}", e);
+                                }
+                            });
+                        }
                         "quit" => {
                             app.exit(0);
                         }
@@ -93,24 +149,53 @@ fn main() {
             // 个人档案相关命令
             get_profile,
             save_profile,
+            verify_profile_files,
+            dry_run_profile_upload,
+            get_profile_completeness,
+            set_id_card_roles,
+            relink_file,
+            repair_file_columns,
             
             // IP资产相关命令
             get_ip_assets,
             get_ip_asset,
             save_ip_asset,
+            save_ip_assets_batch,
+            import_ip_assets_csv,
+            download_import_template,
+            duplicate_ip_asset,
             delete_ip_asset,
+            get_archived_ip_assets,
+            restore_ip_asset,
+            purge_ip_asset,
             
             // 案件相关命令
             get_cases,
+            query_cases,
             save_case,
+            get_case,
+            get_case_events,
             delete_case,
+            get_archived_cases,
+            restore_case,
+            purge_case,
+            get_case_statistics,
             
             // 自动化相关命令
             start_automation,
             stop_automation,
             get_automation_status,
             continue_automation_after_verification,
+            reset_automation_state,
+            get_automation_history,
             check_automation_environment,
+            run_first_time_setup,
+            run_self_test,
+            probe_selector,
+            list_browser_tabs,
+            schedule_automation,
+            get_scheduled_runs,
+            cancel_scheduled_run,
             
             // 文件相关命令
             select_file,
@@ -119,24 +204,74 @@ fn main() {
             // 系统相关命令
             open_url,
             show_message,
+            show_message_with_kind,
             show_confirm_dialog,
+            confirm_message,
             
             // 调试命令
             test_database,
             test_sqlite_connection_strategies,
+            diagnose_profile_save,
             get_database_diagnostics,
             clear_database_cache,
+            run_database_maintenance,
+            is_degraded_mode,
+            backup_database,
+            export_case_pdf,
+            restore_database,
             check_automation_environment,
             
             // 浏览器连接相关命令
             check_browser_connection_status,
+            get_browser_diagnostics,
+            get_remaining_quota_estimate,
+            get_work_type_options,
+            preview_automation_script,
+            enqueue_automation,
+            get_run_queue,
+            cancel_queued,
+            clear_queue,
             get_browser_launch_command,
+            copy_browser_launch_command,
+            launch_browser_for_login,
             force_restart_chrome,
             
             // 文件管理相关命令
             copy_file_to_app_data,
-            get_app_file_path
+            cancel_file_copy,
+            ingest_dropped_files,
+            get_app_file_path,
+            
+            // 日志相关命令
+            get_recent_logs,
+            open_log_folder,
+            open_app_data_folder,
+            open_files_folder,
+            
+            // 应用设置相关命令
+            get_settings,
+            save_settings,
+            get_last_used_ip_asset,
+            get_notifications_enabled,
+            set_notifications_enabled
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(database::close_pool());
+            }
+        });
+}
+
+// 托盘中显示的自动化状态文本
+fn tray_status_text(status: &models::AutomationStatus) -> String {
+    if status.is_running {
+        format!("自动化: 运行中 ({:.0}%)", status.progress.unwrap_or(0.0))
+    } else {
+        match status.current_step.as_deref() {
+            Some(step) => format!("自动化: {}", step),
+            None => "自动化: 空闲".to_string(),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row, Error as SqlxError};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Utc};
 use std::str::FromStr;
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Profile {
@@ -14,12 +15,33 @@ pub struct Profile {
     pub id_card_number: String,
     #[serde(rename = "idCardFiles")]
     pub id_card_files: Option<String>, // JSON string of file paths
+    /// 身份证文件的正反面标记，JSON对象: 存储路径 -> IdCardRole，由set_id_card_roles写入
+    #[serde(rename = "idCardRoles", default)]
+    pub id_card_roles: Option<String>,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// 身份证文件的角色，B站要求先上传人像面再上传国徽面
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum IdCardRole {
+    /// 人像面
+    Front,
+    /// 国徽面
+    Back,
+    Other,
+}
+
+/// 档案中的一个身份证文件及其角色，set_id_card_roles按此顺序接收
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdCardFileRole {
+    pub path: String,
+    pub role: IdCardRole,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IpAsset {
     pub id: Option<Uuid>,
@@ -46,6 +68,8 @@ pub struct IpAsset {
     #[serde(rename = "workProofFiles")]
     pub work_proof_files: Option<String>, // JSON string of file paths
     pub status: String,
+    #[serde(default)]
+    pub archived: bool,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
@@ -64,20 +88,141 @@ pub struct Case {
     pub status: String,
     #[serde(rename = "submissionDate")]
     pub submission_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub archived: bool,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<DateTime<Utc>>,
+    /// 关联IP资产的作品名，仅在查询JOIN了ip_assets时有值
+    #[serde(rename = "associatedIpName", default)]
+    pub associated_ip_name: Option<String>,
+    /// 最近一次提交结束时的页面截图，相对应用数据目录的路径，作为提交凭证
+    #[serde(rename = "submissionScreenshot", default)]
+    pub submission_screenshot: Option<String>,
+    /// 最近一次提交的运行结果 (RunResult的JSON)
+    #[serde(rename = "submissionResultJson", default)]
+    pub submission_result_json: Option<String>,
+}
+
+/// 案件历史中的一条事件，如提交、重新提交
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CaseEvent {
+    pub id: i64,
+    #[serde(rename = "caseId")]
+    pub case_id: Uuid,
+    pub event: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// 案件筛选条件，日期筛选基于submission_date
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CaseQuery {
+    pub status: Option<String>,
+    #[serde(rename = "ipAssetId")]
+    pub ip_asset_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct CasePage {
+    pub cases: Vec<Case>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AutomationStage {
+    /// 完整流程: 个人信息 -> 验证码 -> IP资产 -> 申诉详情
+    #[default]
+    Full,
+    /// 从IP资产填写阶段继续 (页面已通过验证码)
+    FromIpAsset,
+    /// 从申诉详情填写阶段继续
+    FromAppealDetails,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SitePlatform {
+    #[default]
+    Bilibili,
+    Douyin,
+    Weibo,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AutomationRequest {
     pub infringing_url: String,
     pub original_url: Option<String>,
     pub ip_asset_id: Option<Uuid>,
+    #[serde(default)]
+    pub start_stage: AutomationStage,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub appeal_url: Option<String>,
+    #[serde(default)]
+    pub platform: SitePlatform,
+    /// 总是关闭已有的调试会话并重新启动Chrome
+    #[serde(default)]
+    pub force_fresh: bool,
+    /// 成功后保持页面打开供用户最终确认；批量运行时可关闭以免Chrome堆积
+    #[serde(default = "default_keep_browser_open")]
+    pub keep_browser_open: bool,
+    /// 要使用的标签页的CDP targetId，未指定时使用空白页或新标签页
+    #[serde(default)]
+    pub target_tab_id: Option<String>,
+    /// 即使已有同一侵权链接的案件，也记录为新案件
+    #[serde(default)]
+    pub new_case: bool,
+    /// 由start_automation生成，用于关联日志和脚本输出
+    #[serde(default)]
+    pub run_id: Option<Uuid>,
+    /// 申诉详情步骤中额外上传的补充证据 (对比截图、往来沟通记录等)，路径格式同档案中的文件
+    #[serde(default)]
+    pub additional_files: Vec<String>,
+    /// 使用指定的Playwright配置文件 (相对项目根目录或绝对路径)，未指定时使用项目中的playwright.config.js
+    #[serde(default)]
+    pub playwright_config: Option<String>,
+    /// 只在配置中的该project下运行
+    #[serde(default)]
+    pub playwright_project: Option<String>,
+    /// 明确允许不选择IP资产运行，IP资产部分需要用户在页面上手动填写
+    #[serde(default)]
+    pub allow_without_ip_asset: bool,
+    /// 填写完成后自动点击"提交"并确认成功；默认只准备表单，由用户手动提交
+    #[serde(default)]
+    pub auto_submit: bool,
+    /// 本次运行Chrome使用的User-Agent，未指定时使用设置中的默认值
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// 测试模式: 使用真实的Chrome/CDP/上传流程，但打开设置中的测试页面 (未设置时为内置的模拟申诉页面)，
+    /// 在最终提交前停止，也不记录案件
+    #[serde(default)]
+    pub test_mode: bool,
+    /// 完整流程中复用已停在IP资产或申诉详情步骤的申诉页面，不重新导航；未指定时使用设置
+    #[serde(default)]
+    pub reuse_current_page: Option<bool>,
+    /// 整个运行 (包括重试) 的总时限(秒)，未指定时使用设置
+    #[serde(default)]
+    pub overall_deadline_secs: Option<u64>,
+    /// 用户已在提示中确认接近每日上限仍继续；只由交互启动设置，排队和计划运行不会带上
+    #[serde(skip)]
+    pub quota_confirmed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+fn default_keep_browser_open() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AutomationStatus {
     #[serde(rename = "isRunning")]
     pub is_running: bool,
@@ -87,6 +232,600 @@ pub struct AutomationStatus {
     pub error: Option<String>,
     #[serde(rename = "startedAt")]
     pub started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(rename = "durationSecs")]
+    pub duration_secs: Option<i64>,
+    #[serde(rename = "errorCode")]
+    pub error_code: Option<String>,
+    /// 每次运行生成的ID，同时出现在日志和运行日志文件名中
+    #[serde(rename = "runId", default)]
+    pub run_id: Option<Uuid>,
+    /// 运行队列中等待的申诉数量
+    #[serde(rename = "queueLength", default)]
+    pub queue_length: u32,
+    /// current_step对应的消息key和参数，前端可据此自行翻译；current_step已按设置的语言渲染
+    #[serde(rename = "stepKey", default)]
+    pub step_key: Option<String>,
+    #[serde(rename = "stepArgs", default)]
+    pub step_args: Vec<String>,
+}
+
+/// 运行队列中等待启动的申诉
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueuedRun {
+    #[serde(rename = "runId")]
+    pub run_id: Uuid,
+    #[serde(rename = "infringingUrl")]
+    pub infringing_url: String,
+    #[serde(rename = "ipAssetId")]
+    pub ip_asset_id: Option<Uuid>,
+    #[serde(rename = "queuedAt")]
+    pub queued_at: DateTime<Utc>,
+    /// 从1开始，1表示下一个运行
+    #[serde(rename = "queuePosition")]
+    pub queue_position: u32,
+}
+
+/// automation_runs表中的一次运行记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutomationRun {
+    #[serde(rename = "runId")]
+    pub run_id: Uuid,
+    #[serde(rename = "infringingUrl")]
+    pub infringing_url: String,
+    #[serde(rename = "originalUrl")]
+    pub original_url: Option<String>,
+    #[serde(rename = "ipAssetId")]
+    pub ip_asset_id: Option<Uuid>,
+    #[serde(rename = "ipAssetName")]
+    pub ip_asset_name: Option<String>,
+    /// running / succeeded / failed / stopped / interrupted
+    pub outcome: String,
+    pub error: Option<String>,
+    #[serde(rename = "errorCode")]
+    pub error_code: Option<String>,
+    #[serde(rename = "startedAt")]
+    pub started_at: DateTime<Utc>,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(rename = "durationSecs")]
+    pub duration_secs: Option<i64>,
+    /// 脚本写出的结构化结果，脚本未能写出时为空
+    #[serde(rename = "runResult")]
+    pub run_result: Option<RunResult>,
+    /// 测试模式的运行，没有提交真实申诉
+    #[serde(rename = "testMode", default)]
+    pub test_mode: bool,
+}
+
+/// 计划在指定时间运行的自动化申诉
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledRun {
+    pub id: Uuid,
+    pub request: AutomationRequest,
+    #[serde(rename = "runAt")]
+    pub run_at: DateTime<Utc>,
+    /// pending / started / failed / cancelled / skipped
+    pub status: String,
+    pub error: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// Playwright脚本结束时写出的结构化结果 (脚本中为snake_case字段)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RunResult {
+    /// started / connected / personal_info / ip_asset_page / ip_asset / appeal_details / ready_for_confirmation / submitted
+    #[serde(rename = "stageReached", alias = "stage_reached")]
+    pub stage_reached: String,
+    #[serde(rename = "fieldsFilled", alias = "fields_filled", default)]
+    pub fields_filled: Vec<String>,
+    #[serde(rename = "filesUploaded", alias = "files_uploaded", default)]
+    pub files_uploaded: u32,
+    /// 只有auto_submit且页面显示提交成功时为true
+    #[serde(default)]
+    pub submitted: bool,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// repair_file_columns改写(或试运行时将改写)的一列，before保存原值便于手动恢复
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileColumnRepair {
+    pub table: String,
+    #[serde(rename = "rowId")]
+    pub row_id: String,
+    pub column: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// 因本地找不到而移除的文件路径
+    #[serde(rename = "droppedPaths")]
+    pub dropped_paths: Vec<String>,
+}
+
+/// 文件列修复结果；无法解析的列保持原样并记入errors
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepairReport {
+    #[serde(rename = "scannedRows")]
+    pub scanned_rows: usize,
+    pub repairs: Vec<FileColumnRepair>,
+    pub errors: Vec<String>,
+    /// 试运行时为false，数据库未被修改
+    pub applied: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseMaintenanceReport {
+    #[serde(rename = "beforeBytes")]
+    pub before_bytes: u64,
+    #[serde(rename = "afterBytes")]
+    pub after_bytes: u64,
+    #[serde(rename = "reclaimedBytes")]
+    pub reclaimed_bytes: u64,
+}
+
+/// save_profile写入的值与库中实际行的一列差异，证件号已脱敏
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileFieldDiff {
+    pub field: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// diagnose_profile_save的结果：写入一份已知档案后读回并逐列比对
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileSaveDiagnosis {
+    #[serde(rename = "profileId")]
+    pub profile_id: Uuid,
+    pub retrieved: bool,
+    pub matches: bool,
+    pub diffs: Vec<ProfileFieldDiff>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MostAppealedAsset {
+    pub id: Uuid,
+    #[serde(rename = "workName")]
+    pub work_name: String,
+    #[serde(rename = "caseCount")]
+    pub case_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaseStats {
+    #[serde(rename = "totalCases")]
+    pub total_cases: i64,
+    #[serde(rename = "byStatus")]
+    pub by_status: HashMap<String, i64>,
+    #[serde(rename = "submittedThisWeek")]
+    pub submitted_this_week: i64,
+    #[serde(rename = "submittedThisMonth")]
+    pub submitted_this_month: i64,
+    #[serde(rename = "mostAppealedAsset")]
+    pub most_appealed_asset: Option<MostAppealedAsset>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppSettings {
+    #[serde(rename = "debugPort")]
+    pub debug_port: u16,
+    pub headless: bool,
+    #[serde(rename = "appealUrl")]
+    pub appeal_url: String,
+    #[serde(rename = "notificationsEnabled")]
+    pub notifications_enabled: bool,
+    #[serde(rename = "chromePath")]
+    pub chrome_path: Option<String>,
+    /// 调试Chrome的 --user-data-dir，为空时使用RightsGuard专用目录
+    #[serde(rename = "chromeUserDataDir", default)]
+    pub chrome_user_data_dir: Option<String>,
+    pub proxy: Option<String>,
+    /// 未指定IP资产时是否自动使用上次使用的IP资产
+    #[serde(rename = "useLastIpAsset", default)]
+    pub use_last_ip_asset: bool,
+    #[serde(default)]
+    pub timing: ScriptTiming,
+    /// 传给 npx playwright test --timeout 的单个测试超时(秒)，需要覆盖人工验证的时间
+    #[serde(rename = "playwrightTimeoutSecs", default = "default_playwright_timeout_secs")]
+    pub playwright_timeout_secs: u64,
+    /// 在主日志中逐行记录Playwright输出；关闭时只记录摘要和错误，完整输出始终保存在运行日志中
+    #[serde(rename = "verboseAutomationLogs", default)]
+    pub verbose_automation_logs: bool,
+    /// 自动化的执行方式，原生后端需要以native-playwright特性编译
+    #[serde(rename = "automationBackend", default)]
+    pub automation_backend: AutomationBackend,
+    /// 估计的B站每日申诉次数上限，用于剩余次数估算和启动前提醒
+    #[serde(rename = "dailyAppealLimit", default = "default_daily_appeal_limit")]
+    pub daily_appeal_limit: u32,
+    /// 复制到应用目录时去除哪些图片的EXIF元数据
+    #[serde(rename = "stripExifOnUpload", default)]
+    pub strip_exif_on_upload: ExifStripping,
+    /// 生成脚本和验证信号文件的目录，为空时使用应用数据目录下的automation
+    #[serde(rename = "scriptOutputDir", default)]
+    pub script_output_dir: Option<String>,
+    /// 调试Chrome默认使用的User-Agent，为空时使用Chrome自带的
+    #[serde(rename = "userAgent", default)]
+    pub user_agent: Option<String>,
+    /// 测试模式打开的页面 (http/https/file)，为空时使用内置的模拟申诉页面
+    #[serde(rename = "testUrl", default)]
+    pub test_url: Option<String>,
+    /// 调试Chrome中已打开且通过验证码的申诉页面直接继续填写，避免重新导航后再次完成验证码
+    #[serde(rename = "reuseCurrentPage", default)]
+    pub reuse_current_page: bool,
+    /// 单次运行的总时限(秒)，超时后结束Chrome和Playwright，避免运行卡住后一直占用运行锁
+    #[serde(rename = "overallDeadlineSecs", default = "default_overall_deadline_secs")]
+    pub overall_deadline_secs: u64,
+    /// 自动化状态和错误信息使用的语言
+    #[serde(default)]
+    pub locale: Locale,
+}
+
+/// 上传文件时去除图片EXIF元数据(GPS位置等)的范围
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ExifStripping {
+    Never,
+    /// 只处理身份证照片，最常见的手机拍摄场景
+    #[default]
+    IdCardsOnly,
+    Always,
+}
+
+impl ExifStripping {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExifStripping::Never => "never",
+            ExifStripping::IdCardsOnly => "idCardsOnly",
+            ExifStripping::Always => "always",
+        }
+    }
+
+    pub fn applies_to(&self, subcategory: &str) -> bool {
+        match self {
+            ExifStripping::Never => false,
+            ExifStripping::IdCardsOnly => subcategory == "id_cards",
+            ExifStripping::Always => true,
+        }
+    }
+}
+
+impl FromStr for ExifStripping {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "never" => Ok(ExifStripping::Never),
+            "idCardsOnly" => Ok(ExifStripping::IdCardsOnly),
+            "always" => Ok(ExifStripping::Always),
+            other => Err(format!("未知的EXIF处理范围: {}", other)),
+        }
+    }
+}
+
+pub fn default_daily_appeal_limit() -> u32 {
+    20
+}
+
+/// 根据本地案件记录估算的今日剩余申诉次数；B站不公开实际额度，仅供参考
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuotaEstimate {
+    #[serde(rename = "dailyLimit")]
+    pub daily_limit: u32,
+    #[serde(rename = "submittedToday")]
+    pub submitted_today: u32,
+    pub remaining: u32,
+    #[serde(rename = "nearLimit")]
+    pub near_limit: bool,
+    /// 下一次额度重置时间 (北京时间零点)
+    #[serde(rename = "resetsAt")]
+    pub resets_at: DateTime<Utc>,
+}
+
+/// 自动化的执行方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AutomationBackend {
+    /// 生成.spec.js并通过 npx playwright test 运行，需要Node.js
+    #[default]
+    NpxSubprocess,
+    /// 通过playwright crate在进程内连接调试Chrome，不需要Node.js
+    NativeRust,
+}
+
+impl AutomationBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutomationBackend::NpxSubprocess => "npxSubprocess",
+            AutomationBackend::NativeRust => "nativeRust",
+        }
+    }
+
+    /// 当前构建是否包含该后端
+    pub fn is_available(&self) -> bool {
+        match self {
+            AutomationBackend::NpxSubprocess => true,
+            AutomationBackend::NativeRust => cfg!(feature = "native-playwright"),
+        }
+    }
+}
+
+impl FromStr for AutomationBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "npxSubprocess" => Ok(AutomationBackend::NpxSubprocess),
+            "nativeRust" => Ok(AutomationBackend::NativeRust),
+            other => Err(format!("未知的自动化后端: {}", other)),
+        }
+    }
+}
+
+/// 后端面向用户的文本使用的语言，默认中文
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::EnUs => "en-US",
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "zh-CN" => Ok(Locale::ZhCn),
+            "en-US" => Ok(Locale::EnUs),
+            other => Err(format!("未知的语言: {}", other)),
+        }
+    }
+}
+
+fn default_playwright_timeout_secs() -> u64 {
+    300
+}
+
+// 足够覆盖验证码等待、上传和几次重试
+fn default_overall_deadline_secs() -> u64 {
+    1800
+}
+
+/// 运行总时限的上限(24小时)，更大的值没有意义，且会让截止时间的计算溢出
+pub const MAX_OVERALL_DEADLINE_SECS: u64 = 24 * 60 * 60;
+
+/// 生成脚本中固定等待的时长(毫秒)，页面渲染较慢的机器上可以调大
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct ScriptTiming {
+    /// 下拉框展开、日期选择器打开等组件动画
+    #[serde(rename = "shortMs")]
+    pub short_ms: u64,
+    /// 页面跳转后的渲染、证明文件上传处理
+    #[serde(rename = "mediumMs")]
+    pub medium_ms: u64,
+    /// 身份证上传结果校验的等待上限
+    #[serde(rename = "longMs")]
+    pub long_ms: u64,
+    /// 每个文件上传后、上传下一个文件前的等待，原先固定为1秒，不随shortMs缩短
+    #[serde(rename = "uploadSettleMs", default = "default_upload_settle_ms")]
+    pub upload_settle_ms: u64,
+}
+
+fn default_upload_settle_ms() -> u64 {
+    1000
+}
+
+impl Default for ScriptTiming {
+    fn default() -> Self {
+        Self {
+            short_ms: 500,
+            medium_ms: 2000,
+            long_ms: 3000,
+            upload_settle_ms: default_upload_settle_ms(),
+        }
+    }
+}
+
+/// 档案中引用的单个文件的检查结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileStatus {
+    /// 数据库中保存的路径
+    pub path: String,
+    #[serde(rename = "resolvedPath")]
+    pub resolved_path: Option<String>,
+    pub exists: bool,
+    /// 文件缺失时在应用数据目录中按文件名找到的替代路径，可传给relink_file
+    #[serde(rename = "suggestedPath")]
+    pub suggested_path: Option<String>,
+}
+
+/// reset_automation_state的清理结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutomationResetReport {
+    #[serde(rename = "wasRunning")]
+    pub was_running: bool,
+    #[serde(rename = "killedChrome")]
+    pub killed_chrome: bool,
+    #[serde(rename = "killedPlaywright")]
+    pub killed_playwright: bool,
+    #[serde(rename = "removedFiles")]
+    pub removed_files: Vec<String>,
+}
+
+/// 单个待上传文件的校验结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UploadFileCheck {
+    pub path: String,
+    pub exists: bool,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: Option<u64>,
+    #[serde(rename = "isImage")]
+    pub is_image: bool,
+    #[serde(rename = "isPdf")]
+    pub is_pdf: bool,
+    /// 该文件能否进入上传；为false时见problems
+    pub valid: bool,
+    pub problems: Vec<String>,
+}
+
+/// 拖放导入单个文件的结果，失败时relative_path为空并给出error
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IngestedFile {
+    #[serde(rename = "sourcePath")]
+    pub source_path: String,
+    #[serde(rename = "relativePath")]
+    pub relative_path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 大文件复制进度，通过file-copy-progress事件发送给前端
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileCopyProgress {
+    #[serde(rename = "copyId")]
+    pub copy_id: String,
+    #[serde(rename = "sourcePath")]
+    pub source_path: String,
+    #[serde(rename = "copiedBytes")]
+    pub copied_bytes: u64,
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+}
+
+/// 上传前对档案文件的检查报告，对应上传脚本中的文件验证部分
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UploadValidationReport {
+    /// 档案中保存的文件数
+    #[serde(rename = "storedCount")]
+    pub stored_count: usize,
+    #[serde(rename = "validCount")]
+    pub valid_count: usize,
+    pub files: Vec<UploadFileCheck>,
+    /// 无法解析到本地文件的档案路径
+    #[serde(rename = "missingFiles")]
+    pub missing_files: Vec<FileStatus>,
+    pub warnings: Vec<String>,
+    /// 是否满足上传条件（至少有正反面两张有效图片）
+    pub ready: bool,
+}
+
+/// 首次申诉前的准备程度：档案字段、身份证正反面和至少一个可用的IP资产
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompletenessReport {
+    /// 已满足的检查项占比，0-100
+    pub percent: u8,
+    pub complete: bool,
+    /// 未满足的检查项，可直接展示给用户
+    pub missing: Vec<String>,
+    /// 材料齐全、可直接用于申诉的IP资产数量
+    #[serde(rename = "readyAssetCount")]
+    pub ready_asset_count: usize,
+}
+
+/// CSV目录导入中一行的结果，row为CSV中的行号 (表头为第1行)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportRowResult {
+    pub row: usize,
+    #[serde(rename = "workName")]
+    pub work_name: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    /// 导入成功时新建的IP资产ID
+    #[serde(rename = "assetId")]
+    pub asset_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportReport {
+    pub total: usize,
+    pub imported: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowResult>,
+}
+
+/// 调试Chrome中打开的标签页
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TabInfo {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+}
+
+/// 在当前页面上测试单个选择器的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SelectorProbe {
+    pub selector: String,
+    #[serde(rename = "pageUrl")]
+    pub page_url: String,
+    pub count: u32,
+    pub visible: bool,
+    pub enabled: bool,
+    #[serde(rename = "firstText")]
+    pub first_text: Option<String>,
+}
+
+/// 调试Chrome连接的详细诊断，用于排查"已连接但运行失败"的情况
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrowserDiagnostics {
+    #[serde(rename = "debugPort")]
+    pub debug_port: u16,
+    pub connected: bool,
+    #[serde(rename = "browserVersion")]
+    pub browser_version: Option<String>,
+    #[serde(rename = "protocolVersion")]
+    pub protocol_version: Option<String>,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    #[serde(rename = "tabCount")]
+    pub tab_count: u32,
+    #[serde(rename = "activeTabUrl")]
+    pub active_tab_url: Option<String>,
+    /// 是否存在B站登录Cookie (SESSDATA)；无法读取Cookie时为None
+    #[serde(rename = "bilibiliSession")]
+    pub bilibili_session: Option<bool>,
+    /// 诊断过程中失败的检查项，不影响其余结果
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SetupCheckStatus {
+    Pass,
+    Fail,
+    /// 不影响其余检查，但需要用户处理 (如安装Playwright浏览器)
+    ActionRequired,
+}
+
+/// 首次运行检查中的一项，对应引导清单中的一行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetupCheck {
+    /// 稳定的检查项标识: appData, database, browser, npx, playwright, playwrightBrowser
+    pub id: String,
+    pub label: String,
+    pub status: SetupCheckStatus,
+    pub detail: String,
+    /// 未通过时建议用户执行的操作
+    pub action: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetupReport {
+    /// 所有检查项均通过
+    pub ready: bool,
+    pub checks: Vec<SetupCheck>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -103,12 +842,102 @@ impl Default for Profile {
             email: String::new(),
             id_card_number: String::new(),
             id_card_files: None,
+            id_card_roles: None,
             created_at: None,
             updated_at: None,
         }
     }
 }
 
+// GB 11643 校验码：前17位加权求和后对11取模
+const ID_CARD_WEIGHTS: [u32; 17] = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+const ID_CARD_CHECK_CODES: [char; 11] = ['1', '0', 'X', '9', '8', '7', '6', '5', '4', '3', '2'];
+
+impl Profile {
+    /// 规范化手机号和身份证号并校验，保证入库的数据可以直接填入B站表单
+    pub fn normalized(&self) -> Result<Self, String> {
+        Ok(Self {
+            name: self.name.trim().to_string(),
+            email: self.email.trim().to_string(),
+            phone: normalize_phone(&self.phone)?,
+            id_card_number: normalize_id_card(&self.id_card_number)?,
+            ..self.clone()
+        })
+    }
+}
+
+pub(crate) fn normalize_phone(phone: &str) -> Result<String, String> {
+    let digits: String = phone
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    let digits = digits
+        .strip_prefix("+86")
+        .or_else(|| digits.strip_prefix("0086"))
+        .unwrap_or(&digits)
+        .to_string();
+
+    let bytes = digits.as_bytes();
+    let valid = bytes.len() == 11
+        && bytes.iter().all(u8::is_ascii_digit)
+        && bytes[0] == b'1'
+        && (b'3'..=b'9').contains(&bytes[1]);
+    if !valid {
+        return Err(format!("手机号格式不正确: {}", phone));
+    }
+    Ok(digits)
+}
+
+pub(crate) fn normalize_id_card(id_card: &str) -> Result<String, String> {
+    let normalized: String = id_card
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+
+    let chars: Vec<char> = normalized.chars().collect();
+    if chars.len() != 18
+        || !chars[..17].iter().all(char::is_ascii_digit)
+        || !(chars[17].is_ascii_digit() || chars[17] == 'X')
+    {
+        return Err(format!("身份证号必须为18位，末位为数字或X: {}", mask_id_card(id_card)));
+    }
+
+    // 省级行政区划代码范围为11-82，港澳台居民居住证使用83
+    let province: u32 = normalized[..2].parse().unwrap_or(0);
+    if !(11..=83).contains(&province) {
+        return Err(format!("身份证号地区码无效: {}", mask_id_card(id_card)));
+    }
+
+    let birth = chrono::NaiveDate::parse_from_str(&normalized[6..14], "%Y%m%d")
+        .map_err(|_| format!("身份证号出生日期无效: {}", mask_id_card(id_card)))?;
+    if birth.year() < 1900 || birth > Utc::now().date_naive() {
+        return Err(format!("身份证号出生日期无效: {}", mask_id_card(id_card)));
+    }
+
+    let sum: u32 = chars[..17]
+        .iter()
+        .zip(ID_CARD_WEIGHTS.iter())
+        .map(|(c, w)| c.to_digit(10).unwrap_or(0) * w)
+        .sum();
+    if ID_CARD_CHECK_CODES[(sum % 11) as usize] != chars[17] {
+        return Err(format!("身份证号校验码不正确: {}", mask_id_card(id_card)));
+    }
+    Ok(normalized)
+}
+
+// 只保留前6位和后4位，记录可以外发而不泄露完整证件号
+pub(crate) fn mask_id_card(id_card: &str) -> String {
+    let chars: Vec<char> = id_card.chars().collect();
+    if chars.len() <= 10 {
+        return id_card.to_string();
+    }
+    let mut masked: String = chars[..6].iter().collect();
+    masked.push_str(&"*".repeat(chars.len() - 10));
+    masked.extend(&chars[chars.len() - 4..]);
+    masked
+}
+
 impl Default for IpAsset {
     fn default() -> Self {
         Self {
@@ -126,6 +955,7 @@ impl Default for IpAsset {
             auth_files: None,
             work_proof_files: None,
             status: "待认证".to_string(),
+            archived: false,
             created_at: None,
             updated_at: None,
         }
@@ -141,8 +971,12 @@ impl Default for Case {
             associated_ip_id: None,
             status: "新建".to_string(),
             submission_date: None,
+            archived: false,
             created_at: None,
             updated_at: None,
+            associated_ip_name: None,
+            submission_screenshot: None,
+            submission_result_json: None,
         }
     }
 }
@@ -179,6 +1013,7 @@ impl<'r> FromRow<'r, sqlx::sqlite::SqliteRow> for Profile {
             email: row.try_get("email")?,
             id_card_number: row.try_get("id_card_number")?,
             id_card_files: row.try_get("id_card_files")?,
+            id_card_roles: row.try_get("id_card_roles")?,
             created_at: parse_datetime("created_at")?,
             updated_at: parse_datetime("updated_at")?,
         })
@@ -224,6 +1059,7 @@ impl<'r> FromRow<'r, sqlx::sqlite::SqliteRow> for IpAsset {
             auth_files: row.try_get("auth_files")?,
             work_proof_files: row.try_get("work_proof_files")?,
             status: row.try_get("status")?,
+            archived: row.try_get("archived")?,
             created_at: parse_datetime("created_at")?,
             updated_at: parse_datetime("updated_at")?,
         })
@@ -263,6 +1099,13 @@ impl<'r> FromRow<'r, sqlx::sqlite::SqliteRow> for Case {
             None => None,
         };
 
+        // 只有JOIN了ip_assets的查询才有该列
+        let associated_ip_name = match row.try_get("associated_ip_name") {
+            Ok(name) => name,
+            Err(SqlxError::ColumnNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
         Ok(Case {
             id,
             infringing_url: row.try_get("infringing_url")?,
@@ -270,8 +1113,207 @@ impl<'r> FromRow<'r, sqlx::sqlite::SqliteRow> for Case {
             associated_ip_id,
             status: row.try_get("status")?,
             submission_date: parse_datetime("submission_date")?,
+            archived: row.try_get("archived")?,
             created_at: parse_datetime("created_at")?,
             updated_at: parse_datetime("updated_at")?,
+            associated_ip_name,
+            submission_screenshot: row.try_get("submission_screenshot")?,
+            submission_result_json: row.try_get("submission_result_json")?,
+        })
+    }
+}
+
+impl<'r> FromRow<'r, sqlx::sqlite::SqliteRow> for AutomationRun {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, SqlxError> {
+        let parse_datetime = |col: &str| -> Result<Option<DateTime<Utc>>, SqlxError> {
+            let value: Option<String> = row.try_get(col)?;
+            match value {
+                Some(s) => DateTime::parse_from_rfc3339(&s)
+                    .map(|dt| Some(dt.with_timezone(&Utc)))
+                    .map_err(|e| SqlxError::ColumnDecode {
+                        index: col.to_string(),
+                        source: Box::new(e),
+                    }),
+                None => Ok(None),
+            }
+        };
+        let parse_uuid = |col: &str| -> Result<Option<Uuid>, SqlxError> {
+            let value: Option<String> = row.try_get(col)?;
+            value
+                .map(|s| Uuid::from_str(&s).map_err(|e| SqlxError::ColumnDecode {
+                    index: col.to_string(),
+                    source: Box::new(e),
+                }))
+                .transpose()
+        };
+
+        Ok(AutomationRun {
+            run_id: parse_uuid("run_id")?.ok_or_else(|| SqlxError::ColumnDecode {
+                index: "run_id".to_string(),
+                source: "run_id is NULL".into(),
+            })?,
+            infringing_url: row.try_get("infringing_url")?,
+            original_url: row.try_get("original_url")?,
+            ip_asset_id: parse_uuid("ip_asset_id")?,
+            ip_asset_name: row.try_get("ip_asset_name")?,
+            outcome: row.try_get("outcome")?,
+            error: row.try_get("error")?,
+            error_code: row.try_get("error_code")?,
+            started_at: parse_datetime("started_at")?.ok_or_else(|| SqlxError::ColumnDecode {
+                index: "started_at".to_string(),
+                source: "started_at is NULL".into(),
+            })?,
+            finished_at: parse_datetime("finished_at")?,
+            duration_secs: row.try_get("duration_secs")?,
+            run_result: row
+                .try_get::<Option<String>, _>("result_json")?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            test_mode: row.try_get("test_mode")?,
+        })
+    }
+}
+
+impl<'r> FromRow<'r, sqlx::sqlite::SqliteRow> for ScheduledRun {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, SqlxError> {
+        let parse_datetime = |col: &str| -> Result<Option<DateTime<Utc>>, SqlxError> {
+            let value: Option<String> = row.try_get(col)?;
+            match value {
+                Some(s) => DateTime::parse_from_rfc3339(&s)
+                    .map(|dt| Some(dt.with_timezone(&Utc)))
+                    .map_err(|e| SqlxError::ColumnDecode {
+                        index: col.to_string(),
+                        source: Box::new(e),
+                    }),
+                None => Ok(None),
+            }
+        };
+        let required_datetime = |col: &str| -> Result<DateTime<Utc>, SqlxError> {
+            parse_datetime(col)?.ok_or_else(|| SqlxError::ColumnDecode {
+                index: col.to_string(),
+                source: format!("{} is NULL", col).into(),
+            })
+        };
+        let id_str: String = row.try_get("id")?;
+        let id = Uuid::from_str(&id_str).map_err(|e| SqlxError::ColumnDecode {
+            index: "id".to_string(),
+            source: Box::new(e),
+        })?;
+        let request_json: String = row.try_get("request_json")?;
+        let request = serde_json::from_str(&request_json).map_err(|e| SqlxError::ColumnDecode {
+            index: "request_json".to_string(),
+            source: Box::new(e),
+        })?;
+
+        Ok(ScheduledRun {
+            id,
+            request,
+            run_at: required_datetime("run_at")?,
+            status: row.try_get("status")?,
+            error: row.try_get("error")?,
+            created_at: required_datetime("created_at")?,
+            started_at: parse_datetime("started_at")?,
+        })
+    }
+}
+
+impl<'r> FromRow<'r, sqlx::sqlite::SqliteRow> for CaseEvent {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, SqlxError> {
+        let case_id_str: String = row.try_get("case_id")?;
+        let case_id = Uuid::from_str(&case_id_str).map_err(|e| SqlxError::ColumnDecode {
+            index: "case_id".to_string(),
+            source: Box::new(e),
+        })?;
+        let created_at_str: String = row.try_get("created_at")?;
+        let created_at = DateTime::parse_from_rfc3339(&created_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| SqlxError::ColumnDecode {
+                index: "created_at".to_string(),
+                source: Box::new(e),
+            })?;
+
+        Ok(CaseEvent {
+            id: row.try_get("id")?,
+            case_id,
+            event: row.try_get("event")?,
+            created_at,
         })
     }
-}
\ No newline at end of file
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            debug_port: 9222,
+            headless: false,
+            appeal_url: crate::automation::DEFAULT_APPEAL_URL.to_string(),
+            notifications_enabled: true,
+            chrome_path: None,
+            chrome_user_data_dir: None,
+            proxy: None,
+            use_last_ip_asset: false,
+            timing: ScriptTiming::default(),
+            playwright_timeout_secs: default_playwright_timeout_secs(),
+            verbose_automation_logs: false,
+            automation_backend: AutomationBackend::default(),
+            daily_appeal_limit: default_daily_appeal_limit(),
+            strip_exif_on_upload: ExifStripping::default(),
+            script_output_dir: None,
+            user_agent: None,
+            test_url: None,
+            reuse_current_page: false,
+            overall_deadline_secs: default_overall_deadline_secs(),
+            locale: Locale::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 以下身份证号均为按GB 11643规则构造的样例，不对应真实个人
+
+    #[test]
+    fn id_card_with_valid_checksum_is_accepted() {
+        assert_eq!(normalize_id_card("440304199001011233"), Ok("440304199001011233".to_string()));
+        assert_eq!(normalize_id_card(" 3201 0219 8506 1530 12 "), Ok("320102198506153012".to_string()));
+    }
+
+    #[test]
+    fn id_card_with_invalid_checksum_is_rejected() {
+        let error = normalize_id_card("440304199001011234").unwrap_err();
+        assert!(error.contains("校验码"), "unexpected error: {}", error);
+        assert!(error.contains("440304********1234"), "id card not masked: {}", error);
+        assert!(!error.contains("440304199001011234"), "full id card leaked: {}", error);
+    }
+
+    #[test]
+    fn id_card_for_hong_kong_macao_taiwan_residents_is_accepted() {
+        assert_eq!(normalize_id_card("830000199001011234"), Ok("830000199001011234".to_string()));
+    }
+
+    #[test]
+    fn id_card_lowercase_x_is_uppercased() {
+        assert_eq!(normalize_id_card("11010519491231002x"), Ok("11010519491231002X".to_string()));
+    }
+
+    #[test]
+    fn id_card_with_invalid_region_or_length_is_rejected() {
+        assert!(normalize_id_card("990304199001011233").is_err());
+        assert!(normalize_id_card("44030419900101123").is_err());
+    }
+
+    #[test]
+    fn phone_prefix_spaces_and_dashes_are_stripped() {
+        assert_eq!(normalize_phone("+86 138-0013-8000"), Ok("13800138000".to_string()));
+        assert_eq!(normalize_phone("0086 139 1234 5678"), Ok("13912345678".to_string()));
+        assert_eq!(normalize_phone("138 0013 8000"), Ok("13800138000".to_string()));
+    }
+
+    #[test]
+    fn phone_with_invalid_prefix_or_length_is_rejected() {
+        assert!(normalize_phone("12800138000").is_err());
+        assert!(normalize_phone("1380013800").is_err());
+        assert!(normalize_phone("+1 415 555 0100").is_err());
+    }
+}
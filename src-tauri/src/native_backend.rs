@@ -0,0 +1,506 @@
+// src-tauri/src/native_backend.rs
+//
+// 原生Playwright后端: 通过playwright crate在进程内连接调试Chrome填写B站申诉表单，
+// 不需要Node.js/npx。选择器与生成脚本共用bilibili_script中的定义。
+
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use playwright::api::{Browser, File, Page};
+use playwright::Playwright;
+use crate::automation::{update_status, AutomationError, SiteAutomation};
+use crate::i18n::Message;
+use crate::bilibili_script::{
+    resolve_appeal_files, resume_stage_target, AppealFiles, BilibiliAutomation, AGENT_TOGGLE_SELECTOR, APPEAL_DESCRIPTION,
+    AUTH_FILES_UPLOAD_SELECTOR, AUTH_PERIOD_SELECTOR, DESCRIPTION_TEXTAREA_SELECTOR, EMAIL_INPUT_SELECTOR,
+    EVIDENCE_UPLOAD_SELECTOR, GUARANTEE_CHECKBOX_SELECTOR, ID_CARD_INPUT_SELECTOR, ID_CARD_UPLOAD_SELECTOR,
+    INFRINGING_URL_INPUT_SELECTOR, LOGIN_WALL_SELECTORS, NAME_INPUT_SELECTOR, PHONE_INPUT_SELECTOR, QUOTA_EXCEEDED_PATTERN, REGION_SELECTOR,
+    RIGHTS_HOLDER_SELECTOR, SUBMIT_SUCCESS_PATTERN, WORK_NAME_SELECTOR, WORK_PERIOD_SELECTOR, WORK_PROOF_UPLOAD_SELECTOR,
+    WORK_TYPE_SELECTOR,
+};
+use crate::models::{AppSettings, AutomationRequest, AutomationStage, IpAsset, Profile, RunResult, ScriptTiming};
+
+// 等待用户完成验证码的上限，与生成脚本一致
+const VERIFICATION_TIMEOUT_MS: f64 = 300_000.0;
+const NAVIGATION_TIMEOUT_MS: f64 = 60_000.0;
+const RESUME_CHECK_TIMEOUT_MS: f64 = 10_000.0;
+
+// playwright crate的错误类型不统一 (Error / Arc<Error>)，统一转换为anyhow
+trait PlaywrightResultExt<T> {
+    fn pw(self, action: &str) -> Result<T>;
+}
+
+impl<T, E: std::fmt::Display> PlaywrightResultExt<T> for std::result::Result<T, E> {
+    fn pw(self, action: &str) -> Result<T> {
+        self.map_err(|e| anyhow!("{}失败: {}", action, e))
+    }
+}
+
+/// 使用原生后端执行一次B站申诉，返回与生成脚本相同结构的运行结果
+pub async fn run_bilibili(
+    profile: &Profile,
+    ip_asset: Option<&IpAsset>,
+    request: &AutomationRequest,
+    settings: &AppSettings,
+) -> Result<RunResult> {
+    let appeal_url = BilibiliAutomation.appeal_url(request, settings)?;
+    let files = resolve_appeal_files(profile, ip_asset, request)?;
+
+    let playwright = Playwright::initialize().await.pw("初始化Playwright")?;
+    let endpoint = format!("http://127.0.0.1:{}", settings.debug_port);
+    let browser = playwright
+        .chromium()
+        .connect_over_cdp_builder(&endpoint)
+        .timeout(settings.playwright_timeout_secs as f64 * 1000.0)
+        .connect_over_cdp()
+        .await
+        .pw("通过CDP连接Chrome")?;
+    tracing::info!("✅ 原生后端已连接Chrome: {}", endpoint);
+
+    let reuse_current_page = request.start_stage == AutomationStage::Full
+        && request.reuse_current_page.unwrap_or(settings.reuse_current_page);
+    // 指定了标签页时只使用该标签页，与生成脚本一致
+    let target_page = match request.target_tab_id.as_deref() {
+        Some(target_tab_id) => Some(find_target_page(&browser, target_tab_id).await?),
+        None => None,
+    };
+    let reused = if reuse_current_page {
+        find_reusable_page(&browser, &appeal_url, target_page.as_ref()).await?
+    } else {
+        None
+    };
+    let (page, reused_stage) = match (reused, target_page) {
+        (Some((page, stage)), _) => (page, Some(stage)),
+        (None, Some(page)) => (page, None),
+        (None, None) => (open_page(&browser, request).await?, None),
+    };
+    // 复用的页面按所在步骤继续，与从中间阶段继续的流程相同
+    let start_stage = reused_stage.unwrap_or(request.start_stage);
+
+    let mut form = NativeForm {
+        page,
+        timing: settings.timing,
+        result: RunResult {
+            stage_reached: "connected".to_string(),
+            fields_filled: Vec::new(),
+            files_uploaded: 0,
+            submitted: false,
+            errors: Vec::new(),
+        },
+        unfilled_fields: Vec::new(),
+    };
+
+    match start_stage {
+        AutomationStage::Full => {
+            form.fill_personal_info(&appeal_url, profile, &files).await?;
+            form.wait_for_verification().await?;
+        }
+        stage => form.check_resume_stage(stage).await?,
+    }
+
+    if start_stage != AutomationStage::FromAppealDetails {
+        if let Some(asset) = ip_asset {
+            form.fill_ip_asset(asset, &files).await?;
+        }
+    }
+    form.fill_appeal_details(&request.infringing_url, &files.additional_files).await?;
+    // 测试模式总是在最终提交前停止
+    form.submit(request.auto_submit && !request.test_mode).await?;
+    if let Some(path) = crate::automation::submission_screenshot_path(request.run_id) {
+        form.capture_screenshot(path).await;
+    }
+
+    // 与CDP断开连接不会关闭用户的Chrome
+    Ok(form.result)
+}
+
+// 从完整流程开始时使用空白页或新建标签页，从中间阶段继续时复用已打开的申诉页面
+async fn open_page(browser: &Browser, request: &AutomationRequest) -> Result<Page> {
+    let context = browser
+        .contexts()
+        .pw("读取浏览器上下文")?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Chrome中没有可用的浏览器上下文"))?;
+    let pages = context.pages().pw("读取标签页")?;
+
+    let existing = if request.start_stage == AutomationStage::Full {
+        pages.into_iter().find(|p| {
+            p.url().map(|url| url == "about:blank" || url == "chrome://newtab/").unwrap_or(false)
+        })
+    } else {
+        pages.into_iter().find(|p| p.url().map(|url| url.contains("/v/copyright/")).unwrap_or(false))
+    };
+    match existing {
+        Some(page) => Ok(page),
+        None if request.start_stage == AutomationStage::Full => context.new_page().await.pw("新建标签页"),
+        None => Err(anyhow!("未找到已打开的申诉页面，无法从中间阶段继续")),
+    }
+}
+
+// playwright crate不提供CDP targetId，通过调试端口的/json/list找到该标签页的地址后按地址匹配
+async fn find_target_page(browser: &Browser, target_tab_id: &str) -> Result<Page> {
+    let tab = crate::automation::list_browser_tabs()
+        .await?
+        .into_iter()
+        .find(|tab| tab.id == target_tab_id)
+        .ok_or_else(|| anyhow!("未找到指定的标签页: {}", target_tab_id))?;
+    for context in browser.contexts().pw("读取浏览器上下文")? {
+        for page in context.pages().pw("读取标签页")? {
+            if page.url().map(|url| url == tab.url).unwrap_or(false) {
+                tracing::info!("🎯 使用指定的标签页: {}", tab.url);
+                return Ok(page);
+            }
+        }
+    }
+    Err(anyhow!("未找到指定的标签页: {}", target_tab_id))
+}
+
+// 查找已停在IP资产或申诉详情步骤的申诉页面 (源和路径都与申诉页面一致)，返回页面及可以继续的阶段
+// 指定了标签页时只检查该标签页；个人信息步骤尚未通过验证码，仍按完整流程重新导航
+async fn find_reusable_page(browser: &Browser, appeal_url: &str, target_page: Option<&Page>) -> Result<Option<(Page, AutomationStage)>> {
+    let appeal_url = reqwest::Url::parse(appeal_url)?;
+    let candidates = match target_page {
+        Some(page) => vec![page.clone()],
+        None => {
+            let mut pages = Vec::new();
+            for context in browser.contexts().pw("读取浏览器上下文")? {
+                pages.extend(context.pages().pw("读取标签页")?);
+            }
+            pages
+        }
+    };
+    for page in candidates {
+        let url = page.url().unwrap_or_default();
+        if !is_appeal_page(&url, &appeal_url) {
+            continue;
+        }
+        let stage = if page.is_visible(INFRINGING_URL_INPUT_SELECTOR, None).await.unwrap_or(false) {
+            AutomationStage::FromAppealDetails
+        } else if page.is_visible(RIGHTS_HOLDER_SELECTOR, None).await.unwrap_or(false) {
+            AutomationStage::FromIpAsset
+        } else {
+            tracing::info!("ℹ️ 已打开的申诉页面不在IP资产或申诉详情步骤，将重新打开: {}", url);
+            continue;
+        };
+        tracing::info!("♻️ 复用已打开的申诉页面，跳过导航和个人信息: {}", url);
+        return Ok(Some((page, stage)));
+    }
+    Ok(None)
+}
+
+// 只比较路径会把其他站点上同路径的页面也当成申诉页面
+fn is_appeal_page(url: &str, appeal_url: &reqwest::Url) -> bool {
+    reqwest::Url::parse(url).map_or(false, |parsed| {
+        parsed.origin() == appeal_url.origin() && parsed.path() == appeal_url.path()
+    })
+}
+
+struct NativeForm {
+    page: Page,
+    timing: ScriptTiming,
+    result: RunResult,
+    // 填写后读回的值与期望不一致的字段，每个步骤结束前统一报告
+    unfilled_fields: Vec<String>,
+}
+
+impl NativeForm {
+    async fn fill_personal_info(&mut self, appeal_url: &str, profile: &Profile, files: &AppealFiles) -> Result<()> {
+        update_status(Message::new("step.native_opening"), 38.0).await;
+        self.page
+            .goto_builder(appeal_url)
+            .timeout(NAVIGATION_TIMEOUT_MS)
+            .goto()
+            .await
+            .pw("打开申诉页面")?;
+
+        let mut login_wall = self.page.url().pw("读取页面地址")?.contains("passport.bilibili.com");
+        for selector in LOGIN_WALL_SELECTORS {
+            if login_wall {
+                break;
+            }
+            login_wall = self.page.is_visible(selector, None).await.unwrap_or(false);
+        }
+        if login_wall {
+            return Err(AutomationError::NotLoggedIn.into());
+        }
+        self.check_quota().await?;
+
+        update_status(Message::new("step.native_personal_info"), 40.0).await;
+        self.fill_verified(NAME_INPUT_SELECTOR, &profile.name, "姓名").await;
+        self.fill_verified(PHONE_INPUT_SELECTOR, &profile.phone, "手机号").await;
+        self.fill_verified(EMAIL_INPUT_SELECTOR, &profile.email, "邮箱").await;
+        self.fill_verified(ID_CARD_INPUT_SELECTOR, &profile.id_card_number, "证件号码").await;
+        self.report_unfilled_fields()?;
+        self.result.stage_reached = "personal_info".to_string();
+        self.result.fields_filled.extend(["姓名", "手机号", "邮箱", "证件号码"].map(String::from));
+
+        self.upload(&files.id_card_files, ID_CARD_UPLOAD_SELECTOR, "身份证").await
+    }
+
+    async fn wait_for_verification(&mut self) -> Result<()> {
+        update_status(Message::new("step.awaiting_captcha"), 60.0).await;
+        tracing::info!("⏳ 等待用户完成验证码并进入下一页...");
+        self.page
+            .wait_for_selector_builder(RIGHTS_HOLDER_SELECTOR)
+            .timeout(VERIFICATION_TIMEOUT_MS)
+            .wait_for_selector()
+            .await
+            .map_err(|_| AutomationError::VerificationTimeout)?;
+        self.result.stage_reached = "ip_asset_page".to_string();
+        Ok(())
+    }
+
+    async fn check_resume_stage(&mut self, stage: AutomationStage) -> Result<()> {
+        let (stage_name, ready_selector) = resume_stage_target(stage);
+        tracing::info!("⏭️ 原生后端从{}阶段继续", stage_name);
+        self.page.bring_to_front().await.pw("切换到申诉页面")?;
+        self.page
+            .wait_for_selector_builder(ready_selector)
+            .timeout(RESUME_CHECK_TIMEOUT_MS)
+            .wait_for_selector()
+            .await
+            .map_err(|_| anyhow!("页面不在{}填写步骤，请完整运行一次流程", stage_name))?;
+        self.check_quota().await
+    }
+
+    // 页面提示申诉次数超限时终止，避免提交被静默拒绝
+    async fn check_quota(&self) -> Result<()> {
+        let notice = format!("text={} >> nth=0", QUOTA_EXCEEDED_PATTERN);
+        if !self.page.is_visible(&notice, None).await.unwrap_or(false) {
+            return Ok(());
+        }
+        let text = self.page.text_content(&notice, None).await.ok().flatten().unwrap_or_default();
+        Err(AutomationError::QuotaExceeded(text.trim().to_string()).into())
+    }
+
+    async fn fill_ip_asset(&mut self, asset: &IpAsset, files: &AppealFiles) -> Result<()> {
+        update_status(Message::new("step.filling_ip_asset"), 70.0).await;
+        self.fill_verified(&format!("{} .el-input__inner", RIGHTS_HOLDER_SELECTOR), &asset.owner, "权利人").await;
+
+        let agent_label = if asset.is_agent { "是" } else { "否" };
+        let agent_radio = format!(r#"{} .el-radio:has-text("{}")"#, AGENT_TOGGLE_SELECTOR, agent_label);
+        if self.page.is_visible(&agent_radio, None).await.unwrap_or(false) {
+            self.click(&agent_radio).await?;
+        } else {
+            tracing::warn!("⚠️ 未找到\"是否代理\"选项，按页面默认值继续");
+        }
+
+        if asset.is_agent {
+            if let (Some(start), Some(end)) = (asset.auth_start_date.as_deref(), asset.auth_end_date.as_deref()) {
+                self.fill_period(AUTH_PERIOD_SELECTOR, start, end).await?;
+            }
+        }
+
+        self.click(&format!(r#"{} input[placeholder="请选择"]"#, WORK_TYPE_SELECTOR)).await?;
+        if self.click_dropdown_option(asset.work_type.trim()).await.is_err() {
+            return Err(AutomationError::FormValidation(format!(
+                "著作类型下拉框中没有\"{}\"选项", asset.work_type
+            )).into());
+        }
+        self.fill_verified(&format!("{} .el-input__inner", WORK_NAME_SELECTOR), &asset.work_name, "著作名称").await;
+
+        let region = asset.region.trim();
+        self.click(&format!("{} .el-select", REGION_SELECTOR)).await?;
+        if self.click_dropdown_option(region).await.is_err() {
+            return Err(AutomationError::FormValidation(format!(
+                "地区下拉框中没有找到\"{}\"选项，请检查IP资产的地区设置", region
+            )).into());
+        }
+
+        if !asset.work_start_date.is_empty() && !asset.work_end_date.is_empty() {
+            self.fill_period(WORK_PERIOD_SELECTOR, &asset.work_start_date, &asset.work_end_date).await?;
+        }
+
+        if asset.is_agent {
+            self.upload(&files.auth_files, AUTH_FILES_UPLOAD_SELECTOR, "授权证明").await?;
+        }
+        self.upload(&files.work_proof_files, WORK_PROOF_UPLOAD_SELECTOR, "作品证明").await?;
+        self.report_unfilled_fields()?;
+
+        self.result.fields_filled.extend(["权利人", "著作类型", "著作名称", "地区", "期限"].map(String::from));
+        self.result.stage_reached = "ip_asset".to_string();
+        self.click_button("下一步").await?;
+        self.pause(self.timing.medium_ms).await;
+        Ok(())
+    }
+
+    async fn fill_appeal_details(&mut self, infringing_url: &str, additional_files: &[String]) -> Result<()> {
+        update_status(Message::new("step.filling_details"), 80.0).await;
+        self.fill_verified(INFRINGING_URL_INPUT_SELECTOR, infringing_url, "侵权链接").await;
+        self.fill_verified(DESCRIPTION_TEXTAREA_SELECTOR, APPEAL_DESCRIPTION, "侵权描述").await;
+        self.report_unfilled_fields()?;
+        if !additional_files.is_empty() {
+            // 与生成脚本一致，补充证据上传到最后一个证据/附件区域
+            let area = format!("{} >> nth=-1", EVIDENCE_UPLOAD_SELECTOR);
+            self.upload(additional_files, &area, "补充证据").await?;
+        }
+        self.click(GUARANTEE_CHECKBOX_SELECTOR).await?;
+        self.result.fields_filled.extend(["侵权链接", "侵权描述"].map(String::from));
+        self.result.stage_reached = "appeal_details".to_string();
+        Ok(())
+    }
+
+    async fn submit(&mut self, auto_submit: bool) -> Result<()> {
+        if !auto_submit {
+            tracing::info!("✅ 表单已填写完成，等待用户确认提交");
+            self.result.stage_reached = "ready_for_confirmation".to_string();
+            return Ok(());
+        }
+
+        self.click_button("提交").await?;
+        let success = format!("text={}", SUBMIT_SUCCESS_PATTERN);
+        self.page
+            .wait_for_selector_builder(&success)
+            .timeout(self.timing.long_ms as f64)
+            .wait_for_selector()
+            .await
+            .map_err(|e| AutomationError::SubmitNotConfirmed(e.to_string()))?;
+        tracing::info!("✅ 申诉已提交");
+        self.result.submitted = true;
+        self.result.stage_reached = "submitted".to_string();
+        Ok(())
+    }
+
+    // 保存流程结束时的页面截图作为提交凭证，失败不影响申诉结果
+    async fn capture_screenshot(&self, path: std::path::PathBuf) {
+        match self.page.screenshot_builder().path(path.clone()).full_page(true).screenshot().await {
+            Ok(_) => tracing::info!("📸 已保存提交结果截图: {:?}", path),
+            Err(e) => tracing::warn!("保存提交结果截图失败: {}", e),
+        }
+    }
+
+    async fn fill(&self, selector: &str, value: &str) -> Result<()> {
+        self.page
+            .fill_builder(&format!("{} >> nth=0", selector), value)
+            .fill()
+            .await
+            .pw(&format!("填写{}", selector))
+    }
+
+    // 填写后读回校验，与生成脚本中的fillAndVerify一致；未填入的字段在步骤结束时统一报告
+    async fn fill_verified(&mut self, selector: &str, value: &str, label: &str) {
+        if let Err(e) = self.fill(selector, value).await {
+            tracing::warn!("⚠️ 填写{}时出错: {}", label, e);
+        }
+        let actual: String = self
+            .page
+            .eval_on_selector(&format!("{} >> nth=0", selector), "element => element.value || ''", None::<()>)
+            .await
+            .unwrap_or_default();
+        if actual.trim() != value.trim() {
+            tracing::error!("❌ {}未填入", label);
+            if !self.unfilled_fields.iter().any(|field| field == label) {
+                self.unfilled_fields.push(label.to_string());
+            }
+        }
+    }
+
+    fn report_unfilled_fields(&mut self) -> Result<()> {
+        if self.unfilled_fields.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<String> = self.unfilled_fields.drain(..).map(|label| format!("{}未填入", label)).collect();
+        Err(AutomationError::FieldsNotFilled(messages.join("; ")).into())
+    }
+
+    async fn click(&self, selector: &str) -> Result<()> {
+        self.page
+            .click_builder(&format!("{} >> nth=0", selector))
+            .click()
+            .await
+            .pw(&format!("点击{}", selector))
+    }
+
+    // 只点击可用的按钮，与生成脚本中的clickButtonIfEnabled一致
+    async fn click_button(&self, text: &str) -> Result<()> {
+        let selector = format!(r#"button:has-text("{}"):not([disabled])"#, text);
+        if !self.page.is_visible(&format!("{} >> nth=0", selector), None).await.unwrap_or(false) {
+            // 按钮因次数超限被禁用时报告超限
+            self.check_quota().await?;
+        }
+        self.click(&selector).await
+    }
+
+    // Element UI的下拉选项挂在body下，只选择当前可见且文字完全一致的那个
+    async fn click_dropdown_option(&self, text: &str) -> Result<()> {
+        let option = format!(r#".el-select-dropdown:visible li:text-is("{}")"#, text);
+        self.page
+            .wait_for_selector_builder(&option)
+            .timeout(self.timing.long_ms as f64)
+            .wait_for_selector()
+            .await
+            .pw(&format!("等待选项{}", text))?;
+        self.click(&option).await
+    }
+
+    // 日期选择器在面板打开后输入并按Enter确认，Tab不会触发el-date-picker的确认
+    async fn fill_period(&self, item_selector: &str, start: &str, end: &str) -> Result<()> {
+        for (placeholder, value) in [("起始时间", start), ("结束时间", end)] {
+            let input = format!(r#"{} >> nth=0 >> input[placeholder="{}"] >> nth=0"#, item_selector, placeholder);
+            self.click(&input).await?;
+            self.page
+                .wait_for_selector_builder(".el-picker-panel:visible >> nth=-1")
+                .timeout(self.timing.long_ms as f64)
+                .wait_for_selector()
+                .await
+                .pw("等待日期选择器")?;
+            self.page.fill_builder(&input, value).fill().await.pw("输入日期")?;
+            self.page.press_builder(&input, "Enter").press().await.pw("确认日期")?;
+            self.pause(self.timing.short_ms).await;
+        }
+        Ok(())
+    }
+
+    // 逐个设置文件，日志格式与生成脚本的 "上传第X/N个文件" 一致
+    async fn upload(&mut self, files: &[String], area_selector: &str, label: &str) -> Result<()> {
+        if files.is_empty() {
+            tracing::info!("ℹ️ 无{}文件需要上传", label);
+            return Ok(());
+        }
+        let input = format!(r#"{} >> nth=0 >> input[type="file"] >> nth=0"#, area_selector);
+        if !self.page.is_visible(&format!("{} >> nth=0", area_selector), None).await.unwrap_or(false) {
+            tracing::warn!("⚠️ 未找到{}上传区域，跳过上传", label);
+            return Ok(());
+        }
+
+        for (index, path) in files.iter().enumerate() {
+            let bytes = std::fs::read(path).map_err(|_| AutomationError::FileNotFound(path.clone()))?;
+            let name = std::path::Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone());
+            tracing::info!("📄 {}: 上传第{}/{}个文件: {}", label, index + 1, files.len(), name);
+            let file = File::new(name, mime_for(path).to_string(), &bytes);
+            self.page
+                .set_input_files_builder(&input, file)
+                .set_input_files()
+                .await
+                .pw(&format!("上传{}", label))?;
+            self.pause(self.timing.upload_settle_ms).await;
+            self.result.files_uploaded += 1;
+        }
+        tracing::info!("✅ {}文件上传完成", label);
+        Ok(())
+    }
+
+    async fn pause(&self, ms: u64) {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+    }
+}
+
+// 上传前PDF已转换为图片，这里只需要区分常见图片格式
+fn mime_for(path: &str) -> &'static str {
+    match std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("pdf") => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
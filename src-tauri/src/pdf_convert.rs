@@ -0,0 +1,82 @@
+// src-tauri/src/pdf_convert.rs
+
+use anyhow::{Result, Context};
+use pdfium_render::prelude::*;
+use std::path::{Path, PathBuf};
+
+// PDF首页渲染为图片时的目标宽度
+const RENDER_TARGET_WIDTH: i32 = 1600;
+
+const OUTPUT_DIR_NAME: &str = "rightsguard-pdf-pages";
+
+pub fn is_pdf(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("pdf"))
+}
+
+/// B站的上传组件只接受图片，把列表中的PDF替换为其首页渲染出的PNG
+/// 转换失败时保留原PDF并记录原因，由上传脚本决定是否跳过
+pub fn convert_pdfs_to_images(files: Vec<String>) -> Vec<String> {
+    if !files.iter().any(|file| is_pdf(file)) {
+        return files;
+    }
+
+    let pdfium = match bind_pdfium() {
+        Ok(pdfium) => pdfium,
+        Err(e) => {
+            tracing::warn!("⚠️ 无法加载pdfium库，PDF文件将按原样上传: {:#}", e);
+            return files;
+        }
+    };
+
+    files
+        .into_iter()
+        .map(|file| {
+            if !is_pdf(&file) {
+                return file;
+            }
+            match render_first_page(&pdfium, Path::new(&file)) {
+                Ok(image_path) => {
+                    let image_path = image_path.to_string_lossy().to_string();
+                    tracing::info!("📄 PDF已转换为图片上传: {} -> {}", file, image_path);
+                    image_path
+                }
+                Err(e) => {
+                    tracing::warn!("⚠️ PDF转换图片失败，将按原样上传: {} ({:#})", file, e);
+                    file
+                }
+            }
+        })
+        .collect()
+}
+
+// 优先使用程序目录下的pdfium库，其次使用系统安装的版本
+fn bind_pdfium() -> Result<Pdfium> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("./"));
+
+    let bindings = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&exe_dir))
+        .or_else(|_| Pdfium::bind_to_system_library())
+        .context("未找到pdfium动态库")?;
+    Ok(Pdfium::new(bindings))
+}
+
+fn render_first_page(pdfium: &Pdfium, pdf_path: &Path) -> Result<PathBuf> {
+    let document = pdfium.load_pdf_from_file(pdf_path, None).context("无法打开PDF文件")?;
+    let page = document.pages().get(0).context("PDF文件没有页面")?;
+    let image = page
+        .render_with_config(&PdfRenderConfig::new().set_target_width(RENDER_TARGET_WIDTH))
+        .context("渲染PDF首页失败")?
+        .as_image();
+
+    // 每次转换使用独立的子目录，同名PDF或并发的转换不会覆盖彼此的图片；文件名保持可读，上传后页面上显示的就是它
+    let output_dir = std::env::temp_dir().join(OUTPUT_DIR_NAME).join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&output_dir).context("无法创建PDF转换目录")?;
+    let stem = pdf_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "document".to_string());
+    let output_path = output_dir.join(format!("{}_第1页.png", stem));
+    image.save(&output_path).context("保存PDF转换图片失败")?;
+    Ok(output_path)
+}
@@ -0,0 +1,135 @@
+// src-tauri/src/run_queue.rs
+//
+// 运行队列: 同一时间只能运行一个自动化，排队的申诉由后台工作任务按先进先出依次启动
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use tokio::sync::Notify;
+use uuid::Uuid;
+use crate::models::{AutomationRequest, QueuedRun};
+
+static QUEUE: Lazy<Mutex<VecDeque<QueuedEntry>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+// 有新申诉入队时唤醒工作任务；运行结束由轮询发现
+static QUEUE_WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+// 上一个运行结束后最多等待这么久启动下一个
+const QUEUE_POLL_INTERVAL_SECS: u64 = 2;
+
+struct QueuedEntry {
+    request: AutomationRequest,
+    queued_at: chrono::DateTime<Utc>,
+}
+
+fn lock_queue() -> std::sync::MutexGuard<'static, VecDeque<QueuedEntry>> {
+    QUEUE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// 把申诉加入队尾，入队时分配run_id，之后的运行记录和日志都使用它
+pub async fn enqueue(mut request: AutomationRequest) -> QueuedRun {
+    let run_id = Uuid::new_v4();
+    request.run_id = Some(run_id);
+    let (queued, length) = {
+        let mut queue = lock_queue();
+        queue.push_back(QueuedEntry { request, queued_at: Utc::now() });
+        let length = queue.len();
+        (to_queued_run(&queue[length - 1], length as u32), length)
+    };
+    tracing::info!(%run_id, "申诉已加入队列，排在第{}位: {}", length, queued.infringing_url);
+    crate::automation::set_queue_length(length as u32).await;
+    QUEUE_WAKE.notify_one();
+    queued
+}
+
+pub fn len() -> usize {
+    lock_queue().len()
+}
+
+/// 当前排队中的申诉，queue_position从1开始
+pub fn queued_runs() -> Vec<QueuedRun> {
+    lock_queue()
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| to_queued_run(entry, index as u32 + 1))
+        .collect()
+}
+
+/// 取消尚未开始的申诉，已开始运行或不在队列中时返回false
+pub async fn cancel(run_id: Uuid) -> bool {
+    let (removed, length) = {
+        let mut queue = lock_queue();
+        let before = queue.len();
+        queue.retain(|entry| entry.request.run_id != Some(run_id));
+        (queue.len() < before, queue.len())
+    };
+    if removed {
+        tracing::info!(%run_id, "已取消排队的申诉");
+        crate::automation::set_queue_length(length as u32).await;
+    }
+    removed
+}
+
+/// 清空队列，不影响正在运行的申诉，返回被移除的数量
+pub async fn clear() -> usize {
+    let removed = {
+        let mut queue = lock_queue();
+        let removed = queue.len();
+        queue.clear();
+        removed
+    };
+    if removed > 0 {
+        tracing::info!("已清空运行队列，移除{}个申诉", removed);
+        crate::automation::set_queue_length(0).await;
+    }
+    removed
+}
+
+fn to_queued_run(entry: &QueuedEntry, queue_position: u32) -> QueuedRun {
+    QueuedRun {
+        run_id: entry.request.run_id.unwrap_or_default(),
+        infringing_url: entry.request.infringing_url.clone(),
+        ip_asset_id: entry.request.ip_asset_id,
+        queued_at: entry.queued_at,
+        queue_position,
+    }
+}
+
+/// 应用启动时调用: 后台依次启动排队的申诉，只有在没有运行中的自动化时才启动下一个
+pub fn spawn_worker() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let _ = tokio::time::timeout(
+                std::time::Duration::from_secs(QUEUE_POLL_INTERVAL_SECS),
+                QUEUE_WAKE.notified(),
+            ).await;
+            if crate::automation::is_run_in_progress() {
+                continue;
+            }
+            start_next().await;
+        }
+    });
+}
+
+async fn start_next() {
+    let (entry, remaining) = {
+        let mut queue = lock_queue();
+        let Some(entry) = queue.pop_front() else {
+            return;
+        };
+        (entry, queue.len())
+    };
+    let run_id = entry.request.run_id.unwrap_or_default();
+    tracing::info!(%run_id, "从队列启动申诉，剩余{}个: {}", remaining, entry.request.infringing_url);
+
+    if let Err(e) = crate::automation::start_automation(entry.request.clone()).await {
+        // 计划运行等其他入口抢先启动时放回队首，等待下一轮
+        if e.is::<crate::automation::AutomationAlreadyRunning>() {
+            lock_queue().push_front(entry);
+            return;
+        }
+        tracing::error!(%run_id, "排队的申诉启动失败: {:#}", e);
+    }
+    crate::automation::set_queue_length(remaining as u32).await;
+}
@@ -0,0 +1,104 @@
+// src-tauri/src/scheduler.rs
+//
+// 计划运行: 后台定期检查到期的计划申诉并启动自动化
+
+use chrono::Utc;
+use tauri::AppHandle;
+use crate::database::{
+    self, SCHEDULE_STATUS_FAILED, SCHEDULE_STATUS_PENDING, SCHEDULE_STATUS_SKIPPED, SCHEDULE_STATUS_STARTED,
+};
+use crate::models::ScheduledRun;
+
+// 检查到期计划的间隔，计划最多延迟这么久开始
+const SCHEDULE_POLL_INTERVAL_SECS: u64 = 30;
+
+/// 应用启动时调用: 先处理应用关闭期间到期的计划，再定期启动到期的计划
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        confirm_overdue_runs(&app).await;
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(SCHEDULE_POLL_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            run_due_schedules().await;
+        }
+    });
+}
+
+// 应用关闭期间到期的计划不直接运行，先询问用户；拒绝时标记为跳过
+async fn confirm_overdue_runs(app: &AppHandle) {
+    use tauri_plugin_dialog::{MessageDialogButtons, MessageDialogKind};
+
+    let overdue = match database::get_due_scheduled_runs(Utc::now()).await {
+        Ok(overdue) if !overdue.is_empty() => overdue,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::warn!("读取过期的计划运行失败: {}", e);
+            return;
+        }
+    };
+
+    let message = format!(
+        "有{}个计划申诉在应用关闭期间到期 (最早计划于{})，是否现在运行？\n选择\"否\"将跳过这些计划。",
+        overdue.len(),
+        overdue[0].run_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+    );
+    let accepted = crate::commands::show_dialog(
+        app,
+        "计划申诉已过期".to_string(),
+        message,
+        MessageDialogKind::Info,
+        MessageDialogButtons::YesNo,
+    ).await;
+
+    if accepted {
+        tracing::info!("用户同意运行{}个过期的计划申诉", overdue.len());
+        return;
+    }
+    for run in &overdue {
+        if let Err(e) = database::transition_scheduled_run(
+            run.id,
+            SCHEDULE_STATUS_PENDING,
+            SCHEDULE_STATUS_SKIPPED,
+            Some("应用关闭期间到期，用户选择跳过"),
+        ).await {
+            tracing::warn!("跳过计划运行{}失败: {}", run.id, e);
+        }
+    }
+    tracing::info!("用户跳过了{}个过期的计划申诉", overdue.len());
+}
+
+// 同一时间只能运行一个自动化，每次轮询最多启动一个到期计划，其余等待后续轮询
+async fn run_due_schedules() {
+    if crate::automation::is_run_in_progress() {
+        return;
+    }
+    match database::get_due_scheduled_runs(Utc::now()).await {
+        Ok(due) => {
+            if let Some(run) = due.into_iter().next() {
+                start_scheduled_run(run).await;
+            }
+        }
+        Err(e) => tracing::warn!("读取到期的计划运行失败: {}", e),
+    }
+}
+
+async fn start_scheduled_run(run: ScheduledRun) {
+    // 先占用计划，期间被取消的计划不再启动
+    match database::transition_scheduled_run(run.id, SCHEDULE_STATUS_PENDING, SCHEDULE_STATUS_STARTED, None).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::warn!("更新计划运行{}状态失败: {}", run.id, e);
+            return;
+        }
+    }
+
+    tracing::info!("⏰ 开始计划运行 {} ({})", run.id, run.request.infringing_url);
+    if let Err(e) = crate::automation::start_automation(run.request).await {
+        let message = format!("{:#}", e);
+        tracing::error!("计划运行{}启动失败: {}", run.id, message);
+        if let Err(e) = database::transition_scheduled_run(run.id, SCHEDULE_STATUS_STARTED, SCHEDULE_STATUS_FAILED, Some(&message)).await {
+            tracing::warn!("更新计划运行{}状态失败: {}", run.id, e);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+// src-tauri/src/setup.rs
+//
+// 首次运行检查: 把目录、数据库、浏览器、npx和Playwright的检查合并成一个引导流程
+// 新用户不必等到运行到一半才发现缺少依赖
+
+use tauri::Manager;
+use crate::automation;
+use crate::database;
+use crate::models::{AppSettings, SetupCheck, SetupCheckStatus, SetupReport};
+
+/// 依次执行所有检查，单项失败不影响其余检查
+/// install_browser为true且缺少Playwright浏览器时直接安装
+pub async fn run_first_time_setup(install_browser: bool) -> SetupReport {
+    let settings = database::get_settings().await.unwrap_or_else(|e| {
+        tracing::warn!("读取应用设置失败，使用默认设置进行检查: {}", e);
+        AppSettings::default()
+    });
+
+    let mut checks = vec![
+        check_app_data_dirs(&settings),
+        check_database().await,
+        check_browser(&settings),
+        check_npx(),
+    ];
+    // 没有npx时Playwright的检查必然失败，不再重复报告
+    if checks.last().map_or(false, |check| check.status == SetupCheckStatus::Pass) {
+        let playwright = check_playwright().await;
+        let playwright_ok = playwright.status == SetupCheckStatus::Pass;
+        checks.push(playwright);
+        if playwright_ok {
+            checks.push(check_playwright_browser(install_browser).await);
+        }
+    }
+
+    let ready = checks.iter().all(|check| check.status == SetupCheckStatus::Pass);
+    for check in &checks {
+        tracing::info!("首次运行检查 [{}] {:?}: {}", check.id, check.status, check.detail);
+    }
+    SetupReport { ready, checks }
+}
+
+fn check(id: &str, label: &str, status: SetupCheckStatus, detail: String, action: Option<&str>) -> SetupCheck {
+    SetupCheck {
+        id: id.to_string(),
+        label: label.to_string(),
+        status,
+        detail,
+        action: action.map(str::to_string),
+    }
+}
+
+// 创建应用数据目录、文件目录和脚本输出目录，并确认可写
+fn check_app_data_dirs(settings: &AppSettings) -> SetupCheck {
+    const ID: &str = "appData";
+    const LABEL: &str = "应用数据目录";
+    let result = (|| -> anyhow::Result<String> {
+        let app_data_dir = database::app_handle()?.path().app_data_dir()?;
+        let files_dir = app_data_dir.join("files");
+        std::fs::create_dir_all(&files_dir)?;
+        let probe = files_dir.join(".write_test");
+        std::fs::write(&probe, b"ok")?;
+        let _ = std::fs::remove_file(&probe);
+        let work_dir = automation::automation_work_dir(settings)?;
+        Ok(format!("{} (脚本目录: {})", app_data_dir.display(), work_dir.display()))
+    })();
+    match result {
+        Ok(detail) => check(ID, LABEL, SetupCheckStatus::Pass, detail, None),
+        Err(e) => check(
+            ID, LABEL, SetupCheckStatus::Fail,
+            format!("无法创建或写入目录: {:#}", e),
+            Some("检查磁盘空间和目录权限，或在设置中指定其他脚本输出目录"),
+        ),
+    }
+}
+
+async fn check_database() -> SetupCheck {
+    const ID: &str = "database";
+    const LABEL: &str = "数据库";
+    if let Err(e) = database::check_connectivity().await {
+        return check(
+            ID, LABEL, SetupCheckStatus::Fail,
+            format!("数据库无法访问: {:#}", e),
+            Some("关闭其他正在运行的RightsGuard后重启应用"),
+        );
+    }
+    if database::is_degraded_mode() {
+        return check(
+            ID, LABEL, SetupCheckStatus::Fail,
+            "数据库文件无法打开，当前使用临时内存数据库，数据不会保存".to_string(),
+            Some("检查应用数据目录权限后重启应用，或从备份恢复数据库"),
+        );
+    }
+    check(ID, LABEL, SetupCheckStatus::Pass, "数据库连接正常".to_string(), None)
+}
+
+// Chrome或Edge任一可用即可，只找到Edge时需要在设置中指定路径
+fn check_browser(settings: &AppSettings) -> SetupCheck {
+    const ID: &str = "browser";
+    const LABEL: &str = "Chrome/Edge浏览器";
+    if let Ok(path) = automation::find_chrome_executable(settings.chrome_path.as_deref()) {
+        return check(ID, LABEL, SetupCheckStatus::Pass, format!("已找到浏览器: {}", path), None);
+    }
+    match automation::find_edge_executable() {
+        Some(path) => check(
+            ID, LABEL, SetupCheckStatus::ActionRequired,
+            format!("未找到Chrome，但检测到Edge: {}", path),
+            Some("在设置中将Chrome路径设为上述Edge路径，或安装Google Chrome"),
+        ),
+        None => check(
+            ID, LABEL, SetupCheckStatus::Fail,
+            "未找到Chrome或Edge".to_string(),
+            Some("安装Google Chrome，或在设置中指定浏览器路径"),
+        ),
+    }
+}
+
+fn check_npx() -> SetupCheck {
+    const ID: &str = "npx";
+    const LABEL: &str = "Node.js (npx)";
+    match automation::find_npx_executable() {
+        Ok(path) => check(ID, LABEL, SetupCheckStatus::Pass, format!("已找到npx: {}", path), None),
+        Err(e) => check(
+            ID, LABEL, SetupCheckStatus::Fail,
+            e.to_string(),
+            Some("从 https://nodejs.org 安装Node.js LTS后重启应用"),
+        ),
+    }
+}
+
+async fn check_playwright() -> SetupCheck {
+    const ID: &str = "playwright";
+    const LABEL: &str = "Playwright";
+    match automation::playwright_version().await {
+        Ok(version) => check(ID, LABEL, SetupCheckStatus::Pass, version, None),
+        Err(e) => check(
+            ID, LABEL, SetupCheckStatus::Fail,
+            format!("{:#}", e),
+            Some("在项目目录中运行 npm install 安装 @playwright/test"),
+        ),
+    }
+}
+
+async fn check_playwright_browser(install_browser: bool) -> SetupCheck {
+    const ID: &str = "playwrightBrowser";
+    const LABEL: &str = "Playwright浏览器";
+    const INSTALL_ACTION: &str = "允许安装Playwright浏览器 (npx playwright install chromium)，下载约需几分钟";
+
+    match automation::playwright_browser_installed() {
+        Some(true) => return check(ID, LABEL, SetupCheckStatus::Pass, "Chromium已安装".to_string(), None),
+        // 无法确定时只在用户同意后尝试安装，安装命令对已安装的浏览器不会重复下载
+        None if !install_browser => {
+            return check(
+                ID, LABEL, SetupCheckStatus::ActionRequired,
+                "无法确定Playwright浏览器是否已安装".to_string(),
+                Some(INSTALL_ACTION),
+            );
+        }
+        Some(false) if !install_browser => {
+            return check(
+                ID, LABEL, SetupCheckStatus::ActionRequired,
+                "未安装Playwright使用的Chromium".to_string(),
+                Some(INSTALL_ACTION),
+            );
+        }
+        _ => {}
+    }
+
+    match automation::install_playwright_browser().await {
+        Ok(()) => check(ID, LABEL, SetupCheckStatus::Pass, "Chromium安装完成".to_string(), None),
+        Err(e) => check(
+            ID, LABEL, SetupCheckStatus::Fail,
+            format!("安装失败: {:#}", e),
+            Some("检查网络连接后重试，或手动运行 npx playwright install chromium"),
+        ),
+    }
+}